@@ -0,0 +1,136 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Compares the strategies to deserialize structures with many fields:
+//!
+//! * the default, which decodes each column directly into the fields
+//! * `#[ar_row(direct_field_writes)]`, which decodes each column to a temporary vector
+//! * [`DeserializeOptions::block_size`], which decodes all columns of a block of rows
+//!   before moving to the next block
+//!
+//! Run with `cargo bench --bench wide_structs`, optionally followed by the number
+//! of rows.
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ar_row::arrow::array::{ArrayRef, Float64Array, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::ArRowDeserialize;
+use ar_row::options::DeserializeOptions;
+use ar_row_derive::ArRowDeserialize;
+
+const ITERATIONS: u32 = 10;
+
+macro_rules! wide_struct {
+    ($name:ident, $(#[$attr:meta])*) => {
+        #[derive(ArRowDeserialize, Default)]
+        $(#[$attr])*
+        #[allow(dead_code)] // Fields are only written
+        struct $name {
+            i0: i64,
+            i1: i64,
+            i2: i64,
+            i3: i64,
+            i4: i64,
+            i5: i64,
+            i6: i64,
+            i7: i64,
+            i8: i64,
+            i9: i64,
+            f0: f64,
+            f1: f64,
+            f2: f64,
+            f3: f64,
+            f4: f64,
+            f5: f64,
+            f6: f64,
+            f7: f64,
+            f8: f64,
+            f9: f64,
+            s0: String,
+            s1: String,
+            s2: String,
+            s3: String,
+            s4: String,
+            s5: String,
+            s6: String,
+            s7: String,
+            s8: String,
+            s9: String,
+        }
+    };
+}
+
+wide_struct!(Wide,);
+wide_struct!(WideDirectFieldWrites, #[ar_row(direct_field_writes)]);
+
+fn batch(num_rows: usize) -> RecordBatch {
+    let int_columns = (0..10).map(|i| {
+        let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+            (0..num_rows as i64).map(|row| row * i),
+        ));
+        (format!("i{}", i), array)
+    });
+    let float_columns = (0..10).map(|i| {
+        let array: ArrayRef = Arc::new(Float64Array::from_iter_values(
+            (0..num_rows).map(|row| (row * i) as f64 / 3.),
+        ));
+        (format!("f{}", i), array)
+    });
+    let string_columns = (0..10).map(|i| {
+        let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+            (0..num_rows).map(|row| format!("row {} column {}", row, i)),
+        ));
+        (format!("s{}", i), array)
+    });
+    RecordBatch::try_from_iter(int_columns.chain(float_columns).chain(string_columns))
+        .expect("Could not build record batch")
+}
+
+/// Runs `f` [`ITERATIONS`] times and prints the average time per row
+fn bench<F: FnMut() -> usize>(name: &str, num_rows: usize, mut f: F) {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let len = f();
+        total += start.elapsed();
+        assert_eq!(len, num_rows);
+    }
+    let per_row = total / ITERATIONS / num_rows as u32;
+    println!("{:<40} {:>10?} per row", name, per_row);
+}
+
+fn main() {
+    let num_rows: usize = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .map(|arg| arg.parse().expect("Invalid number of rows"))
+        .unwrap_or(1_000_000);
+    let batch = batch(num_rows);
+
+    bench("default", num_rows, || {
+        Wide::from_record_batch(batch.clone())
+            .expect("Could not deserialize")
+            .len()
+    });
+    bench("#[ar_row(direct_field_writes)]", num_rows, || {
+        WideDirectFieldWrites::from_record_batch(batch.clone())
+            .expect("Could not deserialize")
+            .len()
+    });
+    for block_size in [64, 1024, 16384] {
+        let options = DeserializeOptions {
+            block_size: NonZeroUsize::new(block_size),
+            ..Default::default()
+        };
+        bench(&format!("block_size = {}", block_size), num_rows, || {
+            Wide::from_record_batch_with_options(batch.clone(), &options)
+                .expect("Could not deserialize")
+                .len()
+        });
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Strings and binaries allocated in a per-batch arena
+//!
+//! Deserializing a column to [`String`] or `Box<[u8]>` allocates every value
+//! separately, which puts a lot of pressure on the allocator when materializing
+//! hundreds of millions of rows.
+//!
+//! Deserializing it to [`ArenaString`] or [`ArenaBytes`] instead copies the values
+//! of the whole column of the batch to a single allocation (the arena), and each
+//! row only holds a reference-counted pointer to the arena and the bounds of its
+//! value. The arena is freed when the last row referencing it is dropped, so
+//! keeping a single row alive keeps the values of the whole batch alive.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! use ar_row::arena::ArenaString;
+//! use ar_row::deserialize::ArRowDeserialize;
+//!
+//! let array: ArrayRef = Arc::new(StringArray::from(vec![Some("foo"), None, Some("bar")]));
+//! let rows = <Option<ArenaString>>::from_array(array).unwrap();
+//! assert_eq!(rows[0].as_deref(), Some("foo"));
+//! assert_eq!(rows[1].as_deref(), None);
+//! assert_eq!(rows[2].as_deref(), Some("bar"));
+//! ```
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, Index, Range};
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::*;
+
+use crate::deserialize::{
+    check_datatype_equals, ArRowDeserialize, ArRowStruct, CheckableDataType, ColumnTree,
+    DeserializationError, DeserializationTarget,
+};
+use crate::dictionaries::{
+    read_from_cached_dictionary_array, read_options_from_cached_dictionary_array,
+    unpack_run_end_encoded,
+};
+
+/// Slice of an arena shared by all the values of a column of a batch
+///
+/// See the [module-level documentation](self).
+pub struct ArenaSlice<A: ?Sized> {
+    arena: Arc<A>,
+    start: usize,
+    end: usize,
+}
+
+/// `String`-like type whose value is allocated in a per-batch arena
+pub type ArenaString = ArenaSlice<str>;
+
+/// `Box<[u8]>`-like type whose value is allocated in a per-batch arena
+pub type ArenaBytes = ArenaSlice<[u8]>;
+
+impl<A: ?Sized + Index<Range<usize>, Output = A>> ArenaSlice<A> {
+    /// Returns the arena this value is a slice of
+    pub fn arena(&self) -> &Arc<A> {
+        &self.arena
+    }
+}
+
+impl<A: ?Sized> Clone for ArenaSlice<A> {
+    fn clone(&self) -> Self {
+        ArenaSlice {
+            arena: self.arena.clone(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A>> Deref for ArenaSlice<A> {
+    type Target = A;
+    fn deref(&self) -> &Self::Target {
+        &self.arena[self.start..self.end]
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A>> AsRef<A> for ArenaSlice<A> {
+    fn as_ref(&self) -> &A {
+        self
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + fmt::Debug> fmt::Debug for ArenaSlice<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl fmt::Display for ArenaString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + PartialEq> PartialEq for ArenaSlice<A> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + Eq> Eq for ArenaSlice<A> {}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + PartialOrd> PartialOrd for ArenaSlice<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + Ord> Ord for ArenaSlice<A> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<A: ?Sized + Index<Range<usize>, Output = A> + Hash> Hash for ArenaSlice<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl Default for ArenaString {
+    fn default() -> Self {
+        "".into()
+    }
+}
+
+impl Default for ArenaBytes {
+    fn default() -> Self {
+        (&[][..]).into()
+    }
+}
+
+/// Allocates a new arena containing only `value`
+impl From<&str> for ArenaString {
+    fn from(value: &str) -> Self {
+        ArenaSlice {
+            arena: Arc::from(value),
+            start: 0,
+            end: value.len(),
+        }
+    }
+}
+
+/// Allocates a new arena containing only `value`
+impl From<&[u8]> for ArenaBytes {
+    fn from(value: &[u8]) -> Self {
+        ArenaSlice {
+            arena: Arc::from(value),
+            start: 0,
+            end: value.len(),
+        }
+    }
+}
+
+/// Copies the values of `src` (including the ones in null slots, so that the
+/// offsets of the array can be reused) to a new arena
+fn string_arena<O: OffsetSizeTrait>(src: &GenericStringArray<O>) -> Arc<str> {
+    let offsets = src.value_offsets();
+    let values = &src.value_data()[offsets[0].as_usize()..offsets[src.len()].as_usize()];
+    Arc::from(std::str::from_utf8(values).expect("StringArray contains invalid UTF-8"))
+}
+
+/// Copies the values of `src` (including the ones in null slots, so that the
+/// offsets of the array can be reused) to a new arena
+fn binary_arena<O: OffsetSizeTrait>(src: &GenericBinaryArray<O>) -> Arc<[u8]> {
+    let offsets = src.value_offsets();
+    Arc::from(&src.value_data()[offsets[0].as_usize()..offsets[src.len()].as_usize()])
+}
+
+/// Writes the slice of `arena` for each value to `dst`, given the offsets of the
+/// values in the original array. `wrap` converts each of them (`None` for nulls)
+/// to the items of `dst`.
+fn write_slices<'a, 'b, A, O, T, Item>(
+    arena: Arc<A>,
+    offsets: &[O],
+    nulls: Option<&NullBuffer>,
+    mut dst: &'b mut T,
+    wrap: impl Fn(Option<ArenaSlice<A>>) -> Item,
+) -> Result<usize, DeserializationError>
+where
+    A: ?Sized,
+    O: OffsetSizeTrait,
+    Item: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = Item> + 'b,
+{
+    let num_values = offsets.len().saturating_sub(1);
+    let dst_len = dst.len();
+    if num_values > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: num_values,
+            dst: dst_len,
+        });
+    }
+
+    let first_offset = offsets.first().map(|offset| offset.as_usize()).unwrap_or(0);
+    for ((i, bounds), d) in offsets.windows(2).enumerate().zip(dst.iter_mut()) {
+        *d = wrap(if nulls.map(|nulls| nulls.is_null(i)).unwrap_or(false) {
+            None
+        } else {
+            Some(ArenaSlice {
+                arena: arena.clone(),
+                start: bounds[0].as_usize() - first_offset,
+                end: bounds[1].as_usize() - first_offset,
+            })
+        });
+    }
+
+    Ok(num_values)
+}
+
+macro_rules! impl_arena {
+    ($ty:ty, $datatype:expr, $method:ident, $to_arena:ident) => {
+        impl ArRowStruct for $ty {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                vec![prefix.to_string()]
+            }
+
+            fn column_tree() -> ColumnTree {
+                ColumnTree::Leaf
+            }
+        }
+
+        impl CheckableDataType for $ty {
+            fn check_datatype(datatype: &DataType) -> Result<(), String> {
+                check_datatype_equals(datatype, &$datatype, stringify!($ty))
+            }
+
+            fn expected_datatype() -> DataType {
+                $datatype[0].clone()
+            }
+        }
+
+        impl ArRowDeserialize for $ty {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                if let Some(src) = src.as_any_dictionary_opt() {
+                    return read_from_cached_dictionary_array(src, dst);
+                }
+                if let Some(src) = unpack_run_end_encoded(&src)? {
+                    return Self::read_from_array(src, dst);
+                }
+                if src.nulls().is_some() {
+                    return Err(DeserializationError::UnexpectedNull(format!(
+                        "{} column contains nulls",
+                        stringify!($ty)
+                    )));
+                }
+                if let Some(src) = src.$method::<i32>() {
+                    let arena = $to_arena(src);
+                    write_slices(
+                        arena,
+                        src.value_offsets(),
+                        None,
+                        dst,
+                        Option::unwrap_or_default,
+                    )
+                } else if let Some(src) = src.$method::<i64>() {
+                    let arena = $to_arena(src);
+                    write_slices(
+                        arena,
+                        src.value_offsets(),
+                        None,
+                        dst,
+                        Option::unwrap_or_default,
+                    )
+                } else {
+                    Err(DeserializationError::MismatchedColumnDataType(format!(
+                        "Could not cast {:?} array with {}",
+                        src.data_type(),
+                        stringify!($method)
+                    )))
+                }
+            }
+        }
+
+        impl ArRowDeserialize for Option<$ty> {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                if let Some(src) = src.$method::<i32>() {
+                    let arena = $to_arena(src);
+                    write_slices(arena, src.value_offsets(), src.nulls(), dst, |value| value)
+                } else if let Some(src) = src.$method::<i64>() {
+                    let arena = $to_arena(src);
+                    write_slices(arena, src.value_offsets(), src.nulls(), dst, |value| value)
+                } else if let Some(src) = src.as_any_dictionary_opt() {
+                    read_options_from_cached_dictionary_array(src, dst)
+                } else if let Some(src) = unpack_run_end_encoded(&src)? {
+                    Self::read_from_array(src, dst)
+                } else {
+                    Err(DeserializationError::MismatchedColumnDataType(format!(
+                        "Could not cast {:?} array with {}",
+                        src.data_type(),
+                        stringify!($method)
+                    )))
+                }
+            }
+        }
+    };
+}
+
+impl_arena!(
+    ArenaString,
+    [DataType::Utf8, DataType::LargeUtf8],
+    as_string_opt,
+    string_arena
+);
+impl_arena!(
+    ArenaBytes,
+    [DataType::Binary, DataType::LargeBinary],
+    as_binary_opt,
+    binary_arena
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_arena() {
+        let array: ArrayRef = Arc::new(StringArray::from(vec!["foo", "", "bar"]));
+        let rows = ArenaString::from_array(array).unwrap();
+        assert_eq!(rows, vec!["foo".into(), "".into(), "bar".into()]);
+        assert!(Arc::ptr_eq(rows[0].arena(), rows[2].arena()));
+        assert_eq!(&**rows[0].arena(), "foobar");
+    }
+
+    #[test]
+    fn test_sliced_array() {
+        let array = BinaryArray::from(vec![&b"foo"[..], b"ba", b"r", b"baz"]).slice(1, 2);
+        let rows = ArenaBytes::from_array(Arc::new(array) as ArrayRef).unwrap();
+        assert_eq!(rows, vec![(&b"ba"[..]).into(), (&b"r"[..]).into()]);
+        // Values outside of the slice are not copied
+        assert_eq!(&**rows[0].arena(), b"bar");
+    }
+
+    #[test]
+    fn test_nulls() {
+        let array: ArrayRef = Arc::new(LargeStringArray::from(vec![None, Some("foo")]));
+        assert_eq!(
+            <Option<ArenaString>>::from_array(array.clone()),
+            Ok(vec![None, Some("foo".into())])
+        );
+        assert!(ArenaString::from_array(array).is_err());
+    }
+}
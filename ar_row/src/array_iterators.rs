@@ -3,7 +3,11 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
+//! Iterators used by deserializers, including code generated by `ar_row_derive`
+
 use arrow::array::*;
+use arrow::buffer::NullBuffer;
+use arrow::util::bit_chunk_iterator::BitChunkIterator;
 
 /// Like [`arrow::array::iterator::ArrayIter`] for arrays with no nulls.
 #[derive(Debug, Clone)]
@@ -83,6 +87,89 @@ impl<Values: ExactSizeIterator, Nulls: ExactSizeIterator<Item = bool>> ExactSize
     }
 }
 
+/// Iterator on runs of consecutive valid or null rows of an array, as
+/// `(is_valid, run_length)` pairs.
+///
+/// The validity bitmap is read 64 bits at a time, so words where all rows are valid
+/// (or all are null) are returned as a single run instead of testing each of their bits.
+/// Consecutive runs are not merged across words, so a run may be followed by a run
+/// with the same validity.
+pub struct ValidityRuns<'a> {
+    chunks: Option<BitChunkIterator<'a>>,
+    /// Bits after the last 64-bits chunk, and their number
+    remainder: Option<(u64, usize)>,
+    /// Bits of the current chunk which were not returned yet, from the least
+    /// significant bit, and their number
+    word: u64,
+    word_len: usize,
+}
+
+impl<'a> ValidityRuns<'a> {
+    /// Returns runs over `len` rows with the given validity; if `nulls` is `None`,
+    /// all rows are valid.
+    pub fn new(nulls: Option<&'a NullBuffer>, len: usize) -> ValidityRuns<'a> {
+        match nulls {
+            Some(nulls) if nulls.null_count() > 0 => {
+                let chunks = nulls
+                    .inner()
+                    .inner()
+                    .bit_chunks(nulls.offset(), nulls.len());
+                ValidityRuns {
+                    chunks: Some(chunks.iter()),
+                    remainder: Some((chunks.remainder_bits(), chunks.remainder_len())),
+                    word: 0,
+                    word_len: 0,
+                }
+            }
+            _ => ValidityRuns {
+                chunks: None,
+                remainder: None,
+                word: 0,
+                word_len: len,
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for ValidityRuns<'a> {
+    type Item = (bool, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunks.is_none() {
+            // No validity bitmap, all rows are in the same run
+            let len = std::mem::take(&mut self.word_len);
+            return if len == 0 { None } else { Some((true, len)) };
+        }
+        if self.word_len == 0 {
+            match self.chunks.as_mut().and_then(|chunks| chunks.next()) {
+                Some(word) => {
+                    self.word = word;
+                    self.word_len = 64;
+                }
+                None => {
+                    let (word, word_len) = self.remainder.take()?;
+                    self.word = word;
+                    self.word_len = word_len;
+                }
+            }
+        }
+        if self.word_len == 0 {
+            return None;
+        }
+
+        let is_valid = self.word & 1 == 1;
+        let run_length = if is_valid {
+            (!self.word).trailing_zeros()
+        } else {
+            self.word.trailing_zeros()
+        };
+        let run_length = (run_length as usize).min(self.word_len);
+        self.word = self.word.checked_shr(run_length as u32).unwrap_or(0);
+        self.word_len -= run_length;
+        Some((is_valid, run_length))
+    }
+}
+
 /*
 #[derive(Debug, Clone)]
 pub struct ListArrayIter<OffsetSize: OffsetSizeTrait> {
@@ -120,3 +207,63 @@ impl<OffsetSize: OffsetSizeTrait> ExactSizeIterator for ListArrayIter<OffsetSize
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(validity: &[bool]) -> Vec<(bool, usize)> {
+        let nulls = NullBuffer::from(validity);
+        let mut runs: Vec<(bool, usize)> = Vec::new();
+        // Merge runs split across words, to make expected values easier to write
+        for (is_valid, run_length) in ValidityRuns::new(Some(&nulls), validity.len()) {
+            assert!(run_length > 0);
+            match runs.last_mut() {
+                Some((last_is_valid, last_run_length)) if *last_is_valid == is_valid => {
+                    *last_run_length += run_length
+                }
+                _ => runs.push((is_valid, run_length)),
+            }
+        }
+        runs
+    }
+
+    #[test]
+    fn test_validity_runs() {
+        assert_eq!(runs(&[]), vec![]);
+        assert_eq!(runs(&[true; 3]), vec![(true, 3)]);
+        assert_eq!(runs(&[false; 3]), vec![(false, 3)]);
+        assert_eq!(
+            runs(&[true, false, false, true]),
+            vec![(true, 1), (false, 2), (true, 1)]
+        );
+
+        let mut validity = vec![true; 200];
+        validity[63] = false;
+        validity[64] = false;
+        validity[199] = false;
+        assert_eq!(
+            runs(&validity),
+            vec![(true, 63), (false, 2), (true, 134), (false, 1)]
+        );
+        assert_eq!(runs(&[false; 130]), vec![(false, 130)]);
+    }
+
+    #[test]
+    fn test_validity_runs_sliced() {
+        let nulls = NullBuffer::from(vec![true, false, true, true, false]).slice(1, 3);
+        assert_eq!(
+            ValidityRuns::new(Some(&nulls), 3).collect::<Vec<_>>(),
+            vec![(false, 1), (true, 2)]
+        );
+    }
+
+    #[test]
+    fn test_validity_runs_no_nulls() {
+        assert_eq!(
+            ValidityRuns::new(None, 100).collect::<Vec<_>>(),
+            vec![(true, 100)]
+        );
+        assert_eq!(ValidityRuns::new(None, 0).collect::<Vec<_>>(), vec![]);
+    }
+}
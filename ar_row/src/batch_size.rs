@@ -0,0 +1,169 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to pick the size of record batches from a memory budget
+//!
+//! While a batch is deserialized, both the Arrow arrays and the rows they are
+//! decoded to are in memory, so the batch size given to readers (eg.
+//! `with_batch_size` on ORC and Parquet reader builders) should depend on the size
+//! of both.
+//!
+//! ```
+//! # use ar_row::arrow::datatypes::{DataType, Field, Schema};
+//! use ar_row::batch_size::batch_size_for;
+//!
+//! #[derive(Default)]
+//! struct Row {
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! let schema = Schema::new(vec![
+//!     Field::new("id", DataType::UInt64, false),
+//!     Field::new("name", DataType::Utf8, false),
+//! ]);
+//! // Keep each batch under 64MB
+//! let batch_size = batch_size_for::<Row>(&schema, 64 << 20);
+//! assert!(batch_size > 1000);
+//! ```
+
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+
+/// Assumed average length of strings and binaries, in bytes
+pub const AVERAGE_VARIABLE_WIDTH: usize = 32;
+
+/// Assumed average number of elements in lists and maps
+pub const AVERAGE_LIST_LENGTH: usize = 4;
+
+/// Returns an estimate of the size of a row of a record batch with this schema, in
+/// bytes, based on [`AVERAGE_VARIABLE_WIDTH`] and [`AVERAGE_LIST_LENGTH`].
+pub fn estimated_row_size(schema: &Schema) -> usize {
+    schema
+        .fields()
+        .iter()
+        .map(|field| estimated_value_size(field.data_type()))
+        .sum()
+}
+
+/// Returns an estimate of the size of a single value of the given type in an Arrow
+/// array, in bytes
+pub fn estimated_value_size(datatype: &DataType) -> usize {
+    if let Some(width) = datatype.primitive_width() {
+        return width;
+    }
+    match datatype {
+        DataType::Null => 0,
+        DataType::Boolean => 1,
+        DataType::Utf8 | DataType::Binary => 4 + AVERAGE_VARIABLE_WIDTH,
+        DataType::LargeUtf8 | DataType::LargeBinary => 8 + AVERAGE_VARIABLE_WIDTH,
+        DataType::Utf8View | DataType::BinaryView => 16 + AVERAGE_VARIABLE_WIDTH,
+        DataType::FixedSizeBinary(size) => (*size).try_into().unwrap_or(0),
+        DataType::List(field) | DataType::Map(field, _) => {
+            4 + AVERAGE_LIST_LENGTH * estimated_value_size(field.data_type())
+        }
+        DataType::LargeList(field) => {
+            8 + AVERAGE_LIST_LENGTH * estimated_value_size(field.data_type())
+        }
+        DataType::FixedSizeList(field, size) => {
+            usize::try_from(*size).unwrap_or(0) * estimated_value_size(field.data_type())
+        }
+        DataType::Struct(fields) => fields
+            .iter()
+            .map(|field| estimated_value_size(field.data_type()))
+            .sum(),
+        // Values are shared between rows, so only keys are counted
+        DataType::Dictionary(key, _) => estimated_value_size(key),
+        DataType::Union(fields, _) => {
+            // type id and offset, plus the largest variant
+            1 + 4
+                + fields
+                    .iter()
+                    .map(|(_, field)| estimated_value_size(field.data_type()))
+                    .max()
+                    .unwrap_or(0)
+        }
+        DataType::RunEndEncoded(_, values) => estimated_value_size(values.data_type()),
+        _ => AVERAGE_VARIABLE_WIDTH,
+    }
+}
+
+/// Returns the average size of the rows of `batch`, in bytes, as measured from
+/// the memory used by its arrays.
+///
+/// This is more accurate than [`estimated_row_size`] when `batch` is representative
+/// of the data, eg. when it is the first batch of a file.
+pub fn sampled_row_size(batch: &RecordBatch) -> usize {
+    let memory_size: usize = batch
+        .columns()
+        .iter()
+        .map(|column| column.get_array_memory_size())
+        .sum();
+    memory_size / batch.num_rows().max(1)
+}
+
+/// Returns the number of rows of `row_size` bytes fitting in `memory_budget` bytes,
+/// which is at least 1.
+pub fn batch_size_for_budget(row_size: usize, memory_budget: usize) -> usize {
+    (memory_budget / row_size.max(1)).max(1)
+}
+
+/// Returns the size of batches to read with `schema` and deserialize to `T`, so
+/// that each batch and its deserialized rows fit in `memory_budget` bytes.
+///
+/// Heap allocations of `T` (eg. the content of strings) are assumed to be about the
+/// size of the Arrow values they are deserialized from.
+pub fn batch_size_for<T>(schema: &Schema, memory_budget: usize) -> usize {
+    let arrow_row_size = estimated_row_size(schema);
+    batch_size_for_budget(2 * arrow_row_size + std::mem::size_of::<T>(), memory_budget)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int64Array, StringArray};
+    use arrow::datatypes::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_estimated_row_size() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Boolean, true),
+            Field::new_list("c", Field::new("item", DataType::Int32, false), true),
+        ]);
+        assert_eq!(
+            estimated_row_size(&schema),
+            8 + 1 + 4 + AVERAGE_LIST_LENGTH * 4
+        );
+    }
+
+    #[test]
+    fn test_sampled_row_size() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(Int64Array::from((0..1000).collect::<Vec<_>>())) as ArrayRef,
+            ),
+            (
+                "b",
+                Arc::new(StringArray::from(vec!["x".repeat(100); 1000])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let row_size = sampled_row_size(&batch);
+        assert!(row_size >= 8 + 4 + 100, "{}", row_size);
+        assert!(row_size < 2 * (8 + 4 + 100), "{}", row_size);
+    }
+
+    #[test]
+    fn test_batch_size_for_budget() {
+        assert_eq!(batch_size_for_budget(100, 1000), 10);
+        assert_eq!(batch_size_for_budget(100, 10), 1);
+        assert_eq!(batch_size_for_budget(0, 10), 10);
+    }
+}
@@ -0,0 +1,691 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Command-line tool to convert ORC, Parquet, and Arrow IPC files to JSON or CSV,
+//! inspect and compare their schema and content, and generate Rust structures to
+//! read them
+//!
+//! Requires the `cli` feature.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use ar_row::arrow::datatypes::{DataType, Field, SchemaRef};
+use ar_row::arrow::error::ArrowError;
+use ar_row::arrow::ipc::reader::FileReader;
+use ar_row::arrow::record_batch::{RecordBatch, RecordBatchReader};
+use ar_row::deserialize::{ColumnPath, ColumnTree};
+use ar_row::orc::orc_projection_for_paths;
+use ar_row::parquet::parquet_projection_for_paths;
+use ar_row::projection::project_batch;
+use ar_row::schema::{datatype_diff, rust_struct_definitions, schema_from_json};
+use ar_row::to_csv::{CsvOptions, CsvWriter, ListEncoding};
+use ar_row::to_json::{
+    record_batch_to_json_rows, record_batch_to_json_rows_with_options, BinaryEncoding,
+    ToJsonOptions,
+};
+use ar_row::to_table::{format_table, TableOptions};
+use ar_row::value::ArRowValue;
+use clap::{Parser, Subcommand, ValueEnum};
+use orc_rust::ArrowReaderBuilder;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Converts a file to newline-delimited JSON (one object per row), CSV, or a table
+    Convert(ConvertArgs),
+    /// Prints the Arrow schema of a file, and Rust structures matching it
+    Schema(SchemaArgs),
+    /// Prints rows of a file as a table
+    Cat(CatArgs),
+    /// Same as `schema`, but prints only the Rust structures, eg. to write them
+    /// to a source file
+    Codegen(SchemaArgs),
+    /// Compares two files row by row, and prints the rows which differ
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args)]
+struct CatArgs {
+    /// File to read
+    input: PathBuf,
+    /// Format of the input file; guessed from its extension if omitted
+    #[arg(long)]
+    format: Option<InputFormat>,
+    /// Comma-separated list of columns to print, in this order, with dots to select
+    /// nested fields (eg. `a,b.c`) and backslashes to escape dots in field names
+    /// (eg. `a\.b`). Defaults to all columns.
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+    /// Number of rows to skip
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    /// Maximum number of rows to print
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+}
+
+#[derive(clap::Args)]
+struct SchemaArgs {
+    /// File to read the schema of
+    input: PathBuf,
+    /// Format of the input file; guessed from its extension if omitted
+    #[arg(long)]
+    format: Option<InputFormat>,
+    /// Name of the top-level Rust structure
+    #[arg(long, default_value = "Row")]
+    name: String,
+    /// File to write to, instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// First file to read
+    left: PathBuf,
+    /// Second file to read, in the same or another format
+    right: PathBuf,
+    /// Format of both input files; guessed from their extensions if omitted
+    #[arg(long)]
+    format: Option<InputFormat>,
+    /// Comma-separated list of columns to compare, with dots to select nested fields
+    /// (eg. `a,b.c`) and backslashes to escape dots in field names (eg. `a\.b`).
+    /// Defaults to all columns.
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+    /// Maximum number of differing rows to print; all of them are counted
+    #[arg(long, default_value_t = 10)]
+    max_diffs: usize,
+}
+
+#[derive(clap::Args)]
+struct ConvertArgs {
+    /// File to read
+    input: PathBuf,
+    /// Format of the input file; guessed from its extension if omitted
+    #[arg(long)]
+    format: Option<InputFormat>,
+    /// Comma-separated list of columns to write, in this order, with dots to select
+    /// nested fields (eg. `a,b.c`) and backslashes to escape dots in field names
+    /// (eg. `a\.b`). Defaults to all columns.
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+    /// Number of rows to skip
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    /// Maximum number of rows to write
+    #[arg(long)]
+    limit: Option<usize>,
+    /// Number of rows per record batch read from the input
+    #[arg(long, default_value_t = 1024)]
+    batch_size: usize,
+    /// Format to write
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    output_format: OutputFormat,
+    /// Representation of binary values. Defaults to `array` for JSON, and `base64`
+    /// for CSV.
+    #[arg(long, value_enum)]
+    binary: Option<BinaryArg>,
+    /// With `--output-format csv`, join list items with this separator instead of
+    /// writing lists as JSON
+    #[arg(long)]
+    list_separator: Option<String>,
+    /// File to write to, instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InputFormat {
+    Orc,
+    Parquet,
+    Ipc,
+    /// Arrow schema serialized with `ar_row::schema::schema_to_json` (only for
+    /// `schema` and `codegen`)
+    SchemaJson,
+}
+
+impl InputFormat {
+    fn from_path(path: &Path) -> Option<InputFormat> {
+        match path.extension()?.to_str()? {
+            "orc" => Some(InputFormat::Orc),
+            "parquet" | "pq" => Some(InputFormat::Parquet),
+            "arrow" | "ipc" | "feather" => Some(InputFormat::Ipc),
+            "json" => Some(InputFormat::SchemaJson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+    /// Aligned text table, which needs to hold all rows in memory
+    Table,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BinaryArg {
+    Array,
+    Base64,
+    Hex,
+    Utf8,
+}
+
+impl From<BinaryArg> for BinaryEncoding {
+    fn from(arg: BinaryArg) -> BinaryEncoding {
+        match arg {
+            BinaryArg::Array => BinaryEncoding::Array,
+            BinaryArg::Base64 => BinaryEncoding::Base64,
+            BinaryArg::Hex => BinaryEncoding::Hex,
+            BinaryArg::Utf8 => BinaryEncoding::LossyUtf8,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            // Like diff(1), so scripts can tell errors from differing files
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs `command`, and returns `false` if it found differences between files
+fn run(command: Command) -> Result<bool, Box<dyn Error>> {
+    match command {
+        Command::Convert(args) => convert(args).map(|()| true),
+        Command::Schema(args) => schema(args, true).map(|()| true),
+        Command::Codegen(args) => schema(args, false).map(|()| true),
+        Command::Diff(args) => diff(args),
+        Command::Cat(args) => convert(ConvertArgs {
+            input: args.input,
+            format: args.format,
+            columns: args.columns,
+            offset: args.offset,
+            limit: Some(args.limit),
+            batch_size: args.limit.clamp(1, 1024),
+            output_format: OutputFormat::Table,
+            binary: None,
+            list_separator: None,
+            output: None,
+        })
+        .map(|()| true),
+    }
+}
+
+fn input_format(input: &Path, format: Option<InputFormat>) -> Result<InputFormat, Box<dyn Error>> {
+    match format.or_else(|| InputFormat::from_path(input)) {
+        Some(format) => Ok(format),
+        None => Err("Could not guess input format, use --format".into()),
+    }
+}
+
+/// Opens `path`, reading only the top-level columns (or, for Parquet, leaves)
+/// needed for `columns`.
+///
+/// Batches of Arrow IPC files are set when writing them, so they are split in
+/// batches of at most `batch_size` rows, but not merged.
+fn open(
+    path: &Path,
+    format: InputFormat,
+    columns: &[ColumnPath],
+    batch_size: usize,
+) -> Result<Box<dyn RecordBatchReader>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    Ok(match format {
+        InputFormat::Orc => {
+            let mut builder = ArrowReaderBuilder::try_new(file)?.with_batch_size(batch_size);
+            if !columns.is_empty() {
+                let projection =
+                    orc_projection_for_paths(builder.file_metadata().root_data_type(), columns);
+                builder = builder.with_projection(projection);
+            }
+            Box::new(builder.build())
+        }
+        InputFormat::Parquet => {
+            let mut builder =
+                ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(batch_size);
+            if !columns.is_empty() {
+                let projection = parquet_projection_for_paths(builder.parquet_schema(), columns);
+                builder = builder.with_projection(projection);
+            }
+            Box::new(builder.build()?)
+        }
+        InputFormat::Ipc => {
+            let mut reader = FileReader::try_new(file, None)?;
+            if !columns.is_empty() {
+                let schema = reader.schema();
+                let mut projection: Vec<usize> = columns
+                    .iter()
+                    .filter_map(|column| column.parts().first())
+                    .filter_map(|name| schema.index_of(name).ok())
+                    .collect();
+                projection.sort_unstable();
+                projection.dedup();
+                reader = FileReader::try_new(File::open(path)?, Some(projection))?;
+            }
+            Box::new(SplitBatches {
+                reader,
+                batch_size: batch_size.max(1),
+                batch: None,
+                offset: 0,
+            })
+        }
+        InputFormat::SchemaJson => {
+            return Err(format!("{} contains a schema, not rows", path.display()).into())
+        }
+    })
+}
+
+/// Splits batches returned by `reader` into batches of at most `batch_size` rows
+struct SplitBatches<R: RecordBatchReader> {
+    reader: R,
+    batch_size: usize,
+    batch: Option<RecordBatch>,
+    /// Index in `batch` of the first row of the next batch to return
+    offset: usize,
+}
+
+impl<R: RecordBatchReader> Iterator for SplitBatches<R> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(batch) = &self.batch {
+                if self.offset < batch.num_rows() {
+                    let length = self.batch_size.min(batch.num_rows() - self.offset);
+                    let slice = batch.slice(self.offset, length);
+                    self.offset += length;
+                    return Some(Ok(slice));
+                }
+            }
+            self.batch = match self.reader.next()? {
+                Ok(batch) => Some(batch),
+                Err(e) => return Some(Err(e)),
+            };
+            self.offset = 0;
+        }
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for SplitBatches<R> {
+    fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+}
+
+/// Returns the schema of the file at `path`
+fn read_schema(path: &Path, format: InputFormat) -> Result<SchemaRef, Box<dyn Error>> {
+    match format {
+        InputFormat::SchemaJson => {
+            let json = std::fs::read_to_string(path)?;
+            Ok(Arc::new(schema_from_json(&json)?))
+        }
+        _ => Ok(open(path, format, &[], 1)?.schema()),
+    }
+}
+
+/// Parses dot-separated column names given on the command line
+/// (see [`ColumnPath::parse`])
+fn parse_columns(columns: &[String]) -> Vec<ColumnPath> {
+    columns
+        .iter()
+        .map(|column| ColumnPath::parse(column))
+        .collect()
+}
+
+fn convert(args: ConvertArgs) -> Result<(), Box<dyn Error>> {
+    let format = input_format(&args.input, args.format)?;
+    let columns = parse_columns(&args.columns);
+    let tree = ColumnTree::from_paths(&columns);
+    let output: BufWriter<Box<dyn Write>> = BufWriter::new(match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    });
+    let mut output = match args.output_format {
+        OutputFormat::Json => {
+            let binary = args.binary.unwrap_or(BinaryArg::Array).into();
+            Output::Json(
+                output,
+                ToJsonOptions {
+                    binary,
+                    fixed_size_binary: binary,
+                },
+            )
+        }
+        OutputFormat::Csv => Output::Csv(CsvWriter::new(
+            output,
+            CsvOptions {
+                lists: match &args.list_separator {
+                    Some(separator) => ListEncoding::Join(separator.clone()),
+                    None => ListEncoding::Json,
+                },
+                binary: args.binary.unwrap_or(BinaryArg::Base64).into(),
+                ..Default::default()
+            },
+        )),
+        OutputFormat::Table => Output::Table(
+            output,
+            TableOptions {
+                binary: args.binary.unwrap_or(BinaryArg::Hex).into(),
+                ..Default::default()
+            },
+            Vec::new(),
+        ),
+    };
+
+    let mut to_skip = args.offset;
+    let mut remaining = args.limit.unwrap_or(usize::MAX);
+    for batch in open(&args.input, format, &columns, args.batch_size)? {
+        if remaining == 0 {
+            break;
+        }
+        let mut batch = batch?;
+        if to_skip >= batch.num_rows() {
+            to_skip -= batch.num_rows();
+            continue;
+        }
+        let length = (batch.num_rows() - to_skip).min(remaining);
+        batch = batch.slice(to_skip, length);
+        to_skip = 0;
+        remaining -= length;
+
+        if !columns.is_empty() {
+            batch = project_batch(&batch, &tree)?;
+        }
+        output.write_batch(&batch)?;
+    }
+    output.finish()
+}
+
+enum Output<W: Write> {
+    Json(W, ToJsonOptions),
+    Csv(CsvWriter<W>),
+    /// Batches are buffered until the end, to compute the width of columns
+    Table(W, TableOptions, Vec<RecordBatch>),
+}
+
+impl<W: Write> Output<W> {
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), Box<dyn Error>> {
+        match self {
+            Output::Json(writer, options) => {
+                for row in record_batch_to_json_rows_with_options(batch, options)? {
+                    serde_json::to_writer(&mut *writer, &row)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            Output::Csv(writer) => writer.write_batch(batch)?,
+            Output::Table(_, _, batches) => batches.push(batch.clone()),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        let mut writer = match self {
+            Output::Json(writer, _) => writer,
+            Output::Csv(writer) => writer.into_inner()?,
+            Output::Table(mut writer, options, batches) => {
+                writer.write_all(format_table(&batches, &options)?.as_bytes())?;
+                writer
+            }
+        };
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes Rust structures matching the schema of a file, preceded by the Arrow
+/// schema if `with_arrow_schema` is true
+fn schema(args: SchemaArgs, with_arrow_schema: bool) -> Result<(), Box<dyn Error>> {
+    let format = input_format(&args.input, args.format)?;
+    let schema = read_schema(&args.input, format)?;
+    let mut output: BufWriter<Box<dyn Write>> = BufWriter::new(match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    });
+    if with_arrow_schema {
+        writeln!(output, "Arrow schema:")?;
+        writeln!(output)?;
+        for field in schema.fields() {
+            write_field(&mut output, field, 1)?;
+        }
+        writeln!(output)?;
+        writeln!(output, "Rust structures:")?;
+        writeln!(output)?;
+    }
+    writeln!(output, "use ar_row_derive::ArRowDeserialize;")?;
+    writeln!(output)?;
+    write!(output, "{}", rust_struct_definitions(&schema, &args.name))?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Returns whether the files have the same rows
+fn diff(args: DiffArgs) -> Result<bool, Box<dyn Error>> {
+    let columns = parse_columns(&args.columns);
+    let mut left = FileRows::open(&args.left, args.format, &columns)?;
+    let mut right = FileRows::open(&args.right, args.format, &columns)?;
+
+    let left_type = DataType::Struct(left.reader.schema().fields().clone());
+    let right_type = DataType::Struct(right.reader.schema().fields().clone());
+    if left_type != right_type {
+        // Rows may still be equal, eg. if a writer uses LargeUtf8 instead of Utf8
+        eprintln!("Schemas differ:");
+        eprintln!();
+        eprintln!("{}", datatype_diff(&left_type, &right_type));
+        eprintln!();
+    }
+
+    let mut num_rows = 0;
+    let mut num_diffs = 0;
+    let mut only_left = 0;
+    let mut only_right = 0;
+    loop {
+        match (left.next()?, right.next()?) {
+            (None, None) => break,
+            (Some(_), None) => only_left += 1,
+            (None, Some(_)) => only_right += 1,
+            (Some(left_value), Some(right_value)) => {
+                if !values_equal(&left_value, &right_value) {
+                    if num_diffs < args.max_diffs {
+                        print_row_diff(num_rows, &left, &right)?;
+                    }
+                    num_diffs += 1;
+                }
+                num_rows += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} rows compared, {} differ; {} rows only in {}, {} rows only in {}",
+        num_rows,
+        num_diffs,
+        only_left,
+        args.left.display(),
+        only_right,
+        args.right.display()
+    );
+    Ok(num_diffs + only_left + only_right == 0)
+}
+
+/// Same as `==`, but NaN floats are equal to each other, so that files containing
+/// NaN values are identical to themselves
+fn values_equal(left: &ArRowValue, right: &ArRowValue) -> bool {
+    match (left, right) {
+        (ArRowValue::Float32(left), ArRowValue::Float32(right)) => {
+            left == right || (left.is_nan() && right.is_nan())
+        }
+        (ArRowValue::Float64(left), ArRowValue::Float64(right)) => {
+            left == right || (left.is_nan() && right.is_nan())
+        }
+        (ArRowValue::List(left), ArRowValue::List(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| values_equal(left, right))
+        }
+        (ArRowValue::Map(left), ArRowValue::Map(right)) => {
+            left.len() == right.len()
+                && left.iter().zip(right).all(|(left, right)| {
+                    values_equal(&left.0, &right.0) && values_equal(&left.1, &right.1)
+                })
+        }
+        (ArRowValue::Struct(left), ArRowValue::Struct(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| left.0 == right.0 && values_equal(&left.1, &right.1))
+        }
+        (
+            ArRowValue::Union {
+                variant: left_variant,
+                value: left,
+            },
+            ArRowValue::Union {
+                variant: right_variant,
+                value: right,
+            },
+        ) => left_variant == right_variant && values_equal(left, right),
+        _ => left == right,
+    }
+}
+
+/// Prints the columns which differ between the last rows returned by `left` and
+/// `right`, as JSON
+fn print_row_diff(row: usize, left: &FileRows, right: &FileRows) -> Result<(), Box<dyn Error>> {
+    let left_row = left.last_row_as_json()?;
+    let right_row = right.last_row_as_json()?;
+    println!("Row {}:", row);
+    for (name, left_value) in left_row.iter() {
+        let right_value = right_row.get(name).unwrap_or(&serde_json::Value::Null);
+        if left_value != right_value {
+            println!("    {}: {} | {}", name, left_value, right_value);
+        }
+    }
+    for (name, right_value) in right_row.iter() {
+        if !left_row.contains_key(name) {
+            println!("    {}: (missing) | {}", name, right_value);
+        }
+    }
+    Ok(())
+}
+
+/// Rows of a file, deserialized to [`ArRowValue`], with the batch they were read from
+struct FileRows {
+    reader: Box<dyn RecordBatchReader>,
+    /// Columns to compare, or `None` to compare all of them
+    columns: Option<ColumnTree>,
+    batch: Option<RecordBatch>,
+    values: std::vec::IntoIter<ArRowValue>,
+    /// Index in `batch` of the next row
+    next_index: usize,
+}
+
+impl FileRows {
+    fn open(
+        path: &Path,
+        format: Option<InputFormat>,
+        columns: &[ColumnPath],
+    ) -> Result<FileRows, Box<dyn Error>> {
+        let format = input_format(path, format)?;
+        Ok(FileRows {
+            reader: open(path, format, columns, 1024)?,
+            columns: if columns.is_empty() {
+                None
+            } else {
+                Some(ColumnTree::from_paths(columns))
+            },
+            batch: None,
+            values: Vec::new().into_iter(),
+            next_index: 0,
+        })
+    }
+
+    fn next(&mut self) -> Result<Option<ArRowValue>, Box<dyn Error>> {
+        loop {
+            if let Some(value) = self.values.next() {
+                self.next_index += 1;
+                return Ok(Some(value));
+            }
+            let mut batch = match self.reader.next() {
+                Some(batch) => batch?,
+                None => return Ok(None),
+            };
+            if let Some(columns) = &self.columns {
+                batch = project_batch(&batch, columns)?;
+            }
+            self.values = ArRowValue::from_record_batch(batch.clone())?.into_iter();
+            self.batch = Some(batch);
+            self.next_index = 0;
+        }
+    }
+
+    /// Returns the row last returned by [`FileRows::next`], as a JSON object
+    fn last_row_as_json(
+        &self,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error>> {
+        let batch = self
+            .batch
+            .as_ref()
+            .expect("last_row_as_json called before next")
+            .slice(self.next_index - 1, 1);
+        match record_batch_to_json_rows(&batch)?.pop() {
+            Some(serde_json::Value::Object(row)) => Ok(row),
+            row => unreachable!("Unexpected JSON row: {:?}", row),
+        }
+    }
+}
+
+fn write_field(output: &mut impl Write, field: &Field, depth: usize) -> std::io::Result<()> {
+    let indent = "    ".repeat(depth);
+    let nullable = if field.is_nullable() {
+        ", nullable"
+    } else {
+        ""
+    };
+    match field.data_type() {
+        DataType::Struct(fields) => {
+            writeln!(output, "{}{}: Struct{}", indent, field.name(), nullable)?;
+            for child in fields {
+                write_field(output, child, depth + 1)?;
+            }
+        }
+        DataType::List(item) | DataType::LargeList(item) => {
+            writeln!(output, "{}{}: List{}", indent, field.name(), nullable)?;
+            write_field(output, item, depth + 1)?;
+        }
+        datatype => writeln!(
+            output,
+            "{}{}: {}{}",
+            indent,
+            field.name(),
+            datatype,
+            nullable
+        )?,
+    }
+    Ok(())
+}
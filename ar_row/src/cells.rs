@@ -0,0 +1,55 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Flattening of rows to tables of strings, shared by the CSV and table outputs
+
+use arrow::datatypes::{DataType, Fields};
+use serde_json::Value;
+
+use crate::deserialize::ColumnPath;
+
+/// Returns the path to every column of the table, by recursing into structs
+pub(crate) fn flattened_columns(fields: &Fields) -> Vec<ColumnPath> {
+    let mut columns = Vec::new();
+    for field in fields {
+        let path = ColumnPath(vec![field.name().clone()]);
+        match field.data_type() {
+            DataType::Struct(children) => columns.extend(
+                flattened_columns(children)
+                    .into_iter()
+                    .map(|child| ColumnPath([path.0.clone(), child.0].concat())),
+            ),
+            _ => columns.push(path),
+        }
+    }
+    columns
+}
+
+/// Returns the value nested in `row` at `path`, or `Value::Null` if any of the
+/// structs on the way is null
+pub(crate) fn lookup<'a>(row: &'a Value, path: &[String]) -> &'a Value {
+    path.iter()
+        .try_fold(row, |value, part| value.get(part))
+        .unwrap_or(&Value::Null)
+}
+
+/// Returns the content of the cell for `value`.
+///
+/// Lists are joined with `list_separator` if given, and serialized as JSON otherwise
+pub(crate) fn cell(value: &Value, list_separator: Option<&str>) -> String {
+    match (value, list_separator) {
+        (Value::Null, _) => String::new(),
+        (Value::String(s), _) => s.clone(),
+        (Value::Array(items), Some(separator)) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) => item.to_string(),
+                _ => cell(item, list_separator),
+            })
+            .collect::<Vec<_>>()
+            .join(separator),
+        (value, _) => value.to_string(),
+    }
+}
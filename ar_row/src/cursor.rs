@@ -0,0 +1,193 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Access to rows without materializing them
+//!
+//! [`ArRowCursor<T>`] wraps a struct array, and yields a reference to each row
+//! instead of a `T`. For structures `T` with the `#[ar_row(cursor)]` attribute,
+//! `#[derive(ArRowDeserialize)]` generates a `TRef<'a>` type (and implements
+//! [`CursorRow`]) with one method per field, which decodes that field for that row
+//! only. This is cheaper than deserializing whole rows when only one or two fields
+//! are read per row.
+//!
+//! Row references also dereference to [`RowRef`], which gives access to the Arrow
+//! columns themselves, for zero-copy access to their values.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::cursor::ArRowCursor;
+//! use ar_row_derive::ArRowDeserialize;
+//!
+//! #[derive(ArRowDeserialize, Default)]
+//! #[ar_row(cursor)]
+//! struct Row {
+//!     id: u64,
+//!     name: Option<String>,
+//! }
+//!
+//! let batch = RecordBatch::try_from_iter(vec![
+//!     ("id", Arc::new(UInt64Array::from(vec![1, 2])) as ArrayRef),
+//!     ("name", Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef),
+//! ])
+//! .unwrap();
+//!
+//! let cursor = ArRowCursor::<Row>::from_record_batch(batch).unwrap();
+//! let ids: Vec<u64> = cursor.iter().map(|row| row.id().unwrap()).collect();
+//! assert_eq!(ids, vec![1, 2]);
+//! assert_eq!(cursor.get(0).unwrap().name(), Ok(Some("foo".to_owned())));
+//! assert_eq!(cursor.get(2).map(|_| ()), None);
+//! ```
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::NullBuffer;
+use arrow::record_batch::RecordBatch;
+
+use crate::deserialize::{ArRowDeserialize, CheckableDataType, DeserializationError};
+use crate::dictionaries::{unpack_dictionary, unpack_run_end_encoded};
+
+/// Structures whose rows can be accessed through an [`ArRowCursor`]
+///
+/// This is implemented by `#[derive(ArRowDeserialize)]`.
+pub trait CursorRow: CheckableDataType {
+    /// Reference to a row, with a method to decode each field
+    type Ref<'a>: From<RowRef<'a>>;
+}
+
+/// Reference to a row of a struct array
+///
+/// See the [module-level documentation](self).
+#[derive(Clone, Copy, Debug)]
+pub struct RowRef<'a> {
+    columns: &'a [ArrayRef],
+    nulls: Option<&'a NullBuffer>,
+    index: usize,
+}
+
+impl<'a> RowRef<'a> {
+    /// Returns the index of the row in the array
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns whether the structure itself is null in this row
+    pub fn is_null(&self) -> bool {
+        self.nulls
+            .map(|nulls| nulls.is_null(self.index))
+            .unwrap_or(false)
+    }
+
+    /// Returns the `i`-th column of the array. The value of this row is at
+    /// [`index`](Self::index).
+    ///
+    /// # Panics
+    ///
+    /// If there are not more than `i` columns.
+    pub fn column(&self, i: usize) -> &'a ArrayRef {
+        &self.columns[i]
+    }
+
+    /// Decodes the value of this row in the `i`-th column, without decoding other
+    /// rows. Used by code generated by `ar_row_derive`.
+    ///
+    /// The column is sliced, which does not copy its buffers, and the value is
+    /// decoded in place instead of through a vector.
+    #[doc(hidden)]
+    pub fn read<F: ArRowDeserialize>(&self, i: usize) -> Result<F, DeserializationError> {
+        let mut value: [F; 1] = Default::default();
+        F::read_from_array(self.columns[i].slice(self.index, 1), &mut value)?;
+        let [value] = value;
+        Ok(value)
+    }
+}
+
+/// Cursor over the rows of a struct array, yielding references to its rows
+///
+/// See the [module-level documentation](self).
+pub struct ArRowCursor<T: CursorRow> {
+    array: StructArray,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: CursorRow> Clone for ArRowCursor<T> {
+    fn clone(&self) -> Self {
+        ArRowCursor {
+            array: self.array.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: CursorRow> ArRowCursor<T> {
+    /// Returns a cursor over `array`, after checking its type matches `T`
+    pub fn new(array: impl Array + AsArray) -> Result<Self, DeserializationError> {
+        if let Some(array) = array.as_any_dictionary_opt() {
+            return Self::new(unpack_dictionary(array)?);
+        }
+        if let Some(array) = unpack_run_end_encoded(&array)? {
+            return Self::new(array);
+        }
+        T::check_datatype(array.data_type())
+            .map_err(DeserializationError::MismatchedColumnDataType)?;
+        let array = array.as_struct_opt().ok_or_else(|| {
+            DeserializationError::MismatchedColumnDataType(format!(
+                "Could not cast {:?} array to struct array",
+                array.data_type(),
+            ))
+        })?;
+        Ok(ArRowCursor {
+            array: array.clone(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns a cursor over the rows of `batch`, after checking its schema
+    /// matches `T`
+    pub fn from_record_batch(batch: RecordBatch) -> Result<Self, DeserializationError> {
+        Self::new(Arc::new(StructArray::from(batch)) as ArrayRef)
+    }
+
+    /// Returns the number of rows
+    pub fn len(&self) -> usize {
+        self.array.len()
+    }
+
+    /// Returns whether there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
+    /// Returns the struct array rows are read from
+    pub fn array(&self) -> &StructArray {
+        &self.array
+    }
+
+    /// Returns a reference to the row at `index`, or `None` if out of bounds
+    pub fn get(&self, index: usize) -> Option<T::Ref<'_>> {
+        if index < self.len() {
+            Some(self.row_ref(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns references to every row, in order
+    pub fn iter(&self) -> impl Iterator<Item = T::Ref<'_>> + '_ {
+        (0..self.len()).map(move |index| self.row_ref(index))
+    }
+
+    fn row_ref(&self, index: usize) -> T::Ref<'_> {
+        RowRef {
+            columns: self.array.columns(),
+            nulls: self.array.nulls(),
+            index,
+        }
+        .into()
+    }
+}
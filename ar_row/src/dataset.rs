@@ -0,0 +1,122 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading datasets split into many files, in parallel
+//!
+//! Datasets (such as Software Heritage's exports) are usually directories of ORC or
+//! Parquet files with the same schema. [`par_read_dir`] lists such a directory and
+//! reads all its files with Rayon; [`par_read_files`] and [`par_send_rows`] take a list
+//! of files instead, eg. from a glob pattern expanded with the `glob` crate.
+//!
+//! Each file is opened with [`open_rows`], so its format is detected from its first
+//! bytes and its schema is checked against `T` before reading it. Errors name the
+//! file which could not be read.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use arrow::error::ArrowError;
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct};
+use crate::open::open_rows;
+use crate::reader::ReadError;
+
+/// Error returned when a file of a dataset could not be read
+#[derive(Debug, Error)]
+#[error("Could not read {}: {source}", .path.display())]
+pub struct FileError {
+    /// Path of the file (or of the directory, if it could not be listed)
+    pub path: PathBuf,
+    pub source: ReadError,
+}
+
+/// Returns the files in `dir` whose name ends with `suffix` (eg. `".orc"`), sorted
+/// by name. Subdirectories and hidden files are skipped.
+pub fn list_files(dir: impl AsRef<Path>, suffix: &str) -> Result<Vec<PathBuf>, FileError> {
+    let dir = dir.as_ref();
+    let error = |e: std::io::Error| FileError {
+        path: dir.to_owned(),
+        source: ArrowError::from(e).into(),
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') || !file_name.ends_with(suffix) {
+            continue;
+        }
+        if entry.file_type().map_err(error)?.is_dir() {
+            continue;
+        }
+        paths.push(entry.path());
+    }
+    paths.sort_unstable();
+    Ok(paths)
+}
+
+/// Reads all rows of all files in `paths` in parallel, and returns them in the
+/// order of `paths`.
+///
+/// If any file cannot be read, returns the error of one of them.
+pub fn par_read_files<T: ArRowDeserialize + ArRowStruct + Send + 'static>(
+    paths: &[PathBuf],
+) -> Result<Vec<T>, FileError> {
+    let files: Vec<Vec<T>> = paths
+        .par_iter()
+        .map(|path| {
+            read_file(path).map_err(|source| FileError {
+                path: path.clone(),
+                source,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(files.into_iter().flatten().collect())
+}
+
+/// Lists files in `dir` whose name ends with `suffix` with [`list_files`], and
+/// reads them with [`par_read_files`]
+pub fn par_read_dir<T: ArRowDeserialize + ArRowStruct + Send + 'static>(
+    dir: impl AsRef<Path>,
+    suffix: &str,
+) -> Result<Vec<T>, FileError> {
+    par_read_files(&list_files(dir, suffix)?)
+}
+
+/// Reads all rows of all files in `paths` in parallel, and sends them to `sender`
+/// as they are read.
+///
+/// Rows of each file are sent in order, but rows of different files are interleaved.
+/// If any file cannot be read, stops reading other files and returns its error.
+/// Reading also stops early if the receiver is dropped.
+pub fn par_send_rows<T: ArRowDeserialize + ArRowStruct + Send + 'static>(
+    paths: &[PathBuf],
+    sender: Sender<T>,
+) -> Result<(), FileError> {
+    paths
+        .par_iter()
+        .map_with(sender, |sender, path| {
+            let error = |source| FileError {
+                path: path.clone(),
+                source,
+            };
+            for row in open_rows::<T>(path).map_err(error)? {
+                if sender.send(row.map_err(error)?).is_err() {
+                    // Receiver was dropped, nobody needs the other rows
+                    break;
+                }
+            }
+            Ok(())
+        })
+        .collect()
+}
+
+fn read_file<T: ArRowDeserialize + ArRowStruct + 'static>(
+    path: &Path,
+) -> Result<Vec<T>, ReadError> {
+    open_rows(path)?.collect()
+}
@@ -0,0 +1,65 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading [Delta Lake](https://delta.io/) tables with the [`deltalake`] crate
+//!
+//! Data files of the current version of the table are read with
+//! [`open_parquet_rows`], so only the columns `T` is deserialized from are read.
+//! Partition columns (which are not stored in data files) are not supported, and
+//! tables with [deletion vectors](https://github.com/delta-io/delta/blob/master/PROTOCOL.md#deletion-vectors)
+//! are rejected, as their deleted rows would be returned.
+//!
+//! Requires the `delta` feature.
+
+use arrow::error::ArrowError;
+use deltalake::DeltaTableError;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct};
+use crate::object_store::open_parquet_rows;
+use crate::reader::ReadError;
+
+fn delta_error(e: DeltaTableError) -> ReadError {
+    ReadError::Arrow(ArrowError::ExternalError(Box::new(e)))
+}
+
+/// Opens the Delta table at `table_uri`, and returns a stream of the rows of
+/// all its data files.
+///
+/// The schema of each data file is checked against `T` before reading its rows.
+///
+/// Returns [`ArrowError::NotYetImplemented`] if a data file has a deletion vector.
+pub async fn open_delta_rows<T>(
+    table_uri: &str,
+) -> Result<BoxStream<'static, Result<T, ReadError>>, ReadError>
+where
+    T: ArRowDeserialize + ArRowStruct + Send + 'static,
+{
+    let table = deltalake::open_table(table_uri)
+        .await
+        .map_err(delta_error)?;
+    let snapshot = table.snapshot().map_err(delta_error)?;
+    if let Some(add) = snapshot
+        .file_actions()
+        .map_err(delta_error)?
+        .into_iter()
+        .find(|add| add.deletion_vector.is_some())
+    {
+        return Err(ReadError::Arrow(ArrowError::NotYetImplemented(format!(
+            "Data file {} has a deletion vector, which is not supported",
+            add.path
+        ))));
+    }
+    let store = table.object_store();
+    let files: Vec<_> = table.get_files_iter().map_err(delta_error)?.collect();
+
+    Ok(stream::iter(files)
+        .then(move |path| {
+            let store = store.clone();
+            async move { open_parquet_rows::<T>(store, &path).await }
+        })
+        .try_flatten()
+        .boxed())
+}
@@ -11,21 +11,33 @@ use std::convert::{TryFrom, TryInto};
 use std::sync::Arc;
 
 use arrow::array::*;
+use arrow::buffer::BooleanBuffer;
 use arrow::datatypes::*;
 //use rust_decimal::Decimal;
 use thiserror::Error;
 
+use std::any::{Any, TypeId};
 use std::iter::Map;
 use std::num::TryFromIntError;
+use std::ops::Range;
 use std::slice::IterMut;
 
-use crate::array_iterators::{NotNullArrayIter, NullableValuesIterator};
-use crate::dictionaries::{read_from_dictionary_array, read_options_from_dictionary_array};
-use crate::{Date, FixedSizeBinary, NaiveDecimal128, Timestamp};
+use crate::array_iterators::{NotNullArrayIter, NullableValuesIterator, ValidityRuns};
+use crate::dictionaries::{
+    decoded_datatype, read_from_cached_dictionary_array, read_options_from_cached_dictionary_array,
+    unpack_dictionary, unpack_run_end_encoded,
+};
+use crate::options::DeserializeOptions;
+use crate::schema::SchemaOptions;
+use crate::{
+    Date, DecimalF64, DisplayString, EpochMillis, EpochNanos, FixedSizeBinary, FixedSizeList,
+    NaiveDecimal128, SharedString, Timestamp,
+};
 
 const DECIMAL_PRECISION: u8 = 38;
 const DECIMAL_SCALE: i8 = 9;
-const TIMESTAMP_DECIMAL128_TYPE: DataType = DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE);
+pub(crate) const TIMESTAMP_DECIMAL128_TYPE: DataType =
+    DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE);
 
 /// Error returned when failing to read a particular batch of data
 #[derive(Debug, Error, PartialEq)]
@@ -68,13 +80,21 @@ pub enum DeserializationError {
     /// Could not convert [`Decimal128Type`] to [`Timestamp`]
     #[error("Could not represent number of seconds ({seconds}) as a 64-bits signed integer")]
     TimestampOverflow { seconds: i128 },
+    /// Could not convert a timestamp to [`EpochMillis`] or [`EpochNanos`]
+    #[error("Could not represent timestamp {value} ({from:?}) in {to:?} as a 64-bits integer")]
+    EpochOverflow {
+        value: i64,
+        from: TimeUnit,
+        to: TimeUnit,
+    },
 }
 
-fn check_datatype_equals(
+pub(crate) fn check_datatype_equals(
     got_datatype: &DataType,
     expected_datatypes: &[DataType],
     type_name: &str,
 ) -> Result<(), String> {
+    let got_datatype = decoded_datatype(got_datatype);
     if expected_datatypes.contains(got_datatype) {
         Ok(())
     } else {
@@ -105,11 +125,79 @@ pub trait CheckableDataType {
     /// Returns whether records of the type can be deserialized from
     /// a [`RecordBatch`] with the given schema
     ///
+    /// Types which are not deserialized from a structure can also be deserialized
+    /// from record batches with a single column (see
+    /// [`reader_schema`](Self::reader_schema)).
+    ///
     /// This should be called before any method provided by [`ArRowDeserialize`],
     /// to get errors early and with a human-readable error message instead of cast errors
     /// or deserialization into incorrect types (eg. if a file has two fields swapped).
+    /// When `Self` is deserialized from a structure, the error ends with a
+    /// [`datatype_diff`](crate::schema::datatype_diff) of the expected and actual schemas.
     fn check_schema(schema: &Schema) -> Result<(), String> {
-        Self::check_datatype(&DataType::Struct(schema.fields().clone()))
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "check_schema",
+            type_name = std::any::type_name::<Self>(),
+            columns = schema.fields().len()
+        )
+        .entered();
+
+        let mut result = Self::check_datatype(&DataType::Struct(schema.fields().clone()));
+        if let (Err(_), [field]) = (&result, &schema.fields()[..]) {
+            if Self::check_datatype(field.data_type()).is_ok() {
+                result = Ok(());
+            }
+        }
+        if let Err(e) = &mut result {
+            let expected = Self::expected_datatype();
+            if let DataType::Struct(_) = expected {
+                let actual = DataType::Struct(schema.fields().clone());
+                let diff = crate::schema::datatype_diff(&expected, &actual);
+                *e = format!("{e}\n\n{diff}");
+            }
+        }
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &result {
+            tracing::debug!(error = e.as_str(), "schema does not match");
+        }
+        result
+    }
+
+    /// Returns the data type of Arrow arrays this type is deserialized from.
+    ///
+    /// When [`check_datatype`](Self::check_datatype) accepts several data types,
+    /// this returns the most common one.
+    ///
+    /// Defaults to [`DataType::Null`], for implementations written before this method
+    /// was added. Such types have no meaningful [`arrow_schema`](Self::arrow_schema),
+    /// and [`check_schema`](Self::check_schema) errors do not include a schema diff.
+    fn expected_datatype() -> DataType {
+        DataType::Null
+    }
+
+    /// Returns whether this type can be deserialized from Arrow arrays containing nulls
+    fn nullable() -> bool {
+        false
+    }
+
+    /// Returns the schema of record batches this type is deserialized from, using
+    /// the default [`SchemaOptions`]
+    fn arrow_schema() -> Schema {
+        Self::reader_schema(&SchemaOptions::default())
+    }
+
+    /// Returns a schema of record batches this type can be deserialized from,
+    /// which can be given to readers to override the types of columns they return
+    /// (eg. `orc_rust::ArrowReaderBuilder::with_schema`).
+    ///
+    /// Types which are not deserialized from a structure are represented by a
+    /// schema with a single field, with an empty name.
+    fn reader_schema(options: &SchemaOptions) -> Schema {
+        match options.apply(Self::expected_datatype()) {
+            DataType::Struct(fields) => Schema::new(fields),
+            datatype => Schema::new(vec![Field::new("", datatype, Self::nullable())]),
+        }
     }
 }
 
@@ -120,12 +208,225 @@ impl<T: CheckableDataType> CheckableDataType for Option<T> {
     fn check_datatype(datatype: &DataType) -> Result<(), String> {
         T::check_datatype(datatype)
     }
+
+    fn expected_datatype() -> DataType {
+        T::expected_datatype()
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+}
+
+/// Path to an Arrow column, as the list of field names leading to it from the root.
+///
+/// Unlike the dotted strings returned by [`ArRowStruct::columns`], field names
+/// are stored as they are, without escaping.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ColumnPath(pub Vec<String>);
+
+impl ColumnPath {
+    /// Parses field names separated by dots, like [`ArRowStruct::columns`].
+    ///
+    /// Dots and backslashes which are part of a field name must be escaped with a
+    /// backslash (eg. `a\.b.c` is the field `c` of the column named `a.b`), as done
+    /// by the [`Display`](std::fmt::Display) implementation.
+    pub fn parse(path: &str) -> ColumnPath {
+        let mut parts = vec![String::new()];
+        let mut chars = path.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => parts.push(String::new()),
+                // A trailing backslash has nothing to escape, so it is kept as is
+                '\\' => parts
+                    .last_mut()
+                    .expect("parts is not empty")
+                    .push(chars.next().unwrap_or('\\')),
+                c => parts.last_mut().expect("parts is not empty").push(c),
+            }
+        }
+        ColumnPath(parts)
+    }
+
+    /// Returns the field names leading to this column
+    pub fn parts(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Returns the path of a field nested in this column
+    pub fn child(&self, name: &str) -> ColumnPath {
+        let mut parts = self.0.clone();
+        parts.push(name.to_owned());
+        ColumnPath(parts)
+    }
+
+    /// Returns the name of the column, as configured by `options`
+    pub fn to_name(&self, options: &ColumnNameOptions) -> String {
+        match options.flattening {
+            Flattening::FullPath => self.0.join(&options.separator),
+            Flattening::TopLevel => self.0.first().cloned().unwrap_or_default(),
+            Flattening::LeafName => self.0.last().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Formats the path with dots as separator, like [`ArRowStruct::columns`], but
+/// escapes dots and backslashes in field names so it can be read back with
+/// [`ColumnPath::parse`]
+impl std::fmt::Display for ColumnPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        for (i, part) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char('.')?;
+            }
+            for c in part.chars() {
+                if c == '.' || c == '\\' {
+                    f.write_char('\\')?;
+                }
+                f.write_char(c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which part of a [`ColumnPath`] is used to name a column
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Flattening {
+    /// Names of all fields from the root to the column, joined with the separator
+    FullPath,
+    /// Name of the top-level field only, so all the columns nested in the same
+    /// top-level field share a name
+    TopLevel,
+    /// Name of the innermost field only
+    LeafName,
+}
+
+/// Configuration of column names returned by [`ArRowStruct::columns_with_options`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ColumnNameOptions {
+    /// Inserted between the names of nested fields. Defaults to `"."`.
+    pub separator: String,
+    /// Defaults to [`Flattening::FullPath`]
+    pub flattening: Flattening,
+}
+
+impl Default for ColumnNameOptions {
+    fn default() -> Self {
+        ColumnNameOptions {
+            separator: ".".to_owned(),
+            flattening: Flattening::FullPath,
+        }
+    }
+}
+
+/// Tree of the Arrow columns a type is deserialized from, returned by
+/// [`ArRowStruct::column_tree`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnTree {
+    /// A column which is read as a whole (scalars, and lists of scalars)
+    Leaf,
+    /// A structure, with the name and tree of each of its fields, in order
+    Struct(Vec<(String, ColumnTree)>),
+}
+
+impl ColumnTree {
+    /// Returns the path of every leaf of the tree, in depth-first order
+    pub fn paths(&self) -> Vec<ColumnPath> {
+        let mut paths = Vec::new();
+        self.add_paths(&ColumnPath::default(), &mut paths);
+        paths
+    }
+
+    /// Returns the tree whose leaves are `paths`, in the order they first appear.
+    ///
+    /// This is the converse of [`ColumnTree::paths`]. If a path is a prefix of another,
+    /// the column it points to is selected as a whole.
+    pub fn from_paths(paths: &[ColumnPath]) -> ColumnTree {
+        let mut tree = ColumnTree::Struct(Vec::new());
+        for path in paths {
+            tree.add_path(path.parts());
+        }
+        tree
+    }
+
+    fn add_path(&mut self, path: &[String]) {
+        let (name, rest) = match path.split_first() {
+            Some(split) => split,
+            None => {
+                *self = ColumnTree::Leaf;
+                return;
+            }
+        };
+        match self {
+            // The whole column is already selected
+            ColumnTree::Leaf => {}
+            ColumnTree::Struct(fields) => {
+                match fields.iter_mut().find(|(field_name, _)| field_name == name) {
+                    Some((_, tree)) => tree.add_path(rest),
+                    None => {
+                        let mut tree = ColumnTree::Struct(Vec::new());
+                        tree.add_path(rest);
+                        fields.push((name.clone(), tree));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the path of every leaf of the tree, prefixed with `path`,
+    /// in depth-first order.
+    ///
+    /// `path` is used as a buffer, and is left unchanged when this returns.
+    pub fn for_each_path<'a>(&'a self, path: &mut Vec<&'a str>, f: &mut dyn FnMut(&[&str])) {
+        match self {
+            ColumnTree::Leaf => f(path),
+            ColumnTree::Struct(fields) => {
+                for (name, tree) in fields {
+                    path.push(name);
+                    tree.for_each_path(path, f);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    fn add_paths(&self, prefix: &ColumnPath, paths: &mut Vec<ColumnPath>) {
+        match self {
+            ColumnTree::Leaf => paths.push(prefix.clone()),
+            ColumnTree::Struct(fields) => {
+                for (name, tree) in fields {
+                    tree.add_paths(&prefix.child(name), paths)
+                }
+            }
+        }
+    }
+
+    /// Builds a tree from the column names returned by [`ArRowStruct::columns`],
+    /// parsed with [`ColumnPath::parse`]
+    fn from_dotted_columns(columns: &[String]) -> ColumnTree {
+        let paths: Vec<ColumnPath> = columns
+            .iter()
+            .map(|column| {
+                if column.is_empty() {
+                    // Scalars are read from the prefix itself
+                    ColumnPath::default()
+                } else {
+                    ColumnPath::parse(column)
+                }
+            })
+            .collect();
+        ColumnTree::from_paths(&paths)
+    }
 }
 
 /// Types which provide a static `columns` method, which returns the names of all
 /// Arrow columns the struct expects to read from.
 ///
-/// Nested field names are separated by dots.
+/// Nested field names are separated by dots, and dots and backslashes in field names
+/// are escaped with a backslash, so they can be parsed with [`ColumnPath::parse`].
 ///
 /// For scalars, this method simply returns the prefix.
 pub trait ArRowStruct {
@@ -134,12 +435,107 @@ pub trait ArRowStruct {
     }
 
     fn columns_with_prefix(prefix: &str) -> Vec<String>;
+
+    /// Returns the structure of the columns the type is deserialized from.
+    ///
+    /// The default implementation parses the output of [`columns`](Self::columns);
+    /// implementations in this crate and in `ar_row_derive` override it to avoid
+    /// building and parsing strings.
+    fn column_tree() -> ColumnTree {
+        ColumnTree::from_dotted_columns(&Self::columns())
+    }
+
+    /// Same as [`columns`](Self::columns), but returns structured paths
+    fn column_paths() -> Vec<ColumnPath> {
+        Self::column_tree().paths()
+    }
+
+    /// Calls `f` with the path of every column the type expects, prefixed with
+    /// `path`, in the same order as [`column_paths`](Self::column_paths).
+    ///
+    /// `path` is used as a buffer, and is left unchanged when this returns.
+    /// Unlike other methods of this trait, the implementations in this crate and in
+    /// `ar_row_derive` do not allocate (besides growing `path`), so this is cheap
+    /// to call repeatedly, eg. to build projections for every file of a dataset.
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        match Self::column_tree() {
+            ColumnTree::Leaf => f(path),
+            tree => {
+                let mut path: Vec<&str> = path.clone();
+                tree.for_each_path(&mut path, f)
+            }
+        }
+    }
+
+    /// Same as [`columns`](Self::columns), but with configurable separator and
+    /// flattening of nested fields.
+    ///
+    /// Duplicate names (eg. when using [`Flattening::TopLevel`]) are only returned once.
+    fn columns_with_options(options: &ColumnNameOptions) -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        for path in Self::column_paths() {
+            let column = path.to_name(options);
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
+
+    /// Returns the path, data type, and nullability of every column the type expects,
+    /// as described by [`CheckableDataType::expected_datatype`].
+    ///
+    /// Fields of nested structures are listed individually, but lists are not
+    /// flattened: a `Vec<T>` field is returned as a single `List` column, even when
+    /// `T` is a structure.
+    fn fields() -> Vec<(ColumnPath, DataType, bool)>
+    where
+        Self: CheckableDataType,
+    {
+        let mut fields = Vec::new();
+        add_fields(
+            ColumnPath::default(),
+            Self::expected_datatype(),
+            Self::nullable(),
+            &mut fields,
+        );
+        fields
+    }
+}
+
+fn add_fields(
+    path: ColumnPath,
+    datatype: DataType,
+    nullable: bool,
+    fields: &mut Vec<(ColumnPath, DataType, bool)>,
+) {
+    match datatype {
+        DataType::Struct(struct_fields) => {
+            for field in struct_fields.iter() {
+                add_fields(
+                    path.child(field.name()),
+                    field.data_type().clone(),
+                    field.is_nullable(),
+                    fields,
+                )
+            }
+        }
+        _ => fields.push((path, datatype, nullable)),
+    }
 }
 
 impl<T: ArRowStruct> ArRowStruct for Option<T> {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         T::columns_with_prefix(prefix)
     }
+
+    fn column_tree() -> ColumnTree {
+        T::column_tree()
+    }
+
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        T::for_each_column_path(path, f)
+    }
 }
 
 /// Types which can be read in batch from Arrow's [`Array`].
@@ -164,6 +560,10 @@ pub trait ArRowDeserialize: Sized + Default + CheckableDataType {
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b;
 
     /// Wrapper for [`read_from_array`](Self::read_from_array)
+    ///
+    /// Like other `*record_batch*` methods, this reads the rows from the only column
+    /// of `src` if `Self` is not deserialized from a structure (see
+    /// [`CheckableDataType::check_schema`]).
     fn read_from_record_batch<'a, 'b, T>(
         src: RecordBatch,
         dst: &'b mut T,
@@ -172,12 +572,9 @@ pub trait ArRowDeserialize: Sized + Default + CheckableDataType {
         Self: 'a,
         &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
     {
-        /// Type helper
-        fn f(a: Arc<dyn Array>) -> Arc<dyn Array> {
-            a
-        }
-        let array: StructArray = src.into();
-        Self::read_from_array(f(Arc::new(array)), dst)
+        crate::metrics::time_batch(std::any::type_name::<Self>(), src, |src| {
+            Self::read_from_array(RootArray::new::<Self>(&src.schema()).get(src), dst)
+        })
     }
 
     /// Reads from a [`Array`] and returns a `Vec<Option<Self>>`
@@ -192,21 +589,132 @@ pub trait ArRowDeserialize: Sized + Default + CheckableDataType {
     /// [`read_from_array`](ArRowDeserialize::read_from_array)
     /// which takes care of allocating a buffer, and returns it.
     fn from_array(array: impl Array + AsArray) -> Result<Vec<Self>, DeserializationError> {
-        let num_elements = array.len();
-        let mut values = Vec::with_capacity(num_elements);
-        values.resize_with(num_elements, Default::default);
-        Self::read_from_array(array, &mut values)?;
+        let mut values = Vec::with_capacity(array.len());
+        Self::read_from_array_into_vec(array, &mut values)?;
         Ok(values)
     }
 
+    /// Same as [`read_from_array`](Self::read_from_array), but resizes `dst` to the
+    /// length of `src` instead of returning [`DeserializationError::MismatchedLength`]
+    /// when it is too short.
+    ///
+    /// Elements already in `dst` are overwritten, and `dst` is truncated if it was
+    /// longer than `src`, so the same buffer can be reused across batches.
+    fn read_from_array_into_vec(
+        src: impl Array + AsArray,
+        dst: &mut Vec<Self>,
+    ) -> Result<usize, DeserializationError> {
+        dst.resize_with(src.len(), Default::default);
+        Self::read_from_array(src, dst)
+    }
+
+    /// Wrapper for [`read_from_array_into_vec`](Self::read_from_array_into_vec)
+    fn read_from_record_batch_into_vec(
+        src: RecordBatch,
+        dst: &mut Vec<Self>,
+    ) -> Result<usize, DeserializationError> {
+        RootArray::new::<Self>(&src.schema()).read_into_vec(src, dst)
+    }
+
     /// Wrapper for [`from_array`](Self::from_array)
     fn from_record_batch(record_batch: RecordBatch) -> Result<Vec<Self>, DeserializationError> {
-        /// Type helper
-        fn f(a: Arc<dyn Array>) -> Arc<dyn Array> {
-            a
+        RootArray::new::<Self>(&record_batch.schema()).read(record_batch)
+    }
+
+    /// Same as [`from_array`](Self::from_array), with the policies set in `options`
+    fn from_array_with_options(
+        array: ArrayRef,
+        options: &DeserializeOptions,
+    ) -> Result<Vec<Self>, DeserializationError>
+    where
+        Self: ArRowStruct + Send,
+    {
+        let mut values = Vec::with_capacity(array.len());
+        options.read_from_array_into_vec(array, &mut values)?;
+        Ok(values)
+    }
+
+    /// Same as [`from_record_batch`](Self::from_record_batch), with the policies set
+    /// in `options`
+    fn from_record_batch_with_options(
+        record_batch: RecordBatch,
+        options: &DeserializeOptions,
+    ) -> Result<Vec<Self>, DeserializationError>
+    where
+        Self: ArRowStruct + Send,
+    {
+        let mut values = Vec::with_capacity(record_batch.num_rows());
+        options.read_from_record_batch_into_vec(record_batch, &mut values)?;
+        Ok(values)
+    }
+}
+
+/// Which array the rows of record batches are read from, decided once from their
+/// schema so it does not need to be checked again for every batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RootArray {
+    /// The only column of the batch
+    Column,
+    /// A [`StructArray`] of all the columns of the batch
+    Struct,
+}
+
+impl RootArray {
+    /// Returns [`RootArray::Column`] if `schema` has a single field `T` can be read
+    /// from, but `T` cannot be read from a structure of all fields (see
+    /// [`CheckableDataType::reader_schema`])
+    pub(crate) fn new<T: CheckableDataType>(schema: &Schema) -> RootArray {
+        match &schema.fields()[..] {
+            [field]
+                if T::check_datatype(field.data_type()).is_ok()
+                    && T::check_datatype(&DataType::Struct(schema.fields().clone())).is_err() =>
+            {
+                RootArray::Column
+            }
+            _ => RootArray::Struct,
+        }
+    }
+
+    /// Returns [`RootArray::Column`] if `schema` has a single field and `T` is not a
+    /// structure.
+    ///
+    /// Unlike [`new`](Self::new), this does not check which one `T` can be read from,
+    /// as neither may be before applying [`DeserializeOptions`].
+    pub(crate) fn without_checks<T: ArRowStruct>(schema: &Schema) -> RootArray {
+        match (T::column_tree(), schema.fields().len()) {
+            (ColumnTree::Leaf, 1) => RootArray::Column,
+            _ => RootArray::Struct,
+        }
+    }
+
+    /// Returns the array the rows of `batch` should be read from
+    pub(crate) fn get(self, batch: RecordBatch) -> ArrayRef {
+        match (self, batch.columns()) {
+            (RootArray::Column, [column]) => column.clone(),
+            _ => Arc::new(StructArray::from(batch)),
         }
-        let array: StructArray = record_batch.into();
-        Self::from_array(f(Arc::new(array)))
+    }
+
+    /// Same as [`ArRowDeserialize::read_from_record_batch_into_vec`], with this
+    /// root array
+    pub(crate) fn read_into_vec<T: ArRowDeserialize>(
+        self,
+        batch: RecordBatch,
+        dst: &mut Vec<T>,
+    ) -> Result<usize, DeserializationError> {
+        crate::metrics::time_batch(std::any::type_name::<T>(), batch, |batch| {
+            T::read_from_array_into_vec(self.get(batch), dst)
+        })
+    }
+
+    /// Same as [`ArRowDeserialize::from_record_batch`], with this root array
+    pub(crate) fn read<T: ArRowDeserialize>(
+        self,
+        batch: RecordBatch,
+    ) -> Result<Vec<T>, DeserializationError> {
+        crate::metrics::time_batch(std::any::type_name::<T>(), batch, |batch| {
+            T::from_array(self.get(batch))
+        })
     }
 }
 
@@ -219,12 +727,20 @@ macro_rules! impl_scalar {
             fn columns_with_prefix(prefix: &str) -> Vec<String> {
                 vec![prefix.to_string()]
             }
+
+            fn column_tree() -> ColumnTree {
+                ColumnTree::Leaf
+            }
         }
 
         impl CheckableDataType for $ty {
             fn check_datatype(datatype: &DataType) -> Result<(), String> {
                 check_datatype_equals(datatype, &$datatype, stringify!($ty))
             }
+
+            fn expected_datatype() -> DataType {
+                $datatype[0].clone()
+            }
         }
 
         impl_scalar_deser!($ty, $datatype, $method, $array_ty, $cast);
@@ -258,7 +774,9 @@ macro_rules! impl_scalar_deser {
                         }
                     }
                 } else if let Some(src) = src.as_any_dictionary_opt() {
-                    read_from_dictionary_array(src, dst)
+                    read_from_cached_dictionary_array(src, dst)
+                } else if let Some(src) = unpack_run_end_encoded(&src)? {
+                    Self::read_from_array(src, dst)
                 } else {
                     Err(DeserializationError::MismatchedColumnDataType(format!(
                         "Could not cast {:?} array with {}",
@@ -279,16 +797,27 @@ macro_rules! impl_scalar_deser {
             {
                 if let Some(src) = src.$method() {
                     let src: &$array_ty = src;
-                    for (s, d) in src.iter().zip(dst.iter_mut()) {
-                        match s {
-                            None => *d = None,
-                            Some(s) => *d = Some(($cast)(s)?),
+                    let mut dst_iter = dst.iter_mut();
+                    let mut index = 0;
+                    for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                        if is_valid {
+                            for d in dst_iter.by_ref().take(run_length) {
+                                *d = Some(($cast)(src.value(index))?);
+                                index += 1;
+                            }
+                        } else {
+                            for d in dst_iter.by_ref().take(run_length) {
+                                *d = None;
+                            }
+                            index += run_length;
                         }
                     }
 
                     Ok(src.len())
                 } else if let Some(src) = src.as_any_dictionary_opt() {
-                    read_options_from_dictionary_array(src, dst)
+                    read_options_from_cached_dictionary_array(src, dst)
+                } else if let Some(src) = unpack_run_end_encoded(&src)? {
+                    Self::read_from_array(src, dst)
                 } else {
                     Err(DeserializationError::MismatchedColumnDataType(format!(
                         "Could not cast {:?} array with {}",
@@ -301,7 +830,110 @@ macro_rules! impl_scalar_deser {
     };
 }
 
-impl_scalar!(bool, [DataType::Boolean], as_boolean_opt, BooleanArray);
+impl ArRowStruct for bool {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+impl CheckableDataType for bool {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        check_datatype_equals(datatype, &[DataType::Boolean], "bool")
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::Boolean
+    }
+}
+
+/// Writes `values` to `dst`, unpacking them 64 at a time instead of looking up each
+/// bit separately
+fn unpack_booleans<'d>(values: &BooleanBuffer, dst: impl Iterator<Item = &'d mut bool>) {
+    let chunks = values.inner().bit_chunks(values.offset(), values.len());
+    let mut dst = dst;
+    for chunk in chunks.iter() {
+        for (i, d) in dst.by_ref().take(64).enumerate() {
+            *d = chunk & (1 << i) != 0;
+        }
+    }
+    let remainder = chunks.remainder_bits();
+    for (i, d) in dst.take(chunks.remainder_len()).enumerate() {
+        *d = remainder & (1 << i) != 0;
+    }
+}
+
+impl ArRowDeserialize for bool {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_boolean_opt() {
+            if src.null_count() > 0 {
+                return Err(DeserializationError::UnexpectedNull(
+                    "bool column contains nulls".to_string(),
+                ));
+            }
+            unpack_booleans(src.values(), dst.iter_mut());
+            Ok(src.len())
+        } else if let Some(src) = src.as_any_dictionary_opt() {
+            read_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
+        } else {
+            Err(DeserializationError::MismatchedColumnDataType(format!(
+                "Could not cast {:?} array with as_boolean_opt",
+                src.data_type(),
+            )))
+        }
+    }
+}
+
+impl ArRowDeserialize for Option<bool> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_boolean_opt() {
+            let mut dst_iter = dst.iter_mut();
+            let mut index = 0;
+            for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                if is_valid {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        *d = Some(src.value(index));
+                        index += 1;
+                    }
+                } else {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        *d = None;
+                    }
+                    index += run_length;
+                }
+            }
+
+            Ok(src.len())
+        } else if let Some(src) = src.as_any_dictionary_opt() {
+            read_options_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
+        } else {
+            Err(DeserializationError::MismatchedColumnDataType(format!(
+                "Could not cast {:?} array with as_boolean_opt",
+                src.data_type(),
+            )))
+        }
+    }
+}
+
 impl_scalar!(
     i8,
     [DataType::Int8],
@@ -376,6 +1008,13 @@ impl_scalar!(
     StringArray,
     |s: &str| Ok(s.to_owned())
 );
+impl_scalar!(
+    SharedString,
+    [DataType::Utf8, DataType::LargeUtf8],
+    as_string_opt,
+    StringArray,
+    |s: &str| Ok(SharedString::from(s))
+);
 impl_scalar!(
     Box<[u8]>,
     [DataType::Binary, DataType::LargeBinary],
@@ -388,11 +1027,15 @@ impl<const N: usize> ArRowStruct for FixedSizeBinary<N> {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         vec![prefix.to_string()]
     }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
 }
 
 impl<const N: usize> CheckableDataType for FixedSizeBinary<N> {
     fn check_datatype(datatype: &DataType) -> Result<(), String> {
-        match datatype {
+        match decoded_datatype(datatype) {
             DataType::FixedSizeBinary(size) => {
                 match i32::try_from(N) {
                     Ok(expected_size) if expected_size == *size => Ok(()),
@@ -406,6 +1049,10 @@ impl<const N: usize> CheckableDataType for FixedSizeBinary<N> {
             )),
         }
     }
+
+    fn expected_datatype() -> DataType {
+        DataType::FixedSizeBinary(N as i32)
+    }
 }
 
 impl<const N: usize> ArRowDeserialize for FixedSizeBinary<N> {
@@ -437,7 +1084,9 @@ impl<const N: usize> ArRowDeserialize for FixedSizeBinary<N> {
                 }
             }
         } else if let Some(src) = src.as_any_dictionary_opt() {
-            read_from_dictionary_array(src, dst)
+            read_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
         } else {
             Err(DeserializationError::MismatchedColumnDataType(format!(
                 "Could not cast {:?} array with as_fixed_size_binary_opt",
@@ -457,23 +1106,33 @@ impl<const N: usize> ArRowDeserialize for Option<FixedSizeBinary<N>> {
     {
         if let Some(src) = src.as_fixed_size_binary_opt() {
             let src: &FixedSizeBinaryArray = src;
-            for (s, d) in src.iter().zip(dst.iter_mut()) {
-                match s {
-                    None => *d = None,
-                    Some(s) => {
+            let mut dst_iter = dst.iter_mut();
+            let mut index = 0;
+            for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                if is_valid {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        let s = src.value(index);
                         *d = Some(FixedSizeBinary(s.try_into().map_err(|_| {
                             DeserializationError::MismatchedBinarySize {
                                 src: s.len(),
                                 dst: N,
                             }
-                        })?))
+                        })?));
+                        index += 1;
+                    }
+                } else {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        *d = None;
                     }
+                    index += run_length;
                 }
             }
 
             Ok(src.len())
         } else if let Some(src) = src.as_any_dictionary_opt() {
-            read_options_from_dictionary_array(src, dst)
+            read_options_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
         } else {
             Err(DeserializationError::MismatchedColumnDataType(format!(
                 "Could not cast {:?} array with as_fixed_size_binary_opt",
@@ -483,20 +1142,138 @@ impl<const N: usize> ArRowDeserialize for Option<FixedSizeBinary<N>> {
     }
 }
 
+impl ArRowStruct for DisplayString {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+impl CheckableDataType for DisplayString {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        match decoded_datatype(datatype) {
+            DataType::Boolean | DataType::Utf8 | DataType::LargeUtf8 => Ok(()),
+            decoded if decoded.is_primitive() => Ok(()),
+            _ => Err(format!(
+                "DisplayString must be decoded from a primitive, boolean, or string Arrow type, not Arrow {datatype:?}"
+            )),
+        }
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::Utf8
+    }
+}
+
+/// Overwrites `dst` with the display form of the value at `index` of the array
+/// formatted by `formatter`
+fn write_display_string(
+    formatter: &arrow::util::display::ArrayFormatter<'_>,
+    index: usize,
+    dst: &mut DisplayString,
+) -> Result<(), DeserializationError> {
+    dst.0.clear();
+    formatter.value(index).write(&mut dst.0).map_err(|e| {
+        DeserializationError::MismatchedColumnDataType(format!("Could not format value: {e}"))
+    })
+}
+
+/// Returns a formatter of the values of `src`, with the default options
+fn display_formatter(
+    src: &dyn Array,
+) -> Result<arrow::util::display::ArrayFormatter<'_>, DeserializationError> {
+    arrow::util::display::ArrayFormatter::try_new(src, &Default::default()).map_err(|e| {
+        DeserializationError::MismatchedColumnDataType(format!(
+            "Could not format {:?} array: {e}",
+            src.data_type()
+        ))
+    })
+}
+
+impl ArRowDeserialize for DisplayString {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
+        if src.null_count() > 0 {
+            return Err(DeserializationError::UnexpectedNull(
+                "DisplayString column contains nulls".to_string(),
+            ));
+        }
+        if src.len() > dst.len() {
+            return Err(DeserializationError::MismatchedLength {
+                src: src.len(),
+                dst: dst.len(),
+            });
+        }
+        let formatter = display_formatter(&src)?;
+        for (index, d) in dst.iter_mut().take(src.len()).enumerate() {
+            write_display_string(&formatter, index, d)?;
+        }
+        Ok(src.len())
+    }
+}
+
+impl ArRowDeserialize for Option<DisplayString> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
+        if src.len() > dst.len() {
+            return Err(DeserializationError::MismatchedLength {
+                src: src.len(),
+                dst: dst.len(),
+            });
+        }
+        let formatter = display_formatter(&src)?;
+        for (index, d) in dst.iter_mut().take(src.len()).enumerate() {
+            if src.is_null(index) {
+                *d = None;
+            } else {
+                write_display_string(&formatter, index, d.get_or_insert_with(Default::default))?;
+            }
+        }
+        Ok(src.len())
+    }
+}
+
 impl ArRowStruct for NaiveDecimal128 {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         vec![prefix.to_string()]
     }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
 }
 impl CheckableDataType for NaiveDecimal128 {
     fn check_datatype(datatype: &DataType) -> Result<(), String> {
-        match datatype {
+        match decoded_datatype(datatype) {
             DataType::Decimal128(_, _) => Ok(()),
             _ => Err(format!(
                 "NaiveDecimal128 must be decoded from Arrow Decimal128(_, _), not Arrow {datatype:?}"
             )),
         }
     }
+
+    fn expected_datatype() -> DataType {
+        DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE)
+    }
 }
 impl_scalar_deser!(
     NaiveDecimal128,
@@ -506,10 +1283,131 @@ impl_scalar_deser!(
     |v| Ok(NaiveDecimal128(v))
 );
 
+impl ArRowStruct for DecimalF64 {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+impl CheckableDataType for DecimalF64 {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        match decoded_datatype(datatype) {
+            DataType::Decimal128(_, _) => Ok(()),
+            _ => Err(format!(
+                "DecimalF64 must be decoded from Arrow Decimal128(_, _), not Arrow {datatype:?}"
+            )),
+        }
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE)
+    }
+}
+
+/// Returns a function converting values of `src` to the decimals they represent
+fn decimal_to_f64(src: &Decimal128Array) -> impl Fn(i128) -> f64 {
+    let scale = src.scale();
+    // Powers of 10 are exact up to 10^22, unlike their inverses
+    let factor = 10f64.powi(i32::from(scale).abs());
+    move |value| {
+        if scale >= 0 {
+            value as f64 / factor
+        } else {
+            value as f64 * factor
+        }
+    }
+}
+
+impl ArRowDeserialize for DecimalF64 {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_primitive_opt::<Decimal128Type>() {
+            let to_f64 = decimal_to_f64(src);
+            match NotNullArrayIter::new(src) {
+                None => Err(DeserializationError::UnexpectedNull(
+                    "DecimalF64 column contains nulls".to_string(),
+                )),
+                Some(it) => {
+                    for (s, d) in it.zip(dst.iter_mut()) {
+                        *d = DecimalF64(to_f64(s));
+                    }
+
+                    Ok(src.len())
+                }
+            }
+        } else if let Some(src) = src.as_any_dictionary_opt() {
+            read_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
+        } else {
+            Err(DeserializationError::MismatchedColumnDataType(format!(
+                "Could not cast {:?} array with as_primitive_opt::<Decimal128Type>",
+                src.data_type(),
+            )))
+        }
+    }
+}
+
+impl ArRowDeserialize for Option<DecimalF64> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_primitive_opt::<Decimal128Type>() {
+            let to_f64 = decimal_to_f64(src);
+            let values = src.values();
+            let mut dst_iter = dst.iter_mut();
+            let mut index = 0;
+            for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                if is_valid {
+                    for (s, d) in values[index..index + run_length]
+                        .iter()
+                        .zip(dst_iter.by_ref())
+                    {
+                        *d = Some(DecimalF64(to_f64(*s)));
+                    }
+                } else {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        *d = None;
+                    }
+                }
+                index += run_length;
+            }
+
+            Ok(src.len())
+        } else if let Some(src) = src.as_any_dictionary_opt() {
+            read_options_from_cached_dictionary_array(src, dst)
+        } else if let Some(src) = unpack_run_end_encoded(&src)? {
+            Self::read_from_array(src, dst)
+        } else {
+            Err(DeserializationError::MismatchedColumnDataType(format!(
+                "Could not cast {:?} array with as_primitive_opt::<Decimal128Type>",
+                src.data_type(),
+            )))
+        }
+    }
+}
+
 impl ArRowStruct for Timestamp {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         vec![prefix.to_string()]
     }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
 }
 
 impl CheckableDataType for Timestamp {
@@ -527,6 +1425,10 @@ impl CheckableDataType for Timestamp {
             "Timestamp",
         )
     }
+
+    fn expected_datatype() -> DataType {
+        DataType::Timestamp(TimeUnit::Nanosecond, None)
+    }
 }
 
 macro_rules! impl_timestamp {
@@ -538,11 +1440,9 @@ macro_rules! impl_timestamp {
                 ))),
                 Some(it) => {
                     for (s, d) in it.zip($dst.iter_mut()) {
-                        *d = Timestamp {
-                            seconds: s / $ratio,
-                            #[allow(clippy::modulo_one)]
-                            nanoseconds: (s % $ratio) * (1_000_000_000 / $ratio),
-                        }
+                        #[allow(clippy::modulo_one)]
+                        let nanoseconds = (s % $ratio) * (1_000_000_000 / $ratio);
+                        *d = Timestamp::normalized(s / $ratio, nanoseconds);
                     }
 
                     Ok(src.len())
@@ -588,7 +1488,10 @@ impl ArRowDeserialize for Timestamp {
         }
 
         if let Some(src) = src.as_any_dictionary_opt() {
-            return read_from_dictionary_array(src, dst);
+            return read_from_cached_dictionary_array(src, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
         }
 
         Err(DeserializationError::MismatchedColumnDataType(format!(
@@ -601,17 +1504,25 @@ impl ArRowDeserialize for Timestamp {
 macro_rules! impl_timestamp_option {
     ($src:expr, $ty:ty, $ratio:expr, $dst:expr) => {{
         if let Some(src) = $src.as_primitive_opt::<$ty>() {
-            for (s, d) in src.iter().zip($dst.iter_mut()) {
-                match s {
-                    None => *d = None,
-                    Some(s) => {
-                        *d = Some(Timestamp {
-                            seconds: s / $ratio,
-                            #[allow(clippy::modulo_one)]
-                            nanoseconds: (s % $ratio) * (1_000_000_000 / $ratio),
-                        })
+            let values = src.values();
+            let mut dst_iter = $dst.iter_mut();
+            let mut index = 0;
+            for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                if is_valid {
+                    for (s, d) in values[index..index + run_length]
+                        .iter()
+                        .zip(dst_iter.by_ref())
+                    {
+                        #[allow(clippy::modulo_one)]
+                        let nanoseconds = (s % $ratio) * (1_000_000_000 / $ratio);
+                        *d = Some(Timestamp::normalized(s / $ratio, nanoseconds));
+                    }
+                } else {
+                    for d in dst_iter.by_ref().take(run_length) {
+                        *d = None;
                     }
                 }
+                index += run_length;
             }
             return Ok(src.len());
         }
@@ -645,31 +1556,206 @@ impl ArRowDeserialize for Option<Timestamp> {
                     Some(s) => *d = Some(timestamp_from_decimal128(s)?),
                 }
             }
-            return Ok(src.len());
-        }
-
-        if let Some(src) = src.as_any_dictionary_opt() {
-            return read_options_from_dictionary_array(src, dst);
+            return Ok(src.len());
+        }
+
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return read_options_from_cached_dictionary_array(src, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
+
+        Err(DeserializationError::MismatchedColumnDataType(format!(
+            "Could not cast {:?} array with {}",
+            src.data_type(),
+            stringify!($method)
+        )))
+    }
+}
+
+fn timestamp_from_decimal128(s: i128) -> Result<Timestamp, DeserializationError> {
+    let dividend = 10u64.pow(DECIMAL_SCALE.try_into().unwrap());
+    let seconds = s.div_euclid(i128::from(dividend));
+    let nanoseconds = s.rem_euclid(i128::from(dividend));
+    Ok(Timestamp {
+        seconds: i64::try_from(seconds)
+            .map_err(|_| DeserializationError::TimestampOverflow { seconds })?,
+        nanoseconds: nanoseconds.try_into().unwrap(), // can't overflow, dividend fits in u64
+    })
+}
+
+/// Returns the number of `unit`s in a second
+fn units_per_second(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    }
+}
+
+/// Converts a number of `from`s since the epoch to a number of `to`s, rounding down
+fn convert_epoch(value: i64, from: &TimeUnit, to: &TimeUnit) -> Result<i64, DeserializationError> {
+    let (from_ratio, to_ratio) = (units_per_second(from), units_per_second(to));
+    if from_ratio >= to_ratio {
+        Ok(value.div_euclid(from_ratio / to_ratio))
+    } else {
+        value.checked_mul(to_ratio / from_ratio).ok_or_else(|| {
+            DeserializationError::EpochOverflow {
+                value,
+                from: *from,
+                to: *to,
+            }
+        })
+    }
+}
+
+/// Returns the values of a timestamp array without timezone, and their unit
+fn timestamp_values(src: &dyn Array) -> Option<(&[i64], TimeUnit)> {
+    match src.data_type() {
+        DataType::Timestamp(TimeUnit::Second, None) => Some((
+            &src.as_primitive::<TimestampSecondType>().values()[..],
+            TimeUnit::Second,
+        )),
+        DataType::Timestamp(TimeUnit::Millisecond, None) => Some((
+            &src.as_primitive::<TimestampMillisecondType>().values()[..],
+            TimeUnit::Millisecond,
+        )),
+        DataType::Timestamp(TimeUnit::Microsecond, None) => Some((
+            &src.as_primitive::<TimestampMicrosecondType>().values()[..],
+            TimeUnit::Microsecond,
+        )),
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => Some((
+            &src.as_primitive::<TimestampNanosecondType>().values()[..],
+            TimeUnit::Nanosecond,
+        )),
+        _ => None,
+    }
+}
+
+macro_rules! impl_epoch {
+    ($ty:ident, $unit:expr) => {
+        impl ArRowStruct for $ty {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                vec![prefix.to_string()]
+            }
+
+            fn column_tree() -> ColumnTree {
+                ColumnTree::Leaf
+            }
+        }
+
+        impl CheckableDataType for $ty {
+            fn check_datatype(datatype: &DataType) -> Result<(), String> {
+                use arrow::datatypes::TimeUnit::*;
+                check_datatype_equals(
+                    datatype,
+                    &[
+                        DataType::Timestamp(Second, None),
+                        DataType::Timestamp(Millisecond, None),
+                        DataType::Timestamp(Microsecond, None),
+                        DataType::Timestamp(Nanosecond, None),
+                    ],
+                    stringify!($ty),
+                )
+            }
+
+            fn expected_datatype() -> DataType {
+                DataType::Timestamp($unit, None)
+            }
+        }
+
+        impl ArRowDeserialize for $ty {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                if let Some(src) = src.as_any_dictionary_opt() {
+                    return read_from_cached_dictionary_array(src, dst);
+                }
+                if let Some(src) = unpack_run_end_encoded(&src)? {
+                    return Self::read_from_array(src, dst);
+                }
+                let (values, unit) = timestamp_values(&src).ok_or_else(|| {
+                    DeserializationError::MismatchedColumnDataType(format!(
+                        "Could not cast {:?} array to timestamp array without timezone",
+                        src.data_type(),
+                    ))
+                })?;
+                if src.null_count() > 0 {
+                    return Err(DeserializationError::UnexpectedNull(format!(
+                        "{} column contains nulls",
+                        stringify!($ty)
+                    )));
+                }
+                if src.len() > dst.len() {
+                    return Err(DeserializationError::MismatchedLength {
+                        src: src.len(),
+                        dst: dst.len(),
+                    });
+                }
+                for (s, d) in values.iter().zip(dst.iter_mut()) {
+                    *d = $ty(convert_epoch(*s, &unit, &$unit)?);
+                }
+                Ok(src.len())
+            }
+        }
+
+        impl ArRowDeserialize for Option<$ty> {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                if let Some(src) = src.as_any_dictionary_opt() {
+                    return read_options_from_cached_dictionary_array(src, dst);
+                }
+                if let Some(src) = unpack_run_end_encoded(&src)? {
+                    return Self::read_from_array(src, dst);
+                }
+                let (values, unit) = timestamp_values(&src).ok_or_else(|| {
+                    DeserializationError::MismatchedColumnDataType(format!(
+                        "Could not cast {:?} array to timestamp array without timezone",
+                        src.data_type(),
+                    ))
+                })?;
+                if src.len() > dst.len() {
+                    return Err(DeserializationError::MismatchedLength {
+                        src: src.len(),
+                        dst: dst.len(),
+                    });
+                }
+                let mut dst_iter = dst.iter_mut();
+                let mut index = 0;
+                for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                    if is_valid {
+                        for (s, d) in values[index..index + run_length]
+                            .iter()
+                            .zip(dst_iter.by_ref())
+                        {
+                            *d = Some($ty(convert_epoch(*s, &unit, &$unit)?));
+                        }
+                    } else {
+                        for d in dst_iter.by_ref().take(run_length) {
+                            *d = None;
+                        }
+                    }
+                    index += run_length;
+                }
+                Ok(src.len())
+            }
         }
-
-        Err(DeserializationError::MismatchedColumnDataType(format!(
-            "Could not cast {:?} array with {}",
-            src.data_type(),
-            stringify!($method)
-        )))
-    }
+    };
 }
 
-fn timestamp_from_decimal128(s: i128) -> Result<Timestamp, DeserializationError> {
-    let dividend = 10u64.pow(DECIMAL_SCALE.try_into().unwrap());
-    let seconds = s / i128::from(dividend);
-    let nanoseconds = s % i128::from(dividend);
-    Ok(Timestamp {
-        seconds: i64::try_from(seconds)
-            .map_err(|_| DeserializationError::TimestampOverflow { seconds })?,
-        nanoseconds: nanoseconds.try_into().unwrap(), // can't overflow, dividend fits in u64
-    })
-}
+impl_epoch!(EpochMillis, TimeUnit::Millisecond);
+impl_epoch!(EpochNanos, TimeUnit::Nanosecond);
 
 /* TODO rust_decimal
 impl_scalar!(
@@ -785,15 +1871,314 @@ impl<T: ArRowStruct> ArRowStruct for Vec<T> {
     fn columns_with_prefix(prefix: &str) -> Vec<String> {
         T::columns_with_prefix(prefix)
     }
+
+    fn column_tree() -> ColumnTree {
+        T::column_tree()
+    }
+
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        T::for_each_column_path(path, f)
+    }
 }
 
+/// `Vec<u8>` can be read from both lists of `UInt8` and from binary arrays, and
+/// `Vec<MapEntry<K, V>>` (or any `Vec` of structures with two fields) from maps.
 impl<T: CheckableDataType> CheckableDataType for Vec<T> {
     fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        let datatype = decoded_datatype(datatype);
         match datatype {
-            DataType::List(inner) => T::check_datatype(inner.data_type()),
+            DataType::List(inner) | DataType::FixedSizeList(inner, _) | DataType::Map(inner, _) => {
+                T::check_datatype(inner.data_type())
+            }
+            // Only u8 is deserialized from non-nullable UInt8
+            DataType::Binary | DataType::LargeBinary
+                if T::expected_datatype() == DataType::UInt8 && !T::nullable() =>
+            {
+                Ok(())
+            }
             _ => Err(format!("Must be a List, not {datatype:?}")),
         }
     }
+
+    fn expected_datatype() -> DataType {
+        DataType::new_list(T::expected_datatype(), T::nullable())
+    }
+}
+
+/// Returns whether `I` is `U`
+fn is_type<I: 'static, U: 'static>() -> bool {
+    TypeId::of::<I>() == TypeId::of::<U>()
+}
+
+/// Returns `vec` if `I` is `U`, and `None` otherwise
+fn downcast_vec<I: 'static, U: 'static>(vec: &mut Vec<I>) -> Option<&mut Vec<U>> {
+    (vec as &mut dyn Any).downcast_mut()
+}
+
+/// Values of a list array which can be written directly to each `Vec<I>`, instead
+/// of being deserialized to a temporary buffer first
+enum DirectListValues<'a> {
+    /// `I` is `u8` and the values are `UInt8`
+    Bytes(&'a [u8]),
+    /// `I` is `String` and the values are `Utf8`
+    Strings(&'a StringArray),
+    /// `I` is `String` and the values are `LargeUtf8`
+    LargeStrings(&'a LargeStringArray),
+}
+
+impl<'a> DirectListValues<'a> {
+    /// Returns `None` if the values need to go through the generic path, ie. if
+    /// they contain nulls or `I` is neither `u8` nor `String`.
+    fn new<I: 'static>(values: &'a ArrayRef) -> Option<Self> {
+        if values.nulls().is_some() {
+            None
+        } else if is_type::<I, u8>() {
+            values
+                .as_primitive_opt::<UInt8Type>()
+                .map(|values| DirectListValues::Bytes(&values.values()[..]))
+        } else if is_type::<I, String>() {
+            values
+                .as_string_opt::<i32>()
+                .map(DirectListValues::Strings)
+                .or_else(|| {
+                    values
+                        .as_string_opt::<i64>()
+                        .map(DirectListValues::LargeStrings)
+                })
+        } else {
+            None
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            DirectListValues::Bytes(_) => "Vec<u8>",
+            DirectListValues::Strings(_) | DirectListValues::LargeStrings(_) => "Vec<String>",
+        }
+    }
+
+    /// Overwrites `dst` with the values in `range`
+    fn write<I: 'static>(&self, range: Range<usize>, dst: &mut Vec<I>) {
+        match self {
+            DirectListValues::Bytes(bytes) => {
+                let dst = downcast_vec::<I, u8>(dst).expect("Bytes are only read to Vec<u8>");
+                dst.clear();
+                dst.extend_from_slice(&bytes[range]);
+            }
+            DirectListValues::Strings(values) => write_strings(
+                values,
+                range,
+                downcast_vec::<I, String>(dst).expect("Strings are only read to Vec<String>"),
+            ),
+            DirectListValues::LargeStrings(values) => write_strings(
+                values,
+                range,
+                downcast_vec::<I, String>(dst).expect("Strings are only read to Vec<String>"),
+            ),
+        }
+    }
+}
+
+/// Overwrites `dst` with the strings in `range`, reusing the allocations of the
+/// strings already in `dst`
+fn write_strings<O: OffsetSizeTrait>(
+    values: &GenericStringArray<O>,
+    mut range: Range<usize>,
+    dst: &mut Vec<String>,
+) {
+    dst.truncate(range.len());
+    dst.reserve_exact(range.len() - dst.len());
+    for (s, i) in dst.iter_mut().zip(range.by_ref()) {
+        s.clear();
+        s.push_str(values.value(i));
+    }
+    dst.extend(range.map(|i| values.value(i).to_owned()));
+}
+
+/// Returns an error if binary arrays cannot be deserialized to `Vec<I>` (ie. if `I`
+/// is not `u8`), or if `src_len > dst_len`
+fn check_binary_read<I: 'static>(
+    datatype: &DataType,
+    src_len: usize,
+    dst_len: usize,
+) -> Result<(), DeserializationError> {
+    if !is_type::<I, u8>() {
+        return Err(DeserializationError::MismatchedColumnDataType(format!(
+            "Could not read {:?} array as Vec<{}>",
+            datatype,
+            std::any::type_name::<I>()
+        )));
+    }
+    if src_len > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: src_len,
+            dst: dst_len,
+        });
+    }
+    Ok(())
+}
+
+/// Overwrites each `Vec<u8>` in `dst` with a value of `src`
+fn read_binaries<'a, 'b, I, O, T>(
+    src: &GenericBinaryArray<O>,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: ArRowDeserialize + 'static,
+    O: OffsetSizeTrait,
+    &'b mut T: DeserializationTarget<'a, Item = Vec<I>> + 'b,
+{
+    check_binary_read::<I>(src.data_type(), src.len(), dst.len())?;
+    if src.nulls().is_some() {
+        return Err(DeserializationError::UnexpectedNull(
+            "Vec<u8> column contains nulls".to_string(),
+        ));
+    }
+    for (i, d) in dst.iter_mut().take(src.len()).enumerate() {
+        let d = downcast_vec::<I, u8>(d).expect("check_binary_read checked I is u8");
+        d.clear();
+        d.extend_from_slice(src.value(i));
+    }
+    Ok(src.len())
+}
+
+/// Overwrites each `Option<Vec<u8>>` in `dst` with a value of `src`
+fn read_binary_options<'a, 'b, I, O, T>(
+    src: &GenericBinaryArray<O>,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: ArRowDeserialize + 'static,
+    O: OffsetSizeTrait,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Vec<I>>> + 'b,
+{
+    check_binary_read::<I>(src.data_type(), src.len(), dst.len())?;
+    for (i, d) in dst.iter_mut().take(src.len()).enumerate() {
+        if src.is_null(i) {
+            *d = None;
+        } else {
+            let d = downcast_vec::<I, u8>(d.get_or_insert_with(Vec::new))
+                .expect("check_binary_read checked I is u8");
+            d.clear();
+            d.extend_from_slice(src.value(i));
+        }
+    }
+    Ok(src.len())
+}
+
+/// Values of a list array, written directly to each `Vec<I>` if possible, or
+/// deserialized to a temporary buffer first
+enum ListElements<'a, I> {
+    Direct(DirectListValues<'a>),
+    Deserialized(std::vec::IntoIter<I>),
+}
+
+impl<'a, I: ArRowDeserialize + 'static> ListElements<'a, I> {
+    fn new(values: &'a ArrayRef) -> Result<Self, DeserializationError> {
+        match DirectListValues::new::<I>(values) {
+            Some(values) => Ok(ListElements::Direct(values)),
+            None => {
+                let mut elements = Vec::with_capacity(values.len());
+                I::read_from_array_into_vec(values.clone(), &mut elements)?;
+                Ok(ListElements::Deserialized(elements.into_iter()))
+            }
+        }
+    }
+
+    /// Overwrites `dst` with the values in `range`, which must directly follow the
+    /// range of the previous call
+    fn write(&mut self, range: Range<usize>, dst: &mut Vec<I>) {
+        match self {
+            ListElements::Direct(values) => values.write(range, dst),
+            ListElements::Deserialized(elements) => {
+                dst.clear();
+                dst.extend(elements.by_ref().take(range.len()));
+            }
+        }
+    }
+}
+
+/// Implementation of [`read_from_array`](ArRowDeserialize::read_from_array) for
+/// `Vec<I>` on `FixedSizeList` arrays
+fn read_fixed_size_lists<'a, 'b, I, T>(
+    src: &FixedSizeListArray,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: ArRowDeserialize + 'static,
+    &'b mut T: DeserializationTarget<'a, Item = Vec<I>> + 'b,
+{
+    if src.nulls().is_some() {
+        return Err(DeserializationError::UnexpectedNull(format!(
+            "Vec<{}> column contains nulls",
+            std::any::type_name::<I>()
+        )));
+    }
+    let num_lists = src.len();
+    let dst_len = dst.len();
+    if num_lists > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: num_lists,
+            dst: dst_len,
+        });
+    }
+
+    let size = src.value_length().as_usize();
+    let values = src.values().slice(0, num_lists * size);
+    let mut elements = ListElements::new(&values)?;
+    let mut dst = dst.iter_mut();
+    for i in 0..num_lists {
+        let dst_item: &mut Vec<I> = next_dst_item!(dst, num_lists, dst_len);
+        elements.write(i * size..(i + 1) * size, dst_item);
+    }
+    Ok(num_lists)
+}
+
+/// Implementation of
+/// [`read_options_from_array`](ArRowDeserializeOption::read_options_from_array) for
+/// `Vec<I>` on `FixedSizeList` arrays
+///
+/// Values of null lists are skipped, as they are usually null themselves.
+fn read_fixed_size_list_options<'a, 'b, I, T>(
+    src: &FixedSizeListArray,
+    mut dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    I: ArRowDeserialize + 'static,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Vec<I>>> + 'b,
+{
+    let num_lists = src.len();
+    let dst_len = dst.len();
+    if num_lists > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: num_lists,
+            dst: dst_len,
+        });
+    }
+
+    let size = src.value_length().as_usize();
+    let mut dst = dst.iter_mut();
+    let mut start = 0;
+    for (is_valid, run_length) in ValidityRuns::new(src.nulls(), num_lists) {
+        if is_valid {
+            let values = src.values().slice(start * size, run_length * size);
+            let mut elements = ListElements::new(&values)?;
+            for i in 0..run_length {
+                let dst_item: &mut Option<Vec<I>> = next_dst_item!(dst, num_lists, dst_len);
+                elements.write(
+                    i * size..(i + 1) * size,
+                    dst_item.get_or_insert_with(Vec::new),
+                );
+            }
+        } else {
+            for _ in 0..run_length {
+                let dst_item: &mut Option<Vec<I>> = next_dst_item!(dst, num_lists, dst_len);
+                *dst_item = None;
+            }
+        }
+        start += run_length;
+    }
+    Ok(num_lists)
 }
 
 /// Shared initialization code of `impl<I> ArRowDeserializeOption for Vec<I>`
@@ -807,9 +2192,8 @@ macro_rules! init_list_read {
 
         // Deserialize the inner elements recursively into this temporary buffer.
         // TODO: write them directly to the final location to avoid a copy
-        let mut elements = Vec::with_capacity(num_elements);
-        elements.resize_with(num_elements, Default::default);
-        ArRowDeserialize::read_from_array::<Vec<I>>(values.clone(), &mut elements)?;
+        let mut elements: Vec<I> = Vec::with_capacity(num_elements);
+        I::read_from_array_into_vec(values.clone(), &mut elements)?;
 
         let elements = elements.into_iter();
 
@@ -841,10 +2225,53 @@ macro_rules! build_list_item {
     }};
 }
 
+/// Returns the next item of the destination, in list deserialization loops.
+/// This cannot fail for well-behaved
+/// [`DeserializationTarget`]s as we checked `num_lists <= dst.len()` beforehand,
+/// but nothing prevents other implementations from yielding fewer than `len()` items.
+macro_rules! next_dst_item {
+    ($dst_iter:expr, $num_lists:expr, $dst_len:expr) => {{
+        match $dst_iter.next() {
+            Some(dst_item) => dst_item,
+            None => {
+                return Err(DeserializationError::MismatchedLength {
+                    src: $num_lists,
+                    dst: $dst_len,
+                })
+            }
+        }
+    }};
+}
+
 /// Implementation of [`read_options_from_array`] generalized over offset type
 macro_rules! read_list_of_options_from_array {
     ($src:expr, $offset_ty:ty, $dst: expr) => {{
         if let Some(src) = $src.as_list_opt::<$offset_ty>() {
+            if let Some(values) = DirectListValues::new::<I>(src.values()) {
+                let num_lists = src.len();
+                let dst_len = $dst.len();
+                if num_lists > dst_len {
+                    return Err(DeserializationError::MismatchedLength {
+                        src: num_lists,
+                        dst: dst_len,
+                    });
+                }
+
+                let mut dst = $dst.iter_mut();
+                for (i, offsets) in src.offsets().windows(2).enumerate() {
+                    let dst_item: &mut Option<Vec<I>> = next_dst_item!(dst, num_lists, dst_len);
+                    if src.is_null(i) {
+                        *dst_item = None;
+                    } else {
+                        values.write(
+                            offsets[0].as_usize()..offsets[1].as_usize(),
+                            dst_item.get_or_insert_with(Vec::new),
+                        );
+                    }
+                }
+                return Ok(num_lists);
+            }
+
             let (src, mut elements) = init_list_read!(src, $dst);
             let mut offsets = src.offsets().iter().copied();
 
@@ -853,20 +2280,19 @@ macro_rules! read_list_of_options_from_array {
             let offsets =
                 NullableValuesIterator::new(offsets, src.nulls().map(|nulls| nulls.iter()));
             let num_lists = offsets.len();
+            let dst_len = $dst.len();
 
-            if num_lists > $dst.len() {
+            if num_lists > dst_len {
                 return Err(DeserializationError::MismatchedLength {
                     src: num_lists,
-                    dst: $dst.len(),
+                    dst: dst_len,
                 });
             }
 
             let mut dst = $dst.iter_mut();
 
             for offset in offsets {
-                // Safe because we checked dst.len() == num_elements, and num_elements
-                // is also the size of offsets
-                let dst_item: &mut Option<Vec<I>> = unsafe { dst.next().unwrap_unchecked() };
+                let dst_item: &mut Option<Vec<I>> = next_dst_item!(dst, num_lists, dst_len);
                 match offset {
                     None => *dst_item = None,
                     Some(offset) => {
@@ -890,7 +2316,7 @@ macro_rules! read_list_of_options_from_array {
 /// available: `impl<I: ArRowDeserializeOption> ArRowDeserialize for Option<I>`.
 impl<I> ArRowDeserializeOption for Vec<I>
 where
-    I: Default + ArRowDeserialize,
+    I: Default + ArRowDeserialize + 'static,
 {
     fn read_options_from_array<'a, 'b, T>(
         src: impl Array + AsArray,
@@ -901,6 +2327,24 @@ where
     {
         read_list_of_options_from_array!(src, i32, dst);
         read_list_of_options_from_array!(src, i64, dst);
+        if let Some(src) = src.as_fixed_size_list_opt() {
+            return read_fixed_size_list_options(src, dst);
+        }
+        if let Some(src) = src.as_binary_opt::<i32>() {
+            return read_binary_options(src, dst);
+        }
+        if let Some(src) = src.as_binary_opt::<i64>() {
+            return read_binary_options(src, dst);
+        }
+        if let Some(src) = src.as_map_opt() {
+            return Self::read_options_from_array(map_entries_as_list(src), dst);
+        }
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return Self::read_options_from_array(unpack_dictionary(src)?, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_options_from_array(src, dst);
+        }
         Err(DeserializationError::MismatchedColumnDataType(format!(
             "Could not cast {:?} array with as_list_opt",
             src.data_type()
@@ -908,10 +2352,48 @@ where
     }
 }
 
+/// Returns the entries of each map of `src` as a list of structures
+fn map_entries_as_list(src: &MapArray) -> ListArray {
+    let field = match src.data_type() {
+        DataType::Map(field, _) => field.clone(),
+        datatype => panic!("MapArray has type {:?}", datatype),
+    };
+    ListArray::new(
+        field,
+        src.offsets().clone(),
+        Arc::new(src.entries().clone()),
+        src.nulls().cloned(),
+    )
+}
+
 /// Implementation of [`read_from_array`] generalized over offset type
 macro_rules! read_list_from_array {
     ($src:expr, $offset_ty:ty, $dst: expr) => {{
         if let Some(src) = $src.as_list_opt::<$offset_ty>() {
+            if let Some(values) = DirectListValues::new::<I>(src.values()) {
+                if src.nulls().is_some() {
+                    return Err(DeserializationError::UnexpectedNull(format!(
+                        "{} column contains nulls",
+                        values.type_name()
+                    )));
+                }
+                let num_lists = src.len();
+                let dst_len = $dst.len();
+                if num_lists > dst_len {
+                    return Err(DeserializationError::MismatchedLength {
+                        src: num_lists,
+                        dst: dst_len,
+                    });
+                }
+
+                let mut dst = $dst.iter_mut();
+                for offsets in src.offsets().windows(2) {
+                    let dst_item: &mut Vec<I> = next_dst_item!(dst, num_lists, dst_len);
+                    values.write(offsets[0].as_usize()..offsets[1].as_usize(), dst_item);
+                }
+                return Ok(num_lists);
+            }
+
             let (src, mut elements) = init_list_read!(src, $dst);
             return match src.nulls() {
                 Some(_) => Err(DeserializationError::UnexpectedNull(format!(
@@ -923,20 +2405,19 @@ macro_rules! read_list_from_array {
 
                     let mut previous_offset = offsets.next().unwrap_or(0);
                     let num_lists = offsets.len();
+                    let dst_len = $dst.len();
 
-                    if num_lists > $dst.len() {
+                    if num_lists > dst_len {
                         return Err(DeserializationError::MismatchedLength {
                             src: num_lists,
-                            dst: $dst.len(),
+                            dst: dst_len,
                         });
                     }
 
                     let mut dst = $dst.iter_mut();
 
                     for offset in offsets {
-                        // Safe because we checked dst.len() == num_elements, and num_elements
-                        // is also the size of offsets
-                        let dst_item: &mut Vec<I> = unsafe { dst.next().unwrap_unchecked() };
+                        let dst_item: &mut Vec<I> = next_dst_item!(dst, num_lists, dst_len);
 
                         *dst_item = build_list_item!(offset, previous_offset, elements);
                     }
@@ -954,7 +2435,7 @@ macro_rules! read_list_from_array {
 /// Deserialization of Arrow lists without nullable values
 impl<I> ArRowDeserialize for Vec<I>
 where
-    I: ArRowDeserialize,
+    I: ArRowDeserialize + 'static,
 {
     fn read_from_array<'a, 'b, T>(
         src: impl Array + AsArray,
@@ -965,6 +2446,24 @@ where
     {
         read_list_from_array!(src, i32, dst);
         read_list_from_array!(src, i64, dst);
+        if let Some(src) = src.as_fixed_size_list_opt() {
+            return read_fixed_size_lists(src, dst);
+        }
+        if let Some(src) = src.as_binary_opt::<i32>() {
+            return read_binaries(src, dst);
+        }
+        if let Some(src) = src.as_binary_opt::<i64>() {
+            return read_binaries(src, dst);
+        }
+        if let Some(src) = src.as_map_opt() {
+            return Self::read_from_array(map_entries_as_list(src), dst);
+        }
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return Self::read_from_array(unpack_dictionary(src)?, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
         Err(DeserializationError::MismatchedColumnDataType(format!(
             "Could not cast {:?} array with as_list_opt",
             src.data_type()
@@ -972,18 +2471,175 @@ where
     }
 }
 
+impl<T: ArRowStruct, const N: usize> ArRowStruct for FixedSizeList<T, N> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        T::columns_with_prefix(prefix)
+    }
+
+    fn column_tree() -> ColumnTree {
+        T::column_tree()
+    }
+
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        T::for_each_column_path(path, f)
+    }
+}
+
+/// `FixedSizeList<T, N>` is read from `FixedSizeList` arrays of size `N`. Elements
+/// declared as nullable must be read as `FixedSizeList<Option<T>, N>`.
+impl<T: CheckableDataType, const N: usize> CheckableDataType for FixedSizeList<T, N> {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        let datatype = decoded_datatype(datatype);
+        match datatype {
+            DataType::FixedSizeList(inner, size) if usize::try_from(*size) == Ok(N) => {
+                if inner.is_nullable() && !T::nullable() {
+                    let type_name = std::any::type_name::<T>();
+                    return Err(format!(
+                        "FixedSizeList<{type_name}, {N}> cannot be decoded from nullable \
+                         elements, use FixedSizeList<Option<{type_name}>, {N}>"
+                    ));
+                }
+                T::check_datatype(inner.data_type())
+            }
+            _ => Err(format!(
+                "Must be a FixedSizeList of size {N}, not {datatype:?}"
+            )),
+        }
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::new_fixed_size_list(
+            T::expected_datatype(),
+            N.try_into().expect("Array size overflows i32"),
+            T::nullable(),
+        )
+    }
+}
+
+/// Returns `src` as a fixed-size list array of the given size
+fn fixed_size_list_of_size(
+    src: &(impl Array + AsArray),
+    size: usize,
+) -> Result<&FixedSizeListArray, DeserializationError> {
+    match src.as_fixed_size_list_opt() {
+        Some(src) if src.value_length().as_usize() == size => Ok(src),
+        _ => Err(DeserializationError::MismatchedColumnDataType(format!(
+            "Could not cast {:?} array with as_fixed_size_list_opt of size {}",
+            src.data_type(),
+            size
+        ))),
+    }
+}
+
+/// Deserialization of Arrow fixed-size lists without nulls
+///
+/// Fixed-size binary arrays are read with [`FixedSizeBinary`] instead.
+impl<I: ArRowDeserialize, const N: usize> ArRowDeserialize for FixedSizeList<I, N> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return Self::read_from_array(unpack_dictionary(src)?, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
+        let src = fixed_size_list_of_size(&src, N)?;
+        if src.nulls().is_some() {
+            return Err(DeserializationError::UnexpectedNull(format!(
+                "FixedSizeList<{}, {}> column contains nulls",
+                std::any::type_name::<I>(),
+                N
+            )));
+        }
+        let num_lists = src.len();
+        let dst_len = dst.len();
+        if num_lists > dst_len {
+            return Err(DeserializationError::MismatchedLength {
+                src: num_lists,
+                dst: dst_len,
+            });
+        }
+
+        let mut elements = I::from_array(src.values().slice(0, num_lists * N))?.into_iter();
+        let mut dst = dst.iter_mut();
+        for _ in 0..num_lists {
+            let dst_item: &mut FixedSizeList<I, N> = next_dst_item!(dst, num_lists, dst_len);
+            for (dst_element, element) in dst_item.0.iter_mut().zip(elements.by_ref()) {
+                *dst_element = element;
+            }
+        }
+        Ok(num_lists)
+    }
+}
+
+/// Values of null lists are skipped, as they are usually null themselves.
+impl<I: ArRowDeserialize, const N: usize> ArRowDeserializeOption for FixedSizeList<I, N> {
+    fn read_options_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+    {
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return Self::read_options_from_array(unpack_dictionary(src)?, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_options_from_array(src, dst);
+        }
+        let src = fixed_size_list_of_size(&src, N)?;
+        let num_lists = src.len();
+        let dst_len = dst.len();
+        if num_lists > dst_len {
+            return Err(DeserializationError::MismatchedLength {
+                src: num_lists,
+                dst: dst_len,
+            });
+        }
+
+        let mut dst = dst.iter_mut();
+        let mut start = 0;
+        for (is_valid, run_length) in ValidityRuns::new(src.nulls(), num_lists) {
+            if is_valid {
+                let values = src.values().slice(start * N, run_length * N);
+                let mut elements = I::from_array(values)?.into_iter();
+                for _ in 0..run_length {
+                    let dst_item: &mut Option<FixedSizeList<I, N>> =
+                        next_dst_item!(dst, num_lists, dst_len);
+                    let dst_item = dst_item.get_or_insert_with(Default::default);
+                    for (dst_element, element) in dst_item.0.iter_mut().zip(elements.by_ref()) {
+                        *dst_element = element;
+                    }
+                }
+            } else {
+                for _ in 0..run_length {
+                    let dst_item: &mut Option<FixedSizeList<I, N>> =
+                        next_dst_item!(dst, num_lists, dst_len);
+                    *dst_item = None;
+                }
+            }
+            start += run_length;
+        }
+        Ok(num_lists)
+    }
+}
+
 /// The trait of things that can have Arrow data written to them.
 ///
 /// It must be (mutably) iterable, exact-size, and iterable multiple times (one for
 /// each column it contains).
 ///
-/// # Safety
-///
-/// Implementations returning `len()` values larger than the
-/// actual length of the iterator returned by `iter_mut()` would lead to
-/// undefined behavior (values yielded by the iterator are unwrapped unsafely,
-/// for performance).
-pub unsafe trait DeserializationTarget<'a> {
+/// `len()` should return the number of items yielded by `iter_mut()`. Deserializers
+/// rely on it to return [`DeserializationError::MismatchedLength`] early, but do not
+/// trust it for memory safety: implementations yielding fewer items than `len()`
+/// get the same error (or have the extra rows of the batch ignored), never
+/// undefined behavior.
+pub trait DeserializationTarget<'a> {
     type Item: 'a;
     type IterMut<'b>: Iterator<Item = &'b mut Self::Item>
     where
@@ -1006,7 +2662,7 @@ pub unsafe trait DeserializationTarget<'a> {
     }
 }
 
-unsafe impl<'a, V: Sized + 'a> DeserializationTarget<'a> for &mut Vec<V> {
+impl<'a, V: Sized + 'a> DeserializationTarget<'a> for &mut Vec<V> {
     type Item = V;
     type IterMut<'b> = IterMut<'b, V> where V: 'b, 'a: 'b, Self: 'b;
 
@@ -1019,27 +2675,57 @@ unsafe impl<'a, V: Sized + 'a> DeserializationTarget<'a> for &mut Vec<V> {
     }
 }
 
+/// Mutable slices, eg. to deserialize to part of a buffer
+impl<'a, V: Sized + 'a> DeserializationTarget<'a> for &mut &mut [V] {
+    type Item = V;
+    type IterMut<'b> = IterMut<'b, V> where V: 'b, 'a: 'b, Self: 'b;
+
+    fn len(&self) -> usize {
+        <[_]>::len(self)
+    }
+
+    fn iter_mut(&mut self) -> IterMut<'_, V> {
+        <[_]>::iter_mut(self)
+    }
+}
+
+/// Fixed-size arrays, eg. to deserialize a single row without allocating a [`Vec`]
+impl<'a, V: Sized + 'a, const N: usize> DeserializationTarget<'a> for &mut [V; N] {
+    type Item = V;
+    type IterMut<'b> = IterMut<'b, V> where V: 'b, 'a: 'b, Self: 'b;
+
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn iter_mut(&mut self) -> IterMut<'_, V> {
+        <[_]>::iter_mut(*self)
+    }
+}
+
 /// A map that can be iterated multiple times
 pub struct MultiMap<'c, T: Sized, F> {
     iter: &'c mut T,
     f: F,
 }
 
-unsafe impl<'a, V: Sized + 'a, V2: Sized + 'a, T, F> DeserializationTarget<'a>
+/// The projection `F` is borrowed by each iteration rather than copied, so it
+/// does not need to be [`Copy`]; it may capture state by mutable reference.
+impl<'a, V: Sized + 'a, V2: Sized + 'a, T, F> DeserializationTarget<'a>
     for &mut MultiMap<'_, T, F>
 where
-    F: Copy + for<'b> FnMut(&'b mut V) -> &'b mut V2,
+    F: for<'b> FnMut(&'b mut V) -> &'b mut V2,
     T: DeserializationTarget<'a, Item = V>,
 {
     type Item = V2;
-    type IterMut<'b> = Map<T::IterMut<'b>, F> where T: 'b, 'a: 'b, F: 'b, Self: 'b;
+    type IterMut<'b> = Map<T::IterMut<'b>, &'b mut F> where T: 'b, 'a: 'b, F: 'b, Self: 'b;
 
     fn len(&self) -> usize {
         self.iter.len()
     }
 
-    fn iter_mut(&mut self) -> Map<T::IterMut<'_>, F> {
-        self.iter.iter_mut().map(self.f)
+    fn iter_mut(&mut self) -> Map<T::IterMut<'_>, &'_ mut F> {
+        self.iter.iter_mut().map(&mut self.f)
     }
 }
 
@@ -1070,6 +2756,32 @@ impl<I: ArRowDeserializeOption> ArRowDeserialize for Option<I> {
     }
 }
 
+/// Structures whose fields can be overwritten from arrays with only some of their
+/// columns, leaving other fields untouched
+///
+/// This allows joining columns read from separate files (or separate reads of the
+/// same file) into the same rows. It is implemented by `#[derive(ArRowDeserialize)]`.
+pub trait ArRowUpdate: ArRowDeserialize {
+    /// Overwrites fields of the first `src.len()` items of `dst` with the columns of
+    /// `src` (a struct array) which have the same name.
+    ///
+    /// Returns an error if a column does not match any field, or cannot be
+    /// deserialized into it. Columns of nested structures replace the whole field.
+    /// Nulls of `src` itself are ignored, only nulls of its columns are read.
+    fn update_from_array(
+        src: impl Array + AsArray,
+        dst: &mut [Self],
+    ) -> Result<usize, DeserializationError>;
+
+    /// Wrapper for [`update_from_array`](Self::update_from_array)
+    fn update_from_record_batch(
+        src: RecordBatch,
+        dst: &mut [Self],
+    ) -> Result<usize, DeserializationError> {
+        Self::update_from_array(StructArray::from(src), dst)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use arrow::datatypes::DataType;
@@ -1123,6 +2835,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_map_non_copy_closure() {
+        let array: Arc<dyn Array> = Arc::new(Int64Array::from(vec![1, 2, 3]));
+        let mut rows = vec![(0i64, 0i64); 3];
+        let mut visited = 0;
+        let mut dst = &mut rows;
+        i64::read_from_array::<MultiMap<&mut Vec<(i64, i64)>, _>>(
+            array,
+            &mut dst.map(|row| {
+                visited += 1;
+                &mut row.1
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(visited, 3);
+        assert_eq!(rows, vec![(0, 1), (0, 2), (0, 3)]);
+    }
+
     #[test]
     fn test_check_datatype() {
         assert_eq!(i64::check_datatype(&DataType::Int64), Ok(()));
@@ -1169,4 +2900,35 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_booleans() {
+        let values: Vec<bool> = (0..200).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+        let array = BooleanArray::from(values.clone());
+        assert_eq!(
+            bool::from_array(Arc::new(array.clone()) as ArrayRef),
+            Ok(values.clone())
+        );
+
+        // Offset not aligned on a byte
+        let array: ArrayRef = Arc::new(array.slice(5, 130));
+        assert_eq!(bool::from_array(array), Ok(values[5..135].to_vec()));
+    }
+
+    #[test]
+    fn test_column_path_parse() {
+        assert_eq!(
+            ColumnPath::parse("a.b"),
+            ColumnPath(vec!["a".to_owned(), "b".to_owned()])
+        );
+        assert_eq!(
+            ColumnPath::parse(r"a\.b.c\\"),
+            ColumnPath(vec!["a.b".to_owned(), r"c\".to_owned()])
+        );
+        assert_eq!(ColumnPath::parse(r"a\"), ColumnPath(vec![r"a\".to_owned()]));
+
+        let path = ColumnPath(vec![r"a.\b".to_owned(), "c".to_owned()]);
+        assert_eq!(path.to_string(), r"a\.\\b.c");
+        assert_eq!(ColumnPath::parse(&path.to_string()), path);
+    }
 }
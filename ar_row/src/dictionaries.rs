@@ -3,16 +3,438 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
 use arrow::array::*;
+use arrow::datatypes::*;
 
 use crate::deserialize::{ArRowDeserialize, DeserializationError, DeserializationTarget};
 
+/// Number of dictionaries whose deserialized values are kept by a [`DictionaryCache`]
+const DICTIONARY_CACHE_SIZE: usize = 16;
+
+/// Dictionaries with more than this many values per row of the array only have
+/// the values referenced by the array deserialized (and are not cached)
+const SPARSE_DICTIONARY_RATIO: usize = 4;
+
+struct CachedDictionary {
+    /// Values of the dictionary. Holding them prevents their buffers from being freed,
+    /// so their addresses cannot be reused by a different dictionary while cached.
+    values: ArrayData,
+    /// `Arc<Vec<Item>>`
+    deserialized: Arc<dyn Any + Send + Sync>,
+}
+
+thread_local! {
+    /// Entries of the [`DictionaryCache`] whose [`scope`](DictionaryCache::scope)
+    /// is running on this thread, most recently used dictionaries first
+    static ACTIVE_DICTIONARY_CACHE: RefCell<Option<VecDeque<CachedDictionary>>> = RefCell::new(None);
+}
+
+/// Deserialized values of the last dictionaries read in a [`scope`](Self::scope)
+///
+/// Readers usually reuse the same dictionary values across batches of a column,
+/// so this avoids deserializing them again for every batch.
+/// [`RowIterator`](crate::row_iterator::RowIterator) owns one, so they are freed
+/// with it.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use ar_row::arrow::array::*;
+/// # use ar_row::arrow::datatypes::Int32Type;
+/// use ar_row::deserialize::ArRowDeserialize;
+/// use ar_row::dictionaries::DictionaryCache;
+///
+/// let values: ArrayRef = Arc::new(StringArray::from(vec!["a", "b"]));
+/// let arrays: Vec<ArrayRef> = vec![
+///     Arc::new(DictionaryArray::<Int32Type>::new(Int32Array::from(vec![0, 1]), values.clone())),
+///     Arc::new(DictionaryArray::<Int32Type>::new(Int32Array::from(vec![1, 1]), values)),
+/// ];
+///
+/// let mut cache = DictionaryCache::new();
+/// for array in arrays {
+///     // Values are only deserialized for the first array
+///     let rows = cache.scope(|| String::from_array(array)).unwrap();
+///     assert_eq!(rows.len(), 2);
+/// }
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Default)]
+pub struct DictionaryCache {
+    entries: VecDeque<CachedDictionary>,
+}
+
+impl DictionaryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `f`, with dictionaries deserialized by the current thread looked up in
+    /// and added to this cache.
+    ///
+    /// Deserialization on other threads (eg. spawned by `f`) does not use the cache.
+    pub fn scope<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        /// Moves entries back to the cache and reactivates the outer scope's
+        /// cache, even if `f` panics
+        struct Restore<'a> {
+            entries: &'a mut VecDeque<CachedDictionary>,
+            outer: Option<VecDeque<CachedDictionary>>,
+        }
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                *self.entries = ACTIVE_DICTIONARY_CACHE
+                    .with(|cache| cache.replace(self.outer.take()))
+                    .unwrap_or_default();
+            }
+        }
+
+        let entries = std::mem::take(&mut self.entries);
+        let outer = ACTIVE_DICTIONARY_CACHE.with(|cache| cache.replace(Some(entries)));
+        let _restore = Restore {
+            entries: &mut self.entries,
+            outer,
+        };
+        f()
+    }
+
+    /// Returns the number of dictionaries in the cache
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops all deserialized values
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
+
+impl std::fmt::Debug for DictionaryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DictionaryCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Returns the deserialized values of a dictionary if they are in the active cache
+fn cached_values<Item>(values: &ArrayRef) -> Option<Arc<Vec<Item>>>
+where
+    Item: Send + Sync + 'static,
+{
+    ACTIVE_DICTIONARY_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let cache = cache.as_mut()?;
+        let data = values.to_data();
+        let index = cache
+            .iter()
+            .position(|entry| entry.values.ptr_eq(&data) && entry.deserialized.is::<Vec<Item>>())?;
+        let entry = cache.remove(index)?;
+        let deserialized = entry.deserialized.clone();
+        cache.push_front(entry);
+        deserialized.downcast::<Vec<Item>>().ok()
+    })
+}
+
+/// Deserializes the values of a dictionary, or returns them from the active cache
+/// if they were already deserialized for a previous batch.
+fn deserialize_cached_values<Item>(
+    values: &ArrayRef,
+) -> Result<Arc<Vec<Item>>, DeserializationError>
+where
+    Item: ArRowDeserialize + Send + Sync + 'static,
+{
+    if let Some(deserialized) = cached_values(values) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("dictionary found in cache");
+        return Ok(deserialized);
+    }
+
+    let deserialized = deserialize_values(values)?;
+    ACTIVE_DICTIONARY_CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.truncate(DICTIONARY_CACHE_SIZE - 1);
+            cache.push_front(CachedDictionary {
+                values: values.to_data(),
+                deserialized: deserialized.clone(),
+            });
+        }
+    });
+    Ok(deserialized)
+}
+
+fn deserialize_values<Item: ArRowDeserialize>(
+    values: &ArrayRef,
+) -> Result<Arc<Vec<Item>>, DeserializationError> {
+    Ok(Arc::new(Item::from_array(unpack_views(values.clone()))?))
+}
+
+/// Returns a `Utf8` (resp. `Binary`) copy of `values` if it is a `Utf8View` (resp.
+/// `BinaryView`) array, or `values` itself otherwise.
+///
+/// Recent writers emit dictionaries with view values, which scalar types are not
+/// deserialized from. Values are unpacked after looking up the cache, which is keyed
+/// by the original array.
+fn unpack_views(values: ArrayRef) -> ArrayRef {
+    if let Some(values) = values.as_any().downcast_ref::<StringViewArray>() {
+        Arc::new(values.iter().collect::<StringArray>())
+    } else if let Some(values) = values.as_any().downcast_ref::<BinaryViewArray>() {
+        Arc::new(values.iter().collect::<BinaryArray>())
+    } else {
+        values
+    }
+}
+/// Runs `$body` with `$keys` bound to the (concretely typed) key array of `$src`,
+/// so they can be iterated on without copying them to a `Vec<usize>` like
+/// [`AnyDictionaryArray::normalized_keys`] does.
+macro_rules! with_typed_keys {
+    ($src:expr, |$keys:ident| $body:block) => {{
+        let src: &dyn AnyDictionaryArray = $src;
+        let key_type = match src.data_type() {
+            DataType::Dictionary(key_type, _) => key_type.as_ref(),
+            data_type => panic!("Dictionary array has non-dictionary type {}", data_type),
+        };
+        match key_type {
+            DataType::Int8 => {
+                let $keys = typed_keys::<Int8Type>(src);
+                $body
+            }
+            DataType::Int16 => {
+                let $keys = typed_keys::<Int16Type>(src);
+                $body
+            }
+            DataType::Int32 => {
+                let $keys = typed_keys::<Int32Type>(src);
+                $body
+            }
+            DataType::Int64 => {
+                let $keys = typed_keys::<Int64Type>(src);
+                $body
+            }
+            DataType::UInt8 => {
+                let $keys = typed_keys::<UInt8Type>(src);
+                $body
+            }
+            DataType::UInt16 => {
+                let $keys = typed_keys::<UInt16Type>(src);
+                $body
+            }
+            DataType::UInt32 => {
+                let $keys = typed_keys::<UInt32Type>(src);
+                $body
+            }
+            DataType::UInt64 => {
+                let $keys = typed_keys::<UInt64Type>(src);
+                $body
+            }
+            key_type => {
+                return Err(DeserializationError::MismatchedColumnDataType(format!(
+                    "Dictionary keys must be integers, not {}",
+                    key_type
+                )))
+            }
+        }
+    }};
+}
+
+fn typed_keys<K: ArrowDictionaryKeyType>(src: &dyn AnyDictionaryArray) -> &PrimitiveArray<K> {
+    src.as_any()
+        .downcast_ref::<DictionaryArray<K>>()
+        .expect("Dictionary array does not match its key type")
+        .keys()
+}
+
+/// Deserialized values of a dictionary
+enum DecodedValues<Item> {
+    /// All values of the dictionary, indexed by key
+    All(Arc<Vec<Item>>),
+    /// Only the values referenced by keys of the array; `values[i]` is the value
+    /// of `keys[i]`, and `keys` is sorted
+    Referenced { keys: Vec<usize>, values: Vec<Item> },
+}
+
+impl<Item: ArRowDeserialize> DecodedValues<Item> {
+    /// Deserializes the values of `src`'s dictionary, or only those referenced by its
+    /// keys if the dictionary is much larger than the array or if some of its values
+    /// cannot be deserialized.
+    fn new(src: &dyn AnyDictionaryArray) -> Result<Self, DeserializationError> {
+        Self::decode(src, deserialize_values, |_| None)
+    }
+
+    /// Same as [`new`](Self::new), but looks up and adds the values to the active
+    /// [`DictionaryCache`]
+    fn new_cached(src: &dyn AnyDictionaryArray) -> Result<Self, DeserializationError>
+    where
+        Item: Send + Sync + 'static,
+    {
+        Self::decode(src, deserialize_cached_values, cached_values)
+    }
+
+    fn decode(
+        src: &dyn AnyDictionaryArray,
+        deserialize_all: impl FnOnce(&ArrayRef) -> Result<Arc<Vec<Item>>, DeserializationError>,
+        cached: impl FnOnce(&ArrayRef) -> Option<Arc<Vec<Item>>>,
+    ) -> Result<Self, DeserializationError> {
+        let values = src.values();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "decode_dictionary",
+            type_name = std::any::type_name::<Item>(),
+            rows = src.len(),
+            values = values.len()
+        )
+        .entered();
+
+        if values.len() / SPARSE_DICTIONARY_RATIO <= src.len() {
+            // Values which no key references may not be deserializable (eg. nulls,
+            // when `Item` is not an `Option`); in this case only referenced values
+            // are decoded, like for sparse dictionaries, so both give the same result.
+            match deserialize_all(values) {
+                Ok(values) => return Ok(DecodedValues::All(values)),
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(error = %_e, "could not decode whole dictionary");
+                }
+            }
+        } else if let Some(deserialized) = cached(values) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!("dictionary found in cache");
+            return Ok(DecodedValues::All(deserialized));
+        }
+        // Referenced values only apply to this array's keys, so they are not cached
+        #[cfg(feature = "tracing")]
+        tracing::trace!("decoding only referenced values of dictionary");
+
+        let mut keys = with_typed_keys!(src, |keys| {
+            keys.iter()
+                .flatten()
+                .map(|key| key.as_usize())
+                .collect::<Vec<_>>()
+        });
+        keys.sort_unstable();
+        keys.dedup();
+        // Out-of-range keys are left out, so `get` returns an error for them
+        keys.truncate(keys.partition_point(|&key| key < values.len()));
+
+        let indices = UInt64Array::from_iter_values(keys.iter().map(|&key| key as u64));
+        let values = Item::from_array(unpack_views(take_values(src, &indices)?))?;
+        Ok(DecodedValues::Referenced { keys, values })
+    }
+
+    /// Returns the value for `key`, or [`DeserializationError::DictionaryOverflow`]
+    fn get(&self, src: &dyn AnyDictionaryArray, key: usize) -> Result<&Item, DeserializationError> {
+        let value = match self {
+            DecodedValues::All(values) => values.get(key),
+            DecodedValues::Referenced { keys, values } => {
+                keys.binary_search(&key).ok().map(|i| &values[i])
+            }
+        };
+        value.ok_or_else(|| DeserializationError::DictionaryOverflow {
+            key,
+            len: src.values().len(),
+            data_type: src.data_type().clone(),
+        })
+    }
+}
+
+/// Returns the values of `src`'s dictionary at the given indices
+fn take_values(
+    src: &dyn AnyDictionaryArray,
+    indices: &UInt64Array,
+) -> Result<ArrayRef, DeserializationError> {
+    // `take` does not support run-end encoded arrays
+    let values = match unpack_run_end_encoded(src.values())? {
+        Some(values) => values,
+        None => src.values().clone(),
+    };
+    arrow::compute::take(values.as_ref(), indices, None).map_err(|e| unpack_error(src, e))
+}
+
+fn unpack_error(src: &dyn AnyDictionaryArray, e: arrow::error::ArrowError) -> DeserializationError {
+    DeserializationError::MismatchedColumnDataType(format!(
+        "Could not unpack {} dictionary: {}",
+        src.data_type(),
+        e
+    ))
+}
+
 /// Decodes non-`Option`s from a
 /// [dictionary-encoded](https://arrow.apache.org/docs/format/Columnar.html#dictionary-encoded-layout)
 /// array
+///
+/// Dictionary values are deserialized on every call; types implemented by this crate
+/// use a [`DictionaryCache`] when one is in [scope](DictionaryCache::scope).
 pub fn read_from_dictionary_array<'a, 'b, T, Item>(
+    src: &dyn AnyDictionaryArray,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    Item: 'a + Clone + ArRowDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = Item> + 'b,
+{
+    read_decoded_values(src, dst, DecodedValues::new)
+}
+
+/// Decodes `Option`s from a
+/// [dictionary-encoded](https://arrow.apache.org/docs/format/Columnar.html#dictionary-encoded-layout)
+/// array
+///
+/// See [`read_from_dictionary_array`]
+pub fn read_options_from_dictionary_array<'a, 'b, T, Item>(
+    src: &dyn AnyDictionaryArray,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    Item: 'a + Clone + ArRowDeserialize,
+    Option<Item>: 'a + Clone + ArRowDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Item>> + 'b,
+{
+    match src.nulls() {
+        None => read_from_dictionary_array(src, dst),
+        Some(nulls) => read_decoded_options(src, nulls, dst, DecodedValues::new),
+    }
+}
+
+/// Same as [`read_from_dictionary_array`], but uses the active [`DictionaryCache`]
+pub(crate) fn read_from_cached_dictionary_array<'a, 'b, T, Item>(
+    src: &dyn AnyDictionaryArray,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    Item: 'a + Clone + ArRowDeserialize + Send + Sync + 'static,
+    &'b mut T: DeserializationTarget<'a, Item = Item> + 'b,
+{
+    read_decoded_values(src, dst, DecodedValues::new_cached)
+}
+
+/// Same as [`read_options_from_dictionary_array`], but uses the active
+/// [`DictionaryCache`]
+pub(crate) fn read_options_from_cached_dictionary_array<'a, 'b, T, Item>(
+    src: &dyn AnyDictionaryArray,
+    dst: &'b mut T,
+) -> Result<usize, DeserializationError>
+where
+    Item: 'a + Clone + ArRowDeserialize + Send + Sync + 'static,
+    Option<Item>: 'a + Clone + ArRowDeserialize,
+    &'b mut T: DeserializationTarget<'a, Item = Option<Item>> + 'b,
+{
+    match src.nulls() {
+        None => read_from_cached_dictionary_array(src, dst),
+        Some(nulls) => read_decoded_options(src, nulls, dst, DecodedValues::new_cached),
+    }
+}
+
+fn read_decoded_values<'a, 'b, T, Item>(
     src: &dyn AnyDictionaryArray,
     mut dst: &'b mut T,
+    decode: impl FnOnce(&dyn AnyDictionaryArray) -> Result<DecodedValues<Item>, DeserializationError>,
 ) -> Result<usize, DeserializationError>
 where
     Item: 'a + Clone + ArRowDeserialize,
@@ -24,61 +446,170 @@ where
             std::any::type_name::<Item>(),
         )));
     };
-    let deserialized_values = <Item>::from_array(src.values().clone())?;
-    for (key, d) in src.normalized_keys().into_iter().zip(dst.iter_mut()) {
-        // FIXME: does Rustc eliminate the copy in normalized_keys()?
-        // If not, we should match on the key type and iter on that.
-        *d = deserialized_values
-            .get(key)
-            .ok_or_else(|| DeserializationError::DictionaryOverflow {
-                key,
-                len: deserialized_values.len(),
-                data_type: src.data_type().clone(),
-            })?
-            .clone();
-    }
+    let decoded_values = decode(src)?;
+    with_typed_keys!(src, |keys| {
+        for (key, d) in keys.values().iter().zip(dst.iter_mut()) {
+            *d = decoded_values.get(src, key.as_usize())?.clone();
+        }
+    });
     Ok(src.len())
 }
 
-/// Decodes `Option`s from a
-/// [dictionary-encoded](https://arrow.apache.org/docs/format/Columnar.html#dictionary-encoded-layout)
-/// array
-pub fn read_options_from_dictionary_array<'a, 'b, T, Item>(
+fn read_decoded_options<'a, 'b, T, Item>(
     src: &dyn AnyDictionaryArray,
+    nulls: &arrow::buffer::NullBuffer,
     mut dst: &'b mut T,
+    decode: impl FnOnce(&dyn AnyDictionaryArray) -> Result<DecodedValues<Item>, DeserializationError>,
 ) -> Result<usize, DeserializationError>
 where
     Item: 'a + Clone + ArRowDeserialize,
     Option<Item>: 'a + Clone + ArRowDeserialize,
     &'b mut T: DeserializationTarget<'a, Item = Option<Item>> + 'b,
 {
-    let deserialized_values = <Item>::from_array(src.values().clone())?;
-    match src.nulls() {
-        None => read_from_dictionary_array(src, dst),
-        Some(nulls) => {
-            for ((not_null, key), d) in nulls
-                .iter()
-                .zip(src.normalized_keys().into_iter())
-                .zip(dst.iter_mut())
-            {
-                // FIXME: does Rustc eliminate the copy in normalized_keys()?
-                // If not, we should match on the key type and iter on that.
-                if not_null {
-                    *d = Some(
-                        deserialized_values
-                            .get(key)
-                            .ok_or_else(|| DeserializationError::DictionaryOverflow {
-                                key,
-                                len: deserialized_values.len(),
-                                data_type: src.data_type().clone(),
-                            })?
-                            .clone(),
-                    );
-                } else {
-                    *d = None;
+    let decoded_values = decode(src)?;
+    with_typed_keys!(src, |keys| {
+        for ((not_null, key), d) in nulls.iter().zip(keys.values().iter()).zip(dst.iter_mut()) {
+            if not_null {
+                *d = Some(decoded_values.get(src, key.as_usize())?.clone());
+            } else {
+                *d = None;
+            }
+        }
+    });
+    Ok(src.len())
+}
+
+/// Replaces each key of a
+/// [dictionary-encoded](https://arrow.apache.org/docs/format/Columnar.html#dictionary-encoded-layout)
+/// array with its value, returning an array of the dictionary's value type.
+///
+/// This is used to decode dictionaries whose values are nested (lists, structs, ...),
+/// which are deserialized to types that are not necessarily [`Clone`].
+/// Rows with a null key are null in the returned array, but (unlike with
+/// [`arrow::compute::take`]) their children are not.
+pub fn unpack_dictionary(src: &dyn AnyDictionaryArray) -> Result<ArrayRef, DeserializationError> {
+    let values = src.values();
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("unpack_dictionary", rows = src.len(), values = values.len())
+        .entered();
+    let indices = with_typed_keys!(src, |keys| {
+        let mut indices = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            match key.map(|key| key.as_usize()) {
+                // Point null rows to any value, so the children of nested values
+                // are not null for them
+                None => indices.push(0u64),
+                Some(key) if key < values.len() => indices.push(key as u64),
+                Some(key) => {
+                    return Err(DeserializationError::DictionaryOverflow {
+                        key,
+                        len: values.len(),
+                        data_type: src.data_type().clone(),
+                    })
                 }
             }
-            Ok(src.len())
+        }
+        UInt64Array::from(indices)
+    });
+    if values.is_empty() {
+        // All keys are null, or we would have returned an overflow error
+        return Ok(new_null_array(values.data_type(), src.len()));
+    }
+
+    let unpacked = take_values(src, &indices)?;
+    match src.nulls() {
+        None => Ok(unpacked),
+        Some(nulls) => {
+            let data = unpacked
+                .to_data()
+                .into_builder()
+                .nulls(arrow::buffer::NullBuffer::union(
+                    Some(nulls),
+                    unpacked.nulls(),
+                ))
+                .build()
+                .map_err(|e| unpack_error(src, e))?;
+            Ok(make_array(data))
+        }
+    }
+}
+
+/// Returns the data type `datatype` is deserialized as, ie. the type of the values of
+/// dictionaries and [run-end encoded](https://arrow.apache.org/docs/format/Columnar.html#run-end-encoded-layout)
+/// arrays (in any order and nesting), which are decoded transparently.
+///
+/// Dictionaries of `Utf8View` or `BinaryView` values are deserialized as `Utf8` or
+/// `Binary` dictionaries.
+pub fn decoded_datatype(datatype: &DataType) -> &DataType {
+    match datatype {
+        DataType::Dictionary(_, values) => match decoded_datatype(values) {
+            DataType::Utf8View => &DataType::Utf8,
+            DataType::BinaryView => &DataType::Binary,
+            values => values,
+        },
+        DataType::RunEndEncoded(_, values) => decoded_datatype(values.data_type()),
+        datatype => datatype,
+    }
+}
+
+/// Returns the logical values of `src` if it is a
+/// [run-end encoded](https://arrow.apache.org/docs/format/Columnar.html#run-end-encoded-layout)
+/// array, or `None` otherwise.
+///
+/// Some writers nest run-end encoding and dictionary encoding, in either order, so
+/// the returned array is decoded with the same dispatch as `src`, which unpacks the
+/// next layer.
+pub fn unpack_run_end_encoded(src: &dyn Array) -> Result<Option<ArrayRef>, DeserializationError> {
+    let run_ends_type = match src.data_type() {
+        DataType::RunEndEncoded(run_ends, _) => run_ends.data_type(),
+        _ => return Ok(None),
+    };
+    let unpacked = match run_ends_type {
+        DataType::Int16 => unpack_runs(src.as_run::<Int16Type>()),
+        DataType::Int32 => unpack_runs(src.as_run::<Int32Type>()),
+        DataType::Int64 => unpack_runs(src.as_run::<Int64Type>()),
+        run_ends_type => {
+            return Err(DeserializationError::MismatchedColumnDataType(format!(
+                "Run ends must be Int16, Int32, or Int64, not {}",
+                run_ends_type
+            )))
+        }
+    };
+    unpacked.map(Some).map_err(|e| {
+        DeserializationError::MismatchedColumnDataType(format!(
+            "Could not unpack {} array: {}",
+            src.data_type(),
+            e
+        ))
+    })
+}
+
+fn unpack_runs<R: RunEndIndexType>(
+    src: &RunArray<R>,
+) -> Result<ArrayRef, arrow::error::ArrowError> {
+    let values = src.values();
+    if src.is_empty() {
+        return Ok(new_empty_array(values.data_type()));
+    }
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!(
+        "unpack_run_end_encoded",
+        rows = src.len(),
+        values = values.len()
+    )
+    .entered();
+    let run_ends = src.run_ends();
+    // Run ends are not adjusted when slicing the array
+    let (offset, len) = (run_ends.offset(), run_ends.len());
+    let mut indices = Vec::with_capacity(len);
+    for physical_index in run_ends.get_start_physical_index()..=run_ends.get_end_physical_index() {
+        let run_end = run_ends.values()[physical_index]
+            .as_usize()
+            .saturating_sub(offset)
+            .min(len);
+        if run_end > indices.len() {
+            indices.resize(run_end, physical_index as u64);
         }
     }
+    arrow::compute::take(values.as_ref(), &UInt64Array::from(indices), None)
 }
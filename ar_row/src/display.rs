@@ -0,0 +1,104 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Formatting of values which have no native representation in JSON, serde or
+//! Python, shared by their conversions
+
+#[cfg(any(feature = "python", feature = "serde"))]
+use std::collections::HashMap;
+
+use arrow::array::Array;
+#[cfg(any(feature = "python", feature = "serde"))]
+use arrow::datatypes::DataType;
+use arrow::error::ArrowError;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+
+#[cfg(any(feature = "python", feature = "serde"))]
+use crate::structured_reader::ArrayTree;
+
+/// Format of timestamps without a timezone, which are assumed to be in UTC
+const NAIVE_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.fZ";
+
+/// Returns Arrow's formatter of the values of `array`, ie. RFC3339 for timestamps,
+/// ISO 8601 for dates, times and durations, and decimal notation for decimals.
+pub(crate) fn value_formatter(array: &dyn Array) -> Result<ArrayFormatter<'_>, ArrowError> {
+    let options = FormatOptions::new().with_timestamp_format(Some(NAIVE_TIMESTAMP_FORMAT));
+    ArrayFormatter::try_new(array, &options)
+}
+
+/// Returns whether values of this type are converted to strings by [`value_formatter`]
+#[cfg(any(feature = "python", feature = "serde"))]
+pub(crate) fn is_formatted(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Date32
+            | DataType::Date64
+            | DataType::Time32(_)
+            | DataType::Time64(_)
+            | DataType::Timestamp(_, _)
+            | DataType::Duration(_)
+            | DataType::Interval(_)
+            | DataType::Decimal128(_, _)
+            | DataType::Decimal256(_, _)
+    )
+}
+
+/// Formatters of the leaves of an [`ArrayTree`] whose values are converted to strings,
+/// so conversions which go row by row build them once per column instead of once
+/// per value.
+#[cfg(any(feature = "python", feature = "serde"))]
+pub(crate) struct LeafFormatters<'a> {
+    formatters: HashMap<*const u8, ArrayFormatter<'a>>,
+}
+
+#[cfg(any(feature = "python", feature = "serde"))]
+impl<'a> LeafFormatters<'a> {
+    pub(crate) fn new(tree: &ArrayTree<'a>) -> Result<Self, ArrowError> {
+        let mut formatters = LeafFormatters {
+            formatters: HashMap::new(),
+        };
+        formatters.add_tree(tree)?;
+        Ok(formatters)
+    }
+
+    fn add_tree(&mut self, tree: &ArrayTree<'a>) -> Result<(), ArrowError> {
+        match tree {
+            ArrayTree::Leaf(array) => {
+                if is_formatted(array.data_type()) {
+                    self.formatters
+                        .insert(address(*array), value_formatter(*array)?);
+                }
+            }
+            ArrayTree::Struct { fields, .. } => {
+                for (_, field) in fields {
+                    self.add_tree(field)?;
+                }
+            }
+            ArrayTree::List { elements, .. } => self.add_tree(elements)?,
+            ArrayTree::Map { keys, values, .. } => {
+                self.add_tree(keys)?;
+                self.add_tree(values)?;
+            }
+            ArrayTree::Dictionary { values, .. } => self.add_tree(values)?,
+            ArrayTree::Union { variants, .. } => {
+                for (_, _, variant) in variants {
+                    self.add_tree(variant)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the formatter of a leaf of the tree this was built from, or `None`
+    /// if its values are not converted to strings
+    pub(crate) fn get(&self, array: &dyn Array) -> Option<&ArrayFormatter<'a>> {
+        self.formatters.get(&address(array))
+    }
+}
+
+#[cfg(any(feature = "python", feature = "serde"))]
+fn address(array: &dyn Array) -> *const u8 {
+    array as *const dyn Array as *const u8
+}
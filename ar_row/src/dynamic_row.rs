@@ -0,0 +1,314 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Rows whose schema is only known at runtime
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::datatypes::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::dynamic_row::DynamicRow;
+//!
+//! let a = StructArray::from(vec![(
+//!     Arc::new(Field::new("b", DataType::Int32, true)),
+//!     Arc::new(Int32Array::from(vec![Some(1), None])) as ArrayRef,
+//! )]);
+//! let batch = RecordBatch::try_from_iter(vec![("a", Arc::new(a) as ArrayRef)]).unwrap();
+//!
+//! let rows = DynamicRow::from_record_batch(batch).unwrap();
+//! assert_eq!(rows[0].get::<i64>("a.b").unwrap(), 1);
+//! assert_eq!(rows[1].get::<Option<i64>>("a.b").unwrap(), None);
+//! assert!(rows[1].get::<i64>("a.b").is_err());
+//! ```
+
+use arrow::record_batch::RecordBatch;
+use thiserror::Error;
+
+use crate::deserialize::{ColumnPath, DeserializationError};
+use crate::value::ArRowValue;
+use crate::{Date, NaiveDecimal128, Timestamp};
+
+static NULL: ArRowValue = ArRowValue::Null;
+
+/// Error returned by [`DynamicRow::get`]
+#[derive(Debug, Error, PartialEq)]
+pub enum DynamicRowError {
+    /// There is no column at the given path
+    #[error("No column {0}")]
+    MissingColumn(String),
+    /// The value at the given path cannot be converted to the requested type
+    #[error("Column {path} cannot be read as {expected}: {value:?}")]
+    MismatchedType {
+        path: String,
+        expected: &'static str,
+        value: ArRowValue,
+    },
+}
+
+/// Types which can be read from an [`ArRowValue`] by [`DynamicRow::get`]
+pub trait FromArRowValue: Sized {
+    /// Name of the type, for error messages
+    const NAME: &'static str;
+
+    /// Returns `None` if `value` is not of a compatible type
+    fn from_value(value: &ArRowValue) -> Option<Self>;
+}
+
+impl FromArRowValue for ArRowValue {
+    const NAME: &'static str = "ArRowValue";
+
+    fn from_value(value: &ArRowValue) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl<T: FromArRowValue> FromArRowValue for Option<T> {
+    const NAME: &'static str = T::NAME;
+
+    fn from_value(value: &ArRowValue) -> Option<Self> {
+        match value {
+            ArRowValue::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromArRowValue> FromArRowValue for Vec<T> {
+    const NAME: &'static str = "Vec";
+
+    fn from_value(value: &ArRowValue) -> Option<Self> {
+        match value {
+            ArRowValue::List(items) => items.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Integers can be read from any integer column, as long as the value fits.
+macro_rules! impl_from_integer {
+    ($ty:ty) => {
+        impl FromArRowValue for $ty {
+            const NAME: &'static str = stringify!($ty);
+
+            fn from_value(value: &ArRowValue) -> Option<Self> {
+                let value: i128 = match *value {
+                    ArRowValue::Int8(v) => v.into(),
+                    ArRowValue::Int16(v) => v.into(),
+                    ArRowValue::Int32(v) => v.into(),
+                    ArRowValue::Int64(v) => v.into(),
+                    ArRowValue::UInt8(v) => v.into(),
+                    ArRowValue::UInt16(v) => v.into(),
+                    ArRowValue::UInt32(v) => v.into(),
+                    ArRowValue::UInt64(v) => v.into(),
+                    _ => return None,
+                };
+                value.try_into().ok()
+            }
+        }
+    };
+}
+
+impl_from_integer!(i8);
+impl_from_integer!(i16);
+impl_from_integer!(i32);
+impl_from_integer!(i64);
+impl_from_integer!(u8);
+impl_from_integer!(u16);
+impl_from_integer!(u32);
+impl_from_integer!(u64);
+
+macro_rules! impl_from_variant {
+    ($ty:ty, $pattern:pat => $value:expr) => {
+        impl FromArRowValue for $ty {
+            const NAME: &'static str = stringify!($ty);
+
+            fn from_value(value: &ArRowValue) -> Option<Self> {
+                match value {
+                    $pattern => Some($value),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_from_variant!(bool, ArRowValue::Boolean(v) => *v);
+impl_from_variant!(f32, ArRowValue::Float32(v) => *v);
+impl_from_variant!(String, ArRowValue::String(v) => v.clone());
+impl_from_variant!(Box<[u8]>, ArRowValue::Binary(v) => v.as_slice().into());
+impl_from_variant!(NaiveDecimal128, ArRowValue::Decimal128 { value, .. } => *value);
+impl_from_variant!(Date, ArRowValue::Date(v) => *v);
+impl_from_variant!(Timestamp, ArRowValue::Timestamp(v) => *v);
+
+impl FromArRowValue for f64 {
+    const NAME: &'static str = "f64";
+
+    fn from_value(value: &ArRowValue) -> Option<Self> {
+        match *value {
+            ArRowValue::Float32(v) => Some(v.into()),
+            ArRowValue::Float64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Row of a record batch, as a list of named [`ArRowValue`]s
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DynamicRow {
+    fields: Vec<(String, ArRowValue)>,
+}
+
+impl DynamicRow {
+    pub fn new(fields: Vec<(String, ArRowValue)>) -> DynamicRow {
+        DynamicRow { fields }
+    }
+
+    /// Returns a [`DynamicRow`] for every row of `batch`
+    pub fn from_record_batch(batch: RecordBatch) -> Result<Vec<DynamicRow>, DeserializationError> {
+        Ok(ArRowValue::from_record_batch(batch)?
+            .into_iter()
+            .map(|row| match row {
+                ArRowValue::Struct(fields) => DynamicRow { fields },
+                // Rows of a record batch cannot be null
+                _ => DynamicRow::default(),
+            })
+            .collect())
+    }
+
+    /// Returns the value at `path`, which is a list of field names separated by
+    /// dots (eg. `a.b` for the field `b` of the struct in column `a`), parsed with
+    /// [`ColumnPath::parse`] (so `a\.b` is the column named `a.b`).
+    ///
+    /// If any of the structs on the way is null, returns [`ArRowValue::Null`].
+    pub fn get_value(&self, path: &str) -> Option<&ArRowValue> {
+        let path = ColumnPath::parse(path);
+        let mut parts = path.parts().iter();
+        let root = parts.next()?;
+        let mut value = lookup(&self.fields, root)?;
+        for part in parts {
+            value = match value {
+                ArRowValue::Struct(fields) => lookup(fields, part)?,
+                ArRowValue::Null => return Some(&NULL),
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Returns the value at `path` (see [`get_value`](Self::get_value)), converted
+    /// to `T`.
+    ///
+    /// Use `Option<T>` to read nullable columns.
+    pub fn get<T: FromArRowValue>(&self, path: &str) -> Result<T, DynamicRowError> {
+        let value = self
+            .get_value(path)
+            .ok_or_else(|| DynamicRowError::MissingColumn(path.to_owned()))?;
+        T::from_value(value).ok_or_else(|| DynamicRowError::MismatchedType {
+            path: path.to_owned(),
+            expected: T::NAME,
+            value: value.clone(),
+        })
+    }
+
+    /// Returns an iterator on the name and value of every top-level column
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &ArRowValue)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns the name and value of every top-level column
+    pub fn into_fields(self) -> Vec<(String, ArRowValue)> {
+        self.fields
+    }
+}
+
+fn lookup<'a>(fields: &'a [(String, ArRowValue)], name: &str) -> Option<&'a ArRowValue> {
+    fields
+        .iter()
+        .find(|(field_name, _)| field_name == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> DynamicRow {
+        DynamicRow::new(vec![
+            ("id".to_owned(), ArRowValue::UInt32(42)),
+            (
+                "a".to_owned(),
+                ArRowValue::Struct(vec![
+                    ("b".to_owned(), ArRowValue::String("foo".to_owned())),
+                    (
+                        "c".to_owned(),
+                        ArRowValue::List(vec![ArRowValue::Float32(1.5), ArRowValue::Null]),
+                    ),
+                ]),
+            ),
+            ("d".to_owned(), ArRowValue::Null),
+        ])
+    }
+
+    #[test]
+    fn test_get() {
+        let row = row();
+        assert_eq!(row.get::<i64>("id"), Ok(42));
+        assert_eq!(row.get::<u8>("id"), Ok(42));
+        assert_eq!(row.get::<String>("a.b"), Ok("foo".to_owned()));
+        assert_eq!(
+            row.get::<Vec<Option<f64>>>("a.c"),
+            Ok(vec![Some(1.5), None])
+        );
+        assert_eq!(row.get::<Option<String>>("d"), Ok(None));
+        assert_eq!(row.get::<Option<String>>("d.e"), Ok(None));
+    }
+
+    #[test]
+    fn test_get_errors() {
+        let row = row();
+        assert_eq!(
+            row.get::<i64>("a.z"),
+            Err(DynamicRowError::MissingColumn("a.z".to_owned()))
+        );
+        assert_eq!(
+            row.get::<i64>("id.z"),
+            Err(DynamicRowError::MissingColumn("id.z".to_owned()))
+        );
+        assert_eq!(
+            row.get::<i64>("a.b"),
+            Err(DynamicRowError::MismatchedType {
+                path: "a.b".to_owned(),
+                expected: "i64",
+                value: ArRowValue::String("foo".to_owned())
+            })
+        );
+        assert!(row.get::<i8>("d").is_err());
+        assert!(row.get::<Vec<f64>>("a.c").is_err());
+    }
+
+    #[test]
+    fn test_get_dotted_name() {
+        let row = DynamicRow::new(vec![
+            (
+                "a".to_owned(),
+                ArRowValue::Struct(vec![("b".to_owned(), ArRowValue::UInt32(1))]),
+            ),
+            ("a.b".to_owned(), ArRowValue::UInt32(2)),
+        ]);
+        assert_eq!(row.get::<u32>("a.b"), Ok(1));
+        assert_eq!(row.get::<u32>(r"a\.b"), Ok(2));
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(
+            row().fields().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["id", "a", "d"]
+        );
+    }
+}
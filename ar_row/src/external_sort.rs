@@ -0,0 +1,279 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Sorting rows which do not fit in memory, eg. to merge-join files by key
+//!
+//! [`external_sort_by_key`] reads rows (eg. from [`CheckedRows`](crate::reader::CheckedRows)
+//! or [`open_rows`](crate::open::open_rows)) into runs of
+//! [`SortOptions::run_size`] rows, sorts each run and writes it to a temporary file,
+//! then returns an iterator merging all runs. The sort is stable: rows with equal
+//! keys are returned in the order they were read.
+//!
+//! As `ar_row` cannot write Arrow arrays yet, runs are written with a [`RunCodec`];
+//! with the `serde` and `json` features, [`JsonLinesCodec`] writes them as
+//! newline-delimited JSON.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use thiserror::Error;
+
+use crate::reader::ReadError;
+
+/// Error returned while sorting rows
+#[derive(Debug, Error)]
+pub enum SortError {
+    /// A row could not be read from the input
+    #[error("Could not read row: {0}")]
+    Read(#[from] ReadError),
+    /// A run could not be written to or read from its temporary file
+    #[error("Could not access temporary run: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Encoding of rows in temporary files
+pub trait RunCodec<T> {
+    /// Writes a single row
+    fn write_row(&self, writer: &mut dyn Write, row: &T) -> io::Result<()>;
+
+    /// Reads the next row written by [`write_row`](Self::write_row), or returns `None`
+    /// at the end of the file
+    fn read_row(&self, reader: &mut dyn BufRead) -> io::Result<Option<T>>;
+}
+
+/// Writes rows as newline-delimited JSON, with [`serde_json`]
+///
+/// Rows must be read back identically from JSON; in particular, floats must not be
+/// NaN or infinite.
+///
+/// Requires the `serde` and `json` features.
+#[cfg(all(feature = "serde", feature = "json"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonLinesCodec;
+
+#[cfg(all(feature = "serde", feature = "json"))]
+impl<T: ::serde::Serialize + ::serde::de::DeserializeOwned> RunCodec<T> for JsonLinesCodec {
+    fn write_row(&self, writer: &mut dyn Write, row: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut *writer, row)?;
+        writer.write_all(b"\n")
+    }
+
+    fn read_row(&self, reader: &mut dyn BufRead) -> io::Result<Option<T>> {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&line)?))
+    }
+}
+
+/// Options of [`external_sort_by_key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortOptions {
+    /// Number of rows sorted in memory before being written to a temporary file
+    pub run_size: usize,
+    /// Directory in which a subdirectory is created to hold temporary files, removed
+    /// when the returned iterator is dropped
+    pub temp_dir: PathBuf,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        SortOptions {
+            run_size: 1_000_000,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+/// Used to give a different name to temporary directories of concurrent sorts
+static NEXT_SORT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Reads all `rows`, and returns them sorted by the key returned by `key`.
+///
+/// If all rows fit in a single run, they are sorted in memory and no temporary file
+/// is created. Otherwise, all runs but the last are written with `codec` to a new
+/// directory in [`SortOptions::temp_dir`].
+///
+/// Returns the first error of `rows`, if any.
+///
+/// # Panics
+///
+/// If `options.run_size` is zero.
+pub fn external_sort_by_key<T, K, F, C, I>(
+    rows: I,
+    key: F,
+    codec: C,
+    options: &SortOptions,
+) -> Result<SortedRows<T, K, F, C>, SortError>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+    C: RunCodec<T>,
+    I: IntoIterator<Item = Result<T, ReadError>>,
+{
+    assert!(options.run_size > 0, "run_size must be positive");
+    let mut temp_dir: Option<TempDir> = None;
+    let mut run_paths = Vec::new();
+    let mut buffer = Vec::with_capacity(options.run_size.min(1024));
+    for row in rows {
+        buffer.push(row?);
+        if buffer.len() == options.run_size {
+            buffer.sort_by_key(&key);
+            if temp_dir.is_none() {
+                temp_dir = Some(TempDir::new(options)?);
+            }
+            let path = temp_dir
+                .as_ref()
+                .expect("temp_dir was just created")
+                .0
+                .join(format!("run-{}", run_paths.len()));
+            let mut writer = BufWriter::new(File::create(&path)?);
+            for row in buffer.drain(..) {
+                codec.write_row(&mut writer, &row)?;
+            }
+            writer.flush()?;
+            run_paths.push(path);
+        }
+    }
+    buffer.sort_by_key(&key);
+
+    let mut runs = run_paths
+        .iter()
+        .map(|path| Ok(Run::File(BufReader::new(File::open(path)?))))
+        .collect::<io::Result<Vec<_>>>()?;
+    // The last run is never written, as it would be read back immediately
+    runs.push(Run::Memory(buffer.into_iter()));
+
+    let mut sorted_rows = SortedRows {
+        runs,
+        heads: Vec::new(),
+        heap: BinaryHeap::new(),
+        key,
+        codec,
+        pending_error: None,
+        _temp_dir: temp_dir,
+    };
+    for run_index in 0..sorted_rows.runs.len() {
+        sorted_rows.heads.push(None);
+        sorted_rows.advance(run_index)?;
+    }
+    Ok(sorted_rows)
+}
+
+/// Directory holding the runs of a sort, removed with them when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(options: &SortOptions) -> io::Result<TempDir> {
+        let path = options.temp_dir.join(format!(
+            "ar_row-sort-{}-{}",
+            std::process::id(),
+            NEXT_SORT_ID.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        std::fs::create_dir(&path)?;
+        Ok(TempDir(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+enum Run<T> {
+    File(BufReader<File>),
+    Memory(std::vec::IntoIter<T>),
+}
+
+/// Key of the next row of a run, ordered so the smallest key (and, among equal keys,
+/// the earliest run) is the greatest, as [`BinaryHeap`] is a max-heap
+struct HeapEntry<K> {
+    key: K,
+    run_index: usize,
+}
+
+impl<K: Ord> Ord for HeapEntry<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .cmp(&self.key)
+            .then(other.run_index.cmp(&self.run_index))
+    }
+}
+
+impl<K: Ord> PartialOrd for HeapEntry<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> PartialEq for HeapEntry<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord> Eq for HeapEntry<K> {}
+
+/// Iterator on rows sorted by [`external_sort_by_key`]
+///
+/// Temporary files are removed when it is dropped.
+pub struct SortedRows<T, K: Ord, F: Fn(&T) -> K, C: RunCodec<T>> {
+    runs: Vec<Run<T>>,
+    /// Next row of each run, whose key is in `heap`
+    heads: Vec<Option<T>>,
+    heap: BinaryHeap<HeapEntry<K>>,
+    key: F,
+    codec: C,
+    /// Error reading a run, returned after the row which was read before it
+    pending_error: Option<SortError>,
+    /// Dropped after `runs`, as files must be closed before they can be removed on
+    /// Windows
+    _temp_dir: Option<TempDir>,
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K, C: RunCodec<T>> SortedRows<T, K, F, C> {
+    /// Reads the next row of the given run into `heads`
+    fn advance(&mut self, run_index: usize) -> Result<(), SortError> {
+        let row = match &mut self.runs[run_index] {
+            Run::File(reader) => self.codec.read_row(reader)?,
+            Run::Memory(rows) => rows.next(),
+        };
+        if let Some(row) = &row {
+            self.heap.push(HeapEntry {
+                key: (self.key)(row),
+                run_index,
+            });
+        }
+        self.heads[run_index] = row;
+        Ok(())
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K, C: RunCodec<T>> Iterator for SortedRows<T, K, F, C> {
+    type Item = Result<T, SortError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.pending_error.take() {
+            // Stop after the first error, as a run is missing rows
+            self.heap.clear();
+            return Some(Err(e));
+        }
+        let HeapEntry { run_index, .. } = self.heap.pop()?;
+        let row = self.heads[run_index]
+            .take()
+            .expect("Run in the heap has no head");
+        if let Err(e) = self.advance(run_index) {
+            self.pending_error = Some(e);
+        }
+        Some(Ok(row))
+    }
+}
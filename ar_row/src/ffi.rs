@@ -0,0 +1,48 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Deserialization of arrays exported through the
+//! [Arrow C Data Interface](https://arrow.apache.org/docs/format/CDataInterface.html)
+//! and the [Arrow C Stream Interface](https://arrow.apache.org/docs/format/CStreamInterface.html)
+//!
+//! This allows reading arrays handed over by C/C++ or Python (eg. with pyarrow's
+//! `_export_to_c`) without copying their buffers.
+//!
+//! Requires the `ffi` feature.
+
+use arrow::array::make_array;
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError};
+use crate::reader::{CheckedReader, ReadError};
+
+/// Deserializes an array exported through the C Data Interface, after checking
+/// its data type against `T`.
+///
+/// Record batches are exported as struct arrays, so they can be deserialized into
+/// structures deriving `ArRowDeserialize`.
+///
+/// # Safety
+///
+/// `array` and `schema` must be valid and consistent with each other, as
+/// specified by the C Data Interface; see [`arrow::ffi::from_ffi`].
+pub unsafe fn from_ffi<T: ArRowDeserialize>(
+    array: FFI_ArrowArray,
+    schema: &FFI_ArrowSchema,
+) -> Result<Vec<T>, ReadError> {
+    let array = make_array(arrow::ffi::from_ffi(array, schema)?);
+    T::check_datatype(array.data_type()).map_err(DeserializationError::MismatchedColumnDataType)?;
+    Ok(T::from_array(array)?)
+}
+
+/// Returns a reader of the record batches of a stream exported through the
+/// C Stream Interface, after checking its schema against `T`.
+pub fn ffi_stream_reader_for<T: ArRowDeserialize>(
+    stream: FFI_ArrowArrayStream,
+) -> Result<CheckedReader<ArrowArrayStreamReader, T>, ReadError> {
+    let reader = ArrowArrayStreamReader::try_new(stream)?;
+    Ok(CheckedReader::new(reader)?)
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Deserialization of the results of
+//! [Arrow Flight SQL](https://arrow.apache.org/docs/format/FlightSql.html) queries
+//!
+//! Requires the `flight-sql` feature.
+
+use arrow::error::ArrowError;
+use arrow_flight::error::FlightError;
+use arrow_flight::sql::client::FlightSqlServiceClient;
+use futures::TryStreamExt;
+use thiserror::Error;
+use tonic::transport::Channel;
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError};
+
+/// Error returned when failing to run a Flight SQL query or to deserialize its results
+#[derive(Debug, Error)]
+pub enum FlightSqlError {
+    /// The server rejected the query, or a request failed
+    #[error("Flight SQL request failed: {0}")]
+    Arrow(#[from] ArrowError),
+    /// Record batches of an endpoint could not be fetched
+    #[error("Could not fetch record batches: {0}")]
+    Flight(#[from] FlightError),
+    /// The server returned an endpoint that cannot be fetched. Contains its index.
+    #[error("Flight endpoint #{0} has no ticket")]
+    MissingTicket(usize),
+    /// The results could not be deserialized
+    #[error(transparent)]
+    Deserialization(#[from] DeserializationError),
+}
+
+/// Runs `sql` on a Flight SQL server, and returns all rows of its results.
+///
+/// Every endpoint of the result is fetched through `client`, in order; endpoints
+/// whose locations point to other servers are not supported.
+///
+/// If the server returns the schema of the results, it is checked against `T`
+/// before fetching any record batch.
+pub async fn flight_sql_query_rows<T: ArRowDeserialize>(
+    client: &mut FlightSqlServiceClient<Channel>,
+    sql: impl Into<String>,
+) -> Result<Vec<T>, FlightSqlError> {
+    let info = client.execute(sql.into(), None).await?;
+    if !info.schema.is_empty() {
+        let schema = info.clone().try_decode_schema()?;
+        T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+    }
+
+    let mut rows = Vec::new();
+    for (i, endpoint) in info.endpoint.into_iter().enumerate() {
+        let ticket = endpoint.ticket.ok_or(FlightSqlError::MissingTicket(i))?;
+        let mut batches = client.do_get(ticket).await?;
+        while let Some(batch) = batches.try_next().await? {
+            rows.extend(T::from_record_batch(batch)?);
+        }
+    }
+    Ok(rows)
+}
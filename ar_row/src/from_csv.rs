@@ -0,0 +1,73 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading CSV files into deserializable types, through Arrow record batches
+//!
+//! Rows are decoded by [`arrow::csv`] using the schema returned by
+//! [`CheckableDataType::arrow_schema`](crate::deserialize::CheckableDataType::arrow_schema),
+//! so CSV files are read with the same structures as ORC or Parquet files.
+//! As CSV has no nested values, these structures can only have scalar fields.
+//!
+//! Requires the `csv` feature.
+
+use std::io::BufRead;
+use std::sync::Arc;
+
+use arrow::csv::reader::{BufReader, ReaderBuilder};
+use arrow::error::ArrowError;
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError};
+use crate::reader::{CheckedReader, ReadError};
+
+/// Default number of rows per record batch
+const BATCH_SIZE: usize = 1024;
+
+/// Returns a reader of the CSV rows in `reader`, as record batches with the schema
+/// of `T`.
+///
+/// The first line of `reader` must be a header, whose column names are checked
+/// against the fields of `T`.
+pub fn csv_reader_for<T: ArRowDeserialize, R: BufRead>(
+    mut reader: R,
+    batch_size: usize,
+) -> Result<CheckedReader<BufReader<R>, T>, ReadError> {
+    let schema = T::arrow_schema();
+
+    let mut header = String::new();
+    reader.read_line(&mut header).map_err(ArrowError::from)?;
+    let header = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(header.as_bytes())
+        .records()
+        .next()
+        .transpose()
+        .map_err(|e| ArrowError::CsvError(e.to_string()))?
+        .unwrap_or_default();
+    let columns: Vec<&str> = header.iter().collect();
+    let expected_columns: Vec<&str> = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    if columns != expected_columns {
+        return Err(DeserializationError::MismatchedColumnDataType(format!(
+            "CSV columns must be {}, not {}",
+            expected_columns.join(","),
+            columns.join(",")
+        ))
+        .into());
+    }
+
+    let reader = ReaderBuilder::new(Arc::new(schema))
+        .with_header(false)
+        .with_batch_size(batch_size)
+        .build_buffered(reader)?;
+    Ok(CheckedReader::new(reader)?)
+}
+
+/// Reads all rows of the CSV in `reader` to a vector of `T`
+pub fn from_csv<T: ArRowDeserialize, R: BufRead>(reader: R) -> Result<Vec<T>, ReadError> {
+    csv_reader_for::<T, R>(reader, BATCH_SIZE)?.rows().collect()
+}
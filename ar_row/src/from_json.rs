@@ -0,0 +1,57 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading newline-delimited JSON into deserializable types, through Arrow record
+//! batches
+//!
+//! This is the inverse of [`to_json`](crate::to_json): objects are decoded by
+//! [`arrow::json`] using the schema returned by
+//! [`CheckableDataType::arrow_schema`](crate::deserialize::CheckableDataType::arrow_schema),
+//! so JSON fixtures go through the same deserialization code as ORC or Parquet files.
+//!
+//! Requires the `json` feature.
+
+use std::io::BufRead;
+use std::sync::Arc;
+
+use arrow::json::reader::{Reader, ReaderBuilder};
+
+use crate::deserialize::ArRowDeserialize;
+use crate::reader::{CheckedReader, CheckedRows, ReadError};
+
+/// Default number of rows per record batch
+const BATCH_SIZE: usize = 1024;
+
+/// Returns a reader of the newline-delimited JSON objects in `reader`, as record
+/// batches with the schema of `T`.
+///
+/// Objects with keys which are not columns of `T` are rejected, and so are
+/// values which do not match the type of their column.
+pub fn json_reader_for<T: ArRowDeserialize, R: BufRead>(
+    reader: R,
+    batch_size: usize,
+) -> Result<CheckedReader<Reader<R>, T>, ReadError> {
+    let reader = ReaderBuilder::new(Arc::new(T::arrow_schema()))
+        .with_batch_size(batch_size)
+        .with_strict_mode(true)
+        .build(reader)?;
+    Ok(CheckedReader::new(reader)?)
+}
+
+/// Returns an iterator on the newline-delimited JSON objects in `reader`, decoded
+/// as `T`.
+///
+/// Objects are decoded by batches of 1024, so errors in an object may be returned
+/// before the rows preceding it in the same batch.
+pub fn json_rows<T: ArRowDeserialize, R: BufRead>(
+    reader: R,
+) -> Result<CheckedRows<Reader<R>, T>, ReadError> {
+    Ok(json_reader_for::<T, R>(reader, BATCH_SIZE)?.rows())
+}
+
+/// Reads all newline-delimited JSON objects in `reader` to a vector of `T`
+pub fn from_json<T: ArRowDeserialize, R: BufRead>(reader: R) -> Result<Vec<T>, ReadError> {
+    json_rows::<T, R>(reader)?.collect()
+}
@@ -0,0 +1,455 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Stable hashes of rows, eg. to deduplicate them or as join keys
+//!
+//! [`StableHash`] is like [`Hash`](std::hash::Hash), but values are always written
+//! to the hasher the same way, regardless of the platform and of the Rust version, so
+//! hashes can be stored or compared across processes. With [`StableHasher`], a row
+//! hashes to the same value as long as the schema it is read from does not change.
+//!
+//! Integers and floats are written as little-endian bytes, strings, binaries and lists
+//! are prefixed with their length, and options with whether they are set.
+//! `#[derive(ArRowHash)]` implements [`StableHash`] for structures by writing the
+//! name of each field before its value, in order; so, like the schema, hashes change
+//! when fields are renamed or reordered.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::deserialize::ArRowDeserialize;
+//! use ar_row::hash::hash_rows;
+//!
+//! let array: ArrayRef = Arc::new(StringArray::from(vec![Some("foo"), None, Some("foo")]));
+//! let rows = <Option<String>>::from_array(array).unwrap();
+//! let hashes = hash_rows(&rows);
+//! assert_eq!(hashes[0], hashes[2]);
+//! assert_ne!(hashes[0], hashes[1]);
+//! ```
+//!
+//! [`hash_rows`] hashes rows which are already deserialized. To avoid going through
+//! them again, deserialize [`Hashed<T>`] instead of `T`: rows are then hashed while
+//! they are deserialized, by blocks small enough to still be in cache.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! use ar_row::deserialize::ArRowDeserialize;
+//! use ar_row::hash::{stable_hash, Hashed};
+//!
+//! let array: ArrayRef = Arc::new(StringArray::from(vec![Some("foo"), None, Some("foo")]));
+//! let rows = <Hashed<Option<String>>>::from_array(array).unwrap();
+//! assert_eq!(rows[0].hash, stable_hash(&Some("foo")));
+//! assert_eq!(rows[0].hash, rows[2].hash);
+//! assert_eq!(rows[1].value, None);
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hasher;
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::DataType;
+
+use crate::arena::{ArenaBytes, ArenaString};
+use crate::deserialize::{
+    ArRowDeserialize, ArRowStruct, CheckableDataType, ColumnTree, DeserializationError,
+    DeserializationTarget,
+};
+
+/// Number of rows [`Hashed`] deserializes before hashing them
+const HASHED_BLOCK_SIZE: usize = 1024;
+
+/// Like [`Hash`](std::hash::Hash), but independent of the platform and the Rust
+/// version
+///
+/// See the [module-level documentation](self).
+pub trait StableHash {
+    /// Writes this value to `state`, with explicit lengths and byte order
+    fn stable_hash<H: Hasher>(&self, state: &mut H);
+}
+
+/// 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hasher, whose output
+/// only depends on the bytes written to it
+#[derive(Clone, Copy, Debug)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Returns the hash of `value` with a new [`StableHasher`]
+pub fn stable_hash<T: StableHash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.stable_hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the hash of each row, with [`stable_hash`]
+pub fn hash_rows<T: StableHash>(rows: &[T]) -> Vec<u64> {
+    rows.iter().map(stable_hash).collect()
+}
+
+/// Row of type `T` with its [`stable_hash`], computed while deserializing it
+///
+/// See the [module-level documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hashed<T> {
+    pub hash: u64,
+    pub value: T,
+}
+
+impl<T: StableHash> Hashed<T> {
+    /// Hashes `value`
+    pub fn new(value: T) -> Self {
+        Hashed {
+            hash: stable_hash(&value),
+            value,
+        }
+    }
+}
+
+impl<T: ArRowStruct> ArRowStruct for Hashed<T> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        T::columns_with_prefix(prefix)
+    }
+
+    fn column_tree() -> ColumnTree {
+        T::column_tree()
+    }
+
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        T::for_each_column_path(path, f)
+    }
+}
+
+impl<T: CheckableDataType> CheckableDataType for Hashed<T> {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        T::check_datatype(datatype)
+    }
+
+    fn expected_datatype() -> DataType {
+        T::expected_datatype()
+    }
+
+    fn nullable() -> bool {
+        T::nullable()
+    }
+}
+
+/// Rows are deserialized by blocks of [`HASHED_BLOCK_SIZE`], and each row is hashed
+/// as it is moved from its block to `dst`.
+impl<T: ArRowDeserialize + StableHash> ArRowDeserialize for Hashed<T> {
+    fn read_from_array<'a, 'b, D>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut D,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut D: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let num_rows = src.len();
+        let dst_len = dst.len();
+        if num_rows > dst_len {
+            return Err(DeserializationError::MismatchedLength {
+                src: num_rows,
+                dst: dst_len,
+            });
+        }
+
+        let mut block = Vec::with_capacity(HASHED_BLOCK_SIZE.min(num_rows));
+        let mut dst_iter = dst.iter_mut();
+        let mut start = 0;
+        while start < num_rows {
+            let len = HASHED_BLOCK_SIZE.min(num_rows - start);
+            T::read_from_array_into_vec(src.slice(start, len), &mut block)?;
+            // `block` goes first, so `dst_iter` is not advanced past its last row
+            for (value, row) in block.drain(..).zip(dst_iter.by_ref()) {
+                *row = Hashed::new(value);
+            }
+            start += len;
+        }
+        Ok(num_rows)
+    }
+}
+
+/// Writes the length of a string or list, as a `u64` so it does not depend on the
+/// platform
+fn write_len<H: Hasher>(len: usize, state: &mut H) {
+    state.write(&(len as u64).to_le_bytes());
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {
+        $(
+            impl StableHash for $ty {
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    state.write(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+impl StableHash for bool {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&[u8::from(*self)]);
+    }
+}
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            /// `0.0` and `-0.0` hash the same, and so do all NaNs
+            impl StableHash for $ty {
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    let value = if *self == 0.0 {
+                        0.0
+                    } else if self.is_nan() {
+                        <$ty>::NAN
+                    } else {
+                        *self
+                    };
+                    state.write(&value.to_bits().to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+impl StableHash for str {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        write_len(self.len(), state);
+        state.write(self.as_bytes());
+    }
+}
+
+impl StableHash for String {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().stable_hash(state)
+    }
+}
+
+impl<T: StableHash> StableHash for [T] {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        write_len(self.len(), state);
+        for item in self {
+            item.stable_hash(state);
+        }
+    }
+}
+
+impl<T: StableHash> StableHash for Box<[T]> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+impl<T: StableHash> StableHash for Vec<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().stable_hash(state)
+    }
+}
+
+/// Arrays have a fixed size, which is part of the schema, so it is not written
+impl<T: StableHash, const N: usize> StableHash for [T; N] {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.stable_hash(state);
+        }
+    }
+}
+
+impl<T: StableHash> StableHash for Option<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            None => state.write(&[0]),
+            Some(value) => {
+                state.write(&[1]);
+                value.stable_hash(state);
+            }
+        }
+    }
+}
+
+impl<T: StableHash + ?Sized> StableHash for &T {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+/// Items are written in order, so this is the same as for a sorted [`Vec`]
+impl<T: StableHash> StableHash for BTreeSet<T> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        write_len(self.len(), state);
+        for item in self {
+            item.stable_hash(state);
+        }
+    }
+}
+
+/// As iteration order is random, items are hashed separately with [`stable_hash`],
+/// then their hashes are written in increasing order
+impl<T: StableHash, S> StableHash for HashSet<T, S> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        let mut hashes: Vec<u64> = self.iter().map(stable_hash).collect();
+        hashes.sort_unstable();
+        hashes.stable_hash(state);
+    }
+}
+
+impl StableHash for ArenaString {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+impl StableHash for ArenaBytes {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+impl StableHash for crate::SharedString {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+impl StableHash for crate::DisplayString {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).stable_hash(state)
+    }
+}
+
+impl<const N: usize> StableHash for crate::FixedSizeBinary<N> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.stable_hash(state)
+    }
+}
+
+impl<T: StableHash, const N: usize> StableHash for crate::FixedSizeList<T, N> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.stable_hash(state)
+    }
+}
+
+impl StableHash for crate::Timestamp {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.seconds.stable_hash(state);
+        self.nanoseconds.stable_hash(state);
+    }
+}
+
+impl<K: StableHash, V: StableHash> StableHash for crate::MapEntry<K, V> {
+    fn stable_hash<H: Hasher>(&self, state: &mut H) {
+        self.key.stable_hash(state);
+        self.value.stable_hash(state);
+    }
+}
+
+macro_rules! impl_newtype {
+    ($($ty:ty),*) => {
+        $(
+            impl StableHash for $ty {
+                fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                    self.0.stable_hash(state)
+                }
+            }
+        )*
+    };
+}
+
+impl_newtype!(
+    crate::Date,
+    crate::DecimalF64,
+    crate::EpochMillis,
+    crate::EpochNanos,
+    crate::NaiveDecimal128
+);
+
+impl StableHash for () {
+    fn stable_hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: StableHash),+> StableHash for ($($name,)+) {
+            fn stable_hash<H: Hasher>(&self, state: &mut H) {
+                $(self.$index.stable_hash(state);)+
+            }
+        }
+    };
+}
+
+impl_tuple!(A 0);
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hashes must never change, as users may store them
+    #[test]
+    fn test_stable_values() {
+        assert_eq!(stable_hash(&[0u8; 0][..]), 0xa8c7_f832_281a_39c5);
+        assert_eq!(stable_hash(&1u8), 0xaf63_bc4c_8601_b62c);
+        assert_eq!(stable_hash("foo"), 0x8731_bbd2_3194_e5b6);
+        assert_eq!(stable_hash(&None::<i64>), 0xaf63_bd4c_8601_b7df);
+    }
+
+    #[test]
+    fn test_normalized_floats() {
+        assert_eq!(stable_hash(&0.0f64), stable_hash(&-0.0f64));
+        assert_eq!(stable_hash(&f64::NAN), stable_hash(&-f64::NAN));
+        assert_ne!(stable_hash(&1.0f64), stable_hash(&-1.0f64));
+    }
+
+    #[test]
+    fn test_lengths() {
+        assert_ne!(
+            stable_hash(&("ab".to_owned(), "c".to_owned())),
+            stable_hash(&("a".to_owned(), "bc".to_owned()))
+        );
+        assert_ne!(
+            stable_hash(&vec![None::<u8>]),
+            stable_hash(&Vec::<u8>::new())
+        );
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let set: HashSet<i64> = (0..100).collect();
+        let btree_set: BTreeSet<i64> = (0..100).collect();
+        assert_eq!(stable_hash(&set), stable_hash(&set.clone()));
+        assert_ne!(stable_hash(&set), stable_hash(&HashSet::<i64>::new()));
+        assert_ne!(
+            stable_hash(&btree_set),
+            stable_hash(&BTreeSet::<i64>::new())
+        );
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to read the [Arrow IPC](https://arrow.apache.org/docs/format/Columnar.html#serialization-and-interprocess-communication-ipc)
+//! formats with [`arrow::ipc`]
+//!
+//! Besides files, Arrow IPC data can be read from memory with [`ipc_rows_from_bytes`]
+//! and [`ipc_stream_reader_for`], which do not need a filesystem and are therefore
+//! usable on `wasm32-unknown-unknown`.
+//!
+//! With the `mmap` feature, [`open_ipc_rows_mmap`] reads Arrow IPC files through
+//! [`MmapIpcReader`], which maps them in memory and builds arrays which point to the
+//! mapped pages instead of copying them.
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+#[cfg(feature = "mmap")]
+use std::ptr::NonNull;
+#[cfg(feature = "mmap")]
+use std::sync::Arc;
+
+#[cfg(feature = "mmap")]
+use arrow::buffer::Buffer;
+use arrow::datatypes::Schema;
+#[cfg(feature = "mmap")]
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+#[cfg(feature = "mmap")]
+use arrow::ipc::{
+    convert::fb_to_schema,
+    reader::{read_footer_length, FileDecoder},
+    root_as_footer, Block,
+};
+#[cfg(feature = "mmap")]
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct, ColumnNameOptions, Flattening};
+use crate::reader::{CheckedReader, CheckedRows, ReadError};
+
+/// Returns the indices of the top-level columns of `schema` which `T` is
+/// deserialized from, in the order of the schema.
+///
+/// Arrow IPC readers can only project top-level columns, so selecting a nested column
+/// selects all the other columns nested in the same top-level column.
+pub fn ipc_projection_for<T: ArRowStruct>(schema: &Schema) -> Vec<usize> {
+    let roots = T::columns_with_options(&ColumnNameOptions {
+        flattening: Flattening::TopLevel,
+        ..Default::default()
+    });
+    schema
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| roots.contains(field.name()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns a reader of the Arrow IPC stream in `reader` (eg. [`std::io::stdin`]),
+/// after checking its schema against `T`.
+///
+/// Use [`CheckedReader::rows`] to iterate on rows instead of record batches.
+/// `reader` is read in small chunks, so it should be buffered.
+pub fn ipc_stream_reader_for<T: ArRowDeserialize, R: Read>(
+    reader: R,
+) -> Result<CheckedReader<StreamReader<R>, T>, ReadError> {
+    let reader = StreamReader::try_new(reader, None)?;
+    Ok(CheckedReader::new(reader)?)
+}
+
+/// Opens the Arrow IPC file (aka. Feather V2 file) at `path`, and returns an
+/// iterator on its rows.
+///
+/// Only the top-level columns `T` is deserialized from are read (see
+/// [`ipc_projection_for`]), and the schema of the file is checked against `T`
+/// before reading any row.
+pub fn open_ipc_rows<T: ArRowDeserialize + ArRowStruct>(
+    path: impl AsRef<Path>,
+) -> Result<CheckedRows<FileReader<BufReader<File>>, T>, ReadError> {
+    let path = path.as_ref();
+    let open = || -> Result<_, ArrowError> { Ok(BufReader::new(File::open(path)?)) };
+
+    // Only reads the footer, to get the schema needed to build the projection
+    let schema = FileReader::try_new(open()?, None)?.schema();
+    let reader = FileReader::try_new(open()?, Some(ipc_projection_for::<T>(&schema)))?;
+    Ok(CheckedReader::new(reader)?.rows())
+}
+
+/// Same as [`open_ipc_rows`], but reads an Arrow IPC file which is already in memory
+/// (eg. downloaded by a web browser).
+pub fn ipc_rows_from_bytes<T: ArRowDeserialize + ArRowStruct>(
+    bytes: &[u8],
+) -> Result<CheckedRows<FileReader<Cursor<&[u8]>>, T>, ReadError> {
+    let schema = FileReader::try_new(Cursor::new(bytes), None)?.schema();
+    let reader = FileReader::try_new(Cursor::new(bytes), Some(ipc_projection_for::<T>(&schema)))?;
+    Ok(CheckedReader::new(reader)?.rows())
+}
+
+/// Same as [`open_ipc_rows`], but memory-maps the file with [`MmapIpcReader`]
+/// instead of reading it.
+///
+/// Requires the `mmap` feature.
+///
+/// # Safety
+///
+/// See [`MmapIpcReader::open`]
+#[cfg(feature = "mmap")]
+pub unsafe fn open_ipc_rows_mmap<T: ArRowDeserialize + ArRowStruct>(
+    path: impl AsRef<Path>,
+) -> Result<CheckedRows<MmapIpcReader, T>, ReadError> {
+    let reader = MmapIpcReader::open_with_projection(path, ipc_projection_for::<T>)?;
+    Ok(CheckedReader::new(reader)?.rows())
+}
+
+/// Reader of an Arrow IPC file mapped in memory
+///
+/// Buffers of the record batches it returns are slices of the mapped file (unless
+/// they are compressed), so batches are only read from disk as their arrays are
+/// accessed, and they are never copied to the heap. This is much faster than
+/// [`FileReader`] on large local files, especially when only few columns are read.
+///
+/// Like [`FileReader`], it is an iterator on record batches, which can also read
+/// batches in any order with [`read_batch`](Self::read_batch) and
+/// [`RandomAccessBatchReader`](crate::random_access::RandomAccessBatchReader).
+///
+/// Requires the `mmap` feature.
+#[cfg(feature = "mmap")]
+pub struct MmapIpcReader {
+    buffer: Buffer,
+    decoder: FileDecoder,
+    schema: SchemaRef,
+    batches: Vec<Block>,
+    next_batch: usize,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapIpcReader {
+    /// Maps the Arrow IPC file at `path` in memory, and reads its footer and
+    /// dictionaries.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified (including truncated) by this or any other
+    /// process until both this reader and all the batches it returned are dropped.
+    /// See [`memmap2::Mmap::map`].
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self, ArrowError> {
+        Self::open_with_projection(path, |schema| (0..schema.fields().len()).collect())
+    }
+
+    /// Same as [`open`](Self::open), but only reads the top-level columns whose
+    /// indices are returned by `projection` (eg. [`ipc_projection_for`]), given
+    /// the schema of the file.
+    ///
+    /// # Safety
+    ///
+    /// See [`open`](Self::open)
+    pub unsafe fn open_with_projection(
+        path: impl AsRef<Path>,
+        projection: impl FnOnce(&Schema) -> Vec<usize>,
+    ) -> Result<Self, ArrowError> {
+        let file = File::open(path)?;
+        let mmap = memmap2::Mmap::map(&file)?;
+        let buffer = match NonNull::new(mmap.as_ptr() as *mut u8) {
+            // Owns the mapping, which is unmapped when the last slice of the buffer
+            // is dropped
+            Some(ptr) => Buffer::from_custom_allocation(ptr, mmap.len(), Arc::new(mmap)),
+            None => Buffer::from_vec(Vec::<u8>::new()),
+        };
+        Self::from_buffer(buffer, projection)
+    }
+
+    /// Same as [`open_with_projection`](Self::open_with_projection), but reads an
+    /// Arrow IPC file which is already in `buffer`.
+    pub fn from_buffer(
+        buffer: Buffer,
+        projection: impl FnOnce(&Schema) -> Vec<usize>,
+    ) -> Result<Self, ArrowError> {
+        if buffer.len() < 10 {
+            return Err(ArrowError::ParseError(
+                "Arrow IPC file is too short".to_owned(),
+            ));
+        }
+        let trailer: [u8; 10] = buffer[buffer.len() - 10..].try_into().unwrap();
+        let footer_len = read_footer_length(trailer)?;
+        if footer_len > buffer.len() - 10 {
+            return Err(ArrowError::ParseError(format!(
+                "Footer of {footer_len} bytes does not fit in a file of {} bytes",
+                buffer.len()
+            )));
+        }
+        let footer_start = buffer.len() - 10 - footer_len;
+        let footer = root_as_footer(&buffer[footer_start..buffer.len() - 10])
+            .map_err(|e| ArrowError::ParseError(format!("Invalid footer: {e}")))?;
+
+        let file_schema = footer
+            .schema()
+            .ok_or_else(|| ArrowError::ParseError("Footer has no schema".to_owned()))?;
+        let file_schema = Arc::new(fb_to_schema(file_schema));
+        let projection = projection(&file_schema);
+        let schema = Arc::new(file_schema.project(&projection)?);
+        let mut decoder =
+            FileDecoder::new(file_schema, footer.version()).with_projection(projection);
+
+        for block in footer.dictionaries().iter().flatten() {
+            let data = block_data(&buffer, block)?;
+            decoder.read_dictionary(block, &data)?;
+        }
+        let batches = footer
+            .recordBatches()
+            .map(|batches| batches.iter().copied().collect())
+            .unwrap_or_default();
+
+        Ok(MmapIpcReader {
+            buffer,
+            decoder,
+            schema,
+            batches,
+            next_batch: 0,
+        })
+    }
+
+    /// Returns the number of record batches in the file
+    pub fn num_batches(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Sets the index of the next batch returned by the iterator
+    pub fn set_index(&mut self, batch_index: usize) {
+        self.next_batch = batch_index;
+    }
+
+    /// Returns the `batch_index`-th batch, or `None` if it is out of bounds
+    pub fn read_batch(&self, batch_index: usize) -> Option<Result<RecordBatch, ArrowError>> {
+        let block = self.batches.get(batch_index)?;
+        Some(block_data(&self.buffer, block).and_then(|data| {
+            self.decoder
+                .read_record_batch(block, &data)?
+                .ok_or_else(|| {
+                    ArrowError::ParseError(format!("Block {batch_index} is not a record batch"))
+                })
+        }))
+    }
+}
+
+/// Returns the slice of `buffer` which contains the message in `block`
+#[cfg(feature = "mmap")]
+fn block_data(buffer: &Buffer, block: &Block) -> Result<Buffer, ArrowError> {
+    let offset = usize::try_from(block.offset());
+    let len = (block.metaDataLength() as i64)
+        .checked_add(block.bodyLength())
+        .ok_or_else(|| {
+            ArrowError::ParseError(format!(
+                "Length of block at offset {} overflows",
+                block.offset()
+            ))
+        })?;
+    let len = usize::try_from(len);
+    match (offset, len) {
+        (Ok(offset), Ok(len))
+            if offset
+                .checked_add(len)
+                .map_or(false, |end| end <= buffer.len()) =>
+        {
+            Ok(buffer.slice_with_length(offset, len))
+        }
+        _ => Err(ArrowError::ParseError(format!(
+            "Block at offset {} is out of the file's {} bytes",
+            block.offset(),
+            buffer.len()
+        ))),
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Iterator for MmapIpcReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.read_batch(self.next_batch)?;
+        self.next_batch += 1;
+        Some(batch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.batches.len().saturating_sub(self.next_batch);
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl RecordBatchReader for MmapIpcReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_data_overflow() {
+        let buffer = Buffer::from_vec(vec![0u8; 16]);
+        assert_eq!(block_data(&buffer, &Block::new(8, 4, 4)).unwrap().len(), 8);
+        assert!(matches!(
+            block_data(&buffer, &Block::new(8, 4, 5)),
+            Err(ArrowError::ParseError(_))
+        ));
+        assert!(matches!(
+            block_data(&buffer, &Block::new(0, i32::MAX, i64::MAX)),
+            Err(ArrowError::ParseError(_))
+        ));
+        assert!(matches!(
+            block_data(&buffer, &Block::new(0, 0, -1)),
+            Err(ArrowError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Fields decoded on first access
+//!
+//! Deserializing a column to [`Lazy<T>`] instead of `T` only stores a reference to
+//! the Arrow array and the index of the row, and decodes the value when
+//! [`Lazy::get`] is first called. This avoids decoding expensive fields (eg. large
+//! binaries or deep lists) for rows which are filtered out based on other fields.
+//!
+//! As each row keeps a reference to the array, keeping a single row alive keeps the
+//! whole array alive until that row is decoded.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! use ar_row::deserialize::ArRowDeserialize;
+//! use ar_row::lazy::Lazy;
+//!
+//! let array: ArrayRef = Arc::new(StringArray::from(vec![Some("foo"), None]));
+//! let mut rows = <Lazy<Option<String>>>::from_array(array).unwrap();
+//! assert!(!rows[0].is_decoded());
+//! assert_eq!(rows[0].get(), Ok(&Some("foo".to_owned())));
+//! assert!(rows[0].is_decoded());
+//! assert_eq!(rows[1].get(), Ok(&None));
+//! ```
+
+use std::fmt;
+
+use arrow::array::*;
+use arrow::datatypes::*;
+
+use crate::deserialize::{
+    ArRowDeserialize, ArRowStruct, CheckableDataType, ColumnTree, DeserializationError,
+    DeserializationTarget,
+};
+
+#[derive(Clone)]
+enum LazyState<T> {
+    Pending { array: ArrayRef, index: usize },
+    Decoded(T),
+}
+
+/// Value of type `T` decoded from an Arrow array on first access
+///
+/// See the [module-level documentation](self).
+#[derive(Clone)]
+pub struct Lazy<T> {
+    state: LazyState<T>,
+}
+
+impl<T> Lazy<T> {
+    /// Returns an already decoded value
+    pub fn new(value: T) -> Self {
+        Lazy {
+            state: LazyState::Decoded(value),
+        }
+    }
+
+    /// Returns whether the value was decoded already
+    pub fn is_decoded(&self) -> bool {
+        matches!(self.state, LazyState::Decoded(_))
+    }
+}
+
+impl<T: ArRowDeserialize> Lazy<T> {
+    /// Decodes the value if it was not decoded yet, and returns it
+    pub fn get(&mut self) -> Result<&T, DeserializationError> {
+        if let LazyState::Pending { array, index } = &self.state {
+            self.state = LazyState::Decoded(decode(array, *index)?);
+        }
+        match &self.state {
+            LazyState::Decoded(value) => Ok(value),
+            LazyState::Pending { .. } => unreachable!("Lazy value was just decoded"),
+        }
+    }
+
+    /// Decodes the value if it was not decoded yet, and returns it
+    pub fn into_inner(self) -> Result<T, DeserializationError> {
+        match self.state {
+            LazyState::Pending { array, index } => decode(&array, index),
+            LazyState::Decoded(value) => Ok(value),
+        }
+    }
+}
+
+/// Decodes the row at `index` of `array`
+fn decode<T: ArRowDeserialize>(array: &ArrayRef, index: usize) -> Result<T, DeserializationError> {
+    Ok(T::from_array(array.slice(index, 1))?
+        .pop()
+        .expect("Deserializing a single-row array returned no value"))
+}
+
+impl<T: Default> Default for Lazy<T> {
+    fn default() -> Self {
+        Lazy::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.state {
+            LazyState::Pending { index, .. } => f
+                .debug_struct("Lazy")
+                .field("index", index)
+                .finish_non_exhaustive(),
+            LazyState::Decoded(value) => f.debug_tuple("Lazy").field(value).finish(),
+        }
+    }
+}
+
+impl<T: ArRowStruct> ArRowStruct for Lazy<T> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        T::columns_with_prefix(prefix)
+    }
+
+    fn column_tree() -> ColumnTree {
+        T::column_tree()
+    }
+
+    fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+        T::for_each_column_path(path, f)
+    }
+}
+
+impl<T: CheckableDataType> CheckableDataType for Lazy<T> {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        T::check_datatype(datatype)
+    }
+
+    fn expected_datatype() -> DataType {
+        T::expected_datatype()
+    }
+
+    fn nullable() -> bool {
+        T::nullable()
+    }
+}
+
+/// Nulls are checked when reading the array, but values are only decoded by
+/// [`Lazy::get`] and [`Lazy::into_inner`].
+impl<T: ArRowDeserialize> ArRowDeserialize for Lazy<T> {
+    fn read_from_array<'a, 'b, D>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut D,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut D: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if src.null_count() > 0 && !T::nullable() {
+            return Err(DeserializationError::UnexpectedNull(format!(
+                "{} column contains nulls",
+                std::any::type_name::<T>()
+            )));
+        }
+        let num_rows = src.len();
+        let dst_len = dst.len();
+        if num_rows > dst_len {
+            return Err(DeserializationError::MismatchedLength {
+                src: num_rows,
+                dst: dst_len,
+            });
+        }
+
+        let array = make_array(src.to_data());
+        for (index, d) in dst.iter_mut().take(num_rows).enumerate() {
+            d.state = LazyState::Pending {
+                array: array.clone(),
+                index,
+            };
+        }
+        Ok(num_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_lazy() {
+        let array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]).slice(1, 2));
+        let rows = <Lazy<i32>>::from_array(array).unwrap();
+        assert!(rows.iter().all(|row| !row.is_decoded()));
+        let values: Result<Vec<_>, _> = rows.into_iter().map(Lazy::into_inner).collect();
+        assert_eq!(values, Ok(vec![2, 3]));
+    }
+
+    #[test]
+    fn test_nulls() {
+        let array: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None]));
+        assert!(<Lazy<i32>>::from_array(array.clone()).is_err());
+        let mut rows = <Lazy<Option<i32>>>::from_array(array).unwrap();
+        assert_eq!(rows[1].get(), Ok(&None));
+        assert!(rows[1].is_decoded());
+        assert!(!rows[0].is_decoded());
+    }
+
+    #[test]
+    fn test_mismatched_type() {
+        let array: ArrayRef = Arc::new(StringArray::from(vec!["foo"]));
+        // Errors are only returned on access
+        let mut rows = <Lazy<i32>>::from_array(array).unwrap();
+        assert!(rows[0].get().is_err());
+        assert!(!rows[0].is_decoded());
+    }
+}
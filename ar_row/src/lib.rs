@@ -21,35 +21,637 @@
 //! [`deserialize::ArRowDeserialize::read_from_array`], or iterated through
 //! [`row_iterator::RowIterator`].
 //!
+//! [`reader::CheckedReader`] wraps a [`RecordBatchReader`](arrow::record_batch::RecordBatchReader)
+//! after checking its schema, and provides both batch- and row-oriented access.
+//! Sources which can read any batch on demand (IPC files, ORC stripes, Parquet row
+//! groups) implement [`random_access::RandomAccessBatchReader`]. Wrapped in
+//! [`random_access::RandomAccessBatches`], a [`row_iterator::RowIterator`] on them can
+//! iterate on rows from both ends and seek to arbitrary rows.
+//! With the `rayon` feature, [`parallel_row_iterator::ParallelRowIterator`] iterates on
+//! their rows with Rayon, and [`dataset`] reads directories of files in parallel.
+//!
+//! Rows which do not fit in memory can be sorted with
+//! [`external_sort::external_sort_by_key`], eg. to merge-join files by key.
+//!
+//! When the schema is only known at runtime, [`value::ArRowValue`] provides a dynamically
+//! typed representation of rows.
+//!
+//! Fields of type [`arena::ArenaString`] and [`arena::ArenaBytes`] can be used instead
+//! of `String` and `Box<[u8]>` to allocate the values of each column of a batch
+//! together, when materializing many rows.
+//! Fields of type [`lazy::Lazy`] are only decoded when accessed, for expensive fields of
+//! rows which may be filtered out.
+//!
+//! [`hash::StableHash`] (implemented by `#[derive(ArRowHash)]`) hashes rows the same
+//! way on all platforms, to deduplicate them or use them as join keys.
+//!
+//! Tuples of up to 6 elements can also be deserialized from structures with as many
+//! fields, positionally, to avoid defining a named structure in ad-hoc code.
+//! [`HashSet`](std::collections::HashSet) and [`BTreeSet`](std::collections::BTreeSet)
+//! can be deserialized from lists, dropping duplicate values.
+//!
 //! # Examples
 //!
 //! See the [`ar_row_derive` documentation](https://docs.rs/ar_row_derive/)
 
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::Duration;
+
 pub use arrow;
+#[cfg(feature = "rayon")]
+pub use rayon;
 
+pub mod arena;
 mod array_iterators;
+pub mod batch_size;
+#[cfg(feature = "json")]
+mod cells;
+pub mod cursor;
+#[cfg(feature = "rayon")]
+pub mod dataset;
+#[cfg(feature = "delta")]
+pub mod delta;
 pub mod deserialize;
 pub mod dictionaries;
+#[cfg(any(feature = "json", feature = "python", feature = "serde"))]
+mod display;
+pub mod dynamic_row;
+pub mod external_sort;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "flight-sql")]
+pub mod flight_sql;
+#[cfg(feature = "csv")]
+pub mod from_csv;
+#[cfg(feature = "json")]
+pub mod from_json;
+pub mod hash;
+pub mod ipc;
+pub mod lazy;
+pub mod metrics;
+#[cfg(feature = "ndarray")]
+pub mod ndarray;
+#[cfg(feature = "object_store")]
+pub mod object_store;
+pub mod open;
+pub mod options;
+#[cfg(feature = "orc")]
+pub mod orc;
+#[cfg(feature = "rayon")]
+pub mod parallel_row_iterator;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod prelude;
+pub mod projection;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random_access;
+pub mod reader;
 pub mod row_iterator;
+#[cfg(feature = "async")]
+pub mod row_stream;
+pub mod schema;
+pub mod seek;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod sets;
+pub mod structured_reader;
+#[cfg(feature = "csv")]
+pub mod to_csv;
+#[cfg(feature = "json")]
+pub mod to_json;
+#[cfg(feature = "json")]
+pub mod to_table;
+mod tuples;
+pub mod typed_batch;
+pub mod value;
 
 /// Timezone-less timestamp
+///
+/// Timestamps returned by this crate are normalized (see [`Timestamp::normalized`]),
+/// so they are ordered chronologically.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct Timestamp {
     pub seconds: i64,
     pub nanoseconds: i64,
 }
 
+impl Timestamp {
+    /// Returns the timestamp `seconds + nanoseconds / 10^9`, with
+    /// `0 <= nanoseconds < 1_000_000_000`.
+    ///
+    /// ```
+    /// # use ar_row::Timestamp;
+    /// assert_eq!(
+    ///     Timestamp::normalized(-1, -500_000_000),
+    ///     Timestamp {
+    ///         seconds: -2,
+    ///         nanoseconds: 500_000_000
+    ///     }
+    /// );
+    /// ```
+    ///
+    /// Panics on overflow; see [`Timestamp::checked_normalized`]
+    pub fn normalized(seconds: i64, nanoseconds: i64) -> Timestamp {
+        Timestamp::checked_normalized(seconds, nanoseconds)
+            .expect("overflow when normalizing timestamp")
+    }
+
+    /// Same as [`Timestamp::normalized`], but returns `None` on overflow
+    ///
+    /// ```
+    /// # use ar_row::Timestamp;
+    /// assert_eq!(Timestamp::checked_normalized(i64::MAX, 1_000_000_000), None);
+    /// ```
+    pub fn checked_normalized(seconds: i64, nanoseconds: i64) -> Option<Timestamp> {
+        Some(Timestamp {
+            seconds: seconds.checked_add(nanoseconds.div_euclid(1_000_000_000))?,
+            nanoseconds: nanoseconds.rem_euclid(1_000_000_000),
+        })
+    }
+
+    /// Formats the timestamp as RFC 3339, in UTC, with as many fractional digits as
+    /// needed (none, 3, 6, or 9).
+    ///
+    /// Years before 0 or after 9999 cannot be represented in RFC 3339, and are
+    /// written with a sign and as many digits as needed (eg. `+10000-01-01T00:00:00Z`).
+    ///
+    /// ```
+    /// # use ar_row::Timestamp;
+    /// assert_eq!(
+    ///     Timestamp::normalized(1_700_000_000, 500_000_000).to_rfc3339(),
+    ///     "2023-11-14T22:13:20.500Z"
+    /// );
+    /// assert_eq!(Timestamp::normalized(-1, 0).to_rfc3339(), "1969-12-31T23:59:59Z");
+    /// ```
+    pub fn to_rfc3339(&self) -> String {
+        let Timestamp {
+            seconds,
+            nanoseconds,
+        } = Timestamp::normalized(self.seconds, self.nanoseconds);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let seconds_of_day = seconds.rem_euclid(86_400);
+        let year = if (0..=9999).contains(&year) {
+            format!("{:04}", year)
+        } else {
+            format!("{:+05}", year)
+        };
+        let fraction = if nanoseconds == 0 {
+            String::new()
+        } else if nanoseconds % 1_000_000 == 0 {
+            format!(".{:03}", nanoseconds / 1_000_000)
+        } else if nanoseconds % 1_000 == 0 {
+            format!(".{:06}", nanoseconds / 1_000)
+        } else {
+            format!(".{:09}", nanoseconds)
+        };
+        format!(
+            "{}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+            year,
+            month,
+            day,
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            seconds_of_day % 60,
+            fraction
+        )
+    }
+
+    /// Parses an RFC 3339 timestamp, such as `2023-11-14T22:13:20.5+01:00`, and
+    /// converts it to UTC.
+    ///
+    /// `t` and a space are accepted instead of `T`, and `z` instead of `Z`. Fractional
+    /// digits after the 9th are ignored, and leap seconds are read as the first
+    /// second of the next minute.
+    ///
+    /// ```
+    /// # use ar_row::Timestamp;
+    /// assert_eq!(
+    ///     Timestamp::parse_rfc3339("2023-11-14T23:13:20.5+01:00"),
+    ///     Ok(Timestamp::normalized(1_700_000_000, 500_000_000))
+    /// );
+    /// assert!(Timestamp::parse_rfc3339("2023-02-29T00:00:00Z").is_err());
+    /// ```
+    pub fn parse_rfc3339(s: &str) -> Result<Timestamp, ParseTimestampError> {
+        let error = || ParseTimestampError(s.to_owned());
+        let bytes = s.as_bytes();
+        let number = |range: std::ops::Range<usize>| -> Result<i64, ParseTimestampError> {
+            let digits = bytes.get(range).ok_or_else(error)?;
+            if !digits.iter().all(u8::is_ascii_digit) {
+                return Err(error());
+            }
+            Ok(digits
+                .iter()
+                .fold(0, |acc, digit| acc * 10 + i64::from(digit - b'0')))
+        };
+        let separator = |index: usize, allowed: &[u8]| match bytes.get(index) {
+            Some(byte) if allowed.contains(byte) => Ok(()),
+            _ => Err(error()),
+        };
+
+        let year = number(0..4)?;
+        separator(4, b"-")?;
+        let month = number(5..7)?;
+        separator(7, b"-")?;
+        let day = number(8..10)?;
+        separator(10, b"Tt ")?;
+        let hour = number(11..13)?;
+        separator(13, b":")?;
+        let minute = number(14..16)?;
+        separator(16, b":")?;
+        let second = number(17..19)?;
+
+        let mut index = 19;
+        let mut nanoseconds = 0;
+        if bytes.get(index) == Some(&b'.') {
+            index += 1;
+            let num_digits = bytes[index..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            if num_digits == 0 {
+                return Err(error());
+            }
+            let significant_digits = num_digits.min(9);
+            nanoseconds = number(index..index + significant_digits)?
+                * 10i64.pow(9 - significant_digits as u32);
+            index += num_digits;
+        }
+
+        let offset = match bytes.get(index..) {
+            Some(b"Z") | Some(b"z") => 0,
+            Some([sign @ (b'+' | b'-'), ..]) => {
+                let sign = if *sign == b'+' { 1 } else { -1 };
+                separator(index + 3, b":")?;
+                if bytes.len() != index + 6 {
+                    return Err(error());
+                }
+                let offset_hours = number(index + 1..index + 3)?;
+                let offset_minutes = number(index + 4..index + 6)?;
+                if offset_hours > 23 || offset_minutes > 59 {
+                    return Err(error());
+                }
+                sign * (offset_hours * 3600 + offset_minutes * 60)
+            }
+            _ => return Err(error()),
+        };
+
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return Err(error());
+        }
+        let days = days_from_civil(year, month, day);
+        Ok(Timestamp::normalized(
+            days * 86_400 + hour * 3600 + minute * 60 + second - offset,
+            nanoseconds,
+        ))
+    }
+
+    /// Returns the date of this timestamp, in UTC
+    pub fn date(&self) -> Date {
+        let Timestamp { seconds, .. } = Timestamp::normalized(self.seconds, self.nanoseconds);
+        Date(seconds.div_euclid(86_400))
+    }
+
+    /// Returns `self + duration`, or `None` on overflow
+    pub fn checked_add(&self, duration: Duration) -> Option<Timestamp> {
+        let Timestamp {
+            seconds,
+            nanoseconds,
+        } = Timestamp::checked_normalized(self.seconds, self.nanoseconds)?;
+        let nanoseconds = nanoseconds + i64::from(duration.subsec_nanos());
+        let seconds = seconds
+            .checked_add(i64::try_from(duration.as_secs()).ok()?)?
+            .checked_add(nanoseconds / 1_000_000_000)?;
+        Some(Timestamp {
+            seconds,
+            nanoseconds: nanoseconds % 1_000_000_000,
+        })
+    }
+
+    /// Returns `self - duration`, or `None` on overflow
+    pub fn checked_sub(&self, duration: Duration) -> Option<Timestamp> {
+        let Timestamp {
+            seconds,
+            nanoseconds,
+        } = Timestamp::checked_normalized(self.seconds, self.nanoseconds)?;
+        let nanoseconds = nanoseconds - i64::from(duration.subsec_nanos());
+        let seconds = seconds
+            .checked_sub(i64::try_from(duration.as_secs()).ok()?)?
+            .checked_add(nanoseconds.div_euclid(1_000_000_000))?;
+        Some(Timestamp {
+            seconds,
+            nanoseconds: nanoseconds.rem_euclid(1_000_000_000),
+        })
+    }
+
+    /// Returns the duration elapsed from `earlier` to `self`, or `None` if `earlier`
+    /// is after `self`
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use ar_row::Timestamp;
+    /// let start = Timestamp::normalized(10, 0);
+    /// let end = Timestamp::normalized(12, 500_000_000);
+    /// assert_eq!(end.checked_duration_since(start), Some(Duration::from_millis(2500)));
+    /// assert_eq!(start.checked_duration_since(end), None);
+    /// ```
+    pub fn checked_duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        let end = Timestamp::checked_normalized(self.seconds, self.nanoseconds)?;
+        let start = Timestamp::checked_normalized(earlier.seconds, earlier.nanoseconds)?;
+        let mut seconds = end.seconds.checked_sub(start.seconds)?;
+        let mut nanoseconds = end.nanoseconds - start.nanoseconds;
+        if nanoseconds < 0 {
+            seconds = seconds.checked_sub(1)?;
+            nanoseconds += 1_000_000_000;
+        }
+        Some(Duration::new(
+            u64::try_from(seconds).ok()?,
+            nanoseconds as u32,
+        ))
+    }
+}
+
+/// Panics on overflow; see [`Timestamp::checked_add`]
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+/// Panics on overflow; see [`Timestamp::checked_sub`]
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// Panics if `earlier` is after `self`; see [`Timestamp::checked_duration_since`]
+///
+/// ```
+/// # use std::time::Duration;
+/// # use ar_row::Timestamp;
+/// let start = Timestamp::normalized(1_700_000_000, 0);
+/// let window = start..(start + Duration::from_secs(3600));
+/// let timestamp = Timestamp::normalized(1_700_000_060, 0);
+/// assert!(window.contains(&timestamp));
+/// assert_eq!(timestamp - start, Duration::from_secs(60));
+/// ```
+impl Sub<Timestamp> for Timestamp {
+    type Output = Duration;
+
+    fn sub(self, earlier: Timestamp) -> Duration {
+        self.checked_duration_since(earlier)
+            .expect("timestamp subtracted from an earlier timestamp")
+    }
+}
+
+/// Error returned by [`Timestamp::parse_rfc3339`], with the string which could not
+/// be parsed
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid RFC 3339 timestamp: {0:?}")]
+pub struct ParseTimestampError(pub String);
+
+/// Returns the number of days between 1970-01-01 and the given date of the
+/// proleptic Gregorian calendar (from <https://howardhinnant.github.io/date_algorithms.html>)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`], returns `(year, month, day)`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // March is 0
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Timezone-less timestamp, as a number of milliseconds since the Unix epoch
+///
+/// This is decoded from the same arrays as [`Timestamp`], but is a single integer.
+/// Timestamps with a finer unit are rounded down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct EpochMillis(pub i64);
+
+/// Timezone-less timestamp, as a number of nanoseconds since the Unix epoch
+///
+/// This is decoded from the same arrays as [`Timestamp`], but is a single integer,
+/// which covers years 1677 to 2262.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct EpochNanos(pub i64);
+
+/// Decimal number converted to a floating-point number, losing precision
+///
+/// This is decoded from `Decimal128(_, scale)` arrays by dividing their values by
+/// 10^scale, for code which does not need exact decimals.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct DecimalF64(pub f64);
+
 /// Scale-less decimal number
 ///
 /// To get a meaningful value, it should be divided by 10^(the schema's scale)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct NaiveDecimal128(pub i128);
 
+impl NaiveDecimal128 {
+    /// Returns a wrapper implementing [`Display`](std::fmt::Display), which writes
+    /// the decimal with `scale` fractional digits
+    ///
+    /// Negative scales (as allowed by Arrow) multiply the value by 10^-scale instead.
+    ///
+    /// ```
+    /// # use ar_row::NaiveDecimal128;
+    /// assert_eq!(NaiveDecimal128(-10_005_000).display(4).to_string(), "-1000.5000");
+    /// assert_eq!(NaiveDecimal128(5).display(3).to_string(), "0.005");
+    /// assert_eq!(NaiveDecimal128(12).display(-2).to_string(), "1200");
+    /// ```
+    pub fn display(self, scale: i8) -> DisplayDecimal128 {
+        DisplayDecimal128 { value: self, scale }
+    }
+
+    /// Returns the decimal formatted with `scale` fractional digits, see
+    /// [`NaiveDecimal128::display`]
+    pub fn format(self, scale: i8) -> String {
+        self.display(scale).to_string()
+    }
+}
+
+/// [`NaiveDecimal128`] with its scale, returned by [`NaiveDecimal128::display`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayDecimal128 {
+    pub value: NaiveDecimal128,
+    pub scale: i8,
+}
+
+impl std::fmt::Display for DisplayDecimal128 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.value.0.unsigned_abs().to_string();
+        let number = if self.scale <= 0 {
+            let zeros = if self.value.0 == 0 {
+                0
+            } else {
+                usize::from(self.scale.unsigned_abs())
+            };
+            format!("{}{}", digits, "0".repeat(zeros))
+        } else {
+            let scale = usize::from(self.scale.unsigned_abs());
+            // At least one digit before the decimal point
+            let digits = format!("{:0>width$}", digits, width = scale + 1);
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            format!("{}.{}", integer, fraction)
+        };
+        // Writes the sign, and supports width and alignment like integers
+        f.pad_integral(self.value.0 >= 0, "", &number)
+    }
+}
+
 /// Days since epoch
+///
+/// Days can be added to and subtracted from dates, and subtracting two dates returns
+/// the number of days between them:
+///
+/// ```
+/// # use ar_row::Date;
+/// let date = Date(19_000);
+/// assert_eq!(date + 7, Date(19_007));
+/// assert_eq!(date - 1, Date(18_999));
+/// assert_eq!(Date(19_007) - date, 7);
+/// ```
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct Date(pub i64);
 
+impl Date {
+    /// Returns the date `days` days later (or earlier, if negative), or `None` on
+    /// overflow
+    pub fn checked_add_days(self, days: i64) -> Option<Date> {
+        self.0.checked_add(days).map(Date)
+    }
+
+    /// Returns the date `days` days earlier (or later, if negative), or `None` on
+    /// overflow
+    pub fn checked_sub_days(self, days: i64) -> Option<Date> {
+        self.0.checked_sub(days).map(Date)
+    }
+
+    /// Returns midnight UTC of this date, or `None` on overflow (for dates more
+    /// than 292 billion years away from 1970)
+    ///
+    /// ```
+    /// # use ar_row::{Date, Timestamp};
+    /// assert_eq!(Date(1).checked_to_timestamp(), Some(Timestamp::normalized(86_400, 0)));
+    /// assert_eq!(Date(i64::MAX).checked_to_timestamp(), None);
+    /// ```
+    pub fn checked_to_timestamp(self) -> Option<Timestamp> {
+        Some(Timestamp {
+            seconds: self.0.checked_mul(86_400)?,
+            nanoseconds: 0,
+        })
+    }
+}
+
+/// Panics on overflow; see [`Date::checked_add_days`]
+impl Add<i64> for Date {
+    type Output = Date;
+
+    fn add(self, days: i64) -> Date {
+        self.checked_add_days(days)
+            .expect("overflow when adding days to date")
+    }
+}
+
+impl AddAssign<i64> for Date {
+    fn add_assign(&mut self, days: i64) {
+        *self = *self + days;
+    }
+}
+
+/// Panics on overflow; see [`Date::checked_sub_days`]
+impl Sub<i64> for Date {
+    type Output = Date;
+
+    fn sub(self, days: i64) -> Date {
+        self.checked_sub_days(days)
+            .expect("overflow when subtracting days from date")
+    }
+}
+
+impl SubAssign<i64> for Date {
+    fn sub_assign(&mut self, days: i64) {
+        *self = *self - days;
+    }
+}
+
+/// Number of days from `other` to `self`
+///
+/// Panics on overflow
+impl Sub<Date> for Date {
+    type Output = i64;
+
+    fn sub(self, other: Date) -> i64 {
+        self.0
+            .checked_sub(other.0)
+            .expect("overflow when subtracting dates")
+    }
+}
+
 /// Array wrapper that implements [`Default`]
+///
+/// With the `serde` feature, it is serialized as bytes, as `serde` cannot derive
+/// implementations for arrays of arbitrary length.
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct FixedSizeBinary<const N: usize>(pub [u8; N]);
@@ -84,3 +686,140 @@ impl<const N: usize> std::ops::DerefMut for FixedSizeBinary<N> {
         &mut self.0
     }
 }
+
+/// Array wrapper that implements [`Default`] for any `N`, read from Arrow
+/// `FixedSizeList` arrays
+///
+/// Elements which may be null must be read as `FixedSizeList<Option<T>, N>`; this is
+/// checked against the nullability of the list's field. Fixed-size binary arrays are
+/// read with [`FixedSizeBinary`] instead.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FixedSizeList<T, const N: usize>(pub [T; N]);
+
+impl<T: Default, const N: usize> Default for FixedSizeList<T, N> {
+    fn default() -> Self {
+        FixedSizeList(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for FixedSizeList<T, N> {
+    fn from(value: [T; N]) -> Self {
+        FixedSizeList(value)
+    }
+}
+
+impl<T, const N: usize> From<FixedSizeList<T, N>> for [T; N] {
+    fn from(value: FixedSizeList<T, N>) -> Self {
+        value.0
+    }
+}
+
+impl<T, const N: usize> std::ops::Deref for FixedSizeList<T, N> {
+    type Target = [T; N];
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const N: usize> std::ops::DerefMut for FixedSizeList<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// `Arc<str>` wrapper that implements [`Default`]
+///
+/// When deserialized from a dictionary-encoded array, all rows with the same key
+/// share the same allocation, which saves memory on low-cardinality columns.
+#[repr(transparent)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SharedString(pub std::sync::Arc<str>);
+
+impl Default for SharedString {
+    fn default() -> Self {
+        SharedString(std::sync::Arc::from(""))
+    }
+}
+
+impl From<&str> for SharedString {
+    fn from(value: &str) -> Self {
+        SharedString(std::sync::Arc::from(value))
+    }
+}
+
+impl From<std::sync::Arc<str>> for SharedString {
+    fn from(value: std::sync::Arc<str>) -> Self {
+        SharedString(value)
+    }
+}
+
+impl From<SharedString> for std::sync::Arc<str> {
+    fn from(value: SharedString) -> Self {
+        value.0
+    }
+}
+
+impl std::ops::Deref for SharedString {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for SharedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// `String` wrapper deserialized from the display form of the values of any
+/// primitive, boolean, or string array (eg. `"42"`, `"1.5"`, `"2024-01-31"`)
+///
+/// This is what `#[ar_row(stringify)]` fields are read through in `ar_row_derive`.
+#[repr(transparent)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DisplayString(pub String);
+
+impl From<DisplayString> for String {
+    fn from(value: DisplayString) -> Self {
+        value.0
+    }
+}
+
+impl std::ops::Deref for DisplayString {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for DisplayString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Entry of an Arrow `Map`, so map columns can be read as `Vec<MapEntry<K, V>>`
+///
+/// As per the Arrow specification, keys are never null but values may be, so `V`
+/// is usually an `Option`. Entries are yielded in the order they are stored, and
+/// duplicate keys are kept.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MapEntry<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+impl<K, V> From<(K, V)> for MapEntry<K, V> {
+    fn from((key, value): (K, V)) -> Self {
+        MapEntry { key, value }
+    }
+}
+
+impl<K, V> From<MapEntry<K, V>> for (K, V) {
+    fn from(entry: MapEntry<K, V>) -> Self {
+        (entry.key, entry.value)
+    }
+}
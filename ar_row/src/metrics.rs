@@ -0,0 +1,137 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Hooks to export deserialization metrics
+//!
+//! Once a [`DeserializationMetrics`] implementation is registered with
+//! [`set_metrics`], it is called for every record batch deserialized by
+//! [`ArRowDeserialize`](crate::deserialize::ArRowDeserialize)'s `*record_batch*`
+//! methods (which are used by [`RowIterator`](crate::row_iterator::RowIterator),
+//! [`CheckedReader`](crate::reader::CheckedReader), ...), and for every column
+//! deserialized by implementations generated by `ar_row_derive`.
+//!
+//! When no implementation is registered, the only overhead is an atomic load
+//! for each batch and column.
+//!
+//! With the `tracing` feature, deserialization of batches and columns is also
+//! wrapped in [`tracing`] spans (`deserialize_batch` at the debug level, and
+//! `deserialize_column` at the trace level), regardless of [`set_metrics`].
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! use ar_row::metrics::{set_metrics, DeserializationMetrics};
+//!
+//! #[derive(Default)]
+//! struct RowCounter(AtomicUsize);
+//!
+//! impl DeserializationMetrics for RowCounter {
+//!     fn batch(&self, rows: usize, _bytes: usize, _duration: Duration) {
+//!         self.0.fetch_add(rows, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(RowCounter::default());
+//! set_metrics(Some(counter.clone()));
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use arrow::record_batch::RecordBatch;
+
+/// Receives metrics of deserialization. All methods do nothing by default.
+pub trait DeserializationMetrics: Send + Sync {
+    /// Called after a record batch of `rows` rows, whose arrays use `bytes` bytes
+    /// of memory, was deserialized in `duration`.
+    fn batch(&self, _rows: usize, _bytes: usize, _duration: Duration) {}
+
+    /// Called after `rows` rows of the `field` column of `structure` were
+    /// deserialized in `duration`.
+    ///
+    /// This includes the time spent in nested structures, which report their own
+    /// columns as well.
+    fn column(
+        &self,
+        _structure: &'static str,
+        _field: &'static str,
+        _rows: usize,
+        _duration: Duration,
+    ) {
+    }
+}
+
+/// Whether [`METRICS`] is set, to avoid taking the lock when it is not
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static METRICS: RwLock<Option<Arc<dyn DeserializationMetrics>>> = RwLock::new(None);
+
+/// Sets the process-wide implementation receiving metrics, or disables them if
+/// `None`.
+pub fn set_metrics(metrics: Option<Arc<dyn DeserializationMetrics>>) {
+    let mut guard = METRICS.write().unwrap_or_else(|e| e.into_inner());
+    ENABLED.store(metrics.is_some(), Ordering::Release);
+    *guard = metrics;
+}
+
+/// Returns the implementation set with [`set_metrics`], if any
+pub fn metrics() -> Option<Arc<dyn DeserializationMetrics>> {
+    if !ENABLED.load(Ordering::Acquire) {
+        return None;
+    }
+    METRICS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Runs `f` on `batch`, which deserializes it to `type_name`, and reports it to
+/// [`metrics`]
+pub(crate) fn time_batch<R>(
+    type_name: &'static str,
+    batch: RecordBatch,
+    f: impl FnOnce(RecordBatch) -> R,
+) -> R {
+    #[cfg(feature = "tracing")]
+    let _span =
+        tracing::debug_span!("deserialize_batch", type_name, rows = batch.num_rows()).entered();
+    #[cfg(not(feature = "tracing"))]
+    let _ = type_name;
+
+    match metrics() {
+        None => f(batch),
+        Some(metrics) => {
+            let rows = batch.num_rows();
+            let bytes = batch.get_array_memory_size();
+            let start = Instant::now();
+            let result = f(batch);
+            metrics.batch(rows, bytes, start.elapsed());
+            result
+        }
+    }
+}
+
+/// Runs `f`, which deserializes `rows` rows of a column, and reports it to
+/// [`metrics`]. Used by code generated by `ar_row_derive`.
+#[doc(hidden)]
+pub fn time_column<R>(
+    structure: &'static str,
+    field: &'static str,
+    rows: usize,
+    f: impl FnOnce() -> R,
+) -> R {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("deserialize_column", structure, field, rows).entered();
+
+    match metrics() {
+        None => f(),
+        Some(metrics) => {
+            let start = Instant::now();
+            let result = f();
+            metrics.column(structure, field, rows, start.elapsed());
+            result
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Deserialization of columns into [`ndarray`](::ndarray) arrays
+//!
+//! One-dimensional arrays and views (eg. a column of a matrix returned by
+//! [`ArrayBase::column_mut`]) implement [`DeserializationTarget`], so they can be
+//! passed to [`ArRowDeserialize::read_from_array`] like a `Vec`.
+//! [`read_record_batch_into_array2`] and [`record_batch_to_array2`] fill matrices
+//! with one column per column of a record batch, eg. to extract features without
+//! going through a `Vec` of structures.
+//!
+//! Requires the `ndarray` feature.
+
+use ::ndarray::iter::IterMut;
+use ::ndarray::{Array2, ArrayBase, DataMut, Ix1, Ix2};
+use arrow::record_batch::RecordBatch;
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, DeserializationTarget};
+
+impl<'a, V: Sized + 'a, S: DataMut<Elem = V>> DeserializationTarget<'a> for &mut ArrayBase<S, Ix1> {
+    type Item = V;
+    type IterMut<'b> = IterMut<'b, V, Ix1> where V: 'b, 'a: 'b, Self: 'b;
+
+    fn len(&self) -> usize {
+        ArrayBase::len(*self)
+    }
+
+    fn iter_mut(&mut self) -> IterMut<'_, V, Ix1> {
+        ArrayBase::iter_mut(*self)
+    }
+}
+
+/// Deserializes each column of `batch` into the column of `dst` with the same index,
+/// and returns the number of rows written.
+///
+/// `dst` must have as many columns as `batch`, and at least as many rows. Like with
+/// [`ArRowDeserialize::read_from_array`], rows of `dst` past the number of rows of
+/// `batch` are left unchanged.
+pub fn read_record_batch_into_array2<T: ArRowDeserialize, S: DataMut<Elem = T>>(
+    batch: &RecordBatch,
+    dst: &mut ArrayBase<S, Ix2>,
+) -> Result<usize, DeserializationError> {
+    let (num_rows, num_columns) = dst.dim();
+    if batch.num_columns() != num_columns {
+        return Err(DeserializationError::MismatchedColumnDataType(format!(
+            "Cannot read {} columns into a matrix with {} columns",
+            batch.num_columns(),
+            num_columns
+        )));
+    }
+    if batch.num_rows() > num_rows {
+        return Err(DeserializationError::MismatchedLength {
+            src: batch.num_rows(),
+            dst: num_rows,
+        });
+    }
+    for (column, mut dst_column) in batch.columns().iter().zip(dst.columns_mut()) {
+        T::read_from_array(column.clone(), &mut dst_column)?;
+    }
+    Ok(batch.num_rows())
+}
+
+/// Returns a matrix with one row per row of `batch`, and one column per column
+/// of `batch`
+///
+/// See [`read_record_batch_into_array2`]
+pub fn record_batch_to_array2<T: ArRowDeserialize>(
+    batch: &RecordBatch,
+) -> Result<Array2<T>, DeserializationError> {
+    let mut array = Array2::default((batch.num_rows(), batch.num_columns()));
+    read_record_batch_into_array2(batch, &mut array)?;
+    Ok(array)
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Asynchronous reading of Parquet files from an [`ObjectStore`] (S3, GCS, Azure, ...)
+//!
+//! Requires the `object_store` feature. Support for each storage service must be
+//! enabled through the features of the [`object_store`](::object_store) crate
+//! (eg. `aws`, `gcp`, `azure`).
+
+use std::sync::Arc;
+
+use ::object_store::path::Path;
+use ::object_store::ObjectStore;
+use ::parquet::arrow::async_reader::{
+    ParquetObjectReader, ParquetRecordBatchStream, ParquetRecordBatchStreamBuilder,
+};
+use arrow::error::ArrowError;
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct};
+use crate::parquet::parquet_projection_for;
+use crate::reader::ReadError;
+use crate::row_stream::RowStream;
+
+/// Stream of rows of a Parquet file, returned by [`open_parquet_rows`]
+pub type ParquetRowStream<T> = RowStream<ParquetRecordBatchStream<ParquetObjectReader>, T>;
+
+fn object_store_error(e: ::object_store::Error) -> ReadError {
+    ReadError::Arrow(ArrowError::ExternalError(Box::new(e)))
+}
+
+/// Opens the Parquet file at `path` in `store`, and returns a stream of its rows.
+///
+/// Only the columns `T` is deserialized from are read, and the schema of the file
+/// is checked against `T` before reading any row.
+pub async fn open_parquet_rows<T: ArRowDeserialize + ArRowStruct>(
+    store: Arc<dyn ObjectStore>,
+    path: &Path,
+) -> Result<ParquetRowStream<T>, ReadError> {
+    let meta = store.head(path).await.map_err(object_store_error)?;
+    let builder = ParquetRecordBatchStreamBuilder::new(ParquetObjectReader::new(store, meta))
+        .await
+        .map_err(ArrowError::from)?;
+    let projection = parquet_projection_for::<T>(builder.parquet_schema());
+    let stream = builder
+        .with_projection(projection)
+        .build()
+        .map_err(ArrowError::from)?;
+    let schema = stream.schema().clone();
+    Ok(RowStream::new(stream, &schema)?)
+}
+
+/// Same as [`open_parquet_rows`], but takes a URL (eg. `s3://bucket/path/file.parquet`)
+/// instead of an [`ObjectStore`] and a path in it.
+pub async fn open_parquet_url_rows<T: ArRowDeserialize + ArRowStruct>(
+    url: &str,
+) -> Result<ParquetRowStream<T>, ReadError> {
+    let url = url::Url::parse(url)
+        .map_err(|e| ReadError::Arrow(ArrowError::ExternalError(Box::new(e))))?;
+    let (store, path) = ::object_store::parse_url(&url).map_err(object_store_error)?;
+    open_parquet_rows(Arc::from(store), &path).await
+}
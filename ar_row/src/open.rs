@@ -0,0 +1,106 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading files without knowing their format in advance
+//!
+//! [`open_rows`] detects the format of a file from its first bytes, and dispatches
+//! to [`open_orc_rows`](crate::orc::open_orc_rows) (if the `orc` feature is enabled),
+//! [`open_parquet_rows`](crate::parquet::open_parquet_rows) (if the `parquet` feature
+//! is enabled), or [`open_ipc_rows`](crate::ipc::open_ipc_rows).
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use arrow::error::ArrowError;
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct};
+use crate::reader::ReadError;
+
+/// Iterator on rows of a file of any supported format, returned by [`open_rows`]
+pub type Rows<T> = Box<dyn Iterator<Item = Result<T, ReadError>>>;
+
+/// File formats recognized by [`detect_format`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFormat {
+    Orc,
+    Parquet,
+    /// Arrow IPC file (aka. Feather V2 file), not to be confused with Arrow IPC streams,
+    /// which have no magic bytes
+    ArrowIpc,
+}
+
+impl FileFormat {
+    /// Returns the format whose magic bytes `header` starts with, if any
+    pub fn from_header(header: &[u8]) -> Option<FileFormat> {
+        if header.starts_with(b"ORC") {
+            Some(FileFormat::Orc)
+        } else if header.starts_with(b"PAR1") {
+            Some(FileFormat::Parquet)
+        } else if header.starts_with(b"ARROW1") {
+            Some(FileFormat::ArrowIpc)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads the first bytes of the file at `path` to detect its format
+pub fn detect_format(path: impl AsRef<Path>) -> std::io::Result<Option<FileFormat>> {
+    let mut header = Vec::with_capacity(6);
+    File::open(path)?.take(6).read_to_end(&mut header)?;
+    Ok(FileFormat::from_header(&header))
+}
+
+/// Opens the file at `path`, detects its format with [`detect_format`], and returns
+/// an iterator on its rows.
+///
+/// Only the columns `T` is deserialized from are read, and the schema of the file
+/// is checked against `T` before reading any row.
+pub fn open_rows<T: ArRowDeserialize + ArRowStruct + 'static>(
+    path: impl AsRef<Path>,
+) -> Result<Rows<T>, ReadError> {
+    let path = path.as_ref();
+    let format = detect_format(path).map_err(ArrowError::from)?;
+    match format {
+        #[cfg(feature = "orc")]
+        Some(FileFormat::Orc) => Ok(Box::new(crate::orc::open_orc_rows(path)?)),
+        #[cfg(feature = "parquet")]
+        Some(FileFormat::Parquet) => Ok(Box::new(crate::parquet::open_parquet_rows(path)?)),
+        Some(FileFormat::ArrowIpc) => Ok(Box::new(crate::ipc::open_ipc_rows(path)?)),
+        #[allow(unreachable_patterns)]
+        Some(format) => Err(ArrowError::InvalidArgumentError(format!(
+            "{} is a {:?} file, but support for this format is not enabled",
+            path.display(),
+            format
+        ))
+        .into()),
+        None => Err(ArrowError::ParseError(format!(
+            "{} is not an ORC, Parquet, or Arrow IPC file",
+            path.display()
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_header() {
+        assert_eq!(FileFormat::from_header(b"ORC\x0a"), Some(FileFormat::Orc));
+        assert_eq!(
+            FileFormat::from_header(b"PAR1\x15"),
+            Some(FileFormat::Parquet)
+        );
+        assert_eq!(
+            FileFormat::from_header(b"ARROW1\0\0"),
+            Some(FileFormat::ArrowIpc)
+        );
+        assert_eq!(FileFormat::from_header(b"ARR"), None);
+        assert_eq!(FileFormat::from_header(b""), None);
+    }
+}
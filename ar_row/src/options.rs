@@ -0,0 +1,277 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Per-call deserialization policies
+//!
+//! [`DeserializeOptions`] bundles the behaviors which can be toggled when calling
+//! [`ArRowDeserialize::from_array_with_options`],
+//! [`ArRowDeserialize::from_record_batch_with_options`] or
+//! [`RowIterator::with_options`](crate::row_iterator::RowIterator::with_options),
+//! instead of each combination of them getting its own function.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! use ar_row::deserialize::ArRowDeserialize;
+//! use ar_row::options::{DeserializeOptions, NullPolicy};
+//!
+//! let array: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]));
+//! assert!(i64::from_array(array.clone()).is_err());
+//!
+//! let options = DeserializeOptions {
+//!     coerce_types: true,
+//!     nulls: NullPolicy::SkipRows,
+//!     ..Default::default()
+//! };
+//! assert_eq!(i64::from_array_with_options(array, &options), Ok(vec![1, 3]));
+//! ```
+
+use std::num::NonZeroUsize;
+
+use arrow::array::{Array, ArrayRef, AsArray, BooleanArray};
+use arrow::buffer::BooleanBuffer;
+use arrow::compute::{can_cast_types, cast, filter};
+use arrow::datatypes::{DataType, Field};
+use arrow::record_batch::RecordBatch;
+
+use crate::deserialize::{
+    ArRowDeserialize, ArRowStruct, ColumnTree, DeserializationError, RootArray,
+};
+use crate::projection::project_array;
+
+/// What to do with columns of a structure which the type does not read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnknownColumns {
+    /// Fail with [`DeserializationError::MismatchedColumnDataType`], like
+    /// [`ArRowDeserialize::from_array`]
+    #[default]
+    Reject,
+    /// Drop them (and reorder the other columns) with
+    /// [`project_array`](crate::projection::project_array) before deserializing
+    Ignore,
+}
+
+/// What to do with nulls in columns read as non-`Option` types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NullPolicy {
+    /// Fail with [`DeserializationError::UnexpectedNull`], like
+    /// [`ArRowDeserialize::from_array`]
+    #[default]
+    Error,
+    /// Skip rows which contain such nulls.
+    ///
+    /// Only top-level rows are skipped: nulls in items of lists still fail.
+    SkipRows,
+}
+
+/// Policies applied by [`ArRowDeserialize::from_array_with_options`] and similar
+/// functions
+///
+/// The [default](Default) options behave like the functions without options,
+/// except that the type of arrays is checked before deserializing them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    /// Cast arrays with Arrow's [`cast`] kernel to the type returned by
+    /// [`CheckableDataType::expected_datatype`](crate::deserialize::CheckableDataType::expected_datatype)
+    /// when they cannot be deserialized as they are (eg. `Int32` to `i64`).
+    ///
+    /// Defaults to `false`.
+    pub coerce_types: bool,
+    /// Defaults to [`UnknownColumns::Reject`]
+    pub unknown_columns: UnknownColumns,
+    /// Defaults to [`NullPolicy::Error`]
+    pub nulls: NullPolicy,
+    /// Keep the deserialized values of dictionaries shared by consecutive batches
+    /// read by [`RowIterator::with_options`](crate::row_iterator::RowIterator::with_options),
+    /// in a [`DictionaryCache`](crate::dictionaries::DictionaryCache) dropped with
+    /// the iterator.
+    ///
+    /// Functions reading a single array ignore it; they can be called in
+    /// [`DictionaryCache::scope`](crate::dictionaries::DictionaryCache::scope) instead.
+    ///
+    /// Defaults to `true`.
+    pub dictionary_cache: bool,
+    /// Deserialize rows in blocks of this many rows: all the columns of the first
+    /// block are read, then all the columns of the next block, etc.
+    ///
+    /// This keeps the destination rows in the CPU cache while their fields are
+    /// written, which may be faster for structures with many fields. Dictionaries
+    /// are decoded once per block, unless they are cached (see
+    /// [`dictionary_cache`](Self::dictionary_cache)).
+    ///
+    /// Defaults to `None` (each column is read for the whole array at once).
+    pub block_size: Option<NonZeroUsize>,
+    /// Split arrays in chunks of this many rows, deserialized in parallel on the
+    /// [`rayon`] thread pool.
+    ///
+    /// Defaults to `None` (deserialize on the current thread).
+    /// Requires the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub parallel_chunk_size: Option<NonZeroUsize>,
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> Self {
+        DeserializeOptions {
+            coerce_types: false,
+            unknown_columns: UnknownColumns::Reject,
+            nulls: NullPolicy::Error,
+            dictionary_cache: true,
+            block_size: None,
+            #[cfg(feature = "rayon")]
+            parallel_chunk_size: None,
+        }
+    }
+}
+
+impl DeserializeOptions {
+    /// Applies [`unknown_columns`](Self::unknown_columns), [`nulls`](Self::nulls)
+    /// and [`coerce_types`](Self::coerce_types) to `array`, and checks `T` can be
+    /// deserialized from the result.
+    pub fn prepare_array<T: ArRowDeserialize + ArRowStruct>(
+        &self,
+        mut array: ArrayRef,
+    ) -> Result<ArrayRef, DeserializationError> {
+        if self.unknown_columns == UnknownColumns::Ignore {
+            if let tree @ ColumnTree::Struct(_) = T::column_tree() {
+                array = project_array(&array, &tree)
+                    .map_err(|e| DeserializationError::MismatchedColumnDataType(e.to_string()))?;
+            }
+        }
+
+        if self.nulls == NullPolicy::SkipRows {
+            let rows = valid_rows(array.as_ref(), &T::expected_datatype(), T::nullable());
+            if rows.count_set_bits() < array.len() {
+                array = filter(&array, &BooleanArray::new(rows, None))
+                    .map_err(|e| DeserializationError::MismatchedColumnDataType(e.to_string()))?;
+            }
+        }
+
+        if let Err(e) = T::check_datatype(array.data_type()) {
+            // Nulls are reported by the deserializer, with a more helpful error
+            let target_datatype = with_nullable_fields(T::expected_datatype());
+            if !self.coerce_types || !can_cast_types(array.data_type(), &target_datatype) {
+                return Err(DeserializationError::MismatchedColumnDataType(e));
+            }
+            array = cast(&array, &target_datatype)
+                .map_err(|e| DeserializationError::MismatchedColumnDataType(e.to_string()))?;
+        }
+
+        Ok(array)
+    }
+
+    /// Same as [`ArRowDeserialize::read_from_array_into_vec`], but applies the options
+    /// (see [`prepare_array`](Self::prepare_array))
+    pub fn read_from_array_into_vec<T: ArRowDeserialize + ArRowStruct + Send>(
+        &self,
+        array: ArrayRef,
+        dst: &mut Vec<T>,
+    ) -> Result<usize, DeserializationError> {
+        let array = self.prepare_array::<T>(array)?;
+
+        #[cfg(feature = "rayon")]
+        if let Some(chunk_size) = self.parallel_chunk_size {
+            use rayon::prelude::*;
+
+            let chunk_size = chunk_size.get();
+            let num_chunks = array.len() / chunk_size + usize::from(array.len() % chunk_size != 0);
+            let chunks = (0..num_chunks)
+                .into_par_iter()
+                .map(|i| {
+                    let offset = i * chunk_size;
+                    let chunk = array.slice(offset, usize::min(chunk_size, array.len() - offset));
+                    let mut rows = Vec::new();
+                    self.read_blocks(chunk, &mut rows)?;
+                    Ok(rows)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            dst.clear();
+            dst.extend(chunks.into_iter().flatten());
+            return Ok(dst.len());
+        }
+
+        self.read_blocks(array, dst)
+    }
+
+    /// Same as [`ArRowDeserialize::read_from_array_into_vec`], but reads blocks of
+    /// [`block_size`](Self::block_size) rows one after the other, if set
+    fn read_blocks<T: ArRowDeserialize>(
+        &self,
+        array: ArrayRef,
+        dst: &mut Vec<T>,
+    ) -> Result<usize, DeserializationError> {
+        let block_size = match self.block_size {
+            Some(block_size) => block_size.get(),
+            None => return T::read_from_array_into_vec(array, dst),
+        };
+        dst.resize_with(array.len(), Default::default);
+        for (i, mut block) in dst.chunks_mut(block_size).enumerate() {
+            T::read_from_array(array.slice(i * block_size, block.len()), &mut block)?;
+        }
+        Ok(dst.len())
+    }
+
+    /// Same as [`ArRowDeserialize::read_from_record_batch_into_vec`], but applies
+    /// the options (see [`prepare_array`](Self::prepare_array))
+    pub fn read_from_record_batch_into_vec<T: ArRowDeserialize + ArRowStruct + Send>(
+        &self,
+        batch: RecordBatch,
+        dst: &mut Vec<T>,
+    ) -> Result<usize, DeserializationError> {
+        let root = RootArray::without_checks::<T>(&batch.schema());
+        self.read_root_into_vec(batch, root, dst)
+    }
+
+    /// Same as [`read_from_record_batch_into_vec`](Self::read_from_record_batch_into_vec),
+    /// with a root array decided once from the schema of all batches
+    pub(crate) fn read_root_into_vec<T: ArRowDeserialize + ArRowStruct + Send>(
+        &self,
+        batch: RecordBatch,
+        root: RootArray,
+        dst: &mut Vec<T>,
+    ) -> Result<usize, DeserializationError> {
+        crate::metrics::time_batch(std::any::type_name::<T>(), batch, |batch| {
+            self.read_from_array_into_vec(root.get(batch), dst)
+        })
+    }
+}
+
+/// Returns `datatype` with all the fields of its structures (recursively) nullable
+fn with_nullable_fields(datatype: DataType) -> DataType {
+    match datatype {
+        DataType::Struct(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|field| {
+                    Field::clone(field)
+                        .with_data_type(with_nullable_fields(field.data_type().clone()))
+                        .with_nullable(true)
+                })
+                .collect(),
+        ),
+        datatype => datatype,
+    }
+}
+
+/// Returns which rows of `array` have no nulls where `datatype` (with the given
+/// nullability) does not allow them, ignoring nulls in lists.
+fn valid_rows(array: &dyn Array, datatype: &DataType, nullable: bool) -> BooleanBuffer {
+    let mut rows = BooleanBuffer::new_set(array.len());
+    if let (DataType::Struct(fields), Some(array)) = (datatype, array.as_struct_opt()) {
+        for (field, column) in fields.iter().zip(array.columns()) {
+            rows = &rows & &valid_rows(column, field.data_type(), field.is_nullable());
+        }
+        // Fields of null structures are never read
+        if let Some(nulls) = array.nulls() {
+            rows = &rows | &!nulls.inner();
+        }
+    }
+    if !nullable {
+        if let Some(nulls) = array.logical_nulls() {
+            rows = &rows & nulls.inner();
+        }
+    }
+    rows
+}
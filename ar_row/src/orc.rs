@@ -0,0 +1,94 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to read ORC files with [`orc_rust`]
+//!
+//! Requires the `orc` feature.
+
+use std::fs::File;
+use std::path::Path;
+
+use arrow::error::ArrowError;
+use orc_rust::projection::ProjectionMask;
+use orc_rust::reader::ChunkReader;
+use orc_rust::schema::RootDataType;
+use orc_rust::{ArrowReader, ArrowReaderBuilder};
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct, ColumnPath};
+use crate::projection::ProjectedReader;
+use crate::reader::{CheckedReader, CheckedRows, ReadError};
+use crate::seek::SeekableBatchSource;
+
+/// Returns a [`ProjectionMask`] selecting the columns `T` is deserialized from.
+///
+/// `orc_rust` can only project top-level columns, so selecting a nested column
+/// selects all the other columns nested in the same top-level column. Wrap the reader
+/// in a [`ProjectedReader`] to prune them.
+pub fn orc_projection_for<T: ArRowStruct>(root_data_type: &RootDataType) -> ProjectionMask {
+    let mut roots: Vec<String> = Vec::new();
+    T::for_each_column_path(&mut Vec::new(), &mut |column| {
+        if let Some(root) = column.first() {
+            if !roots.iter().any(|known_root| known_root == root) {
+                roots.push((*root).to_owned());
+            }
+        }
+    });
+    ProjectionMask::named_roots(root_data_type, roots.as_slice())
+}
+
+/// Returns a [`ProjectionMask`] selecting the top-level columns containing `columns`
+pub fn orc_projection_for_paths(
+    root_data_type: &RootDataType,
+    columns: &[ColumnPath],
+) -> ProjectionMask {
+    let mut roots: Vec<&str> = Vec::new();
+    for root in columns.iter().filter_map(|column| column.parts().first()) {
+        if !roots.contains(&root.as_str()) {
+            roots.push(root);
+        }
+    }
+    ProjectionMask::named_roots(root_data_type, roots.as_slice())
+}
+
+/// Opens the ORC file at `path`, and returns an iterator on its rows.
+///
+/// Only the top-level columns `T` is deserialized from are read (see
+/// [`orc_projection_for`]), then the columns nested in them which `T` does not read
+/// are pruned from each batch (see [`ProjectedReader`]). The schema of the file is
+/// checked against `T` before reading any row.
+pub fn open_orc_rows<T: ArRowDeserialize + ArRowStruct>(
+    path: impl AsRef<Path>,
+) -> Result<CheckedRows<ProjectedReader<ArrowReader<File>>, T>, ReadError> {
+    let file = File::open(path).map_err(ArrowError::from)?;
+    let builder =
+        ArrowReaderBuilder::try_new(file).map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+    let projection = orc_projection_for::<T>(builder.file_metadata().root_data_type());
+    let reader = ProjectedReader::new_for::<T>(builder.with_projection(projection).build())?;
+    Ok(CheckedReader::new(reader)?.rows())
+}
+
+/// Chunks are stripes. Seeking relies on [`ArrowReaderBuilder::with_file_byte_range`],
+/// so it overrides any byte range previously set on the builder.
+impl<R: ChunkReader> SeekableBatchSource for ArrowReaderBuilder<R> {
+    type Reader = ArrowReader<R>;
+
+    fn chunk_lengths(&self) -> Vec<usize> {
+        self.file_metadata()
+            .stripe_metadatas()
+            .iter()
+            .map(|stripe| stripe.number_of_rows() as usize)
+            .collect()
+    }
+
+    fn read_from_chunk(self, first_chunk: usize) -> Result<ArrowReader<R>, ReadError> {
+        let start = self
+            .file_metadata()
+            .stripe_metadatas()
+            .get(first_chunk)
+            .map(|stripe| stripe.offset() as usize)
+            .unwrap_or(usize::MAX);
+        Ok(self.with_file_byte_range(start..usize::MAX).build())
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Parallel iterator on rows of a [`RandomAccessBatchReader`]
+//!
+//! [`ParallelRowIterator`] implements Rayon's [`IndexedParallelIterator`], so rows
+//! can be collected in order with
+//! [`collect_into_vec`](IndexedParallelIterator::collect_into_vec), or zipped with
+//! other indexed iterators.
+//!
+//! The iterator is split on row boundaries, which Rayon chooses without knowing
+//! where batches start: a batch overlapping two jobs is read by both, and each
+//! deserializes its own part of it. As jobs are not split below the average number
+//! of rows per batch, most batches are read once or twice; when reading a batch is
+//! expensive, [`with_min_len`](IndexedParallelIterator::with_min_len) can make jobs
+//! span more batches, so fewer of them are read twice.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::parallel_row_iterator::ParallelRowIterator;
+//! use ar_row::rayon::prelude::*;
+//!
+//! let batches: Vec<RecordBatch> = vec![vec![1i64, 2], vec![3]]
+//!     .into_iter()
+//!     .map(|values| {
+//!         RecordBatch::try_from_iter(vec![("id", Arc::new(Int64Array::from(values)) as ArrayRef)])
+//!             .unwrap()
+//!     })
+//!     .collect();
+//!
+//! let mut rows = Vec::new();
+//! ParallelRowIterator::<_, i64>::new(&batches)
+//!     .unwrap()
+//!     .collect_into_vec(&mut rows);
+//! let rows: Result<Vec<i64>, _> = rows.into_iter().collect();
+//! assert_eq!(rows.unwrap(), vec![1, 2, 3]);
+//! ```
+//!
+//! Sequential readers can be switched to parallel deserialization by replacing
+//! [`into_rows`](crate::row_iterator::IntoRows::into_rows) with
+//! [`into_buffered_par_rows`](IntoParRows::into_buffered_par_rows). As its name says,
+//! it reads all their batches in memory before deserializing any of them, so it is
+//! only suitable for readers whose batches fit in memory:
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::parallel_row_iterator::IntoParRows;
+//! use ar_row::rayon::prelude::*;
+//!
+//! let batches = vec![vec![1i64, 2], vec![3]].into_iter().map(|values| {
+//!     RecordBatch::try_from_iter(vec![("id", Arc::new(Int64Array::from(values)) as ArrayRef)])
+//! });
+//!
+//! let rows: Vec<i64> = batches.into_buffered_par_rows().unwrap().collect();
+//! assert_eq!(rows, vec![1, 2, 3]);
+//! ```
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, RootArray};
+use crate::random_access::{batch_of_row, batch_starts, RandomAccessBatchReader};
+use crate::reader::ReadError;
+use crate::row_iterator::IntoRecordBatch;
+
+/// Parallel iterator on rows of a [`RandomAccessBatchReader`]
+///
+/// Yields one item per row. When the rows of a batch read by a job cannot be read
+/// or deserialized, each of them yields the same error, shared in an [`Arc`].
+///
+/// See the [module-level documentation](self).
+pub struct ParallelRowIterator<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> {
+    reader: &'r R,
+    /// Index of the first row of each batch, followed by the total number of rows
+    batch_starts: Arc<[usize]>,
+    /// Array batches are read from, decided from the schema checked by
+    /// [`new`](Self::new), or for each batch if they have different schemas
+    root: Option<RootArray>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send>
+    ParallelRowIterator<'r, R, T>
+{
+    /// Checks `reader`'s schema can be deserialized into `T`, and counts the rows
+    /// of each batch (see [`RandomAccessBatchReader::batch_num_rows`]).
+    pub fn new(reader: &'r R) -> Result<Self, ReadError> {
+        let schema = reader.schema();
+        T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+        Ok(ParallelRowIterator {
+            reader,
+            batch_starts: batch_starts(reader)?.into(),
+            root: Some(RootArray::new::<T>(&schema)),
+            marker: PhantomData,
+        })
+    }
+
+    fn num_rows(&self) -> usize {
+        *self
+            .batch_starts
+            .last()
+            .expect("batch_starts is never empty")
+    }
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> ParallelIterator
+    for ParallelRowIterator<'r, R, T>
+{
+    type Item = Result<T, Arc<ReadError>>;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.num_rows())
+    }
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> IndexedParallelIterator
+    for ParallelRowIterator<'r, R, T>
+{
+    fn len(&self) -> usize {
+        self.num_rows()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        let end = self.num_rows();
+        callback.callback(RowRange {
+            reader: self.reader,
+            batch_starts: self.batch_starts,
+            root: self.root,
+            start: 0,
+            end,
+            front: Vec::new().into_iter(),
+            back: Vec::new().into_iter(),
+        })
+    }
+}
+
+/// Sequential iterator on a range of rows of a [`RandomAccessBatchReader`], and
+/// producer of [`ParallelRowIterator`]
+struct RowRange<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> {
+    reader: &'r R,
+    batch_starts: Arc<[usize]>,
+    root: Option<RootArray>,
+    /// Rows in `start..end` are not read yet
+    start: usize,
+    end: usize,
+    /// Rows read from the front, before `start`
+    front: std::vec::IntoIter<Result<T, Arc<ReadError>>>,
+    /// Rows read from the back, after `end`
+    back: std::vec::IntoIter<Result<T, Arc<ReadError>>>,
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> RowRange<'r, R, T> {
+    /// Reads `num_rows` rows of the `batch_index`-th batch, starting from
+    /// `first_row` (relative to the start of the reader)
+    fn read_rows(
+        &self,
+        batch_index: usize,
+        first_row: usize,
+        num_rows: usize,
+    ) -> std::vec::IntoIter<Result<T, Arc<ReadError>>> {
+        let offset = first_row - self.batch_starts[batch_index];
+        let rows = self.reader.read_batch(batch_index).and_then(|batch| {
+            let root = self
+                .root
+                .unwrap_or_else(|| RootArray::new::<T>(&batch.schema()));
+            root.read(batch.slice(offset, num_rows))
+                .map_err(ReadError::from)
+        });
+        match rows {
+            Ok(rows) => rows.into_iter().map(Ok).collect::<Vec<_>>().into_iter(),
+            Err(e) => {
+                let e = Arc::new(e);
+                (0..num_rows)
+                    .map(|_| Err(e.clone()))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            }
+        }
+    }
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> Iterator
+    for RowRange<'r, R, T>
+{
+    type Item = Result<T, Arc<ReadError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.front.next() {
+                return Some(row);
+            }
+            if self.start == self.end {
+                return self.back.next();
+            }
+            let batch_index = batch_of_row(&self.batch_starts, self.start);
+            let batch_end = self.batch_starts[batch_index + 1].min(self.end);
+            self.front = self.read_rows(batch_index, self.start, batch_end - self.start);
+            self.start = batch_end;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + (self.end - self.start) + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> DoubleEndedIterator
+    for RowRange<'r, R, T>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.back.next_back() {
+                return Some(row);
+            }
+            if self.start == self.end {
+                return self.front.next_back();
+            }
+            let batch_index = batch_of_row(&self.batch_starts, self.end - 1);
+            let batch_start = self.batch_starts[batch_index].max(self.start);
+            self.back = self.read_rows(batch_index, batch_start, self.end - batch_start);
+            self.end = batch_start;
+        }
+    }
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> ExactSizeIterator
+    for RowRange<'r, R, T>
+{
+}
+
+impl<'r, R: RandomAccessBatchReader + Sync, T: ArRowDeserialize + Send> Producer
+    for RowRange<'r, R, T>
+{
+    type Item = Result<T, Arc<ReadError>>;
+    type IntoIter = Self;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self
+    }
+
+    fn min_len(&self) -> usize {
+        let num_batches = self.batch_starts.len() - 1;
+        let num_rows = *self
+            .batch_starts
+            .last()
+            .expect("batch_starts is never empty");
+        (num_rows / num_batches.max(1)).max(1)
+    }
+
+    /// Splits the range of rows which were not read yet. Producers are only split
+    /// before iteration starts, so `front` and `back` are empty.
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let mid = self.start + index;
+        let right = RowRange {
+            reader: self.reader,
+            batch_starts: self.batch_starts.clone(),
+            root: self.root,
+            start: mid,
+            end: self.end,
+            front: Vec::new().into_iter(),
+            back: self.back,
+        };
+        let left = RowRange {
+            reader: self.reader,
+            batch_starts: self.batch_starts,
+            root: self.root,
+            start: self.start,
+            end: mid,
+            front: self.front,
+            back: Vec::new().into_iter(),
+        };
+        (left, right)
+    }
+}
+
+/// Extension trait of iterators on [`RecordBatch`] or `Result<RecordBatch, _>`, like
+/// [`IntoRows`](crate::row_iterator::IntoRows) but deserializing rows in parallel
+pub trait IntoParRows: Iterator + Sized {
+    /// Reads all batches in memory, checks their schemas can be deserialized into `T`,
+    /// and returns a parallel iterator on their rows, in order.
+    ///
+    /// Nothing is deserialized until the underlying iterator is exhausted, and all
+    /// batches are kept until the returned iterator is dropped. To deserialize
+    /// batches as they are read, use [`par_bridge`](rayon::iter::ParallelBridge::par_bridge)
+    /// on batches instead.
+    ///
+    /// # Panics
+    ///
+    /// When the underlying iterator yields an error. The returned iterator panics
+    /// when a batch cannot be deserialized.
+    fn into_buffered_par_rows<T: ArRowDeserialize + Send>(
+        self,
+    ) -> Result<ParallelBatchRows<T>, DeserializationError>
+    where
+        Self::Item: IntoRecordBatch,
+    {
+        let batches: Vec<RecordBatch> = self.map(IntoRecordBatch::into_record_batch).collect();
+        // Batches usually share their schema, so it is only checked when it changes
+        let mut checked_schema = None;
+        let mut root = None;
+        for (i, batch) in batches.iter().enumerate() {
+            let schema = batch.schema();
+            if checked_schema.as_ref() != Some(&schema) {
+                T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+                // Decided for each batch if they do not all share the same schema
+                root = match i {
+                    0 => Some(RootArray::new::<T>(&schema)),
+                    _ => None,
+                };
+                checked_schema = Some(schema);
+            }
+        }
+        let batch_starts = batch_starts(&batches)
+            .expect("Batches in memory can be counted")
+            .into();
+        Ok(ParallelBatchRows {
+            batches,
+            batch_starts,
+            root,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<I: Iterator> IntoParRows for I {}
+
+/// Parallel iterator on rows of batches read in memory, returned by
+/// [`IntoParRows::into_buffered_par_rows`]
+///
+/// Unlike [`ParallelRowIterator`], it yields rows directly, and panics when they
+/// cannot be deserialized.
+pub struct ParallelBatchRows<T: ArRowDeserialize + Send> {
+    batches: Vec<RecordBatch>,
+    /// Index of the first row of each batch, followed by the total number of rows
+    batch_starts: Arc<[usize]>,
+    /// Same as [`ParallelRowIterator`]'s
+    root: Option<RootArray>,
+    marker: PhantomData<fn() -> T>,
+}
+
+fn unwrap_row<T>(row: Result<T, Arc<ReadError>>) -> T {
+    row.unwrap_or_else(|e| panic!("ParallelBatchRows failed: {}", e))
+}
+
+impl<T: ArRowDeserialize + Send> ParallelIterator for ParallelBatchRows<T> {
+    type Item = T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: ArRowDeserialize + Send> IndexedParallelIterator for ParallelBatchRows<T> {
+    fn len(&self) -> usize {
+        *self
+            .batch_starts
+            .last()
+            .expect("batch_starts is never empty")
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        ParallelRowIterator::<_, T> {
+            reader: &self.batches,
+            batch_starts: self.batch_starts,
+            root: self.root,
+            marker: PhantomData,
+        }
+        .map(unwrap_row as fn(Result<T, Arc<ReadError>>) -> T)
+        .with_producer(callback)
+    }
+}
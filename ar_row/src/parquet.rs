@@ -0,0 +1,187 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to read Parquet files with the [`parquet`](::parquet) crate
+//!
+//! Requires the `parquet` feature.
+
+use std::fs::File;
+use std::path::Path;
+
+use ::parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use ::parquet::arrow::ProjectionMask;
+use ::parquet::file::reader::ChunkReader;
+use ::parquet::schema::types::SchemaDescriptor;
+use arrow::error::ArrowError;
+
+use crate::deserialize::{ArRowDeserialize, ArRowStruct, ColumnPath};
+use crate::reader::{CheckedReader, CheckedRows, ReadError};
+use crate::seek::SeekableBatchSource;
+
+/// Returns a [`ProjectionMask`] selecting the leaf columns `T` is deserialized from.
+///
+/// Unlike ORC, Parquet can project nested columns, so nested structures only read the
+/// fields they declare.
+pub fn parquet_projection_for<T: ArRowStruct>(schema: &SchemaDescriptor) -> ProjectionMask {
+    let leaves = schema.columns();
+    let mut selected = vec![false; leaves.len()];
+    T::for_each_column_path(&mut Vec::new(), &mut |column| {
+        for (leaf, selected) in leaves.iter().zip(selected.iter_mut()) {
+            *selected = *selected || leaf_matches(column, leaf.path().parts());
+        }
+    });
+    ProjectionMask::leaves(
+        schema,
+        selected
+            .into_iter()
+            .enumerate()
+            .filter(|(_, selected)| *selected)
+            .map(|(i, _)| i),
+    )
+}
+
+/// Returns a [`ProjectionMask`] selecting the leaf columns needed to read `columns`,
+/// and all the leaves nested in them.
+pub fn parquet_projection_for_paths(
+    schema: &SchemaDescriptor,
+    columns: &[ColumnPath],
+) -> ProjectionMask {
+    let leaves = schema
+        .columns()
+        .iter()
+        .enumerate()
+        .filter(|(_, leaf)| {
+            columns
+                .iter()
+                .any(|column| leaf_matches(column.parts(), leaf.path().parts()))
+        })
+        .map(|(i, _)| i);
+    ProjectionMask::leaves(schema, leaves)
+}
+
+/// Returns whether the leaf at `leaf_path` needs to be read to deserialize the column
+/// at `column_path`.
+///
+/// Parquet paths contain extra parts for repeated groups (eg. `list.element` in
+/// `my_list.list.element.my_field`), which are absent from [`ArRowStruct::columns`],
+/// so this checks `column_path` is a subsequence of `leaf_path`, starting from the same
+/// top-level column.
+fn leaf_matches<S: AsRef<str>>(column_path: &[S], leaf_path: &[String]) -> bool {
+    match (column_path.split_first(), leaf_path.split_first()) {
+        (Some((column_root, column_rest)), Some((leaf_root, leaf_rest)))
+            if column_root.as_ref() == leaf_root =>
+        {
+            let mut leaf_rest = leaf_rest.iter();
+            column_rest
+                .iter()
+                .all(|part| leaf_rest.any(|leaf_part| leaf_part == part.as_ref()))
+        }
+        _ => false,
+    }
+}
+
+/// Opens the Parquet file at `path`, and returns an iterator on its rows.
+///
+/// Only the columns `T` is deserialized from are read (see [`parquet_projection_for`]),
+/// and the schema of the file is checked against `T` before reading any row.
+pub fn open_parquet_rows<T: ArRowDeserialize + ArRowStruct>(
+    path: impl AsRef<Path>,
+) -> Result<CheckedRows<ParquetRecordBatchReader, T>, ReadError> {
+    open_parquet_rows_inner(path.as_ref(), None)
+}
+
+/// Same as [`open_parquet_rows`], but only reads the given row groups, in the order
+/// they are given.
+pub fn open_parquet_row_groups<T: ArRowDeserialize + ArRowStruct>(
+    path: impl AsRef<Path>,
+    row_groups: Vec<usize>,
+) -> Result<CheckedRows<ParquetRecordBatchReader, T>, ReadError> {
+    open_parquet_rows_inner(path.as_ref(), Some(row_groups))
+}
+
+fn open_parquet_rows_inner<T: ArRowDeserialize + ArRowStruct>(
+    path: &Path,
+    row_groups: Option<Vec<usize>>,
+) -> Result<CheckedRows<ParquetRecordBatchReader, T>, ReadError> {
+    let file = File::open(path).map_err(ArrowError::from)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(ArrowError::from)?;
+    let projection = parquet_projection_for::<T>(builder.parquet_schema());
+    let mut builder = builder.with_projection(projection);
+    if let Some(row_groups) = row_groups {
+        builder = builder.with_row_groups(row_groups);
+    }
+    let reader = builder.build().map_err(ArrowError::from)?;
+    Ok(CheckedReader::new(reader)?.rows())
+}
+
+/// Chunks are row groups. Seeking relies on
+/// [`ParquetRecordBatchReaderBuilder::with_row_groups`], so it overrides any row group
+/// selection previously set on the builder.
+impl<R: ChunkReader + 'static> SeekableBatchSource for ParquetRecordBatchReaderBuilder<R> {
+    type Reader = ParquetRecordBatchReader;
+
+    fn chunk_lengths(&self) -> Vec<usize> {
+        self.metadata()
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_rows() as usize)
+            .collect()
+    }
+
+    fn read_from_chunk(self, first_chunk: usize) -> Result<ParquetRecordBatchReader, ReadError> {
+        let num_row_groups = self.metadata().num_row_groups();
+        Ok(self
+            .with_row_groups((first_chunk..num_row_groups).collect())
+            .build()
+            .map_err(ArrowError::from)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ::parquet::schema::parser::parse_message_type;
+
+    use super::*;
+
+    struct Test;
+
+    impl ArRowStruct for Test {
+        fn columns_with_prefix(_prefix: &str) -> Vec<String> {
+            vec!["int1".to_owned(), "list.string1".to_owned()]
+        }
+    }
+
+    #[test]
+    fn test_parquet_projection_for() {
+        let schema = parse_message_type(
+            "
+            message schema {
+                required int32 int1;
+                optional binary string1 (UTF8);
+                optional group list (LIST) {
+                    repeated group list {
+                        optional group element {
+                            optional int32 int1;
+                            optional binary string1 (UTF8);
+                        }
+                    }
+                }
+            }
+            ",
+        )
+        .unwrap();
+        let schema = SchemaDescriptor::new(Arc::new(schema));
+
+        let mask = parquet_projection_for::<Test>(&schema);
+        assert_eq!(
+            (0..schema.num_columns())
+                .map(|i| mask.leaf_included(i))
+                .collect::<Vec<_>>(),
+            vec![true, false, false, true]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Re-exports of the traits and types needed by most users
+//!
+//! With the `derive` feature, this includes the `ArRowDeserialize` and `ArRowHash`
+//! derive macros from `ar_row_derive`.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use ar_row::arrow::array::{ArrayRef, Int64Array};
+//! use ar_row::prelude::*;
+//!
+//! let array: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+//! assert_eq!(i64::from_array(array).unwrap(), vec![1, 2]);
+//! ```
+
+pub use crate::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType};
+pub use crate::hash::StableHash;
+pub use crate::options::DeserializeOptions;
+#[cfg(feature = "rayon")]
+pub use crate::parallel_row_iterator::IntoParRows;
+pub use crate::row_iterator::{IntoRows, RowIterator};
+pub use crate::value::ArRowValue;
+pub use crate::{
+    Date, FixedSizeBinary, FixedSizeList, MapEntry, NaiveDecimal128, SharedString, Timestamp,
+};
+#[cfg(feature = "derive")]
+pub use ar_row_derive::{ArRowDeserialize, ArRowHash};
@@ -0,0 +1,287 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Pruning of record batches to the columns a type is deserialized from
+//!
+//! Code generated by `ar_row_derive` reads columns in the order of the structure's
+//! fields, and rejects batches with extra columns. [`project_batch_for`] turns
+//! batches from sources which do not support projection (or whose projection does
+//! not reorder columns), such as Arrow Flight or DataFusion, into batches it accepts.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::deserialize::{ArRowDeserialize, ColumnTree};
+//! use ar_row::projection::project_batch;
+//!
+//! let batch = RecordBatch::try_from_iter(vec![
+//!     ("name", Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef),
+//!     ("unused", Arc::new(Int64Array::from(vec![0, 0])) as ArrayRef),
+//!     ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+//! ])
+//! .unwrap();
+//!
+//! // What project_batch_for::<T> does for a structure with fields `id` and `name`
+//! let tree = ColumnTree::Struct(vec![
+//!     ("id".to_owned(), ColumnTree::Leaf),
+//!     ("name".to_owned(), ColumnTree::Leaf),
+//! ]);
+//! let batch = project_batch(&batch, &tree).unwrap();
+//! assert_eq!(
+//!     <(i64, String)>::from_record_batch(batch).unwrap(),
+//!     vec![(1, "a".to_owned()), (2, "b".to_owned())]
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, FixedSizeListArray, LargeListArray, ListArray, MapArray, StructArray,
+};
+use arrow::datatypes::{DataType, Field, FieldRef, Fields, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions, RecordBatchReader};
+
+use crate::deserialize::{ArRowStruct, ColumnTree};
+
+/// Returns a batch with only the columns `T` is deserialized from, in the order
+/// of [`ArRowStruct::column_tree`].
+///
+/// Fields of nested structures are pruned and reordered as well, including in
+/// lists of structures and in the values of maps. Columns `T` reads as a whole
+/// (eg. maps of scalars) are kept as they are.
+///
+/// Returns [`ArrowError::SchemaError`] if a column `T` needs is missing from `batch`.
+/// Data types are not checked; use
+/// [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema)
+/// on the returned batch for this.
+pub fn project_batch_for<T: ArRowStruct>(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+    project_batch(batch, &T::column_tree())
+}
+
+/// Same as [`project_batch_for`], with the tree of columns to keep given explicitly,
+/// eg. built from column names with [`ColumnTree::from_paths`]
+pub fn project_batch(batch: &RecordBatch, tree: &ColumnTree) -> Result<RecordBatch, ArrowError> {
+    match tree {
+        ColumnTree::Leaf => Ok(batch.clone()),
+        ColumnTree::Struct(tree_fields) => {
+            let (fields, arrays) =
+                project_fields(batch.schema().fields(), batch.columns(), tree_fields, "")?;
+            // The row count is needed when `tree` has no columns (unit structures)
+            RecordBatch::try_new_with_options(
+                Arc::new(Schema::new_with_metadata(
+                    fields,
+                    batch.schema().metadata().clone(),
+                )),
+                arrays,
+                &RecordBatchOptions::new().with_row_count(Some(batch.num_rows())),
+            )
+        }
+    }
+}
+
+/// Same as [`project_batch`], for the rows of a [`StructArray`]
+///
+/// Unlike converting the array to a [`RecordBatch`], this works when the structure
+/// itself contains nulls.
+pub fn project_array(array: &ArrayRef, tree: &ColumnTree) -> Result<ArrayRef, ArrowError> {
+    let field = Arc::new(Field::new("", array.data_type().clone(), true));
+    let (_, array) = project_field(&field, array, tree, "")?;
+    Ok(array)
+}
+
+/// Wrapper for a [`RecordBatchReader`] which applies [`project_batch`] to every batch
+/// it reads.
+///
+/// This is useful for readers which can only project top-level columns (such as ORC
+/// and Arrow IPC readers), as the columns nested in them still need to be pruned.
+pub struct ProjectedReader<R: RecordBatchReader> {
+    reader: R,
+    tree: ColumnTree,
+    schema: SchemaRef,
+}
+
+impl<R: RecordBatchReader> ProjectedReader<R> {
+    /// Wraps `reader` to keep only the columns `T` is deserialized from
+    /// (see [`project_batch_for`])
+    pub fn new_for<T: ArRowStruct>(reader: R) -> Result<ProjectedReader<R>, ArrowError> {
+        ProjectedReader::new(reader, T::column_tree())
+    }
+
+    /// Wraps `reader` to keep only the columns in `tree` (see [`project_batch`])
+    ///
+    /// Returns [`ArrowError::SchemaError`] if a column in `tree` is missing from
+    /// `reader`'s schema.
+    pub fn new(reader: R, tree: ColumnTree) -> Result<ProjectedReader<R>, ArrowError> {
+        let schema = project_batch(&RecordBatch::new_empty(reader.schema()), &tree)?.schema();
+        Ok(ProjectedReader {
+            reader,
+            tree,
+            schema,
+        })
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for ProjectedReader<R> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader
+            .next()
+            .map(|batch| project_batch(&batch?, &self.tree))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.reader.size_hint()
+    }
+}
+
+impl<R: RecordBatchReader> RecordBatchReader for ProjectedReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Returns the fields of `fields` (and matching `arrays`) named in `tree_fields`,
+/// in the order of `tree_fields`
+fn project_fields(
+    fields: &Fields,
+    arrays: &[ArrayRef],
+    tree_fields: &[(String, ColumnTree)],
+    prefix: &str,
+) -> Result<(Vec<FieldRef>, Vec<ArrayRef>), ArrowError> {
+    let mut projected_fields = Vec::with_capacity(tree_fields.len());
+    let mut projected_arrays = Vec::with_capacity(tree_fields.len());
+    for (name, tree) in tree_fields {
+        let (index, field) = fields.find(name).ok_or_else(|| {
+            ArrowError::SchemaError(format!("No column named {}{}", prefix, name))
+        })?;
+        let (field, array) =
+            project_field(field, &arrays[index], tree, &format!("{}{}", prefix, name))?;
+        projected_fields.push(field);
+        projected_arrays.push(array);
+    }
+    Ok((projected_fields, projected_arrays))
+}
+
+/// Removes the fields nested in `field` which are not in `tree`.
+///
+/// Lists are transparent, like in [`ArRowStruct::column_tree`]. So are maps: `tree`
+/// selects fields in their values, and keys are always kept.
+fn project_field(
+    field: &FieldRef,
+    array: &ArrayRef,
+    tree: &ColumnTree,
+    path: &str,
+) -> Result<(FieldRef, ArrayRef), ArrowError> {
+    let tree_fields = match tree {
+        ColumnTree::Leaf => return Ok((field.clone(), array.clone())),
+        ColumnTree::Struct(tree_fields) => tree_fields,
+    };
+    let (datatype, array): (DataType, ArrayRef) = match field.data_type() {
+        DataType::Struct(_) => {
+            let array = array.as_struct();
+            let (child_fields, child_arrays) = project_fields(
+                array.fields(),
+                array.columns(),
+                tree_fields,
+                &if path.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}.", path)
+                },
+            )?;
+            let child_fields = Fields::from(child_fields);
+            (
+                DataType::Struct(child_fields.clone()),
+                Arc::new(StructArray::try_new(
+                    child_fields,
+                    child_arrays,
+                    array.nulls().cloned(),
+                )?),
+            )
+        }
+        DataType::List(item_field) => {
+            let array = array.as_list::<i32>();
+            let (item_field, values) = project_field(item_field, array.values(), tree, path)?;
+            (
+                DataType::List(item_field.clone()),
+                Arc::new(ListArray::try_new(
+                    item_field,
+                    array.offsets().clone(),
+                    values,
+                    array.nulls().cloned(),
+                )?),
+            )
+        }
+        DataType::LargeList(item_field) => {
+            let array = array.as_list::<i64>();
+            let (item_field, values) = project_field(item_field, array.values(), tree, path)?;
+            (
+                DataType::LargeList(item_field.clone()),
+                Arc::new(LargeListArray::try_new(
+                    item_field,
+                    array.offsets().clone(),
+                    values,
+                    array.nulls().cloned(),
+                )?),
+            )
+        }
+        DataType::FixedSizeList(item_field, size) => {
+            let array = array.as_fixed_size_list();
+            let (item_field, values) = project_field(item_field, array.values(), tree, path)?;
+            (
+                DataType::FixedSizeList(item_field.clone(), *size),
+                Arc::new(FixedSizeListArray::try_new(
+                    item_field,
+                    *size,
+                    values,
+                    array.nulls().cloned(),
+                )?),
+            )
+        }
+        DataType::Map(entries_field, sorted) => {
+            let array = array.as_map();
+            let entries = array.entries();
+            let (value_field, values) =
+                project_field(&entries.fields()[1], entries.column(1), tree, path)?;
+            let entry_fields = Fields::from(vec![entries.fields()[0].clone(), value_field]);
+            let entries_field = Arc::new(
+                Field::clone(entries_field).with_data_type(DataType::Struct(entry_fields.clone())),
+            );
+            let entries = StructArray::try_new(
+                entry_fields,
+                vec![entries.column(0).clone(), values],
+                entries.nulls().cloned(),
+            )?;
+            (
+                DataType::Map(entries_field.clone(), *sorted),
+                Arc::new(MapArray::try_new(
+                    entries_field,
+                    array.offsets().clone(),
+                    entries,
+                    array.nulls().cloned(),
+                    *sorted,
+                )?),
+            )
+        }
+        datatype => {
+            return Err(ArrowError::SchemaError(format!(
+                "Column {} is a {}, it has no nested columns to select",
+                path, datatype
+            )))
+        }
+    };
+    Ok((
+        Arc::new(Field::clone(field).with_data_type(datatype)),
+        array,
+    ))
+}
@@ -0,0 +1,229 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Python bindings with [`pyo3`]
+//!
+//! Record batches are received from `pyarrow` through the Arrow C Data Interface
+//! (without copying them), and each row is converted to a Python `dict` by walking
+//! its [`ArrayTree`]:
+//!
+//! * structs and maps become `dict`s, and lists become `list`s
+//! * unions become single-entry `dict`s, whose key is the name of the variant
+//! * binaries become `bytes`
+//! * temporal and decimal values become strings, formatted like in JSON conversion
+//!
+//! Requires the `python` feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use pyo3::prelude::*;
+//!
+//! #[pymodule]
+//! fn my_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+//!     ar_row::python::add_to_module(module)
+//! }
+//! ```
+//!
+//! ```python
+//! my_module.record_batch_to_rows(pyarrow.record_batch({"a": [1, 2]}))
+//! # [{'a': 1}, {'a': 2}]
+//! ```
+
+// The `*_bound` constructors and `IntoPy` are deprecated by pyo3 v0.23, but their
+// replacements do not exist in pyo3 v0.21 and v0.22 (used by arrow v52 and v53)
+#![allow(deprecated)]
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::pyarrow::FromPyArrow;
+use arrow::record_batch::RecordBatch;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+
+use crate::display::LeafFormatters;
+use crate::structured_reader::ArrayTree;
+
+/// Converts a `pyarrow.RecordBatch` to a list with a `dict` for each of its rows
+#[pyfunction]
+pub fn record_batch_to_rows<'py>(
+    py: Python<'py>,
+    batch: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyList>> {
+    let batch = RecordBatch::from_pyarrow_bound(batch)?;
+    let array: StructArray = batch.into();
+    let tree = ArrayTree::new(&array);
+    let formatters = LeafFormatters::new(&tree)
+        .map_err(|e| PyValueError::new_err(format!("Could not format values: {}", e)))?;
+    let rows = (0..tree.len())
+        .map(|index| tree_to_py(py, &tree, &formatters, index))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new_bound(py, rows))
+}
+
+/// Adds the functions of this module to a Python module
+pub fn add_to_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(record_batch_to_rows, module)?)
+}
+
+/// Converts the `index`-th row of `tree` to a Python object
+fn tree_to_py<'a>(
+    py: Python<'_>,
+    tree: &ArrayTree<'a>,
+    formatters: &LeafFormatters<'a>,
+    index: usize,
+) -> PyResult<PyObject> {
+    if tree.is_null(index) {
+        return Ok(py.None());
+    }
+    match tree {
+        ArrayTree::Leaf(array) => leaf_to_py(py, *array, formatters, index),
+        ArrayTree::Struct { fields, .. } => {
+            let dict = PyDict::new_bound(py);
+            for (name, field) in fields {
+                dict.set_item(*name, tree_to_py(py, field, formatters, index)?)?;
+            }
+            Ok(dict.into_py(py))
+        }
+        ArrayTree::List {
+            offsets, elements, ..
+        } => {
+            let items = offsets
+                .range(index)
+                .map(|i| tree_to_py(py, elements, formatters, i))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(PyList::new_bound(py, items).into_py(py))
+        }
+        ArrayTree::Map {
+            offsets,
+            keys,
+            values,
+            ..
+        } => {
+            let dict = PyDict::new_bound(py);
+            for i in offsets.range(index) {
+                dict.set_item(
+                    tree_to_py(py, keys, formatters, i)?,
+                    tree_to_py(py, values, formatters, i)?,
+                )?;
+            }
+            Ok(dict.into_py(py))
+        }
+        ArrayTree::Dictionary { keys, values, .. } => {
+            tree_to_py(py, values, formatters, keys[index])
+        }
+        ArrayTree::Union { array, variants } => {
+            let type_id = array.type_id(index);
+            match variants
+                .iter()
+                .find(|(variant_type_id, _, _)| *variant_type_id == type_id)
+            {
+                Some((_, name, variant)) => {
+                    let dict = PyDict::new_bound(py);
+                    dict.set_item(
+                        *name,
+                        tree_to_py(py, variant, formatters, array.value_offset(index))?,
+                    )?;
+                    Ok(dict.into_py(py))
+                }
+                None => Ok(py.None()),
+            }
+        }
+    }
+}
+
+fn leaf_to_py<'a>(
+    py: Python<'_>,
+    array: &'a dyn Array,
+    formatters: &LeafFormatters<'a>,
+    index: usize,
+) -> PyResult<PyObject> {
+    macro_rules! primitive {
+        ($ty:ty) => {
+            array.as_primitive::<$ty>().value(index).into_py(py)
+        };
+    }
+
+    Ok(match array.data_type() {
+        DataType::Null => py.None(),
+        DataType::Boolean => array.as_boolean().value(index).into_py(py),
+        DataType::Int8 => primitive!(Int8Type),
+        DataType::Int16 => primitive!(Int16Type),
+        DataType::Int32 => primitive!(Int32Type),
+        DataType::Int64 => primitive!(Int64Type),
+        DataType::UInt8 => primitive!(UInt8Type),
+        DataType::UInt16 => primitive!(UInt16Type),
+        DataType::UInt32 => primitive!(UInt32Type),
+        DataType::UInt64 => primitive!(UInt64Type),
+        DataType::Float16 => array
+            .as_primitive::<Float16Type>()
+            .value(index)
+            .to_f32()
+            .into_py(py),
+        DataType::Float32 => primitive!(Float32Type),
+        DataType::Float64 => primitive!(Float64Type),
+        DataType::Utf8 => array.as_string::<i32>().value(index).into_py(py),
+        DataType::LargeUtf8 => array.as_string::<i64>().value(index).into_py(py),
+        DataType::Binary => {
+            PyBytes::new_bound(py, array.as_binary::<i32>().value(index)).into_py(py)
+        }
+        DataType::LargeBinary => {
+            PyBytes::new_bound(py, array.as_binary::<i64>().value(index)).into_py(py)
+        }
+        DataType::FixedSizeBinary(_) => {
+            PyBytes::new_bound(py, array.as_fixed_size_binary().value(index)).into_py(py)
+        }
+        datatype => match formatters.get(array) {
+            Some(formatter) => formatter.value(index).to_string().into_py(py),
+            None => {
+                return Err(PyValueError::new_err(format!(
+                    "Cannot convert Arrow {} values to Python",
+                    datatype
+                )))
+            }
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_RECORD_BATCH_TO_ROWS: &str = r#"
+import datetime
+import decimal
+import pyarrow
+
+batch = pyarrow.record_batch({
+    "id": pyarrow.array([1, 2], pyarrow.int64()),
+    "name": pyarrow.array(["foo", None]),
+    "scores": pyarrow.array([[1, 2], []], pyarrow.list_(pyarrow.int32())),
+    "blob": pyarrow.array([b"\x00\x01", b""]),
+    "date": pyarrow.array([datetime.date(2022, 1, 8), None], pyarrow.date32()),
+    "decimal": pyarrow.array(
+        [decimal.Decimal("123.45"), decimal.Decimal("-0.05")], pyarrow.decimal128(10, 2)
+    ),
+})
+rows = ar_row.record_batch_to_rows(batch)
+assert rows == [
+    {"id": 1, "name": "foo", "scores": [1, 2], "blob": b"\x00\x01", "date": "2022-01-08", "decimal": "123.45"},
+    {"id": 2, "name": None, "scores": [], "blob": b"", "date": None, "decimal": "-0.05"},
+], rows
+"#;
+
+    #[test]
+    fn test_record_batch_to_rows() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "ar_row").unwrap();
+            add_to_module(&module).unwrap();
+            let globals = PyDict::new_bound(py);
+            globals.set_item("ar_row", module).unwrap();
+            py.run_bound(TEST_RECORD_BATCH_TO_ROWS, Some(&globals), None)
+                .unwrap();
+        })
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Reading batches of seekable sources in any order
+//!
+//! [`RandomAccessBatchReader`] is implemented by sources which know how many batches
+//! they contain and can read any of them on demand: record batches in memory,
+//! Arrow IPC files (through a [`Mutex`]), and ORC and Parquet files (through
+//! [`ChunkedBatchReader`], whose batches are stripes and row groups).
+//!
+//! On top of it, [`RandomAccessBatches`] iterates on batches from both ends, so a
+//! [`RowIterator`](crate::row_iterator::RowIterator) built on it can iterate on rows
+//! from both ends and [seek](crate::row_iterator::RowIterator::seek_to_row) to
+//! arbitrary rows.
+//! [`ParallelRowIterator`](crate::parallel_row_iterator::ParallelRowIterator)
+//! deserializes batches on the Rayon thread pool, and [`CachedBatches`] keeps recently
+//! decoded batches to look up rows by index.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::random_access::RandomAccessBatches;
+//! use ar_row::row_iterator::RowIterator;
+//!
+//! let batches: Vec<RecordBatch> = vec![vec![1i64, 2], vec![3]]
+//!     .into_iter()
+//!     .map(|values| {
+//!         RecordBatch::try_from_iter(vec![("id", Arc::new(Int64Array::from(values)) as ArrayRef)])
+//!             .unwrap()
+//!     })
+//!     .collect();
+//!
+//! let mut rows = RowIterator::<_, i64>::new(RandomAccessBatches::new(batches)).unwrap();
+//! assert_eq!(rows.by_ref().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+//!
+//! rows.seek_to_row(1).unwrap();
+//! assert_eq!(rows.collect::<Vec<_>>(), vec![2, 3]);
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{Read, Seek};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use arrow::compute::concat_batches;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::FileReader;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, RootArray};
+use crate::reader::ReadError;
+use crate::seek::SeekableBatchSource;
+
+/// Source of record batches which can be read in any order
+///
+/// Methods take `&self`, so implementations which are [`Sync`] can read batches
+/// from several threads at once.
+pub trait RandomAccessBatchReader {
+    /// Returns the schema of all batches
+    fn schema(&self) -> SchemaRef;
+
+    /// Returns the number of batches
+    fn num_batches(&self) -> usize;
+
+    /// Returns the `batch_index`-th batch
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError>;
+
+    /// Returns the number of rows in the `batch_index`-th batch.
+    ///
+    /// The default implementation reads the batch; implementations should override
+    /// it when this is available from metadata.
+    fn batch_num_rows(&self, batch_index: usize) -> Result<usize, ReadError> {
+        Ok(self.read_batch(batch_index)?.num_rows())
+    }
+}
+
+/// Returns the index of the first row of each batch of `reader`, followed by the
+/// total number of rows
+pub(crate) fn batch_starts<R: RandomAccessBatchReader + ?Sized>(
+    reader: &R,
+) -> Result<Vec<usize>, ReadError> {
+    let mut batch_starts = Vec::with_capacity(reader.num_batches() + 1);
+    let mut num_rows = 0;
+    batch_starts.push(num_rows);
+    for batch_index in 0..reader.num_batches() {
+        num_rows += reader.batch_num_rows(batch_index)?;
+        batch_starts.push(num_rows);
+    }
+    Ok(batch_starts)
+}
+
+/// Returns the index of the batch containing the `row`-th row, given the result of
+/// [`batch_starts`], or the number of batches if there are not that many rows
+pub(crate) fn batch_of_row(batch_starts: &[usize], row: usize) -> usize {
+    // Last batch starting at or before the row, skipping empty batches
+    batch_starts.partition_point(|&start| start <= row) - 1
+}
+
+/// Returns the error for reading a batch past the end
+fn out_of_bounds(batch_index: usize, num_batches: usize) -> ReadError {
+    ReadError::Arrow(ArrowError::InvalidArgumentError(format!(
+        "Cannot read batch {batch_index} out of {num_batches}"
+    )))
+}
+
+/// Batches in memory. They are assumed to have the same schema, which is empty if
+/// there is no batch.
+impl RandomAccessBatchReader for Vec<RecordBatch> {
+    fn schema(&self) -> SchemaRef {
+        self.first()
+            .map(|batch| batch.schema())
+            .unwrap_or_else(|| Arc::new(Schema::empty()))
+    }
+
+    fn num_batches(&self) -> usize {
+        self.len()
+    }
+
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        self.get(batch_index)
+            .cloned()
+            .ok_or_else(|| out_of_bounds(batch_index, self.len()))
+    }
+
+    fn batch_num_rows(&self, batch_index: usize) -> Result<usize, ReadError> {
+        self.get(batch_index)
+            .map(|batch| batch.num_rows())
+            .ok_or_else(|| out_of_bounds(batch_index, self.len()))
+    }
+}
+
+/// Arrow IPC files. As [`FileReader`] needs to seek, batches are read one at a time.
+impl<R: Read + Seek> RandomAccessBatchReader for Mutex<FileReader<R>> {
+    fn schema(&self) -> SchemaRef {
+        self.lock().unwrap_or_else(|e| e.into_inner()).schema()
+    }
+
+    fn num_batches(&self) -> usize {
+        self.lock().unwrap_or_else(|e| e.into_inner()).num_batches()
+    }
+
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        let mut reader = self.lock().unwrap_or_else(|e| e.into_inner());
+        let num_batches = reader.num_batches();
+        if batch_index >= num_batches {
+            return Err(out_of_bounds(batch_index, num_batches));
+        }
+        reader.set_index(batch_index)?;
+        match reader.next() {
+            Some(batch) => Ok(batch?),
+            None => Err(out_of_bounds(batch_index, num_batches)),
+        }
+    }
+}
+
+/// Memory-mapped Arrow IPC files. Batches are decoded without locking, so they can
+/// be read from several threads at once.
+#[cfg(feature = "mmap")]
+impl RandomAccessBatchReader for crate::ipc::MmapIpcReader {
+    fn schema(&self) -> SchemaRef {
+        RecordBatchReader::schema(self)
+    }
+
+    fn num_batches(&self) -> usize {
+        crate::ipc::MmapIpcReader::num_batches(self)
+    }
+
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        match crate::ipc::MmapIpcReader::read_batch(self, batch_index) {
+            Some(batch) => Ok(batch?),
+            None => Err(out_of_bounds(batch_index, self.num_batches())),
+        }
+    }
+}
+
+/// [`RandomAccessBatchReader`] whose batches are the chunks of a
+/// [`SeekableBatchSource`] (ORC stripes or Parquet row groups)
+///
+/// `open` is called to get a new source for every batch read, so it should be cheap
+/// (eg. clone an in-memory buffer, or reopen a file) and configure the source the
+/// same way every time (eg. with the same projection).
+pub struct ChunkedBatchReader<F> {
+    open: F,
+    schema: SchemaRef,
+    chunk_lengths: Vec<usize>,
+}
+
+impl<S: SeekableBatchSource, F: Fn() -> Result<S, ReadError>> ChunkedBatchReader<F> {
+    /// Opens the source once to get its schema and the length of its chunks
+    pub fn new(open: F) -> Result<Self, ReadError> {
+        let source = open()?;
+        let chunk_lengths = source.chunk_lengths();
+        // Reader returning no batch
+        let schema = source.read_from_chunk(chunk_lengths.len())?.schema();
+        Ok(ChunkedBatchReader {
+            open,
+            schema,
+            chunk_lengths,
+        })
+    }
+}
+
+impl<S: SeekableBatchSource, F: Fn() -> Result<S, ReadError>> RandomAccessBatchReader
+    for ChunkedBatchReader<F>
+{
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn num_batches(&self) -> usize {
+        self.chunk_lengths.len()
+    }
+
+    /// Reads all the batches in the chunk, and concatenates them
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        let chunk_length = *self
+            .chunk_lengths
+            .get(batch_index)
+            .ok_or_else(|| out_of_bounds(batch_index, self.chunk_lengths.len()))?;
+        let reader = (self.open)()?.read_from_chunk(batch_index)?;
+        let mut batches = Vec::new();
+        let mut num_rows = 0;
+        for batch in reader {
+            if num_rows >= chunk_length {
+                break;
+            }
+            let batch = batch?;
+            let batch = batch.slice(0, batch.num_rows().min(chunk_length - num_rows));
+            num_rows += batch.num_rows();
+            batches.push(batch);
+        }
+        Ok(concat_batches(&self.schema, &batches)?)
+    }
+
+    fn batch_num_rows(&self, batch_index: usize) -> Result<usize, ReadError> {
+        self.chunk_lengths
+            .get(batch_index)
+            .copied()
+            .ok_or_else(|| out_of_bounds(batch_index, self.chunk_lengths.len()))
+    }
+}
+
+/// Iterator on batches of a [`RandomAccessBatchReader`], from either end
+///
+/// [`RowIterator`](crate::row_iterator::RowIterator)s built on it also iterate on
+/// rows from the end, and can [seek](crate::row_iterator::RowIterator::seek_to_row)
+/// to any row.
+///
+/// # Panics
+///
+/// When a batch cannot be read, like iterators of `Result<RecordBatch, _>` passed to
+/// [`IntoRows::into_rows`](crate::row_iterator::IntoRows::into_rows).
+pub struct RandomAccessBatches<R: RandomAccessBatchReader> {
+    reader: R,
+    /// Batches in this range were not read yet
+    remaining: Range<usize>,
+    /// Set by [`batch_starts`](Self::batch_starts)
+    batch_starts: Option<Vec<usize>>,
+}
+
+impl<R: RandomAccessBatchReader> RandomAccessBatches<R> {
+    /// Returns an iterator on all batches of `reader`
+    pub fn new(reader: R) -> Self {
+        let remaining = 0..reader.num_batches();
+        RandomAccessBatches {
+            reader,
+            remaining,
+            batch_starts: None,
+        }
+    }
+
+    /// Returns the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the indices of the batches which were not read yet from either end
+    pub fn remaining(&self) -> Range<usize> {
+        self.remaining.clone()
+    }
+
+    /// Moves the iterator to the `batch_index`-th batch, and resets its end to the
+    /// last batch.
+    pub fn seek_to_batch(&mut self, batch_index: usize) {
+        let num_batches = self.reader.num_batches();
+        self.remaining = batch_index.min(num_batches)..num_batches;
+    }
+
+    /// Returns the index of the first row of each batch, followed by the total number
+    /// of rows.
+    ///
+    /// They are counted the first time this is called (see
+    /// [`RandomAccessBatchReader::batch_num_rows`]).
+    pub fn batch_starts(&mut self) -> Result<&[usize], ReadError> {
+        if self.batch_starts.is_none() {
+            self.batch_starts = Some(batch_starts(&self.reader)?);
+        }
+        Ok(self
+            .batch_starts
+            .as_deref()
+            .expect("batch_starts was just set"))
+    }
+
+    fn read_batch(&self, batch_index: usize) -> RecordBatch {
+        self.reader
+            .read_batch(batch_index)
+            .unwrap_or_else(|e| panic!("Could not read batch {}: {}", batch_index, e))
+    }
+}
+
+impl<R: RandomAccessBatchReader> Iterator for RandomAccessBatches<R> {
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<RecordBatch> {
+        let batch_index = self.remaining.next()?;
+        Some(self.read_batch(batch_index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining.size_hint()
+    }
+}
+
+impl<R: RandomAccessBatchReader> DoubleEndedIterator for RandomAccessBatches<R> {
+    fn next_back(&mut self) -> Option<RecordBatch> {
+        let batch_index = self.remaining.next_back()?;
+        Some(self.read_batch(batch_index))
+    }
+}
+
+impl<R: RandomAccessBatchReader> ExactSizeIterator for RandomAccessBatches<R> {}
+
+/// Rows of a [`RandomAccessBatchReader`] looked up by index, with a cache of the
+/// most recently used batches
+///
+/// Batches are decoded into `Vec<T>` the first time one of their rows is accessed,
+/// and the `capacity` most recently used ones are kept, so lookups of rows close to
+/// each other (or of the same few rows) only decode their batch once.
+///
+/// The cache is a list scanned on every lookup, so `capacity` should stay small
+/// (a few dozens at most). Batches are decoded without holding the cache's lock,
+/// so concurrent lookups of rows in different batches decode them in parallel.
+pub struct CachedBatches<R: RandomAccessBatchReader, T: ArRowDeserialize> {
+    reader: R,
+    /// Index of the first row of each batch, followed by the total number of rows
+    batch_starts: Vec<usize>,
+    capacity: NonZeroUsize,
+    /// Array batches are read from, decided from the schema checked by
+    /// [`new`](Self::new)
+    root: RootArray,
+    /// Decoded batches and their index, from the most to the least recently used
+    cache: Mutex<VecDeque<(usize, Arc<Vec<T>>)>>,
+}
+
+impl<R: RandomAccessBatchReader, T: ArRowDeserialize> CachedBatches<R, T> {
+    /// Checks `reader`'s schema can be deserialized into `T`, and counts the rows
+    /// of each batch (see [`RandomAccessBatchReader::batch_num_rows`]).
+    pub fn new(reader: R, capacity: NonZeroUsize) -> Result<Self, ReadError> {
+        let schema = reader.schema();
+        T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+        Ok(CachedBatches {
+            batch_starts: batch_starts(&reader)?,
+            reader,
+            capacity,
+            root: RootArray::new::<T>(&schema),
+            cache: Mutex::new(VecDeque::with_capacity(capacity.get())),
+        })
+    }
+
+    /// Returns the total number of rows
+    pub fn num_rows(&self) -> usize {
+        *self
+            .batch_starts
+            .last()
+            .expect("batch_starts is never empty")
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the decoded rows of the `batch_index`-th batch, decoding it if it is
+    /// not in the cache
+    pub fn batch(&self, batch_index: usize) -> Result<Arc<Vec<T>>, ReadError> {
+        {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(position) = cache.iter().position(|(i, _)| *i == batch_index) {
+                let entry = cache.remove(position).expect("position is in bounds");
+                let rows = entry.1.clone();
+                cache.push_front(entry);
+                return Ok(rows);
+            }
+        }
+
+        let rows = Arc::new(self.root.read(self.reader.read_batch(batch_index)?)?);
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        // Another thread may have decoded it in the meantime
+        cache.retain(|(i, _)| *i != batch_index);
+        cache.truncate(self.capacity.get() - 1);
+        cache.push_front((batch_index, rows.clone()));
+        Ok(rows)
+    }
+
+    /// Calls `f` on the `row`-th row, or returns `None` if there are not that many
+    /// rows
+    pub fn with_row<U>(&self, row: usize, f: impl FnOnce(&T) -> U) -> Result<Option<U>, ReadError> {
+        if row >= self.num_rows() {
+            return Ok(None);
+        }
+        let batch_index = batch_of_row(&self.batch_starts, row);
+        let rows = self.batch(batch_index)?;
+        Ok(Some(f(&rows[row - self.batch_starts[batch_index]])))
+    }
+
+    /// Returns a copy of the `row`-th row, or `None` if there are not that many rows
+    pub fn get(&self, row: usize) -> Result<Option<T>, ReadError>
+    where
+        T: Clone,
+    {
+        self.with_row(row, T::clone)
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Typed wrapper around Arrow's [`RecordBatchReader`].
+//!
+//! Unlike calling [`ArRowDeserialize::from_record_batch`] directly, [`CheckedReader`]
+//! runs [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema)
+//! when it is built, so the schema check cannot be forgotten.
+
+use std::marker::PhantomData;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatchReader;
+use thiserror::Error;
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, RootArray};
+
+/// Error returned when failing to read or deserialize a batch from a [`CheckedReader`]
+#[derive(Debug, Error)]
+pub enum ReadError {
+    /// The underlying reader failed to produce a record batch
+    #[error("Could not read record batch: {0}")]
+    Arrow(#[from] ArrowError),
+    /// The record batch could not be deserialized
+    #[error(transparent)]
+    Deserialization(#[from] DeserializationError),
+}
+
+/// Wrapper for a [`RecordBatchReader`] whose schema was checked against `T`.
+///
+/// Iterating on it yields one `Vec<T>` per record batch; use [`rows`](Self::rows)
+/// to iterate on individual rows instead.
+pub struct CheckedReader<R: RecordBatchReader, T: ArRowDeserialize> {
+    reader: R,
+
+    /// Array batches are read from, decided from the schema checked by
+    /// [`new`](Self::new)
+    root: RootArray,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<R: RecordBatchReader, T: ArRowDeserialize> CheckedReader<R, T> {
+    /// Checks `reader`'s schema can be deserialized into `T`, and wraps it.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema))
+    pub fn new(reader: R) -> Result<CheckedReader<R, T>, DeserializationError> {
+        let schema = reader.schema();
+        T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+        Ok(CheckedReader {
+            reader,
+            root: RootArray::new::<T>(&schema),
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the schema of the underlying reader
+    pub fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next record batch into `dst`, resizing it to the size of the batch.
+    ///
+    /// Returns `None` when the underlying reader is exhausted, or the number of rows
+    /// written otherwise.
+    pub fn read_batch_into(&mut self, dst: &mut Vec<T>) -> Option<Result<usize, ReadError>> {
+        let root = self.root;
+        self.reader.next().map(|batch| {
+            root.read_into_vec(batch?, dst)
+                .map_err(ReadError::Deserialization)
+        })
+    }
+
+    /// Returns an iterator on individual rows, which reuses the same buffer
+    /// for every record batch.
+    pub fn rows(self) -> CheckedRows<R, T> {
+        CheckedRows {
+            reader: self,
+            batch: Vec::new(),
+            index: 0,
+        }
+    }
+}
+
+impl<R: RecordBatchReader, T: ArRowDeserialize> Iterator for CheckedReader<R, T> {
+    type Item = Result<Vec<T>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let root = self.root;
+        self.reader
+            .next()
+            .map(|batch| root.read(batch?).map_err(ReadError::Deserialization))
+    }
+}
+
+/// Iterator on rows of a [`CheckedReader`], returned by [`CheckedReader::rows`]
+///
+/// Rows are moved out of the internal buffer, so `T` does not need to implement
+/// [`Clone`].
+pub struct CheckedRows<R: RecordBatchReader, T: ArRowDeserialize> {
+    reader: CheckedReader<R, T>,
+    batch: Vec<T>,
+
+    /// Index in the batch
+    index: usize,
+}
+
+impl<R: RecordBatchReader, T: ArRowDeserialize> CheckedRows<R, T> {
+    /// Returns the schema of the underlying reader
+    pub fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+
+    /// Skips the next `count` rows, or all remaining rows if there are fewer.
+    ///
+    /// Record batches which are entirely skipped are not deserialized.
+    pub fn skip_rows(&mut self, mut count: usize) -> Result<(), ReadError> {
+        let buffered = self.batch.len() - self.index;
+        if count <= buffered {
+            self.index += count;
+            return Ok(());
+        }
+        count -= buffered;
+        self.batch.clear();
+        self.index = 0;
+
+        while count > 0 {
+            let batch = match self.reader.reader.next() {
+                Some(batch) => batch?,
+                None => return Ok(()),
+            };
+            let num_rows = batch.num_rows();
+            if num_rows <= count {
+                count -= num_rows;
+            } else {
+                self.reader
+                    .root
+                    .read_into_vec(batch.slice(count, num_rows - count), &mut self.batch)?;
+                count = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: RecordBatchReader, T: ArRowDeserialize> Iterator for CheckedRows<R, T> {
+    type Item = Result<T, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Exhausted the current batch, read the next non-empty one.
+        while self.index >= self.batch.len() {
+            self.index = 0;
+            if let Err(e) = self.reader.read_batch_into(&mut self.batch)? {
+                self.batch.clear();
+                return Some(Err(e));
+            }
+        }
+
+        let item = std::mem::take(&mut self.batch[self.index]);
+        self.index += 1;
+
+        Some(Ok(item))
+    }
+}
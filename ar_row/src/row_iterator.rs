@@ -3,19 +3,59 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
-//! Iterator on ORC rows.
+//! Iterator on rows of Arrow record batches.
 //!
 //! Iterator items need to implement [`ArRowDeserialize`] trait; `ar_row_derive` can
 //! generate implementations for structures.
 //!
-//! TODO: write a test for this after we add the write API to arrays
-//! (currently it's only indirectly tested in `ar_row_derive`), because all the test
-//! files have a structure at the root and we can't use `#[derive(ArRowDeserialize)]`
-//! in this crate to implement it.
+//! [`IntoRows::into_rows`] builds a [`RowIterator`] from any iterator of
+//! [`RecordBatch`] or of `Result<RecordBatch, _>`, such as Arrow readers.
+//! Built on [`RandomAccessBatches`], it can also iterate from the end and seek to
+//! arbitrary rows.
 
+use arrow::datatypes::Schema;
 use arrow::record_batch::RecordBatch;
+use thiserror::Error;
 
-use crate::deserialize::{ArRowDeserialize, DeserializationError};
+use crate::deserialize::{ArRowDeserialize, ArRowStruct, DeserializationError, RootArray};
+use crate::dictionaries::DictionaryCache;
+use crate::options::DeserializeOptions;
+use crate::random_access::{batch_of_row, RandomAccessBatchReader, RandomAccessBatches};
+use crate::reader::ReadError;
+
+/// Error returned by [`RowIterator::try_next`], locating the batch which could not
+/// be deserialized in the iterator
+#[derive(Debug, Error, PartialEq)]
+#[error("Could not deserialize batch {batch_index} ({}): {source}", display_rows(.first_row, .num_rows))]
+pub struct RowIteratorError {
+    /// Index of the batch, starting from 0
+    pub batch_index: usize,
+    /// Index of the first row of the batch, among all rows of the batches read by
+    /// the iterator (including rows which were skipped)
+    ///
+    /// `None` if the batch was read from the end, and rows of the batches before it
+    /// could not be counted (see [`RandomAccessBatches::batch_starts`]).
+    pub first_row: Option<usize>,
+    /// Number of rows in the batch
+    pub num_rows: usize,
+    pub source: DeserializationError,
+}
+
+fn display_rows(first_row: &Option<usize>, num_rows: &usize) -> String {
+    match first_row {
+        Some(first_row) => format!("rows {}..{}", first_row, first_row + num_rows),
+        None => format!("{} rows", num_rows),
+    }
+}
+
+/// Returned by the callback given to [`RowIterator::on_error`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Decision {
+    /// Skip the row (or batch), and keep iterating
+    Skip,
+    /// Return the error from [`RowIterator::try_next`] (so [`Iterator::next`] panics)
+    Abort,
+}
 
 /// Iterator on rows of yielded by an iterator of [`RecordBatch`].
 ///
@@ -23,50 +63,485 @@ use crate::deserialize::{ArRowDeserialize, DeserializationError};
 /// [`ArRowDeserialize::read_from_array`] and working on the column array,
 /// but provides a more familiar API to work with individual rows.
 ///
+/// When built on [`RandomAccessBatches`], it can also iterate from the end, and
+/// [seek](Self::seek_to_row) to arbitrary rows.
+///
 /// # Panics
 ///
 /// next() repeatedly calls [`ArRowDeserialize::read_from_array`] and panics
-/// when it returns a [`DeserializationError`].
+/// when it returns a [`DeserializationError`]. Use [`try_next`](Self::try_next)
+/// to get a [`RowIteratorError`] instead.
 pub struct RowIterator<R: Iterator<Item = RecordBatch>, T: ArRowDeserialize + Clone> {
     reader: R,
     batch: Vec<T>,
 
+    /// Batch `batch` was deserialized from
+    record_batch: Option<RecordBatch>,
+
+    /// Whether `record_batch` was returned by [`try_next_batch`](Self::try_next_batch)
+    returned_batch: bool,
+
+    /// Index of the next batch to read
+    batch_index: usize,
+
+    /// Number of rows in the batches before the current one
+    first_row: usize,
+
+    /// Number of rows in the current batch, before rows are skipped
+    batch_rows: usize,
+
     /// Index in the batch
     index: usize,
 
     /// Maximum value of the index + 1
     decoded_items: usize,
+
+    /// Set by [`with_options`](Self::with_options), along with `read`
+    options: Option<DeserializeOptions>,
+
+    /// Deserializes a batch into `batch`, with `options` if set
+    read: ReadBatch<T>,
+
+    /// Decides which array `read` deserializes batches from
+    root_array: fn(&Schema) -> RootArray,
+
+    /// Decided by `root_array` from the schema of the first batch, which the
+    /// following ones are assumed to share
+    root: Option<RootArray>,
+
+    /// Dictionaries shared by consecutive batches, unless disabled by
+    /// [`DeserializeOptions::dictionary_cache`]
+    dictionary_cache: Option<DictionaryCache>,
+
+    /// Set by [`on_error`](Self::on_error)
+    on_error: Option<Box<ErrorHandler>>,
+
+    /// First batch, if it could not be deserialized by [`new`](Self::new), so its
+    /// errors are returned by [`try_next`](Self::try_next) and can be handled by
+    /// `on_error`; or part of the batch [`seek_to_row`](Self::seek_to_row) moved to
+    pending_batch: Option<RecordBatch>,
+
+    /// Rows of the last batch read by [`next_back`](DoubleEndedIterator::next_back)
+    /// which were not returned yet
+    back: std::vec::IntoIter<T>,
+}
+
+type ErrorHandler = dyn FnMut(usize, &RowIteratorError) -> Decision + Send;
+
+type ReadBatch<T> = fn(
+    RecordBatch,
+    RootArray,
+    Option<&DeserializeOptions>,
+    &mut Vec<T>,
+) -> Result<usize, DeserializationError>;
+
+/// Runs `f` in the [scope](DictionaryCache::scope) of `dictionary_cache`, if any
+fn in_scope<R>(dictionary_cache: &mut Option<DictionaryCache>, f: impl FnOnce() -> R) -> R {
+    match dictionary_cache {
+        Some(dictionary_cache) => dictionary_cache.scope(f),
+        None => f(),
+    }
 }
 
 impl<R: Iterator<Item = RecordBatch>, T: ArRowDeserialize + Clone> RowIterator<R, T> {
     /// Returns an iterator on rows from an iterator on [`RecordBatch`]
     ///
     /// Errors are detailed descriptions of format mismatch (as returned by
-    /// [`CheckableDataType::check_datatype`](crate::deserialize::CheckableDataType::check_datatype))
+    /// [`CheckableDataType::check_datatype`](crate::deserialize::CheckableDataType::check_datatype)).
+    /// Other errors in the first batch are returned by [`try_next`](Self::try_next),
+    /// like in the following batches.
     pub fn new(reader: R) -> Result<RowIterator<R, T>, DeserializationError> {
+        Self::new_with_read(
+            reader,
+            None,
+            RootArray::new::<T>,
+            |record_batch, root, _options, batch| root.read_into_vec(record_batch, batch),
+        )
+    }
+
+    fn new_with_read(
+        reader: R,
+        options: Option<DeserializeOptions>,
+        root_array: fn(&Schema) -> RootArray,
+        read: ReadBatch<T>,
+    ) -> Result<RowIterator<R, T>, DeserializationError> {
+        let dictionary_cache = match &options {
+            Some(options) if !options.dictionary_cache => None,
+            _ => Some(DictionaryCache::new()),
+        };
         let mut row_iterator = RowIterator {
             reader,
             batch: Vec::new(),
+            record_batch: None,
+            returned_batch: false,
+            batch_index: 0,
+            first_row: 0,
+            batch_rows: 0,
             index: 0,
             decoded_items: 0, // Will be filled on the first run of next()
+            options,
+            read,
+            root_array,
+            root: None,
+            dictionary_cache,
+            on_error: None,
+            pending_batch: None,
+            back: Vec::new().into_iter(),
         };
-        row_iterator.read_batch(/* check_schema */ true)?; // Get an early error if the type is incorrect
+        // Get an early error if the type is incorrect
+        row_iterator
+            .read_batch(/* check_schema */ true)
+            .map_err(|e| e.source)?;
         Ok(row_iterator)
     }
 
-    fn read_batch(&mut self, check_schema: bool) -> Result<bool, DeserializationError> {
+    fn read_batch(&mut self, check_schema: bool) -> Result<bool, RowIteratorError> {
         self.index = 0;
-        match self.reader.next() {
-            Some(record_batch) => {
-                if check_schema {
-                    T::check_schema(&record_batch.schema())
-                        .map_err(DeserializationError::MismatchedColumnDataType)?;
+        self.first_row += self.batch_rows;
+        self.batch_rows = 0;
+        // Don't yield rows of the previous batch again if reading this one fails,
+        // or if called again after the end
+        self.decoded_items = 0;
+        self.record_batch = None;
+        self.returned_batch = false;
+        let record_batch = match self.pending_batch.take().or_else(|| self.reader.next()) {
+            Some(record_batch) => record_batch,
+            None => return Ok(true),
+        };
+        let (batch_index, first_row) = (self.batch_index, self.first_row);
+        let num_rows = record_batch.num_rows();
+        let error = move |source| RowIteratorError {
+            batch_index,
+            first_row: Some(first_row),
+            num_rows,
+            source,
+        };
+        // With options, the schema is checked by DeserializeOptions::prepare_array
+        // after applying them
+        if check_schema && self.options.is_none() {
+            T::check_schema(&record_batch.schema())
+                .map_err(DeserializationError::MismatchedColumnDataType)
+                .map_err(error)?;
+        }
+        self.batch_index += 1;
+        self.batch_rows = num_rows;
+        let root = self.root(&record_batch);
+        let (read, options, batch) = (self.read, self.options.as_ref(), &mut self.batch);
+        let result = in_scope(&mut self.dictionary_cache, || {
+            read(record_batch.clone(), root, options, batch)
+        });
+        self.decoded_items = match result {
+            Ok(decoded_items) => decoded_items,
+            Err(DeserializationError::MismatchedColumnDataType(e)) if check_schema => {
+                return Err(error(DeserializationError::MismatchedColumnDataType(e)));
+            }
+            Err(_) if check_schema => {
+                // new() only returns errors about the schema, so keep the batch
+                // for try_next() to return its error, or pass it to on_error()
+                self.pending_batch = Some(record_batch);
+                self.batch_index -= 1;
+                self.batch_rows = 0;
+                return Ok(false);
+            }
+            Err(source) => {
+                self.batch = self.handle_error(record_batch.clone(), error(source))?;
+                self.batch.len()
+            }
+        };
+        self.record_batch = Some(record_batch);
+        Ok(false)
+    }
+
+    /// Returns the array batches are read from, deciding it from the schema of
+    /// `record_batch` if this is the first one
+    fn root(&mut self, record_batch: &RecordBatch) -> RootArray {
+        let root_array = self.root_array;
+        *self
+            .root
+            .get_or_insert_with(|| root_array(&record_batch.schema()))
+    }
+
+    /// Calls the [`on_error`](Self::on_error) callback, and returns the rows of
+    /// `record_batch` which can be deserialized, or `error` if there is no callback
+    fn handle_error(
+        &mut self,
+        record_batch: RecordBatch,
+        error: RowIteratorError,
+    ) -> Result<Vec<T>, RowIteratorError> {
+        let on_error = match &mut self.on_error {
+            Some(on_error) => on_error,
+            None => return Err(error),
+        };
+        let first_row = match error.first_row {
+            Some(first_row) => first_row,
+            // on_error cannot be told which rows failed
+            None => return Err(error),
+        };
+        if let DeserializationError::MismatchedColumnDataType(_) = error.source {
+            // All rows would fail the same way
+            return match on_error(first_row, &error) {
+                Decision::Skip => Ok(Vec::new()),
+                Decision::Abort => Err(error),
+            };
+        }
+
+        // Find the rows which cannot be deserialized, by deserializing them one at a time
+        let mut rows = Vec::with_capacity(record_batch.num_rows());
+        let mut row = Vec::with_capacity(1);
+        let root = self.root(&record_batch);
+        let (read, options) = (self.read, self.options.as_ref());
+        for i in 0..record_batch.num_rows() {
+            let row_batch = record_batch.slice(i, 1);
+            let result = in_scope(&mut self.dictionary_cache, || {
+                read(row_batch, root, options, &mut row)
+            });
+            match result {
+                Ok(_) => rows.append(&mut row),
+                Err(source) => {
+                    let error = RowIteratorError {
+                        batch_index: error.batch_index,
+                        first_row: Some(first_row + i),
+                        num_rows: 1,
+                        source,
+                    };
+                    match on_error(first_row + i, &error) {
+                        Decision::Skip => (),
+                        Decision::Abort => return Err(error),
+                    }
                 }
-                self.batch.resize(record_batch.num_rows(), T::default());
-                self.decoded_items = T::read_from_record_batch(record_batch, &mut self.batch)?;
-                Ok(false)
             }
-            None => Ok(true),
+        }
+        Ok(rows)
+    }
+
+    /// Same as [`new`](Self::new), but deserializes batches with the policies set
+    /// in `options` (see [`DeserializeOptions`])
+    pub fn with_options(
+        reader: R,
+        options: DeserializeOptions,
+    ) -> Result<RowIterator<R, T>, DeserializationError>
+    where
+        T: ArRowStruct + Send,
+    {
+        Self::new_with_read(
+            reader,
+            Some(options),
+            RootArray::without_checks::<T>,
+            |record_batch, root, options, batch| {
+                options
+                    .expect("RowIterator::with_options has no options")
+                    .read_root_into_vec(record_batch, root, batch)
+            },
+        )
+    }
+
+    /// Calls `f` with the index of each row which cannot be deserialized (counting
+    /// from the first row of the first batch) and the error, and skips the row if
+    /// it returns [`Decision::Skip`].
+    ///
+    /// Rows of a batch are deserialized one at a time to find which ones fail,
+    /// unless the batch has the wrong schema: `f` is then called once, with the
+    /// index of its first row, and the whole batch is skipped.
+    ///
+    /// Errors whose [`first_row`](RowIteratorError::first_row) is unknown are not
+    /// passed to `f`, but returned as if there were no callback.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use ar_row::arrow::array::{ArrayRef, Int64Array};
+    /// # use ar_row::arrow::record_batch::RecordBatch;
+    /// use ar_row::row_iterator::{Decision, IntoRows};
+    ///
+    /// let batch = RecordBatch::try_from_iter(vec![(
+    ///     "a",
+    ///     Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+    /// )])
+    /// .unwrap();
+    /// let rows: Vec<i64> = vec![batch]
+    ///     .into_iter()
+    ///     .into_rows()
+    ///     .unwrap()
+    ///     .on_error(|row, error| {
+    ///         eprintln!("Skipping row {}: {}", row, error.source);
+    ///         Decision::Skip
+    ///     })
+    ///     .collect();
+    /// assert_eq!(rows, vec![1, 3]);
+    /// ```
+    pub fn on_error(
+        mut self,
+        f: impl FnMut(usize, &RowIteratorError) -> Decision + Send + 'static,
+    ) -> Self {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    /// Returns the batch the row last returned by [`next`](Iterator::next) or
+    /// [`try_next`](Self::try_next) was deserialized from, or the batch last
+    /// returned by [`try_next_batch`](Self::try_next_batch).
+    ///
+    /// This allows running columnar operations (eg. with [`arrow::compute`]) on the
+    /// batch while iterating on its rows.
+    pub fn current_batch(&self) -> Option<&RecordBatch> {
+        self.record_batch.as_ref()
+    }
+
+    /// Returns the next batch, and all the rows deserialized from it.
+    ///
+    /// This is the current batch if none of its rows were returned by
+    /// [`next`](Iterator::next) yet (eg. the first batch, which is read by
+    /// [`new`](Self::new)). Otherwise, the rows of the current batch which were not
+    /// returned yet are skipped. The `i`-th row is deserialized from the `i`-th row of the
+    /// batch, unless rows were skipped by [`on_error`](Self::on_error) or
+    /// [`DeserializeOptions::nulls`].
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use ar_row::arrow::array::{ArrayRef, Int64Array};
+    /// # use ar_row::arrow::record_batch::RecordBatch;
+    /// use ar_row::arrow::array::AsArray;
+    /// use ar_row::arrow::compute::sum;
+    /// use ar_row::arrow::datatypes::Int64Type;
+    /// use ar_row::row_iterator::IntoRows;
+    ///
+    /// let batches = vec![
+    ///     RecordBatch::try_from_iter(vec![("a", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef)])
+    ///         .unwrap(),
+    ///     RecordBatch::try_from_iter(vec![("a", Arc::new(Int64Array::from(vec![3])) as ArrayRef)])
+    ///         .unwrap(),
+    /// ];
+    /// let mut iter = batches.into_iter().into_rows::<i64>().unwrap();
+    /// while let Some((batch, rows)) = iter.try_next_batch().unwrap() {
+    ///     let total = sum(batch.column(0).as_primitive::<Int64Type>()).unwrap();
+    ///     for row in rows {
+    ///         println!("{} / {}", row, total);
+    ///     }
+    /// }
+    /// ```
+    pub fn try_next_batch(&mut self) -> Result<Option<(&RecordBatch, &[T])>, RowIteratorError> {
+        let is_current_batch_new =
+            self.record_batch.is_some() && self.index == 0 && !self.returned_batch;
+        if !is_current_batch_new && self.read_batch(/* check_schema */ false)? {
+            return Ok(None);
+        }
+        // Rows are returned with the batch, so next() moves to the next batch
+        self.index = self.decoded_items;
+        self.returned_batch = true;
+        Ok(Some((
+            self.record_batch
+                .as_ref()
+                .expect("read_batch() succeeded but did not set record_batch"),
+            &self.batch[..self.decoded_items],
+        )))
+    }
+
+    /// Same as [`next`](Iterator::next), but returns an error instead of panicking
+    /// when a batch cannot be deserialized.
+    pub fn try_next(&mut self) -> Result<Option<T>, RowIteratorError> {
+        // Exhausted the current batch, read the next non-empty one.
+        while self.index == self.decoded_items {
+            let ended = self.read_batch(/* check_schema */ false)?;
+            if ended {
+                // Rows of the batch read from the back, if any, are the last ones
+                return Ok(self.back.next());
+            }
+        }
+
+        let item = self.batch.get(self.index);
+        self.index += 1;
+
+        Ok(item.cloned())
+    }
+}
+
+impl<R: RandomAccessBatchReader, T: ArRowDeserialize + Clone>
+    RowIterator<RandomAccessBatches<R>, T>
+{
+    /// Moves the iterator to the `row`-th row of the reader, and resets its end to
+    /// the last row.
+    ///
+    /// Batches before the row are not read, if the reader can count their rows
+    /// without reading them (see [`RandomAccessBatches::batch_starts`]). Rows are
+    /// counted before applying [`on_error`](Self::on_error) or [`DeserializeOptions`].
+    /// If `row` is past the end, the iterator becomes empty.
+    pub fn seek_to_row(&mut self, row: usize) -> Result<(), ReadError> {
+        let batch_starts = self.reader.batch_starts()?;
+        let batch_index = batch_of_row(batch_starts, row);
+        let num_rows = batch_starts[batch_starts.len() - 1];
+        self.pending_batch = if row < num_rows {
+            let offset = row - batch_starts[batch_index];
+            let batch = self.reader.get_ref().read_batch(batch_index)?;
+            Some(batch.slice(offset, batch.num_rows() - offset))
+        } else {
+            None
+        };
+        // The pending batch is read by the next call to read_batch()
+        self.reader.seek_to_batch(batch_index + 1);
+        self.batch.clear();
+        self.record_batch = None;
+        self.returned_batch = false;
+        self.batch_index = batch_index;
+        self.first_row = row.min(num_rows);
+        self.batch_rows = 0;
+        self.index = 0;
+        self.decoded_items = 0;
+        self.back = Vec::new().into_iter();
+        Ok(())
+    }
+
+    /// Same as [`next_back`](DoubleEndedIterator::next_back), but returns an error
+    /// instead of panicking when a batch cannot be deserialized.
+    pub fn try_next_back(&mut self) -> Result<Option<T>, RowIteratorError> {
+        if self.pending_batch.is_some() {
+            self.read_batch(/* check_schema */ false)?;
+        }
+        loop {
+            if let Some(row) = self.back.next_back() {
+                return Ok(Some(row));
+            }
+            let record_batch = match self.reader.next_back() {
+                Some(record_batch) => record_batch,
+                None => break,
+            };
+            let batch_index = self.reader.remaining().end;
+            self.back = self.read_back_batch(batch_index, record_batch)?.into_iter();
+        }
+
+        // Every batch was read, so remaining rows are in the batch read from the front
+        if self.index == self.decoded_items {
+            return Ok(None);
+        }
+        self.decoded_items -= 1;
+        Ok(Some(self.batch[self.decoded_items].clone()))
+    }
+
+    fn read_back_batch(
+        &mut self,
+        batch_index: usize,
+        record_batch: RecordBatch,
+    ) -> Result<Vec<T>, RowIteratorError> {
+        let mut rows = Vec::new();
+        let root = self.root(&record_batch);
+        let (read, options) = (self.read, self.options.as_ref());
+        let result = in_scope(&mut self.dictionary_cache, || {
+            read(record_batch.clone(), root, options, &mut rows)
+        });
+        match result {
+            Ok(_) => Ok(rows),
+            Err(source) => {
+                let first_row = self
+                    .reader
+                    .batch_starts()
+                    .ok()
+                    .map(|batch_starts| batch_starts[batch_index]);
+                let error = RowIteratorError {
+                    batch_index,
+                    first_row,
+                    num_rows: record_batch.num_rows(),
+                    source,
+                };
+                self.handle_error(record_batch, error)
+            }
         }
     }
 }
@@ -74,22 +549,104 @@ impl<R: Iterator<Item = RecordBatch>, T: ArRowDeserialize + Clone> RowIterator<R
 /// # Panics
 ///
 /// next() repeatedly calls [`ArRowDeserialize::read_from_array`] and panics
-/// when it returns a [`DeserializationError`].
+/// when it returns a [`DeserializationError`], with the position of the batch
+/// in the panic message.
 impl<R: Iterator<Item = RecordBatch>, T: ArRowDeserialize + Clone> Iterator for RowIterator<R, T> {
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
-        // Exhausted the current batch, read the next one.
-        if self.index == self.decoded_items {
-            let ended = self.read_batch(/* check_schema */ false).expect("ArRowDeserialize::read_from_array() call from RowIterator::next() returns a deserialization error");
-            if ended {
-                return None;
-            }
-        }
+        self.try_next()
+            .unwrap_or_else(|e| panic!("RowIterator::next() failed: {}", e))
+    }
+}
 
-        let item = self.batch.get(self.index);
-        self.index += 1;
+/// # Panics
+///
+/// Like [`next`](Iterator::next)
+impl<R: RandomAccessBatchReader, T: ArRowDeserialize + Clone> DoubleEndedIterator
+    for RowIterator<RandomAccessBatches<R>, T>
+{
+    fn next_back(&mut self) -> Option<T> {
+        self.try_next_back()
+            .unwrap_or_else(|e| panic!("RowIterator::next_back() failed: {}", e))
+    }
+}
+
+/// Items of iterators on which [`IntoRows::into_rows`] can be called: either
+/// [`RecordBatch`] or `Result<RecordBatch, E>`
+pub trait IntoRecordBatch {
+    /// Returns the batch
+    ///
+    /// # Panics
+    ///
+    /// If this is an error
+    fn into_record_batch(self) -> RecordBatch;
+}
+
+impl IntoRecordBatch for RecordBatch {
+    fn into_record_batch(self) -> RecordBatch {
+        self
+    }
+}
 
-        item.cloned()
+impl<E: std::fmt::Debug> IntoRecordBatch for Result<RecordBatch, E> {
+    fn into_record_batch(self) -> RecordBatch {
+        self.unwrap_or_else(|e| panic!("Could not read batch: {:?}", e))
     }
 }
+
+/// Iterator on [`RecordBatch`], returned by [`IntoRows::into_rows`] to wrap an
+/// iterator of [`IntoRecordBatch`]
+pub struct RecordBatches<I>(I);
+
+impl<I: Iterator> Iterator for RecordBatches<I>
+where
+    I::Item: IntoRecordBatch,
+{
+    type Item = RecordBatch;
+
+    fn next(&mut self) -> Option<RecordBatch> {
+        self.0.next().map(IntoRecordBatch::into_record_batch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Extension trait of iterators on [`RecordBatch`] or `Result<RecordBatch, _>`
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use ar_row::arrow::array::{ArrayRef, Int64Array};
+/// # use ar_row::arrow::error::ArrowError;
+/// # use ar_row::arrow::record_batch::RecordBatch;
+/// use ar_row::row_iterator::IntoRows;
+///
+/// let batch = RecordBatch::try_from_iter(vec![(
+///     "a",
+///     Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+/// )])
+/// .unwrap();
+/// let batches: Vec<Result<RecordBatch, ArrowError>> = vec![Ok(batch.clone()), Ok(batch)];
+/// let rows: Vec<i64> = batches.into_iter().into_rows().unwrap().collect();
+/// assert_eq!(rows, vec![1, 2, 1, 2]);
+/// ```
+pub trait IntoRows: Iterator + Sized {
+    /// Returns a [`RowIterator`] on the rows of these batches
+    ///
+    /// # Panics
+    ///
+    /// When the underlying iterator yields an error, either while reading the first
+    /// batch in this function or later while iterating.
+    fn into_rows<T: ArRowDeserialize + Clone>(
+        self,
+    ) -> Result<RowIterator<RecordBatches<Self>, T>, DeserializationError>
+    where
+        Self::Item: IntoRecordBatch,
+    {
+        RowIterator::new(RecordBatches(self))
+    }
+}
+
+impl<I: Iterator> IntoRows for I {}
@@ -0,0 +1,201 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Asynchronous counterpart of [`CheckedRows`](crate::reader::CheckedRows)
+//!
+//! [`RowStream`] decodes batches as they are consumed. [`decode_in_background`]
+//! instead splits reading and decoding from consuming, with a bound on the number of
+//! decoded batches waiting for the consumer, so a slow consumer slows down the reader
+//! instead of accumulating batches in memory.
+//!
+//! Requires the `async` feature.
+
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use arrow::datatypes::Schema;
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use futures::channel::mpsc;
+use futures::future::Either;
+use futures::{ready, SinkExt, Stream, StreamExt};
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, RootArray};
+use crate::reader::ReadError;
+
+/// Stream of rows deserialized from a stream of record batches, whose schema was
+/// checked against `T`.
+///
+/// Rows are moved out of the internal buffer, so `T` does not need to implement
+/// [`Clone`].
+pub struct RowStream<S, T: ArRowDeserialize> {
+    stream: S,
+    batch: Vec<T>,
+
+    /// Index in the batch
+    index: usize,
+
+    /// Array batches are read from, decided from the schema checked by
+    /// [`new`](Self::new)
+    root: RootArray,
+}
+
+impl<S, E, T> RowStream<S, T>
+where
+    S: Stream<Item = Result<RecordBatch, E>> + Unpin,
+    E: Into<ArrowError>,
+    T: ArRowDeserialize,
+{
+    /// Checks `schema` (the schema of record batches yielded by `stream`) can be
+    /// deserialized into `T`, and wraps `stream`.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema))
+    pub fn new(stream: S, schema: &Schema) -> Result<RowStream<S, T>, DeserializationError> {
+        T::check_schema(schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+        Ok(RowStream {
+            stream,
+            batch: Vec::new(),
+            index: 0,
+            root: RootArray::new::<T>(schema),
+        })
+    }
+
+    /// Returns the underlying stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, E, T> Stream for RowStream<S, T>
+where
+    S: Stream<Item = Result<RecordBatch, E>> + Unpin,
+    E: Into<ArrowError>,
+    T: ArRowDeserialize,
+{
+    type Item = Result<T, ReadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        // Exhausted the current batch, read the next non-empty one.
+        while this.index >= this.batch.len() {
+            // Cleared before polling, so rows already moved out are not yielded again
+            // if the stream is pending.
+            this.index = 0;
+            this.batch.clear();
+            match ready!(Pin::new(&mut this.stream).poll_next(cx)) {
+                None => return Poll::Ready(None),
+                Some(Err(e)) => return Poll::Ready(Some(Err(ReadError::Arrow(e.into())))),
+                Some(Ok(batch)) => {
+                    if let Err(e) = this.root.read_into_vec(batch, &mut this.batch) {
+                        this.batch.clear();
+                        return Poll::Ready(Some(Err(e.into())));
+                    }
+                }
+            }
+        }
+
+        let item = std::mem::take(&mut this.batch[this.index]);
+        this.index += 1;
+
+        Poll::Ready(Some(Ok(item)))
+    }
+}
+
+/// Checks `schema` (the schema of record batches yielded by `stream`) can be
+/// deserialized into `T`, and returns a future reading and decoding batches from
+/// `stream`, and a stream of the decoded batches.
+///
+/// At most `max_in_flight_batches` decoded batches are queued for the consumer; when
+/// the queue is full, the future waits for the consumer before decoding the next batch.
+///
+/// The future stops after the first error (which is sent to the consumer), or when
+/// [`DecodedBatches`] is dropped.
+///
+/// Decoding a batch is CPU-bound and does not yield to the executor, so the future
+/// must not run on the threads of an async runtime, where it would delay other
+/// tasks. Run it on a dedicated thread instead, or on the runtime's pool for
+/// blocking tasks (eg. with
+/// `tokio::task::spawn_blocking(move || futures::executor::block_on(decoder))`):
+///
+/// ```
+/// # use std::num::NonZeroUsize;
+/// # use std::sync::Arc;
+/// # use ar_row::arrow::array::*;
+/// # use ar_row::arrow::error::ArrowError;
+/// # use ar_row::arrow::record_batch::RecordBatch;
+/// use ar_row::row_stream::decode_in_background;
+/// use futures::executor::block_on;
+/// use futures::TryStreamExt;
+///
+/// let batch = RecordBatch::try_from_iter(vec![
+///     ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+/// ])
+/// .unwrap();
+/// let schema = batch.schema();
+/// let stream = futures::stream::iter(vec![Ok::<_, ArrowError>(batch)]);
+///
+/// let (decoder, batches) =
+///     decode_in_background::<_, _, i64>(stream, &schema, NonZeroUsize::new(2).unwrap())
+///         .unwrap();
+/// let decoder = std::thread::spawn(move || block_on(decoder));
+/// let rows: Vec<i64> = block_on(batches.rows().try_collect()).unwrap();
+/// decoder.join().unwrap();
+/// assert_eq!(rows, vec![1, 2]);
+/// ```
+pub fn decode_in_background<S, E, T>(
+    stream: S,
+    schema: &Schema,
+    max_in_flight_batches: NonZeroUsize,
+) -> Result<(impl Future<Output = ()>, DecodedBatches<T>), DeserializationError>
+where
+    S: Stream<Item = Result<RecordBatch, E>> + Unpin,
+    E: Into<ArrowError>,
+    T: ArRowDeserialize,
+{
+    T::check_schema(schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+    let root = RootArray::new::<T>(schema);
+    // The channel has room for one item per sender on top of its buffer
+    let (mut sender, receiver) = mpsc::channel(max_in_flight_batches.get() - 1);
+    let decoder = async move {
+        let mut stream = stream;
+        while let Some(batch) = stream.next().await {
+            let rows = match batch {
+                Ok(batch) => root.read::<T>(batch).map_err(ReadError::from),
+                Err(e) => Err(ReadError::Arrow(e.into())),
+            };
+            let failed = rows.is_err();
+            if sender.send(rows).await.is_err() || failed {
+                break;
+            }
+        }
+    };
+    Ok((decoder, DecodedBatches { receiver }))
+}
+
+/// Stream of batches decoded by the future returned by [`decode_in_background`]
+pub struct DecodedBatches<T> {
+    receiver: mpsc::Receiver<Result<Vec<T>, ReadError>>,
+}
+
+impl<T> DecodedBatches<T> {
+    /// Returns a stream of the rows of every batch
+    pub fn rows(self) -> impl Stream<Item = Result<T, ReadError>> {
+        self.flat_map(|batch| match batch {
+            Ok(rows) => Either::Left(futures::stream::iter(rows.into_iter().map(Ok))),
+            Err(e) => Either::Right(futures::stream::once(futures::future::ready(Err(e)))),
+        })
+    }
+}
+
+impl<T> Stream for DecodedBatches<T> {
+    type Item = Result<Vec<T>, ReadError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
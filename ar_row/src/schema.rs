@@ -0,0 +1,764 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Generation of Arrow schemas matching deserializable types, and of Rust types
+//! matching Arrow schemas.
+//!
+//! See [`CheckableDataType::reader_schema`](crate::deserialize::CheckableDataType::reader_schema)
+//! and [`rust_struct_definitions`]. [`datatype_diff`] compares a schema to the one a type
+//! expects.
+//!
+//! With the `schema-json` feature, schemas can be stored as JSON files (see
+//! [`schema_to_json`]), and checked against a type with [`assert_schema!`](crate::assert_schema).
+
+#[cfg(feature = "schema-json")]
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Fields, Schema, TimeUnit};
+#[cfg(feature = "schema-json")]
+use arrow::datatypes::{UnionFields, UnionMode};
+
+use crate::deserialize::TIMESTAMP_DECIMAL128_TYPE;
+
+/// Type preferences when generating a schema with
+/// [`CheckableDataType::reader_schema`](crate::deserialize::CheckableDataType::reader_schema)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaOptions {
+    /// Read [`Timestamp`](crate::Timestamp) columns as `Decimal128(38, 9)` instead of
+    /// Arrow timestamps, which supports [the full range of ORC
+    /// timestamps](https://github.com/datafusion-contrib/datafusion-orc/pull/96).
+    ///
+    /// Defaults to `false`.
+    pub timestamps_as_decimal128: bool,
+    /// Unit of [`Timestamp`](crate::Timestamp) columns, when not read as decimals.
+    ///
+    /// Defaults to [`TimeUnit::Nanosecond`].
+    pub timestamp_unit: TimeUnit,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        SchemaOptions {
+            timestamps_as_decimal128: false,
+            timestamp_unit: TimeUnit::Nanosecond,
+        }
+    }
+}
+
+impl SchemaOptions {
+    /// Returns the data type to read `datatype` as, recursively
+    pub fn apply(&self, datatype: DataType) -> DataType {
+        match datatype {
+            DataType::Timestamp(_, _) if self.timestamps_as_decimal128 => TIMESTAMP_DECIMAL128_TYPE,
+            DataType::Timestamp(_, tz) => DataType::Timestamp(self.timestamp_unit, tz),
+            DataType::Struct(fields) => DataType::Struct(
+                fields
+                    .iter()
+                    .map(|field| self.apply_to_field(field))
+                    .collect(),
+            ),
+            DataType::List(field) => DataType::List(Arc::new(self.apply_to_field(&field))),
+            DataType::LargeList(field) => {
+                DataType::LargeList(Arc::new(self.apply_to_field(&field)))
+            }
+            DataType::FixedSizeList(field, size) => {
+                DataType::FixedSizeList(Arc::new(self.apply_to_field(&field)), size)
+            }
+            DataType::Map(field, sorted) => {
+                DataType::Map(Arc::new(self.apply_to_field(&field)), sorted)
+            }
+            datatype => datatype,
+        }
+    }
+
+    fn apply_to_field(&self, field: &Field) -> Field {
+        field
+            .clone()
+            .with_data_type(self.apply(field.data_type().clone()))
+    }
+}
+
+/// Returns the source code of Rust structures which can be deserialized from
+/// `schema`, with `name` as name of the top-level structure.
+///
+/// Nested structures are named after their parent and field, and columns of types
+/// `ar_row` cannot deserialize are commented out.
+pub fn rust_struct_definitions(schema: &Schema, name: &str) -> String {
+    let mut definitions = Vec::new();
+    write_struct_definition(&mut definitions, name, schema.fields());
+    definitions.join("\n")
+}
+
+fn write_struct_definition(definitions: &mut Vec<String>, name: &str, fields: &Fields) {
+    let index = definitions.len();
+    definitions.push(String::new()); // placeholder, so parents come before children
+
+    let mut definition = String::new();
+    writeln!(
+        definition,
+        "#[derive(ArRowDeserialize, Default, Debug, PartialEq)]"
+    )
+    .unwrap();
+    writeln!(definition, "struct {} {{", name).unwrap();
+    for field in fields {
+        let child_name = format!("{}{}", name, to_camel_case(field.name()));
+        let field_name = to_field_name(field.name());
+        let num_definitions = definitions.len();
+        match rust_type(definitions, &child_name, field) {
+            Some(ty) if field_name == *field.name() || field_name.starts_with("r#") => {
+                writeln!(definition, "    {}: {},", field_name, ty).unwrap()
+            }
+            Some(ty) => {
+                // The field is commented out, so structures nested in it are unused
+                definitions.truncate(num_definitions);
+                writeln!(
+                    definition,
+                    "    // {}: {}, // column {:?} is not a valid Rust identifier",
+                    field_name,
+                    ty,
+                    field.name()
+                )
+                .unwrap()
+            }
+            None => writeln!(
+                definition,
+                "    // {}: ?, // unsupported Arrow type {}",
+                field_name,
+                field.data_type()
+            )
+            .unwrap(),
+        }
+    }
+    writeln!(definition, "}}").unwrap();
+    definitions[index] = definition;
+}
+
+/// Returns the Rust type of `field`, or `None` if it is not supported
+fn rust_type(definitions: &mut Vec<String>, struct_name: &str, field: &Field) -> Option<String> {
+    let ty = match field.data_type() {
+        DataType::Boolean => "bool".to_owned(),
+        DataType::Int8 => "i8".to_owned(),
+        DataType::Int16 => "i16".to_owned(),
+        DataType::Int32 => "i32".to_owned(),
+        DataType::Int64 => "i64".to_owned(),
+        DataType::UInt8 => "u8".to_owned(),
+        DataType::UInt16 => "u16".to_owned(),
+        DataType::UInt32 => "u32".to_owned(),
+        DataType::UInt64 => "u64".to_owned(),
+        DataType::Float32 => "f32".to_owned(),
+        DataType::Float64 => "f64".to_owned(),
+        DataType::Utf8 | DataType::LargeUtf8 => "String".to_owned(),
+        DataType::Binary | DataType::LargeBinary => "Box<[u8]>".to_owned(),
+        DataType::FixedSizeBinary(size) => format!("ar_row::FixedSizeBinary<{}>", size),
+        DataType::Decimal128(_, _) => "ar_row::NaiveDecimal128".to_owned(),
+        DataType::Timestamp(_, None) => "ar_row::Timestamp".to_owned(),
+        DataType::Date32 => "ar_row::Date".to_owned(),
+        DataType::List(item) | DataType::FixedSizeList(item, _) => {
+            format!("Vec<{}>", rust_type(definitions, struct_name, item)?)
+        }
+        DataType::Struct(fields) => {
+            write_struct_definition(definitions, struct_name, fields);
+            struct_name.to_owned()
+        }
+        _ => return None,
+    };
+    if field.is_nullable() {
+        Some(format!("Option<{}>", ty))
+    } else {
+        Some(ty)
+    }
+}
+
+/// Converts a column name to a field name, escaping keywords and replacing characters
+/// not allowed in identifiers
+fn to_field_name(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+        "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    if KEYWORDS.contains(&name) {
+        return format!("r#{}", name);
+    }
+    if ["crate", "self", "Self", "super"].contains(&name) {
+        // Cannot be raw identifiers
+        return format!("{}_", name);
+    }
+    let mut field_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if field_name.is_empty() || field_name.starts_with(|c: char| c.is_numeric()) {
+        field_name.insert(0, '_');
+    }
+    field_name
+}
+
+fn to_camel_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// Returns a side-by-side listing of the fields of `expected` and `actual`, nested
+/// fields of structures (and lists of structures) indented under their parent.
+///
+/// Fields are compared by position, and prefixed with `!` when their names or
+/// types differ. Some of these differences may be accepted by
+/// [`CheckableDataType::check_datatype`](crate::deserialize::CheckableDataType::check_datatype)
+/// (eg. `Utf8` instead of `LargeUtf8`).
+pub fn datatype_diff(expected: &DataType, actual: &DataType) -> String {
+    let mut lines = vec![(' ', "expected".to_owned(), "actual".to_owned())];
+    diff_fields(
+        &mut lines,
+        0,
+        nested_fields(expected),
+        nested_fields(actual),
+    );
+    let width = lines
+        .iter()
+        .map(|(_, expected, _)| expected.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines
+        .into_iter()
+        .map(|(marker, expected, actual)| {
+            format!("{} {:width$} | {}", marker, expected, actual, width = width)
+                .trim_end()
+                .to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the fields of a structure, or of the items of a list of structures
+fn nested_fields(datatype: &DataType) -> Option<&Fields> {
+    match datatype {
+        DataType::Struct(fields) => Some(fields),
+        DataType::List(item) | DataType::LargeList(item) | DataType::FixedSizeList(item, _) => {
+            match item.data_type() {
+                DataType::Struct(fields) => Some(fields),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns a short description of `datatype`, omitting the fields of structures
+fn datatype_label(datatype: &DataType) -> String {
+    match datatype {
+        DataType::Struct(_) => "Struct".to_owned(),
+        DataType::List(item) => format!("List<{}>", datatype_label(item.data_type())),
+        DataType::LargeList(item) => format!("LargeList<{}>", datatype_label(item.data_type())),
+        DataType::FixedSizeList(item, size) => format!(
+            "FixedSizeList<{}, {}>",
+            datatype_label(item.data_type()),
+            size
+        ),
+        DataType::Dictionary(keys, values) => {
+            format!("Dictionary<{:?}, {}>", keys, datatype_label(values))
+        }
+        datatype => format!("{:?}", datatype),
+    }
+}
+
+fn diff_fields(
+    lines: &mut Vec<(char, String, String)>,
+    depth: usize,
+    expected: Option<&Fields>,
+    actual: Option<&Fields>,
+) {
+    let expected: &[_] = expected.map(|fields| &fields[..]).unwrap_or(&[]);
+    let actual: &[_] = actual.map(|fields| &fields[..]).unwrap_or(&[]);
+    let describe = |field: &Field| {
+        format!(
+            "{:indent$}{}: {}",
+            "",
+            field.name(),
+            datatype_label(field.data_type()),
+            indent = depth * 2
+        )
+    };
+    for i in 0..expected.len().max(actual.len()) {
+        let (expected_field, actual_field) = (expected.get(i), actual.get(i));
+        let marker = match (expected_field, actual_field) {
+            (Some(e), Some(a))
+                if e.name() == a.name()
+                    && datatype_label(e.data_type()) == datatype_label(a.data_type()) =>
+            {
+                ' '
+            }
+            (Some(_), _) => '!',
+            // Extra fields are ignored
+            (None, _) => ' ',
+        };
+        lines.push((
+            marker,
+            expected_field
+                .map(|field| describe(field))
+                .unwrap_or_default(),
+            match actual_field {
+                Some(field) => describe(field),
+                None => format!("{:indent$}(missing)", "", indent = depth * 2),
+            },
+        ));
+        diff_fields(
+            lines,
+            depth + 1,
+            expected_field.and_then(|field| nested_fields(field.data_type())),
+            actual_field.and_then(|field| nested_fields(field.data_type())),
+        );
+    }
+}
+
+/// Parses a schema serialized by [`schema_to_json`]
+#[cfg(feature = "schema-json")]
+pub fn schema_from_json(json: &str) -> Result<Schema, serde_json::Error> {
+    serde_json::from_str::<JsonSchema>(json).map(Schema::from)
+}
+
+/// Serializes `schema` to JSON.
+///
+/// Fields are written as their name, type, nullability and metadata only, and leaf
+/// types with arrow-rs' serde representation, so the output does not depend on
+/// the internals of the arrow version `ar_row` was built with.
+///
+/// Storing `T::arrow_schema()` this way allows checking later that `T` still
+/// matches it, with [`assert_schema!`](crate::assert_schema).
+#[cfg(feature = "schema-json")]
+pub fn schema_to_json(schema: &Schema) -> String {
+    serde_json::to_string_pretty(&JsonSchema::from(schema)).expect("Could not serialize schema")
+}
+
+/// JSON representation of a [`Schema`], see [`schema_to_json`]
+#[cfg(feature = "schema-json")]
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+struct JsonSchema {
+    fields: Vec<JsonField>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, String>,
+}
+
+#[cfg(feature = "schema-json")]
+impl From<&Schema> for JsonSchema {
+    fn from(schema: &Schema) -> Self {
+        JsonSchema {
+            fields: json_fields(schema.fields()),
+            metadata: schema.metadata().clone(),
+        }
+    }
+}
+
+#[cfg(feature = "schema-json")]
+impl From<JsonSchema> for Schema {
+    fn from(schema: JsonSchema) -> Self {
+        Schema::new_with_metadata(arrow_fields(schema.fields), schema.metadata)
+    }
+}
+
+/// JSON representation of a [`Field`]
+#[cfg(feature = "schema-json")]
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+struct JsonField {
+    name: String,
+    data_type: JsonDataType,
+    nullable: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<String, String>,
+}
+
+#[cfg(feature = "schema-json")]
+impl From<&Field> for JsonField {
+    fn from(field: &Field) -> Self {
+        JsonField {
+            name: field.name().clone(),
+            data_type: field.data_type().into(),
+            nullable: field.is_nullable(),
+            metadata: field.metadata().clone(),
+        }
+    }
+}
+
+#[cfg(feature = "schema-json")]
+impl From<JsonField> for Field {
+    fn from(field: JsonField) -> Self {
+        Field::new(field.name, field.data_type.into(), field.nullable).with_metadata(field.metadata)
+    }
+}
+
+/// JSON representation of a [`DataType`]: types with child fields are represented
+/// with [`JsonField`], and the others with arrow-rs' serde representation.
+#[cfg(feature = "schema-json")]
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+enum JsonDataType {
+    List(Box<JsonField>),
+    LargeList(Box<JsonField>),
+    FixedSizeList(Box<JsonField>, i32),
+    Struct(Vec<JsonField>),
+    Map(Box<JsonField>, bool),
+    Dictionary(Box<JsonDataType>, Box<JsonDataType>),
+    RunEndEncoded(Box<JsonField>, Box<JsonField>),
+    Union(Vec<(i8, JsonField)>, UnionMode),
+    #[serde(untagged)]
+    Leaf(DataType),
+}
+
+#[cfg(feature = "schema-json")]
+impl From<&DataType> for JsonDataType {
+    fn from(datatype: &DataType) -> Self {
+        let field = |field: &Field| Box::new(JsonField::from(field));
+        match datatype {
+            DataType::List(item) => JsonDataType::List(field(item)),
+            DataType::LargeList(item) => JsonDataType::LargeList(field(item)),
+            DataType::FixedSizeList(item, size) => JsonDataType::FixedSizeList(field(item), *size),
+            DataType::Struct(fields) => JsonDataType::Struct(json_fields(fields)),
+            DataType::Map(entries, sorted) => JsonDataType::Map(field(entries), *sorted),
+            DataType::Dictionary(keys, values) => JsonDataType::Dictionary(
+                Box::new(keys.as_ref().into()),
+                Box::new(values.as_ref().into()),
+            ),
+            DataType::RunEndEncoded(run_ends, values) => {
+                JsonDataType::RunEndEncoded(field(run_ends), field(values))
+            }
+            DataType::Union(fields, mode) => JsonDataType::Union(
+                fields
+                    .iter()
+                    .map(|(type_id, field)| (type_id, field.as_ref().into()))
+                    .collect(),
+                *mode,
+            ),
+            datatype => JsonDataType::Leaf(datatype.clone()),
+        }
+    }
+}
+
+#[cfg(feature = "schema-json")]
+impl From<JsonDataType> for DataType {
+    fn from(datatype: JsonDataType) -> Self {
+        let field = |field: Box<JsonField>| Arc::new(Field::from(*field));
+        match datatype {
+            JsonDataType::List(item) => DataType::List(field(item)),
+            JsonDataType::LargeList(item) => DataType::LargeList(field(item)),
+            JsonDataType::FixedSizeList(item, size) => DataType::FixedSizeList(field(item), size),
+            JsonDataType::Struct(fields) => DataType::Struct(arrow_fields(fields)),
+            JsonDataType::Map(entries, sorted) => DataType::Map(field(entries), sorted),
+            JsonDataType::Dictionary(keys, values) => {
+                DataType::Dictionary(Box::new((*keys).into()), Box::new((*values).into()))
+            }
+            JsonDataType::RunEndEncoded(run_ends, values) => {
+                DataType::RunEndEncoded(field(run_ends), field(values))
+            }
+            JsonDataType::Union(fields, mode) => {
+                let (type_ids, fields): (Vec<_>, Vec<_>) = fields
+                    .into_iter()
+                    .map(|(type_id, field)| (type_id, Field::from(field)))
+                    .unzip();
+                DataType::Union(UnionFields::new(type_ids, fields), mode)
+            }
+            JsonDataType::Leaf(datatype) => datatype,
+        }
+    }
+}
+
+#[cfg(feature = "schema-json")]
+fn json_fields(fields: &Fields) -> Vec<JsonField> {
+    fields.iter().map(|field| field.as_ref().into()).collect()
+}
+
+#[cfg(feature = "schema-json")]
+fn arrow_fields(fields: Vec<JsonField>) -> Fields {
+    fields.into_iter().map(Field::from).collect()
+}
+
+/// Implementation of [`assert_schema!`](crate::assert_schema)
+#[cfg(feature = "schema-json")]
+#[doc(hidden)]
+pub fn assert_schema_json<T: crate::deserialize::CheckableDataType>(json: &str, path: &str) {
+    let schema = schema_from_json(json).unwrap_or_else(|e| panic!("Could not parse {path}: {e}"));
+    if let Err(e) = T::check_schema(&schema) {
+        panic!(
+            "{} cannot be read from the schema in {}:\n{}",
+            std::any::type_name::<T>(),
+            path,
+            e
+        );
+    }
+}
+
+/// Panics if a type cannot be deserialized from the schema stored in a JSON file
+/// (see [`schema_to_json`](crate::schema::schema_to_json)).
+///
+/// The file is embedded at build time with [`include_str!`], so its path is
+/// relative to the file invoking the macro, and the build fails if it is missing.
+/// Requires the `schema-json` feature.
+///
+/// `assert_schema!(MyRow, "schemas/my_row.arrow.json")` checks `MyRow` when it is
+/// evaluated, and `assert_schema!(test_name: MyRow, "schemas/my_row.arrow.json")`
+/// defines a test which does so, to catch changes to `MyRow` which would make it
+/// unable to read existing files:
+///
+/// ```
+/// use ar_row_derive::ArRowDeserialize;
+///
+/// #[derive(ArRowDeserialize, Default)]
+/// struct MyRow {
+///     id: i64,
+///     name: Option<String>,
+/// }
+///
+/// ar_row::assert_schema!(MyRow, "../tests/schemas/my_row.arrow.json");
+/// ar_row::assert_schema!(my_row_matches_schema: MyRow, "../tests/schemas/my_row.arrow.json");
+/// ```
+#[cfg(feature = "schema-json")]
+#[macro_export]
+macro_rules! assert_schema {
+    ($test_name:ident: $ty:ty, $path:expr) => {
+        #[test]
+        fn $test_name() {
+            $crate::assert_schema!($ty, $path);
+        }
+    };
+    ($ty:ty, $path:expr) => {
+        $crate::schema::assert_schema_json::<$ty>(::std::include_str!($path), $path)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_struct_definitions() {
+        let item = DataType::Struct(
+            vec![
+                Field::new("int1", DataType::Int32, true),
+                Field::new("string1", DataType::Utf8, true),
+            ]
+            .into(),
+        );
+        let schema = Schema::new(vec![
+            Field::new("boolean1", DataType::Boolean, false),
+            Field::new("type", DataType::Int64, true),
+            Field::new("items", DataType::new_list(item.clone(), true), false),
+            Field::new("middle-list", DataType::new_list(item, true), true),
+            Field::new("half", DataType::Float16, true),
+            Field::new("self", DataType::Utf8, false),
+        ]);
+
+        assert_eq!(
+            rust_struct_definitions(&schema, "Test1"),
+            "\
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1 {
+    boolean1: bool,
+    r#type: Option<i64>,
+    items: Vec<Option<Test1Items>>,
+    // middle_list: Option<Vec<Option<Test1MiddleList>>>, // column \"middle-list\" is not a valid Rust identifier
+    // half: ?, // unsupported Arrow type Float16
+    // self_: String, // column \"self\" is not a valid Rust identifier
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1Items {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_datatype_diff() {
+        let author = |name_type| {
+            DataType::Struct(
+                vec![
+                    Field::new("name", name_type, true),
+                    Field::new("email", DataType::Utf8, true),
+                ]
+                .into(),
+            )
+        };
+        let expected = DataType::Struct(
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new(
+                    "authors",
+                    DataType::new_list(author(DataType::Utf8), true),
+                    true,
+                ),
+                Field::new("date", DataType::Date32, true),
+            ]
+            .into(),
+        );
+        let actual = DataType::Struct(
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new(
+                    "authors",
+                    DataType::new_list(author(DataType::Binary), true),
+                    true,
+                ),
+            ]
+            .into(),
+        );
+
+        assert_eq!(
+            datatype_diff(&expected, &actual),
+            "\
+  expected              | actual
+  id: Int64             | id: Int64
+  authors: List<Struct> | authors: List<Struct>
+!   name: Utf8          |   name: Binary
+    email: Utf8         |   email: Utf8
+! date: Date32          | (missing)"
+        );
+    }
+
+    #[test]
+    fn test_schema_options_apply_nested() {
+        let options = SchemaOptions {
+            timestamps_as_decimal128: false,
+            timestamp_unit: TimeUnit::Microsecond,
+        };
+        let timestamp = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let expected = DataType::Timestamp(TimeUnit::Microsecond, None);
+
+        assert_eq!(
+            options.apply(DataType::new_large_list(timestamp.clone(), true)),
+            DataType::new_large_list(expected.clone(), true)
+        );
+        assert_eq!(
+            options.apply(DataType::new_fixed_size_list(timestamp.clone(), 2, true)),
+            DataType::new_fixed_size_list(expected.clone(), 2, true)
+        );
+
+        let map = |value: DataType| {
+            DataType::Map(
+                Arc::new(Field::new(
+                    "entries",
+                    DataType::Struct(Fields::from(vec![
+                        Field::new("keys", DataType::Utf8, false),
+                        Field::new("values", value, true),
+                    ])),
+                    false,
+                )),
+                false,
+            )
+        };
+        assert_eq!(options.apply(map(timestamp)), map(expected));
+    }
+
+    #[test]
+    fn test_rust_struct_definitions_lists() {
+        let schema = Schema::new(vec![
+            Field::new_list("list", Field::new("item", DataType::Int32, false), false),
+            Field::new(
+                "fixed_size_list",
+                DataType::new_fixed_size_list(DataType::Float32, 3, true),
+                true,
+            ),
+        ]);
+
+        assert_eq!(
+            rust_struct_definitions(&schema, "Row"),
+            "\
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    list: Vec<i32>,
+    fixed_size_list: Option<Vec<Option<f32>>>,
+}
+"
+        );
+    }
+
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn test_schema_json() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new_list("tags", Field::new("item", DataType::Utf8, true), true),
+        ]);
+        assert_eq!(schema_from_json(&schema_to_json(&schema)).unwrap(), schema);
+        assert!(schema_from_json("{}").is_err());
+
+        // Fields are written without arrow-rs internals, like dictionary ids
+        assert_eq!(
+            schema_to_json(&schema),
+            r#"{
+  "fields": [
+    {
+      "name": "id",
+      "data_type": "Int64",
+      "nullable": false
+    },
+    {
+      "name": "tags",
+      "data_type": {
+        "List": {
+          "name": "item",
+          "data_type": "Utf8",
+          "nullable": true
+        }
+      },
+      "nullable": true
+    }
+  ]
+}"#
+        );
+    }
+
+    #[cfg(feature = "schema-json")]
+    #[test]
+    fn test_schema_json_nested() {
+        let entries = Fields::from(vec![
+            Field::new("keys", DataType::Utf8, false),
+            Field::new("values", DataType::Int32, true),
+        ]);
+        let schema = Schema::new(vec![
+            Field::new(
+                "map",
+                DataType::Map(
+                    Arc::new(Field::new("entries", DataType::Struct(entries), false)),
+                    false,
+                ),
+                true,
+            ),
+            Field::new(
+                "dict",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new(
+                "timestamps",
+                DataType::new_fixed_size_list(
+                    DataType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".into())),
+                    2,
+                    true,
+                ),
+                false,
+            ),
+        ])
+        .with_metadata([("key".to_owned(), "value".to_owned())].into());
+        assert_eq!(schema_from_json(&schema_to_json(&schema)).unwrap(), schema);
+    }
+}
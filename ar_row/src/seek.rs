@@ -0,0 +1,58 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Starting to read files at an arbitrary row
+//!
+//! ORC and Parquet files are split into chunks (respectively stripes and row groups)
+//! which can be read independently. [`seek_to_row`] uses this to skip the chunks
+//! before the requested row without reading them, then skips rows of the first
+//! chunk it reads without deserializing them (see [`CheckedRows::skip_rows`]).
+//!
+//! To read chunks out of order, or in parallel, wrap the source in a
+//! [`ChunkedBatchReader`](crate::random_access::ChunkedBatchReader).
+
+use arrow::record_batch::RecordBatchReader;
+
+use crate::deserialize::ArRowDeserialize;
+use crate::reader::{CheckedReader, CheckedRows, ReadError};
+
+/// Source of record batches made of chunks which can be read independently
+///
+/// This is implemented by `orc_rust`'s `ArrowReaderBuilder` (with the `orc` feature)
+/// and `parquet`'s `ParquetRecordBatchReaderBuilder` (with the `parquet` feature),
+/// which should be configured (eg. with a projection) before seeking.
+pub trait SeekableBatchSource {
+    type Reader: RecordBatchReader;
+
+    /// Returns the number of rows in each chunk, in the order they are read
+    fn chunk_lengths(&self) -> Vec<usize>;
+
+    /// Returns a reader of all the chunks after the `first_chunk`-th one (included).
+    ///
+    /// If `first_chunk` is the number of chunks, the reader returns no batch.
+    fn read_from_chunk(self, first_chunk: usize) -> Result<Self::Reader, ReadError>;
+}
+
+/// Returns an iterator on rows of `source`, starting from the `row`-th row.
+///
+/// If `source` has `row` rows or fewer, the iterator is empty.
+pub fn seek_to_row<S: SeekableBatchSource, T: ArRowDeserialize>(
+    source: S,
+    row: usize,
+) -> Result<CheckedRows<S::Reader, T>, ReadError> {
+    let mut first_chunk = 0;
+    let mut first_chunk_start = 0;
+    for chunk_length in source.chunk_lengths() {
+        if first_chunk_start + chunk_length > row {
+            break;
+        }
+        first_chunk += 1;
+        first_chunk_start += chunk_length;
+    }
+
+    let mut rows = CheckedReader::new(source.read_from_chunk(first_chunk)?)?.rows();
+    rows.skip_rows(row - first_chunk_start)?;
+    Ok(rows)
+}
@@ -0,0 +1,885 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! [`serde`](::serde) support
+//!
+//! [`RowDeserializer`] implements [`Deserializer`] on a row of an [`ArrayTree`],
+//! and is returned by [`ArrayDeserializer::row`],
+//! so any type implementing [`Deserialize`] can be read from Arrow arrays, including
+//! types from other crates which cannot derive `ArRowDeserialize`.
+//! This is slower than `ArRowDeserialize`, as values are dispatched on their data
+//! type for every row instead of once per array.
+//!
+//! Arrow values are passed to visitors as:
+//!
+//! * nulls as `None` or `()`
+//! * structs and maps as maps, and lists as sequences
+//! * unions as single-entry maps (or enums), whose key is the name of the variant
+//! * binaries as bytes (or sequences of `u8`)
+//! * temporal and decimal values as strings, formatted like in JSON conversion
+//!
+//! [`Timestamp`](crate::Timestamp), [`Date`](crate::Date),
+//! [`NaiveDecimal128`](crate::NaiveDecimal128) and [`FixedSizeBinary`] also implement
+//! [`Serialize`] and [`Deserialize`] with this feature.
+//!
+//! Requires the `serde` feature.
+
+use std::fmt::Display;
+use std::ops::Range;
+
+use ::serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+use ::serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use ::serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize, Serializer};
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use thiserror::Error;
+
+use crate::display::LeafFormatters;
+use crate::structured_reader::ArrayTree;
+use crate::FixedSizeBinary;
+
+/// Error returned when failing to deserialize a row with [`RowDeserializer`]
+#[derive(Debug, Error, PartialEq)]
+pub enum SerdeError {
+    /// The array contains values of a type which cannot be deserialized yet
+    #[error("Cannot deserialize Arrow {0} values")]
+    UnsupportedDataType(DataType),
+    /// Arrow failed to format values to strings
+    #[error("Could not format values: {0}")]
+    Format(String),
+    /// Error returned by the [`Deserialize`] implementation
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+/// Deserializes each row of a [`RecordBatch`] into a `T`
+pub fn from_record_batch<T: DeserializeOwned>(batch: &RecordBatch) -> Result<Vec<T>, SerdeError> {
+    let array: StructArray = batch.clone().into();
+    from_array(&array)
+}
+
+/// Deserializes each row of an array into a `T`, which may borrow strings and
+/// binaries from the array
+pub fn from_array<'de, T: Deserialize<'de>>(array: &'de dyn Array) -> Result<Vec<T>, SerdeError> {
+    let deserializer = ArrayDeserializer::new(array)?;
+    (0..deserializer.len())
+        .map(|index| T::deserialize(deserializer.row(index)))
+        .collect()
+}
+
+/// View of an array which returns a [`RowDeserializer`] for each of its rows
+///
+/// Building it once for all rows allows the formatters of temporal and decimal
+/// columns to be shared by all their values.
+pub struct ArrayDeserializer<'de> {
+    tree: ArrayTree<'de>,
+    formatters: LeafFormatters<'de>,
+}
+
+impl<'de> ArrayDeserializer<'de> {
+    pub fn new(array: &'de dyn Array) -> Result<Self, SerdeError> {
+        let tree = ArrayTree::new(array);
+        let formatters =
+            LeafFormatters::new(&tree).map_err(|e| SerdeError::Format(e.to_string()))?;
+        Ok(ArrayDeserializer { tree, formatters })
+    }
+
+    /// Returns the number of rows in the array
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns whether the array has no rows
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the deserializer of the `index`-th row of the array
+    pub fn row(&self, index: usize) -> RowDeserializer<'_, 'de> {
+        RowDeserializer {
+            tree: &self.tree,
+            formatters: &self.formatters,
+            index,
+        }
+    }
+}
+
+/// [`Deserializer`] of the `index`-th row of an [`ArrayTree`]
+#[derive(Clone, Copy)]
+pub struct RowDeserializer<'t, 'de> {
+    tree: &'t ArrayTree<'de>,
+    formatters: &'t LeafFormatters<'de>,
+    index: usize,
+}
+
+impl<'t, 'de> RowDeserializer<'t, 'de> {
+    /// Returns the deserializer of the `index`-th row of `tree`, which is this row's
+    /// tree or one of its descendants
+    fn at(self, tree: &'t ArrayTree<'de>, index: usize) -> Self {
+        RowDeserializer {
+            tree,
+            formatters: self.formatters,
+            index,
+        }
+    }
+
+    /// Returns the deserializer of the dictionary value or union variant of this
+    /// row, if any
+    fn resolve(self) -> Option<(Option<&'de str>, Self)> {
+        match self.tree {
+            ArrayTree::Dictionary { keys, values, .. } => {
+                Some((None, self.at(values, keys[self.index])))
+            }
+            ArrayTree::Union { array, variants } => {
+                let type_id = array.type_id(self.index);
+                variants
+                    .iter()
+                    .find(|(variant_type_id, _, _)| *variant_type_id == type_id)
+                    .map(|(_, name, tree)| {
+                        (Some(*name), self.at(tree, array.value_offset(self.index)))
+                    })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'t, 'de> Deserializer<'de> for RowDeserializer<'t, 'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.tree.is_null(self.index) {
+            return visitor.visit_unit();
+        }
+        match self.tree {
+            ArrayTree::Leaf(array) => {
+                deserialize_leaf(*array, self.formatters, self.index, visitor)
+            }
+            ArrayTree::Struct { fields, .. } => visitor.visit_map(StructAccess {
+                row: self,
+                fields: fields.iter(),
+                value: None,
+            }),
+            ArrayTree::List {
+                offsets, elements, ..
+            } => visitor.visit_seq(ListAccess {
+                row: self,
+                elements,
+                range: offsets.range(self.index),
+            }),
+            ArrayTree::Map {
+                offsets,
+                keys,
+                values,
+                ..
+            } => visitor.visit_map(MapEntriesAccess {
+                row: self,
+                keys,
+                values,
+                range: offsets.range(self.index),
+                value_index: None,
+            }),
+            ArrayTree::Dictionary { .. } | ArrayTree::Union { .. } => match self.resolve() {
+                Some((None, value)) => value.deserialize_any(visitor),
+                Some((Some(name), value)) => visitor.visit_map(UnionAccess {
+                    name: Some(name),
+                    value,
+                }),
+                None => visitor.visit_unit(),
+            },
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.tree.is_null(self.index) {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match (self.tree.is_null(self.index), self.tree) {
+            (false, ArrayTree::Leaf(array)) => match binary_value(*array, self.index) {
+                Some(value) => {
+                    let mut seq = SeqDeserializer::<_, SerdeError>::new(value.iter().copied());
+                    let value = visitor.visit_seq(&mut seq)?;
+                    seq.end()?;
+                    Ok(value)
+                }
+                None => self.deserialize_any(visitor),
+            },
+            (false, ArrayTree::Dictionary { .. }) => match self.resolve() {
+                Some((_, value)) => value.deserialize_seq(visitor),
+                None => visitor.visit_unit(),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        if self.tree.is_null(self.index) {
+            return visitor.visit_unit();
+        }
+        match self.resolve() {
+            Some((None, value)) => value.deserialize_enum(name, variants, visitor),
+            Some((Some(name), value)) => visitor.visit_enum(UnionAccess {
+                name: Some(name),
+                value,
+            }),
+            None => match self.tree {
+                // Unit variants stored as strings
+                ArrayTree::Leaf(array) => match string_value(*array, self.index) {
+                    Some(name) => visitor.visit_enum(BorrowedStrDeserializer::new(name)),
+                    None => self.deserialize_any(visitor),
+                },
+                _ => self.deserialize_any(visitor),
+            },
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+fn deserialize_leaf<'de, V: Visitor<'de>>(
+    array: &'de dyn Array,
+    formatters: &LeafFormatters<'de>,
+    index: usize,
+    visitor: V,
+) -> Result<V::Value, SerdeError> {
+    match array.data_type() {
+        DataType::Null => visitor.visit_unit(),
+        DataType::Boolean => visitor.visit_bool(array.as_boolean().value(index)),
+        DataType::Int8 => visitor.visit_i8(array.as_primitive::<Int8Type>().value(index)),
+        DataType::Int16 => visitor.visit_i16(array.as_primitive::<Int16Type>().value(index)),
+        DataType::Int32 => visitor.visit_i32(array.as_primitive::<Int32Type>().value(index)),
+        DataType::Int64 => visitor.visit_i64(array.as_primitive::<Int64Type>().value(index)),
+        DataType::UInt8 => visitor.visit_u8(array.as_primitive::<UInt8Type>().value(index)),
+        DataType::UInt16 => visitor.visit_u16(array.as_primitive::<UInt16Type>().value(index)),
+        DataType::UInt32 => visitor.visit_u32(array.as_primitive::<UInt32Type>().value(index)),
+        DataType::UInt64 => visitor.visit_u64(array.as_primitive::<UInt64Type>().value(index)),
+        DataType::Float16 => {
+            visitor.visit_f32(array.as_primitive::<Float16Type>().value(index).to_f32())
+        }
+        DataType::Float32 => visitor.visit_f32(array.as_primitive::<Float32Type>().value(index)),
+        DataType::Float64 => visitor.visit_f64(array.as_primitive::<Float64Type>().value(index)),
+        DataType::Utf8 => visitor.visit_borrowed_str(array.as_string::<i32>().value(index)),
+        DataType::LargeUtf8 => visitor.visit_borrowed_str(array.as_string::<i64>().value(index)),
+        DataType::Binary => visitor.visit_borrowed_bytes(array.as_binary::<i32>().value(index)),
+        DataType::LargeBinary => {
+            visitor.visit_borrowed_bytes(array.as_binary::<i64>().value(index))
+        }
+        DataType::FixedSizeBinary(_) => {
+            visitor.visit_borrowed_bytes(array.as_fixed_size_binary().value(index))
+        }
+        datatype => match formatters.get(array) {
+            Some(formatter) => visitor.visit_string(formatter.value(index).to_string()),
+            None => Err(SerdeError::UnsupportedDataType(datatype.clone())),
+        },
+    }
+}
+
+/// Returns the `index`-th value of `array` if it is a string array
+fn string_value(array: &dyn Array, index: usize) -> Option<&str> {
+    match array.data_type() {
+        DataType::Utf8 => Some(array.as_string::<i32>().value(index)),
+        DataType::LargeUtf8 => Some(array.as_string::<i64>().value(index)),
+        _ => None,
+    }
+}
+
+/// Returns the `index`-th value of `array` if it is a binary array
+fn binary_value(array: &dyn Array, index: usize) -> Option<&[u8]> {
+    match array.data_type() {
+        DataType::Binary => Some(array.as_binary::<i32>().value(index)),
+        DataType::LargeBinary => Some(array.as_binary::<i64>().value(index)),
+        DataType::FixedSizeBinary(_) => Some(array.as_fixed_size_binary().value(index)),
+        _ => None,
+    }
+}
+
+/// Fields of a struct row, as a map from field names to values
+struct StructAccess<'t, 'de> {
+    row: RowDeserializer<'t, 'de>,
+    fields: std::slice::Iter<'t, (&'de str, ArrayTree<'de>)>,
+    value: Option<&'t ArrayTree<'de>>,
+}
+
+impl<'t, 'de> MapAccess<'de> for StructAccess<'t, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.fields.next() {
+            None => Ok(None),
+            Some((name, tree)) => {
+                self.value = Some(tree);
+                seed.deserialize(BorrowedStrDeserializer::new(*name))
+                    .map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let tree = self
+            .value
+            .take()
+            .ok_or_else(|| SerdeError::Custom("Struct value read before its key".to_owned()))?;
+        seed.deserialize(self.row.at(tree, self.row.index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Elements of a list row
+struct ListAccess<'t, 'de> {
+    row: RowDeserializer<'t, 'de>,
+    elements: &'t ArrayTree<'de>,
+    range: Range<usize>,
+}
+
+impl<'t, 'de> SeqAccess<'de> for ListAccess<'t, 'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        match self.range.next() {
+            None => Ok(None),
+            Some(index) => seed
+                .deserialize(self.row.at(self.elements, index))
+                .map(Some),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.range.len())
+    }
+}
+
+/// Entries of a map row
+struct MapEntriesAccess<'t, 'de> {
+    row: RowDeserializer<'t, 'de>,
+    keys: &'t ArrayTree<'de>,
+    values: &'t ArrayTree<'de>,
+    range: Range<usize>,
+    value_index: Option<usize>,
+}
+
+impl<'t, 'de> MapAccess<'de> for MapEntriesAccess<'t, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.range.next() {
+            None => Ok(None),
+            Some(index) => {
+                self.value_index = Some(index);
+                seed.deserialize(self.row.at(self.keys, index)).map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let index = self
+            .value_index
+            .take()
+            .ok_or_else(|| SerdeError::Custom("Map value read before its key".to_owned()))?;
+        seed.deserialize(self.row.at(self.values, index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.range.len())
+    }
+}
+
+/// Variant of a union row, as either a single-entry map or an enum
+struct UnionAccess<'t, 'de> {
+    name: Option<&'de str>,
+    value: RowDeserializer<'t, 'de>,
+}
+
+impl<'t, 'de> MapAccess<'de> for UnionAccess<'t, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.name.take() {
+            None => Ok(None),
+            Some(name) => seed
+                .deserialize(BorrowedStrDeserializer::new(name))
+                .map(Some),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        seed.deserialize(self.value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl<'t, 'de> EnumAccess<'de> for UnionAccess<'t, 'de> {
+    type Error = SerdeError;
+    type Variant = RowDeserializer<'t, 'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), SerdeError> {
+        let name = self.name.unwrap_or_default();
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(name))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'t, 'de> VariantAccess<'de> for RowDeserializer<'t, 'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_any(visitor)
+    }
+}
+
+impl<const N: usize> Serialize for FixedSizeBinary<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for FixedSizeBinary<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(FixedSizeBinaryVisitor::<N>)
+    }
+}
+
+/// Reads [`FixedSizeBinary`] from either bytes or sequences of `u8`, as most
+/// formats (eg. JSON) do not have a native bytes type
+struct FixedSizeBinaryVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for FixedSizeBinaryVisitor<N> {
+    type Value = FixedSizeBinary<N>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "{} bytes", N)
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        value
+            .try_into()
+            .map(FixedSizeBinary)
+            .map_err(|_| E::invalid_length(value.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut value = [0; N];
+        for (i, byte) in value.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| <A::Error as de::Error>::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(FixedSizeBinary(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use ::serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn test_from_record_batch() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Inner {
+            c: Option<f64>,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            a: i64,
+            b: Option<String>,
+            list: Option<Vec<i32>>,
+            inner: Inner,
+        }
+
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+        ]);
+        let inner = StructArray::from(vec![(
+            Arc::new(Field::new("c", DataType::Float64, true)),
+            Arc::new(Float64Array::from(vec![Some(2.5), None])) as ArrayRef,
+        )]);
+        let batch = RecordBatch::try_from_iter(vec![
+            ("a", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "b",
+                Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+            ),
+            ("list", Arc::new(list) as ArrayRef),
+            ("inner", Arc::new(inner) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            from_record_batch::<Row>(&batch),
+            Ok(vec![
+                Row {
+                    a: 1,
+                    b: Some("foo".to_owned()),
+                    list: Some(vec![1, 2]),
+                    inner: Inner { c: Some(2.5) },
+                },
+                Row {
+                    a: 2,
+                    b: None,
+                    list: None,
+                    inner: Inner { c: None },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let strings = StringArray::from(vec!["foo", "bar"]);
+        assert_eq!(from_array::<&str>(&strings), Ok(vec!["foo", "bar"]));
+
+        let binaries = BinaryArray::from(vec![&b"foo"[..], &b"bar"[..]]);
+        assert_eq!(
+            from_array::<Vec<u8>>(&binaries),
+            Ok(vec![b"foo".to_vec(), b"bar".to_vec()])
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.append(true).unwrap();
+        builder.append(true).unwrap();
+        let array = builder.finish();
+
+        assert_eq!(
+            from_array::<HashMap<String, i32>>(&array),
+            Ok(vec![HashMap::from([("a".to_owned(), 1)]), HashMap::new()])
+        );
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let array: DictionaryArray<Int8Type> =
+            vec![Some("a"), None, Some("b")].into_iter().collect();
+        assert_eq!(
+            from_array::<Option<String>>(&array),
+            Ok(vec![Some("a".to_owned()), None, Some("b".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Number {
+            Int(i32),
+            Float(f64),
+        }
+
+        let mut builder = UnionBuilder::new_dense();
+        builder.append::<Int32Type>("int", 1).unwrap();
+        builder.append::<Float64Type>("float", 2.5).unwrap();
+        let array = builder.build().unwrap();
+
+        assert_eq!(
+            from_array::<Number>(&array),
+            Ok(vec![Number::Int(1), Number::Float(2.5)])
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let array = StringViewArray::from(vec!["foo"]);
+        assert_eq!(
+            from_array::<String>(&array),
+            Err(SerdeError::UnsupportedDataType(DataType::Utf8View))
+        );
+    }
+
+    #[test]
+    fn test_value_types() {
+        use crate::{Date, NaiveDecimal128, Timestamp};
+
+        let timestamp = Timestamp {
+            seconds: 1,
+            nanoseconds: 500,
+        };
+        let json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(json, r#"{"seconds":1,"nanoseconds":500}"#);
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), timestamp);
+
+        assert_eq!(serde_json::to_string(&Date(3)).unwrap(), "3");
+        assert_eq!(serde_json::from_str::<Date>("3").unwrap(), Date(3));
+
+        assert_eq!(serde_json::to_string(&NaiveDecimal128(-12)).unwrap(), "-12");
+        assert_eq!(
+            serde_json::from_str::<NaiveDecimal128>("-12").unwrap(),
+            NaiveDecimal128(-12)
+        );
+    }
+
+    #[test]
+    fn test_fixed_size_binary() {
+        let json = serde_json::to_string(&FixedSizeBinary([1, 2, 3])).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(
+            serde_json::from_str::<FixedSizeBinary<3>>(&json).unwrap(),
+            FixedSizeBinary([1, 2, 3])
+        );
+        assert!(serde_json::from_str::<FixedSizeBinary<4>>(&json).is_err());
+        assert!(serde_json::from_str::<FixedSizeBinary<2>>(&json).is_err());
+
+        let array =
+            FixedSizeBinaryArray::try_from_iter(vec![[1u8, 2], [3, 4]].into_iter()).unwrap();
+        assert_eq!(
+            from_array::<FixedSizeBinary<2>>(&array),
+            Ok(vec![FixedSizeBinary([1, 2]), FixedSizeBinary([3, 4])])
+        );
+        assert!(from_array::<FixedSizeBinary<3>>(&array).is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use arrow::array::builder::{Int32Builder, MapBuilder, StringBuilder};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        id: i64,
+        name: Option<String>,
+        scores: Vec<i32>,
+        blob: Vec<u8>,
+        counts: BTreeMap<String, i32>,
+    }
+
+    fn batch() -> RecordBatch {
+        let mut counts = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        counts.keys().append_value("a");
+        counts.values().append_value(1);
+        counts.keys().append_value("b");
+        counts.values().append_value(2);
+        counts.append(true).unwrap();
+        counts.append(true).unwrap();
+        RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "name",
+                Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+            ),
+            (
+                "scores",
+                Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                    Some(vec![Some(1), Some(2)]),
+                    Some(vec![]),
+                ])) as ArrayRef,
+            ),
+            (
+                "blob",
+                Arc::new(BinaryArray::from(vec![&b"\x00\x01"[..], &b""[..]])) as ArrayRef,
+            ),
+            ("counts", Arc::new(counts.finish()) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_record_batch() {
+        assert_eq!(
+            from_record_batch::<Row>(&batch()),
+            Ok(vec![
+                Row {
+                    id: 1,
+                    name: Some("foo".to_owned()),
+                    scores: vec![1, 2],
+                    blob: vec![0, 1],
+                    counts: BTreeMap::from([("a".to_owned(), 1), ("b".to_owned(), 2)]),
+                },
+                Row {
+                    id: 2,
+                    name: None,
+                    scores: vec![],
+                    blob: vec![],
+                    counts: BTreeMap::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let array = StringArray::from(vec!["foo", "bar"]);
+        assert_eq!(from_array::<&str>(&array), Ok(vec!["foo", "bar"]));
+    }
+
+    #[test]
+    fn test_temporal_and_decimal() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Row {
+            date: Option<String>,
+            timestamp: String,
+            decimal: String,
+        }
+
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "date",
+                Arc::new(Date32Array::from(vec![Some(19000), None])) as ArrayRef,
+            ),
+            (
+                "timestamp",
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    1_500_000_000_123_456_789,
+                    -1,
+                ])) as ArrayRef,
+            ),
+            (
+                "decimal",
+                Arc::new(
+                    Decimal128Array::from(vec![12345, -5])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            from_record_batch::<Row>(&batch),
+            Ok(vec![
+                Row {
+                    date: Some("2022-01-08".to_owned()),
+                    timestamp: "2017-07-14T02:40:00.123456789Z".to_owned(),
+                    decimal: "123.45".to_owned(),
+                },
+                Row {
+                    date: None,
+                    timestamp: "1969-12-31T23:59:59.999999999Z".to_owned(),
+                    decimal: "-0.05".to_owned(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_dictionary_of_dates() {
+        let array = DictionaryArray::new(
+            Int8Array::from(vec![1, 0, 1]),
+            Arc::new(Date32Array::from(vec![0, 19000])),
+        );
+        assert_eq!(
+            from_array::<String>(&array),
+            Ok(vec![
+                "2022-01-08".to_owned(),
+                "1970-01-01".to_owned(),
+                "2022-01-08".to_owned()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unsupported_data_type() {
+        let array = RunArray::<Int32Type>::try_new(
+            &Int32Array::from(vec![2]),
+            &Int32Array::from(vec![Some(1)]),
+        )
+        .unwrap();
+        assert_eq!(
+            from_array::<i32>(&array),
+            Err(SerdeError::UnsupportedDataType(array.data_type().clone()))
+        );
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Implementations of [`ArRowDeserialize`] for [`HashSet`] and [`BTreeSet`]
+//!
+//! Sets are read from the same columns as [`Vec`] (lists, and binary arrays for
+//! sets of `u8`), and duplicate values of each list are dropped while decoding it.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use std::collections::HashSet;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::datatypes::Int32Type;
+//! use ar_row::deserialize::ArRowDeserialize;
+//!
+//! let array: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+//!     Some(vec![Some(1), Some(2), Some(1)]),
+//!     Some(vec![]),
+//! ]));
+//! assert_eq!(
+//!     <HashSet<i32>>::from_array(array).unwrap(),
+//!     vec![HashSet::from([1, 2]), HashSet::new()]
+//! );
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use arrow::array::*;
+use arrow::datatypes::*;
+
+use crate::deserialize::{
+    ArRowDeserialize, ArRowDeserializeOption, ArRowStruct, CheckableDataType, ColumnTree,
+    DeserializationError, DeserializationTarget,
+};
+
+/// Returns [`DeserializationError::MismatchedLength`] if `num_rows` rows do not fit
+/// in `dst_len` items
+fn check_length(num_rows: usize, dst_len: usize) -> Result<(), DeserializationError> {
+    if num_rows > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: num_rows,
+            dst: dst_len,
+        });
+    }
+    Ok(())
+}
+
+macro_rules! impl_set {
+    ($set:ident<I $(, $hasher:ident)?>, $($bound:path),+) => {
+        impl<I: ArRowStruct $(, $hasher)?> ArRowStruct for $set<I $(, $hasher)?> {
+            fn columns_with_prefix(prefix: &str) -> Vec<String> {
+                I::columns_with_prefix(prefix)
+            }
+
+            fn column_tree() -> ColumnTree {
+                I::column_tree()
+            }
+
+            fn for_each_column_path(path: &mut Vec<&str>, f: &mut dyn FnMut(&[&str])) {
+                I::for_each_column_path(path, f)
+            }
+        }
+
+        impl<I: CheckableDataType $(, $hasher)?> CheckableDataType for $set<I $(, $hasher)?> {
+            fn check_datatype(datatype: &DataType) -> Result<(), String> {
+                <Vec<I>>::check_datatype(datatype)
+            }
+
+            fn expected_datatype() -> DataType {
+                <Vec<I>>::expected_datatype()
+            }
+        }
+
+        impl<I $(, $hasher)?> ArRowDeserialize for $set<I $(, $hasher)?>
+        where
+            I: ArRowDeserialize $(+ $bound)+ + 'static,
+            $($hasher: BuildHasher + Default,)?
+        {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                check_length(src.len(), dst.len())?;
+                let lists = <Vec<I>>::from_array(src)?;
+                let num_rows = lists.len();
+                for (set, list) in dst.iter_mut().zip(lists) {
+                    set.clear();
+                    set.extend(list);
+                }
+                Ok(num_rows)
+            }
+        }
+
+        impl<I $(, $hasher)?> ArRowDeserializeOption for $set<I $(, $hasher)?>
+        where
+            I: ArRowDeserialize $(+ $bound)+ + 'static,
+            $($hasher: BuildHasher + Default,)?
+        {
+            fn read_options_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                check_length(src.len(), dst.len())?;
+                let lists = <Option<Vec<I>>>::from_array(src)?;
+                let num_rows = lists.len();
+                for (set, list) in dst.iter_mut().zip(lists) {
+                    match list {
+                        Some(list) => {
+                            let set = set.get_or_insert_with(Default::default);
+                            set.clear();
+                            set.extend(list);
+                        }
+                        None => *set = None,
+                    }
+                }
+                Ok(num_rows)
+            }
+        }
+    };
+}
+
+impl_set!(HashSet<I, S>, Eq, Hash);
+impl_set!(BTreeSet<I>, Ord);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn array() -> ArrayRef {
+        Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(3), Some(1), Some(3)]),
+            None,
+            Some(vec![Some(2)]),
+        ]))
+    }
+
+    #[test]
+    fn test_check_datatype() {
+        let datatype = array().data_type().clone();
+        assert_eq!(<HashSet<i32>>::check_datatype(&datatype), Ok(()));
+        assert_eq!(<BTreeSet<i32>>::check_datatype(&datatype), Ok(()));
+        assert!(<BTreeSet<String>>::check_datatype(&datatype).is_err());
+        assert_eq!(
+            <BTreeSet<i32>>::expected_datatype(),
+            <Vec<i32>>::expected_datatype()
+        );
+    }
+
+    #[test]
+    fn test_sets() {
+        assert_eq!(
+            <Option<BTreeSet<i32>>>::from_array(array()),
+            Ok(vec![
+                Some(BTreeSet::from([1, 3])),
+                None,
+                Some(BTreeSet::from([2]))
+            ])
+        );
+        assert!(<BTreeSet<i32>>::from_array(array()).is_err());
+
+        let array = array().slice(2, 1);
+        assert_eq!(
+            <HashSet<i32>>::from_array(array),
+            Ok(vec![HashSet::from([2])])
+        );
+    }
+
+    #[test]
+    fn test_reused_sets() {
+        // Values of previous rows are cleared
+        let mut rows = vec![Some(HashSet::from([4, 5])); 3];
+        assert_eq!(
+            <Option<HashSet<i32>>>::read_from_array_into_vec(array(), &mut rows),
+            Ok(3)
+        );
+        assert_eq!(
+            rows,
+            vec![Some(HashSet::from([1, 3])), None, Some(HashSet::from([2]))]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Schema-agnostic tree view over Arrow arrays
+//!
+//! Unlike [`ArRowDeserialize`](crate::deserialize::ArRowDeserialize), this does not
+//! need a Rust structure matching the schema: [`ArrayTree`] mirrors the nesting of
+//! an array's [`DataType`](arrow::datatypes::DataType) at runtime, so generic tools
+//! (like the JSON converter of the `json` feature) can walk structs, lists, maps and
+//! dictionaries without downcasting arrays themselves.
+
+use std::ops::Range;
+
+use arrow::array::*;
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatchReader;
+
+/// Offsets of the elements of each row of a list-like array in its child array
+#[derive(Debug, Clone, Copy)]
+pub enum ListOffsets<'a> {
+    /// Offsets of a `List` or `Map` array
+    Small(&'a [i32]),
+    /// Offsets of a `LargeList` array
+    Large(&'a [i64]),
+    /// Size of every list of a `FixedSizeList` array
+    Fixed(usize),
+}
+
+impl<'a> ListOffsets<'a> {
+    /// Returns the range of items of the `i`-th row in the child array
+    pub fn range(&self, i: usize) -> Range<usize> {
+        match self {
+            ListOffsets::Small(offsets) => (offsets[i] as usize)..(offsets[i + 1] as usize),
+            ListOffsets::Large(offsets) => (offsets[i] as usize)..(offsets[i + 1] as usize),
+            ListOffsets::Fixed(size) => (i * size)..((i + 1) * size),
+        }
+    }
+}
+
+/// Dynamically typed view of an array and its children
+pub enum ArrayTree<'a> {
+    /// Array without children: booleans, numbers, strings, binaries, temporal types, ...
+    Leaf(&'a dyn Array),
+    /// Struct array, with the name and view of each of its fields
+    Struct {
+        array: &'a StructArray,
+        fields: Vec<(&'a str, ArrayTree<'a>)>,
+    },
+    /// `List`, `LargeList`, or `FixedSizeList` array
+    List {
+        array: &'a dyn Array,
+        offsets: ListOffsets<'a>,
+        elements: Box<ArrayTree<'a>>,
+    },
+    /// Map array; `keys` and `values` have one item per entry across all rows
+    Map {
+        array: &'a MapArray,
+        offsets: ListOffsets<'a>,
+        keys: Box<ArrayTree<'a>>,
+        values: Box<ArrayTree<'a>>,
+    },
+    /// Dictionary array; `keys` has one index into `values` per row
+    Dictionary {
+        array: &'a dyn Array,
+        keys: Vec<usize>,
+        values: Box<ArrayTree<'a>>,
+    },
+    /// Union array, with the type id, name, and view of each of its variants
+    Union {
+        array: &'a UnionArray,
+        variants: Vec<(i8, &'a str, ArrayTree<'a>)>,
+    },
+}
+
+impl<'a> ArrayTree<'a> {
+    /// Builds a view of `array` and, recursively, of its children
+    pub fn new(array: &'a dyn Array) -> ArrayTree<'a> {
+        match array.data_type() {
+            DataType::Struct(_) => {
+                let array = array.as_struct();
+                ArrayTree::Struct {
+                    array,
+                    fields: array
+                        .column_names()
+                        .into_iter()
+                        .zip(array.columns())
+                        .map(|(name, column)| (name, ArrayTree::new(column.as_ref())))
+                        .collect(),
+                }
+            }
+            DataType::List(_) => {
+                let list = array.as_list::<i32>();
+                ArrayTree::List {
+                    array,
+                    offsets: ListOffsets::Small(list.value_offsets()),
+                    elements: Box::new(ArrayTree::new(list.values().as_ref())),
+                }
+            }
+            DataType::LargeList(_) => {
+                let list = array.as_list::<i64>();
+                ArrayTree::List {
+                    array,
+                    offsets: ListOffsets::Large(list.value_offsets()),
+                    elements: Box::new(ArrayTree::new(list.values().as_ref())),
+                }
+            }
+            DataType::FixedSizeList(_, size) => {
+                let list = array.as_fixed_size_list();
+                ArrayTree::List {
+                    array,
+                    offsets: ListOffsets::Fixed(*size as usize),
+                    elements: Box::new(ArrayTree::new(list.values().as_ref())),
+                }
+            }
+            DataType::Map(_, _) => {
+                let map = array.as_map();
+                ArrayTree::Map {
+                    array: map,
+                    offsets: ListOffsets::Small(map.value_offsets()),
+                    keys: Box::new(ArrayTree::new(map.keys().as_ref())),
+                    values: Box::new(ArrayTree::new(map.values().as_ref())),
+                }
+            }
+            DataType::Dictionary(_, _) => {
+                let dictionary = array
+                    .as_any_dictionary_opt()
+                    .expect("DataType::Dictionary array is not a dictionary");
+                ArrayTree::Dictionary {
+                    array,
+                    keys: dictionary.normalized_keys(),
+                    values: Box::new(ArrayTree::new(dictionary.values().as_ref())),
+                }
+            }
+            DataType::Union(fields, _) => {
+                let union = array
+                    .as_any()
+                    .downcast_ref::<UnionArray>()
+                    .expect("DataType::Union array is not a UnionArray");
+                ArrayTree::Union {
+                    array: union,
+                    variants: fields
+                        .iter()
+                        .map(|(type_id, field)| {
+                            (
+                                type_id,
+                                field.name().as_str(),
+                                ArrayTree::new(union.child(type_id).as_ref()),
+                            )
+                        })
+                        .collect(),
+                }
+            }
+            _ => ArrayTree::Leaf(array),
+        }
+    }
+
+    /// Returns the array this is a view of
+    pub fn array(&self) -> &'a dyn Array {
+        match self {
+            ArrayTree::Leaf(array) => *array,
+            ArrayTree::Struct { array, .. } => *array,
+            ArrayTree::List { array, .. } => *array,
+            ArrayTree::Map { array, .. } => *array,
+            ArrayTree::Dictionary { array, .. } => *array,
+            ArrayTree::Union { array, .. } => *array,
+        }
+    }
+
+    /// Returns the number of rows in the array
+    pub fn len(&self) -> usize {
+        self.array().len()
+    }
+
+    /// Returns whether the array has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the `i`-th row is null
+    pub fn is_null(&self, i: usize) -> bool {
+        self.array().is_null(i)
+    }
+}
+
+/// Wrapper for a [`RecordBatchReader`] which returns an [`ArrayTree`] for each
+/// record batch.
+///
+/// This is a lending iterator (the tree borrows from the reader), so it does not
+/// implement [`Iterator`].
+pub struct StructuredRowReader<R: RecordBatchReader> {
+    reader: R,
+    batch: Option<StructArray>,
+}
+
+impl<R: RecordBatchReader> StructuredRowReader<R> {
+    pub fn new(reader: R) -> StructuredRowReader<R> {
+        StructuredRowReader {
+            reader,
+            batch: None,
+        }
+    }
+
+    /// Returns the schema of the underlying reader
+    pub fn schema(&self) -> SchemaRef {
+        self.reader.schema()
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next record batch, and returns a view of it as an
+    /// [`ArrayTree::Struct`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<ArrayTree<'_>, ArrowError>> {
+        match self.reader.next()? {
+            Ok(batch) => {
+                let batch = self.batch.insert(batch.into());
+                Some(Ok(ArrayTree::new(&*batch)))
+            }
+            Err(e) => {
+                self.batch = None;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::datatypes::Int32Type;
+    use arrow::record_batch::{RecordBatch, RecordBatchIterator};
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![Some(3)]),
+        ]);
+        RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(StringArray::from(vec![Some("foo"), None, Some("baz")])) as ArrayRef,
+            ),
+            ("b", Arc::new(list) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_array_tree() {
+        let array: StructArray = batch().into();
+        let tree = ArrayTree::new(&array);
+        assert_eq!(tree.len(), 3);
+        let fields = match tree {
+            ArrayTree::Struct { fields, .. } => fields,
+            _ => panic!("Expected struct"),
+        };
+        assert_eq!(
+            fields.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+
+        match &fields[0].1 {
+            ArrayTree::Leaf(array) => {
+                assert_eq!(array.data_type(), &DataType::Utf8);
+                assert!(array.is_null(1));
+            }
+            _ => panic!("Expected leaf"),
+        }
+
+        match &fields[1].1 {
+            ArrayTree::List {
+                offsets, elements, ..
+            } => {
+                assert!(fields[1].1.is_null(1));
+                assert_eq!(offsets.range(0), 0..2);
+                assert_eq!(offsets.range(1), 2..2);
+                assert_eq!(offsets.range(2), 2..3);
+                assert_eq!(elements.len(), 3);
+            }
+            _ => panic!("Expected list"),
+        }
+    }
+
+    #[test]
+    fn test_structured_row_reader() {
+        let batch = batch();
+        let schema = batch.schema();
+        let batches = vec![Ok(batch.clone()), Ok(batch.slice(1, 2))];
+        let mut reader = StructuredRowReader::new(RecordBatchIterator::new(batches, schema));
+
+        let mut lengths = Vec::new();
+        while let Some(tree) = reader.next() {
+            lengths.push(tree.unwrap().len());
+        }
+        assert_eq!(lengths, vec![3, 2]);
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Conversion of Arrow record batches to CSV
+//!
+//! Nested structs are flattened to one column per leaf field, named after the path
+//! to that field (eg. `a.b`). Other nested values (lists, maps, unions) do not fit
+//! in a table, so they are written as a single cell, configured by [`ListEncoding`].
+//!
+//! Requires the `csv` feature.
+
+use std::io::Write;
+
+use arrow::array::StructArray;
+use arrow::record_batch::RecordBatch;
+use thiserror::Error;
+
+use crate::cells::{cell, flattened_columns, lookup};
+use crate::deserialize::{ColumnNameOptions, ColumnPath, Flattening};
+use crate::structured_reader::ArrayTree;
+use crate::to_json::{tree_to_json_values, BinaryEncoding, ToJsonError, ToJsonOptions};
+
+/// Error returned when failing to write a record batch as CSV
+#[derive(Debug, Error)]
+pub enum ToCsvError {
+    /// The batch could not be converted to cells
+    #[error(transparent)]
+    Json(#[from] ToJsonError),
+    /// The underlying writer failed
+    #[error("Could not write CSV: {0}")]
+    Csv(#[from] csv::Error),
+    /// All batches written by a [`CsvWriter`] must have the same columns
+    #[error("Record batch has columns {actual:?}, expected {expected:?}")]
+    MismatchedColumns {
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
+}
+
+/// How to write cells containing lists (and other non-struct nested values)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ListEncoding {
+    /// Serialized as JSON
+    #[default]
+    Json,
+    /// Items are joined with the given separator, and nested items are serialized
+    /// as JSON
+    Join(String),
+}
+
+/// Options of the conversion to CSV
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Separator between fields names in the header of nested columns
+    pub header_separator: String,
+    /// Separator between cells
+    pub delimiter: u8,
+    /// Representation of lists, maps and unions
+    pub lists: ListEncoding,
+    /// Representation of binary values
+    pub binary: BinaryEncoding,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            header_separator: ".".to_owned(),
+            delimiter: b',',
+            lists: ListEncoding::Json,
+            binary: BinaryEncoding::Base64,
+        }
+    }
+}
+
+/// Writes record batches as CSV, with a header row
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    options: CsvOptions,
+    /// Path to each column, known after the first batch is written
+    columns: Option<Vec<ColumnPath>>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W, options: CsvOptions) -> CsvWriter<W> {
+        CsvWriter {
+            writer: csv::WriterBuilder::new()
+                .delimiter(options.delimiter)
+                .from_writer(writer),
+            options,
+            columns: None,
+        }
+    }
+
+    /// Writes all rows of `batch`, preceded by the header if this is the first batch
+    pub fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ToCsvError> {
+        let columns = flattened_columns(batch.schema().fields());
+        match &self.columns {
+            Some(expected) if *expected != columns => {
+                return Err(ToCsvError::MismatchedColumns {
+                    expected: expected.iter().map(ToString::to_string).collect(),
+                    actual: columns.iter().map(ToString::to_string).collect(),
+                })
+            }
+            Some(_) => (),
+            None => {
+                let name_options = ColumnNameOptions {
+                    separator: self.options.header_separator.clone(),
+                    flattening: Flattening::FullPath,
+                };
+                self.writer
+                    .write_record(columns.iter().map(|column| column.to_name(&name_options)))?;
+                self.columns = Some(columns);
+            }
+        }
+        let columns = self.columns.as_ref().expect("columns are unset");
+
+        let json_options = ToJsonOptions {
+            binary: self.options.binary,
+            fixed_size_binary: self.options.binary,
+        };
+        let list_separator = match &self.options.lists {
+            ListEncoding::Json => None,
+            ListEncoding::Join(separator) => Some(separator.as_str()),
+        };
+        let array: StructArray = batch.clone().into();
+        for row in tree_to_json_values(&ArrayTree::new(&array), &json_options)? {
+            self.writer.write_record(
+                columns
+                    .iter()
+                    .map(|column| cell(lookup(&row, column.parts()), list_separator)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn into_inner(self) -> Result<W, ToCsvError> {
+        self.writer
+            .into_inner()
+            .map_err(|e| ToCsvError::Csv(e.into_error().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::*;
+    use arrow::datatypes::{DataType, Field, Int32Type};
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let inner = StructArray::from(vec![
+            (
+                Arc::new(Field::new("c", DataType::Int32, true)),
+                Arc::new(Int32Array::from(vec![Some(1), None])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("d", DataType::Binary, true)),
+                Arc::new(BinaryArray::from(vec![Some(&b"ab"[..]), None])) as ArrayRef,
+            ),
+        ]);
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+        ]);
+        RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(StringArray::from(vec![Some("x,y"), None])) as ArrayRef,
+            ),
+            ("b", Arc::new(inner) as ArrayRef),
+            ("e", Arc::new(list) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    fn to_csv(options: CsvOptions) -> String {
+        let mut writer = CsvWriter::new(Vec::new(), options);
+        writer.write_batch(&batch()).unwrap();
+        writer.write_batch(&batch().slice(1, 1)).unwrap();
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_default_options() {
+        assert_eq!(
+            to_csv(CsvOptions::default()),
+            "a,b.c,b.d,e\n\"x,y\",1,YWI=,\"[1,2]\"\n,,,\n,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_options() {
+        let options = CsvOptions {
+            header_separator: "_".to_owned(),
+            delimiter: b';',
+            lists: ListEncoding::Join("|".to_owned()),
+            binary: BinaryEncoding::Hex,
+        };
+        assert_eq!(to_csv(options), "a;b_c;b_d;e\nx,y;1;6162;1|2\n;;;\n;;;\n");
+    }
+
+    #[test]
+    fn test_mismatched_columns() {
+        let mut writer = CsvWriter::new(Vec::new(), CsvOptions::default());
+        writer.write_batch(&batch()).unwrap();
+        let other = batch().project(&[0]).unwrap();
+        assert!(matches!(
+            writer.write_batch(&other),
+            Err(ToCsvError::MismatchedColumns { .. })
+        ));
+    }
+}
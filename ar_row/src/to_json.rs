@@ -0,0 +1,555 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Conversion of Arrow arrays to JSON values, one per row
+//!
+//! This works on any [`RecordBatch`], without needing a Rust structure matching
+//! its schema, by walking its [`ArrayTree`].
+//!
+//! Requires the `json` feature.
+
+use std::fmt::Write;
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::display::value_formatter;
+use crate::structured_reader::{ArrayTree, ListOffsets};
+
+/// Error returned when failing to convert an array to JSON
+#[derive(Debug, Error, PartialEq)]
+pub enum ToJsonError {
+    /// The array contains values of a type which has no JSON representation yet
+    #[error("Cannot convert Arrow {0} values to JSON")]
+    UnsupportedDataType(DataType),
+    /// Arrow failed to format values to strings
+    #[error("Could not format values: {0}")]
+    Format(String),
+}
+
+/// How to represent binary values in JSON
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinaryEncoding {
+    /// Array of numbers, one per byte
+    #[default]
+    Array,
+    /// String of the standard base64 encoding, with padding
+    Base64,
+    /// String of lowercase hexadecimal digits
+    Hex,
+    /// String decoded as UTF-8, replacing invalid sequences with U+FFFD
+    LossyUtf8,
+}
+
+/// Options of the conversion to JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToJsonOptions {
+    /// Representation of `Binary` and `LargeBinary` values
+    pub binary: BinaryEncoding,
+    /// Representation of `FixedSizeBinary` values
+    pub fixed_size_binary: BinaryEncoding,
+}
+
+/// Converts a [`RecordBatch`] to a JSON object for each of its rows
+pub fn record_batch_to_json_rows(batch: &RecordBatch) -> Result<Vec<Value>, ToJsonError> {
+    record_batch_to_json_rows_with_options(batch, &ToJsonOptions::default())
+}
+
+/// Same as [`record_batch_to_json_rows`], with non-default options
+pub fn record_batch_to_json_rows_with_options(
+    batch: &RecordBatch,
+    options: &ToJsonOptions,
+) -> Result<Vec<Value>, ToJsonError> {
+    let array: StructArray = batch.clone().into();
+    tree_to_json_values(&ArrayTree::new(&array), options)
+}
+
+/// Converts an array to a JSON value for each of its rows.
+///
+/// Null values are converted to [`Value::Null`].
+pub fn array_to_json_values(array: &dyn Array) -> Result<Vec<Value>, ToJsonError> {
+    tree_to_json_values(&ArrayTree::new(array), &ToJsonOptions::default())
+}
+
+/// Same as [`array_to_json_values`], but works on an existing [`ArrayTree`]
+pub fn tree_to_json_values(
+    tree: &ArrayTree<'_>,
+    options: &ToJsonOptions,
+) -> Result<Vec<Value>, ToJsonError> {
+    match tree {
+        ArrayTree::Leaf(array) => leaf_to_json_values(*array, options),
+        ArrayTree::Struct { array, fields } => {
+            let mut columns = Vec::with_capacity(fields.len());
+            for (name, field) in fields {
+                columns.push((name, tree_to_json_values(field, options)?.into_iter()));
+            }
+            Ok((0..array.len())
+                .map(|i| {
+                    let mut object = Map::new();
+                    for (name, values) in columns.iter_mut() {
+                        object.insert(name.to_string(), values.next().unwrap_or(Value::Null));
+                    }
+                    if array.is_null(i) {
+                        Value::Null
+                    } else {
+                        Value::Object(object)
+                    }
+                })
+                .collect())
+        }
+        ArrayTree::List {
+            offsets, elements, ..
+        } => Ok(group_values(
+            tree,
+            offsets,
+            tree_to_json_values(elements, options)?,
+            Value::Array,
+        )),
+        ArrayTree::Map {
+            array,
+            offsets,
+            keys,
+            values,
+        } => {
+            let keys_are_strings = matches!(array.key_type(), DataType::Utf8 | DataType::LargeUtf8);
+            let entries = tree_to_json_values(keys, options)?
+                .into_iter()
+                .zip(tree_to_json_values(values, options)?)
+                .collect();
+            Ok(group_values(tree, offsets, entries, |entries| {
+                map_to_json(entries, keys_are_strings)
+            }))
+        }
+        ArrayTree::Dictionary {
+            array,
+            keys,
+            values,
+        } => {
+            let values = tree_to_json_values(values, options)?;
+            Ok(keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| {
+                    if array.is_null(i) {
+                        Value::Null
+                    } else {
+                        values.get(*key).cloned().unwrap_or(Value::Null)
+                    }
+                })
+                .collect())
+        }
+        ArrayTree::Union { array, variants } => union_to_json_values(array, variants, options),
+    }
+}
+
+fn leaf_to_json_values(
+    array: &dyn Array,
+    options: &ToJsonOptions,
+) -> Result<Vec<Value>, ToJsonError> {
+    macro_rules! primitive {
+        ($ty:ty) => {
+            array
+                .as_primitive::<$ty>()
+                .iter()
+                .map(|value| value.map(Value::from).unwrap_or(Value::Null))
+                .collect()
+        };
+    }
+
+    Ok(match array.data_type() {
+        DataType::Null => vec![Value::Null; array.len()],
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|value| value.map(Value::Bool).unwrap_or(Value::Null))
+            .collect(),
+        DataType::Int8 => primitive!(Int8Type),
+        DataType::Int16 => primitive!(Int16Type),
+        DataType::Int32 => primitive!(Int32Type),
+        DataType::Int64 => primitive!(Int64Type),
+        DataType::UInt8 => primitive!(UInt8Type),
+        DataType::UInt16 => primitive!(UInt16Type),
+        DataType::UInt32 => primitive!(UInt32Type),
+        DataType::UInt64 => primitive!(UInt64Type),
+        DataType::Float16 => array
+            .as_primitive::<Float16Type>()
+            .iter()
+            .map(|value| {
+                value
+                    .map(|value| Value::from(value.to_f32()))
+                    .unwrap_or(Value::Null)
+            })
+            .collect(),
+        DataType::Float32 => primitive!(Float32Type),
+        DataType::Float64 => primitive!(Float64Type),
+        DataType::Utf8 => strings_to_json(array.as_string::<i32>()),
+        DataType::LargeUtf8 => strings_to_json(array.as_string::<i64>()),
+        DataType::Binary => binaries_to_json(array.as_binary::<i32>(), options.binary),
+        DataType::LargeBinary => binaries_to_json(array.as_binary::<i64>(), options.binary),
+        DataType::FixedSizeBinary(_) => {
+            binaries_to_json(array.as_fixed_size_binary(), options.fixed_size_binary)
+        }
+        DataType::Date32
+        | DataType::Date64
+        | DataType::Time32(_)
+        | DataType::Time64(_)
+        | DataType::Timestamp(_, _)
+        | DataType::Duration(_)
+        | DataType::Interval(_)
+        | DataType::Decimal128(_, _)
+        | DataType::Decimal256(_, _) => formatted_to_json(array)?,
+        datatype => return Err(ToJsonError::UnsupportedDataType(datatype.clone())),
+    })
+}
+
+/// Converts values to JSON strings (see [`value_formatter`])
+fn formatted_to_json(array: &dyn Array) -> Result<Vec<Value>, ToJsonError> {
+    let formatter = value_formatter(array).map_err(|e| ToJsonError::Format(e.to_string()))?;
+    Ok((0..array.len())
+        .map(|i| {
+            if array.is_null(i) {
+                Value::Null
+            } else {
+                Value::String(formatter.value(i).to_string())
+            }
+        })
+        .collect())
+}
+
+/// Converts each row of a union to a single-key object, whose key is the name
+/// of the variant
+fn union_to_json_values(
+    array: &UnionArray,
+    variants: &[(i8, &str, ArrayTree<'_>)],
+    options: &ToJsonOptions,
+) -> Result<Vec<Value>, ToJsonError> {
+    let mut variant_values = Vec::with_capacity(variants.len());
+    for (type_id, name, tree) in variants {
+        variant_values.push((*type_id, *name, tree_to_json_values(tree, options)?));
+    }
+    Ok((0..array.len())
+        .map(|i| {
+            let type_id = array.type_id(i);
+            match variant_values
+                .iter()
+                .find(|(variant_type_id, _, _)| *variant_type_id == type_id)
+            {
+                Some((_, name, values)) => {
+                    let mut object = Map::new();
+                    object.insert(
+                        name.to_string(),
+                        values
+                            .get(array.value_offset(i))
+                            .cloned()
+                            .unwrap_or(Value::Null),
+                    );
+                    Value::Object(object)
+                }
+                None => Value::Null,
+            }
+        })
+        .collect())
+}
+
+fn strings_to_json<O: OffsetSizeTrait>(array: &GenericStringArray<O>) -> Vec<Value> {
+    array
+        .iter()
+        .map(|value| {
+            value
+                .map(|value| Value::String(value.to_owned()))
+                .unwrap_or(Value::Null)
+        })
+        .collect()
+}
+
+fn binaries_to_json<'a>(
+    array: impl IntoIterator<Item = Option<&'a [u8]>>,
+    encoding: BinaryEncoding,
+) -> Vec<Value> {
+    array
+        .into_iter()
+        .map(|value| {
+            value
+                .map(|value| binary_to_json(value, encoding))
+                .unwrap_or(Value::Null)
+        })
+        .collect()
+}
+
+fn binary_to_json(value: &[u8], encoding: BinaryEncoding) -> Value {
+    match encoding {
+        BinaryEncoding::Array => Value::Array(value.iter().copied().map(Value::from).collect()),
+        BinaryEncoding::Base64 => Value::String(BASE64_STANDARD.encode(value)),
+        BinaryEncoding::Hex => {
+            let mut hex = String::with_capacity(value.len() * 2);
+            for byte in value {
+                write!(hex, "{:02x}", byte).expect("Could not write to String");
+            }
+            Value::String(hex)
+        }
+        BinaryEncoding::LossyUtf8 => Value::String(String::from_utf8_lossy(value).into_owned()),
+    }
+}
+
+/// Groups `values` according to the `offsets` of the list-like `tree`, returning
+/// `Value::Null` for its null rows.
+fn group_values<T>(
+    tree: &ArrayTree<'_>,
+    offsets: &ListOffsets<'_>,
+    values: Vec<T>,
+    mut group: impl FnMut(Vec<T>) -> Value,
+) -> Vec<Value> {
+    let mut values = values.into_iter();
+    let mut position = 0;
+    (0..tree.len())
+        .map(|i| {
+            let range = offsets.range(i);
+            // Skip values outside any row, when the array was sliced
+            for _ in position..range.start {
+                values.next();
+            }
+            let items: Vec<T> = values.by_ref().take(range.len()).collect();
+            position = range.end;
+            if tree.is_null(i) {
+                Value::Null
+            } else {
+                group(items)
+            }
+        })
+        .collect()
+}
+
+/// Converts maps with string keys to JSON objects, and other maps to lists of
+/// `{"key": ..., "value": ...}` objects
+fn map_to_json(entries: Vec<(Value, Value)>, keys_are_strings: bool) -> Value {
+    if keys_are_strings {
+        Value::Object(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| match key {
+                    Value::String(key) => Some((key, value)),
+                    _ => None,
+                })
+                .collect(),
+        )
+    } else {
+        Value::Array(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let mut entry = Map::new();
+                    entry.insert("key".to_owned(), key);
+                    entry.insert("value".to_owned(), value);
+                    Value::Object(entry)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_record_batch_to_json_rows() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![]),
+        ]);
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+            ),
+            (
+                "b",
+                Arc::new(StringArray::from(vec![Some("foo"), Some("bar"), None])) as ArrayRef,
+            ),
+            ("c", Arc::new(list) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            record_batch_to_json_rows(&batch),
+            Ok(vec![
+                json!({"a": 1, "b": "foo", "c": [1, 2]}),
+                json!({"a": null, "b": "bar", "c": null}),
+                json!({"a": 3, "b": null, "c": []}),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sliced_list() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![Some(3)]),
+            Some(vec![Some(4), Some(5)]),
+        ]);
+        assert_eq!(
+            array_to_json_values(&list.slice(1, 2)),
+            Ok(vec![json!([3]), json!([4, 5])])
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_null();
+        builder.append(true).unwrap();
+        builder.append(false).unwrap();
+        builder.append(true).unwrap();
+        assert_eq!(
+            array_to_json_values(&builder.finish()),
+            Ok(vec![json!({"a": 1, "b": null}), json!(null), json!({})])
+        );
+    }
+
+    #[test]
+    fn test_binary_encodings() {
+        let binary = BinaryArray::from(vec![Some(&b"\x00\xffa"[..]), None]);
+        let fixed_size_binary =
+            FixedSizeBinaryArray::try_from_iter(vec![b"ab", b"\xc3("].into_iter()).unwrap();
+        let batch = RecordBatch::try_from_iter(vec![
+            ("binary", Arc::new(binary) as ArrayRef),
+            ("fixed", Arc::new(fixed_size_binary) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            record_batch_to_json_rows(&batch),
+            Ok(vec![
+                json!({"binary": [0, 255, 97], "fixed": [97, 98]}),
+                json!({"binary": null, "fixed": [195, 40]}),
+            ])
+        );
+
+        let expected = [
+            (
+                BinaryEncoding::Base64,
+                json!("AP9h"),
+                json!("YWI="),
+                json!("wyg="),
+            ),
+            (
+                BinaryEncoding::Hex,
+                json!("00ff61"),
+                json!("6162"),
+                json!("c328"),
+            ),
+            (
+                BinaryEncoding::LossyUtf8,
+                json!("\u{0}\u{fffd}a"),
+                json!("ab"),
+                json!("\u{fffd}("),
+            ),
+        ];
+        for (encoding, binary, fixed1, fixed2) in expected {
+            let options = ToJsonOptions {
+                binary: encoding,
+                fixed_size_binary: encoding,
+            };
+            assert_eq!(
+                record_batch_to_json_rows_with_options(&batch, &options),
+                Ok(vec![
+                    json!({"binary": binary, "fixed": fixed1}),
+                    json!({"binary": null, "fixed": fixed2}),
+                ]),
+                "{:?}",
+                encoding
+            );
+        }
+    }
+
+    #[test]
+    fn test_temporal_and_decimal() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "date",
+                Arc::new(Date32Array::from(vec![Some(19000), None])) as ArrayRef,
+            ),
+            (
+                "timestamp",
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    Some(1_500_000_000_123_456_789),
+                    Some(-1),
+                ])) as ArrayRef,
+            ),
+            (
+                "timestamp_tz",
+                Arc::new(TimestampSecondArray::from(vec![Some(0), None]).with_timezone("+02:00"))
+                    as ArrayRef,
+            ),
+            (
+                "decimal",
+                Arc::new(
+                    Decimal128Array::from(vec![Some(12345), Some(-5)])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            record_batch_to_json_rows(&batch),
+            Ok(vec![
+                json!({
+                    "date": "2022-01-08",
+                    "timestamp": "2017-07-14T02:40:00.123456789Z",
+                    "timestamp_tz": "1970-01-01T02:00:00+02:00",
+                    "decimal": "123.45",
+                }),
+                json!({
+                    "date": null,
+                    "timestamp": "1969-12-31T23:59:59.999999999Z",
+                    "timestamp_tz": null,
+                    "decimal": "-0.05",
+                }),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        let mut builder = UnionBuilder::new_dense();
+        builder.append::<Int32Type>("int", 1).unwrap();
+        builder.append::<Float64Type>("float", 2.5).unwrap();
+        builder.append::<Int32Type>("int", 3).unwrap();
+        let array = builder.build().unwrap();
+        assert_eq!(
+            array_to_json_values(&array),
+            Ok(vec![
+                json!({"int": 1}),
+                json!({"float": 2.5}),
+                json!({"int": 3}),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let array: DictionaryArray<Int8Type> = vec![Some("a"), None, Some("b"), Some("a")]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            array_to_json_values(&array),
+            Ok(vec![json!("a"), json!(null), json!("b"), json!("a")])
+        );
+    }
+}
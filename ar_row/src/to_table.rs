@@ -0,0 +1,186 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Rendering of Arrow record batches as aligned text tables, for quick inspection
+//!
+//! Columns are flattened like in CSV output: each leaf field of nested structs gets
+//! its own column, and other nested values are written as JSON.
+//!
+//! Requires the `json` feature.
+
+use arrow::array::StructArray;
+use arrow::record_batch::RecordBatch;
+
+use crate::cells::{cell, flattened_columns, lookup};
+use crate::structured_reader::ArrayTree;
+use crate::to_json::{tree_to_json_values, BinaryEncoding, ToJsonError, ToJsonOptions};
+
+/// Options of the rendering of tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOptions {
+    /// Maximum number of characters in a cell; longer values are truncated
+    /// and end with `…`
+    pub max_cell_width: usize,
+    /// Representation of binary values
+    pub binary: BinaryEncoding,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            max_cell_width: 40,
+            binary: BinaryEncoding::Hex,
+        }
+    }
+}
+
+/// Renders all rows of `batches` as a table, whose columns are those of the first
+/// batch.
+///
+/// ```text
+/// +---+-----+-----+
+/// | a | b.c | b.d |
+/// +---+-----+-----+
+/// | 1 | foo | 2.5 |
+/// | 2 |     |     |
+/// +---+-----+-----+
+/// ```
+pub fn format_table(
+    batches: &[RecordBatch],
+    options: &TableOptions,
+) -> Result<String, ToJsonError> {
+    let columns = match batches.first() {
+        Some(batch) => flattened_columns(batch.schema().fields()),
+        None => return Ok(String::new()),
+    };
+    let json_options = ToJsonOptions {
+        binary: options.binary,
+        fixed_size_binary: options.binary,
+    };
+
+    let mut rows = vec![columns.iter().map(ToString::to_string).collect::<Vec<_>>()];
+    for batch in batches {
+        let array: StructArray = batch.clone().into();
+        for row in tree_to_json_values(&ArrayTree::new(&array), &json_options)? {
+            rows.push(
+                columns
+                    .iter()
+                    .map(|column| truncate(cell(lookup(&row, column.parts()), None), options))
+                    .collect(),
+            );
+        }
+    }
+
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let separator: String = widths
+        .iter()
+        .map(|width| format!("+{}", "-".repeat(width + 2)))
+        .chain(std::iter::once("+\n".to_owned()))
+        .collect();
+
+    let mut table = separator.clone();
+    for (i, row) in rows.iter().enumerate() {
+        for (value, width) in row.iter().zip(&widths) {
+            let padding = width - value.chars().count();
+            table.push_str(&format!("| {}{} ", value, " ".repeat(padding)));
+        }
+        table.push_str("|\n");
+        if i == 0 {
+            table.push_str(&separator);
+        }
+    }
+    table.push_str(&separator);
+    Ok(table)
+}
+
+/// Replaces line breaks, and shortens `value` to `options.max_cell_width` characters
+fn truncate(value: String, options: &TableOptions) -> String {
+    let value = value.replace('\n', "\\n");
+    if value.chars().count() <= options.max_cell_width {
+        value
+    } else {
+        let mut truncated: String = value
+            .chars()
+            .take(options.max_cell_width.saturating_sub(1))
+            .collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::*;
+    use arrow::datatypes::{DataType, Field};
+
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let inner = StructArray::from(vec![
+            (
+                Arc::new(Field::new("c", DataType::Utf8, true)),
+                Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("d", DataType::Float64, true)),
+                Arc::new(Float64Array::from(vec![Some(2.5), None])) as ArrayRef,
+            ),
+        ]);
+        let batch = RecordBatch::try_from_iter(vec![
+            ("a", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+            ("b", Arc::new(inner) as ArrayRef),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            format_table(&[batch], &TableOptions::default()),
+            Ok("\
++---+-----+-----+
+| a | b.c | b.d |
++---+-----+-----+
+| 1 | foo | 2.5 |
+| 2 |     |     |
++---+-----+-----+
+"
+            .to_owned())
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let batch = RecordBatch::try_from_iter(vec![(
+            "a",
+            Arc::new(StringArray::from(vec!["abcdef", "ab\ncd"])) as ArrayRef,
+        )])
+        .unwrap();
+        let options = TableOptions {
+            max_cell_width: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_table(&[batch], &options),
+            Ok("\
++------+
+| a    |
++------+
+| abc… |
+| ab\\… |
++------+
+"
+            .to_owned())
+        );
+    }
+}
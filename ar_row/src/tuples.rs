@@ -0,0 +1,400 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Implementations of [`ArRowDeserialize`] for tuples of up to 6 elements, and for
+//! [`MapEntry`]
+//!
+//! Tuples are read from structures with exactly as many fields, positionally: field
+//! names are ignored. As they do not know the names of their columns, they do not
+//! implement [`ArRowStruct`](crate::deserialize::ArRowStruct).
+//!
+//! [`MapEntry`] is read the same way as a pair, from the entries of maps. It is
+//! always read from a whole map column, so it implements
+//! [`ArRowStruct`](crate::deserialize::ArRowStruct) as a single column and can be
+//! used in structures deriving `ArRowDeserialize`.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::deserialize::ArRowDeserialize;
+//!
+//! let batch = RecordBatch::try_from_iter(vec![
+//!     ("id", Arc::new(UInt64Array::from(vec![1, 2])) as ArrayRef),
+//!     ("name", Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef),
+//! ])
+//! .unwrap();
+//! assert_eq!(
+//!     <(u64, Option<String>)>::from_record_batch(batch).unwrap(),
+//!     vec![(1, Some("foo".to_owned())), (2, None)]
+//! );
+//! ```
+
+use arrow::array::*;
+use arrow::datatypes::*;
+
+use crate::array_iterators::ValidityRuns;
+use crate::deserialize::{
+    ArRowDeserialize, ArRowDeserializeOption, ArRowStruct, CheckableDataType, ColumnTree,
+    DeserializationError, DeserializationTarget,
+};
+use crate::dictionaries::{decoded_datatype, unpack_dictionary, unpack_run_end_encoded};
+use crate::MapEntry;
+
+/// Returns the columns of `src`, after checking it is a structure with `num_fields`
+/// fields and at most `dst_len` rows
+fn tuple_columns(
+    src: &(impl Array + AsArray),
+    num_fields: usize,
+    dst_len: usize,
+) -> Result<&[ArrayRef], DeserializationError> {
+    let src = src.as_struct_opt().ok_or_else(|| {
+        DeserializationError::MismatchedColumnDataType(format!(
+            "Could not cast {:?} array to struct array",
+            src.data_type(),
+        ))
+    })?;
+    if src.num_columns() != num_fields {
+        return Err(DeserializationError::MismatchedColumnDataType(format!(
+            "{}-tuple cannot be read from a structure with {} fields",
+            num_fields,
+            src.num_columns()
+        )));
+    }
+    if src.len() > dst_len {
+        return Err(DeserializationError::MismatchedLength {
+            src: src.len(),
+            dst: dst_len,
+        });
+    }
+    Ok(src.columns())
+}
+
+macro_rules! impl_tuple {
+    ($num_fields:literal; $($index:tt: $ty:ident),+) => {
+        impl<$($ty: CheckableDataType),+> CheckableDataType for ($($ty,)+) {
+            fn check_datatype(datatype: &DataType) -> Result<(), String> {
+                let datatype = decoded_datatype(datatype);
+                match datatype {
+                    DataType::Struct(fields) if fields.len() == $num_fields => {
+                        let errors: Vec<String> = [$(
+                            <$ty as CheckableDataType>::check_datatype(fields[$index].data_type())
+                                .err()
+                                .map(|e| format!(
+                                    "Field #{} ({}) cannot be decoded: {}",
+                                    $index,
+                                    fields[$index].name(),
+                                    e
+                                )),
+                        )+]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                        if errors.is_empty() {
+                            Ok(())
+                        } else {
+                            Err(format!(
+                                "{}-tuple cannot be decoded:\n\t{}",
+                                $num_fields,
+                                errors.join("\n").replace('\n', "\n\t")
+                            ))
+                        }
+                    }
+                    DataType::Struct(fields) => Err(format!(
+                        "{}-tuple cannot be decoded from a structure with {} fields",
+                        $num_fields,
+                        fields.len()
+                    )),
+                    _ => Err(format!(
+                        "{}-tuple must be decoded from DataType::Struct, not {:?}",
+                        $num_fields, datatype
+                    )),
+                }
+            }
+
+            fn expected_datatype() -> DataType {
+                let fields: Vec<Field> = vec![$(
+                    Field::new(
+                        stringify!($index),
+                        <$ty as CheckableDataType>::expected_datatype(),
+                        <$ty as CheckableDataType>::nullable(),
+                    ),
+                )+];
+                DataType::Struct(fields.into())
+            }
+        }
+
+        impl<$($ty: ArRowDeserialize),+> ArRowDeserialize for ($($ty,)+) {
+            fn read_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+            {
+                if let Some(src) = src.as_any_dictionary_opt() {
+                    return Self::read_from_array(unpack_dictionary(src)?, dst);
+                }
+                if let Some(src) = unpack_run_end_encoded(&src)? {
+                    return Self::read_from_array(src, dst);
+                }
+                let columns = tuple_columns(&src, $num_fields, dst.len())?;
+                $(
+                    let values = <$ty as ArRowDeserialize>::from_array(columns[$index].clone())?;
+                    for (tuple, value) in dst.iter_mut().zip(values) {
+                        tuple.$index = value;
+                    }
+                )+
+                Ok(src.len())
+            }
+        }
+
+        impl<$($ty: ArRowDeserialize),+> ArRowDeserializeOption for ($($ty,)+) {
+            fn read_options_from_array<'a, 'b, T>(
+                src: impl Array + AsArray,
+                mut dst: &'b mut T,
+            ) -> Result<usize, DeserializationError>
+            where
+                &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+            {
+                if let Some(src) = src.as_any_dictionary_opt() {
+                    return Self::read_options_from_array(unpack_dictionary(src)?, dst);
+                }
+                if let Some(src) = unpack_run_end_encoded(&src)? {
+                    return Self::read_options_from_array(src, dst);
+                }
+                let columns = tuple_columns(&src, $num_fields, dst.len())?;
+                // Child arrays have values even for null structs, so write every row
+                // before setting null ones to None.
+                $(
+                    let values = <$ty as ArRowDeserialize>::from_array(columns[$index].clone())?;
+                    for (tuple, value) in dst.iter_mut().zip(values) {
+                        tuple.get_or_insert_with(Default::default).$index = value;
+                    }
+                )+
+                let mut dst_iter = dst.iter_mut();
+                for (is_valid, run_length) in ValidityRuns::new(src.nulls(), src.len()) {
+                    if is_valid {
+                        let _ = dst_iter.nth(run_length - 1);
+                    } else {
+                        for tuple in dst_iter.by_ref().take(run_length) {
+                            *tuple = None;
+                        }
+                    }
+                }
+                Ok(src.len())
+            }
+        }
+    };
+}
+
+impl_tuple!(1; 0: A);
+impl_tuple!(2; 0: A, 1: B);
+impl_tuple!(3; 0: A, 1: B, 2: C);
+impl_tuple!(4; 0: A, 1: B, 2: C, 3: D);
+impl_tuple!(5; 0: A, 1: B, 2: C, 3: D, 4: E);
+impl_tuple!(6; 0: A, 1: B, 2: C, 3: D, 4: E, 5: F);
+
+/// Structures deriving `ArRowDeserialize` need [`ArRowStruct`] for each of their
+/// fields' types, including through `Vec<T>`, which forwards to `T`. Map columns
+/// are selected as a whole (their keys and values are never projected separately),
+/// so `MapEntry` is a single column, like primitive types, rather than a structure
+/// of two columns.
+impl<K, V> ArRowStruct for MapEntry<K, V> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+impl<K: CheckableDataType, V: CheckableDataType> CheckableDataType for MapEntry<K, V> {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        let datatype = decoded_datatype(datatype);
+        match datatype {
+            DataType::Struct(fields) if fields.len() == 2 => {
+                K::check_datatype(fields[0].data_type())
+                    .map_err(|e| format!("MapEntry key cannot be decoded: {}", e))?;
+                V::check_datatype(fields[1].data_type())
+                    .map_err(|e| format!("MapEntry value cannot be decoded: {}", e))
+            }
+            _ => Err(format!(
+                "MapEntry must be decoded from DataType::Struct with 2 fields, not {:?}",
+                datatype
+            )),
+        }
+    }
+
+    fn expected_datatype() -> DataType {
+        let fields = vec![
+            Field::new("key", K::expected_datatype(), false),
+            Field::new("value", V::expected_datatype(), V::nullable()),
+        ];
+        DataType::Struct(fields.into())
+    }
+}
+
+impl<K: ArRowDeserialize, V: ArRowDeserialize> ArRowDeserialize for MapEntry<K, V> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        if let Some(src) = src.as_any_dictionary_opt() {
+            return Self::read_from_array(unpack_dictionary(src)?, dst);
+        }
+        if let Some(src) = unpack_run_end_encoded(&src)? {
+            return Self::read_from_array(src, dst);
+        }
+        // Entries of maps are never null, but the entries struct array may still
+        // have a validity bitmap
+        if src.null_count() > 0 {
+            return Err(DeserializationError::UnexpectedNull(
+                "MapEntry column contains nulls".to_string(),
+            ));
+        }
+        let columns = tuple_columns(&src, 2, dst.len())?;
+        let keys = K::from_array(columns[0].clone())?;
+        for (entry, key) in dst.iter_mut().zip(keys) {
+            entry.key = key;
+        }
+        let values = V::from_array(columns[1].clone())?;
+        for (entry, value) in dst.iter_mut().zip(values) {
+            entry.value = value;
+        }
+        Ok(src.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn array() -> StructArray {
+        StructArray::from(vec![
+            (
+                Arc::new(Field::new("a", DataType::Int32, false)),
+                Arc::new(Int32Array::from(vec![1, 2, 3])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("b", DataType::Utf8, true)),
+                Arc::new(StringArray::from(vec![Some("foo"), None, Some("baz")])) as ArrayRef,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_check_datatype() {
+        let datatype = array().data_type().clone();
+        assert_eq!(<(i32, Option<String>)>::check_datatype(&datatype), Ok(()));
+        assert!(<(i32, String, i32)>::check_datatype(&datatype).is_err());
+        assert!(<(i32,)>::check_datatype(&datatype).is_err());
+        assert!(<(String, String)>::check_datatype(&datatype).is_err());
+        assert_eq!(
+            <(i32, Option<String>)>::expected_datatype(),
+            DataType::Struct(
+                vec![
+                    Field::new("0", DataType::Int32, false),
+                    Field::new("1", DataType::Utf8, true),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        let array: ArrayRef = Arc::new(array());
+        assert_eq!(
+            <(i32, Option<String>)>::from_array(array.clone()),
+            Ok(vec![
+                (1, Some("foo".to_owned())),
+                (2, None),
+                (3, Some("baz".to_owned()))
+            ])
+        );
+        assert!(<(i32, String)>::from_array(array.clone()).is_err());
+        assert!(<(i32,)>::from_array(array).is_err());
+    }
+
+    #[test]
+    fn test_map_entries() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_null();
+        builder.append(true).unwrap();
+        builder.append(false).unwrap();
+        builder.append(true).unwrap();
+        builder.keys().append_value("a");
+        builder.values().append_value(3);
+        builder.append(true).unwrap();
+        let array: ArrayRef = Arc::new(builder.finish());
+
+        type Entries = Vec<MapEntry<String, Option<i32>>>;
+        assert_eq!(Entries::check_datatype(array.data_type()), Ok(()));
+        assert!(<Vec<MapEntry<i32, Option<i32>>>>::check_datatype(array.data_type()).is_err());
+        let entry = |key: &str, value| MapEntry {
+            key: key.to_owned(),
+            value,
+        };
+        assert_eq!(
+            <Option<Entries>>::from_array(array.clone()),
+            Ok(vec![
+                Some(vec![entry("a", Some(1)), entry("b", None)]),
+                None,
+                Some(vec![]),
+                Some(vec![entry("a", Some(3))]),
+            ])
+        );
+        assert!(matches!(
+            Entries::from_array(array),
+            Err(DeserializationError::UnexpectedNull(_))
+        ));
+    }
+
+    #[test]
+    fn test_null_map_entries() {
+        let array = array();
+        let (fields, columns, _) = array.into_parts();
+        let array: ArrayRef = Arc::new(StructArray::new(
+            fields,
+            columns,
+            Some(vec![true, false, true].into()),
+        ));
+        assert_eq!(
+            <MapEntry<i32, Option<String>>>::from_array(array),
+            Err(DeserializationError::UnexpectedNull(
+                "MapEntry column contains nulls".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_null_tuples() {
+        let array = array();
+        let (fields, columns, _) = array.into_parts();
+        let array: ArrayRef = Arc::new(StructArray::new(
+            fields,
+            columns,
+            Some(vec![true, false, true].into()),
+        ));
+        assert_eq!(
+            <Option<(i32, Option<String>)>>::from_array(array),
+            Ok(vec![
+                Some((1, Some("foo".to_owned()))),
+                None,
+                Some((3, Some("baz".to_owned())))
+            ])
+        );
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! [`RecordBatch`] whose schema was checked against a Rust type
+//!
+//! A [`TypedRecordBatch<T>`] can only be built by
+//! [`TypedRecordBatch::try_new`], which runs
+//! [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema).
+//! Functions taking one as argument therefore do not need to check the schema again,
+//! and cannot be passed a batch whose schema was never checked.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ar_row::arrow::array::*;
+//! # use ar_row::arrow::record_batch::RecordBatch;
+//! use ar_row::typed_batch::TypedRecordBatch;
+//!
+//! fn sum(batch: &TypedRecordBatch<i64>) -> i64 {
+//!     batch.rows().unwrap().into_iter().sum()
+//! }
+//!
+//! let batch =
+//!     RecordBatch::try_from_iter(vec![("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef)])
+//!         .unwrap();
+//! let batch = TypedRecordBatch::<i64>::try_new(batch).unwrap();
+//! assert_eq!(sum(&batch), 3);
+//! assert_eq!(batch.row(1), Ok(Some(2)));
+//! ```
+
+use std::marker::PhantomData;
+
+use arrow::record_batch::RecordBatch;
+
+use crate::deserialize::{ArRowDeserialize, DeserializationError, RootArray};
+
+/// Wrapper for a [`RecordBatch`] whose schema was checked against `T`
+///
+/// See the [module-level documentation](self).
+pub struct TypedRecordBatch<T: ArRowDeserialize> {
+    batch: RecordBatch,
+
+    /// Array rows are read from, decided from the schema checked by
+    /// [`try_new`](Self::try_new)
+    root: RootArray,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: ArRowDeserialize> TypedRecordBatch<T> {
+    /// Checks `batch`'s schema can be deserialized into `T`, and wraps it.
+    ///
+    /// Errors are detailed descriptions of format mismatch (as returned by
+    /// [`CheckableDataType::check_schema`](crate::deserialize::CheckableDataType::check_schema))
+    pub fn try_new(batch: RecordBatch) -> Result<Self, DeserializationError> {
+        let schema = batch.schema();
+        T::check_schema(&schema).map_err(DeserializationError::MismatchedColumnDataType)?;
+        Ok(TypedRecordBatch {
+            batch,
+            root: RootArray::new::<T>(&schema),
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the underlying batch
+    pub fn batch(&self) -> &RecordBatch {
+        &self.batch
+    }
+
+    /// Returns the underlying batch
+    pub fn into_inner(self) -> RecordBatch {
+        self.batch
+    }
+
+    /// Returns the number of rows
+    pub fn len(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// Returns whether there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Deserializes all rows
+    ///
+    /// This can still fail, eg. if a non-nullable field of `T` contains nulls, as
+    /// nullability is not checked with the schema.
+    pub fn rows(&self) -> Result<Vec<T>, DeserializationError> {
+        self.root.read(self.batch.clone())
+    }
+
+    /// Deserializes the `index`-th row, or returns `None` if it is out of bounds
+    pub fn row(&self, index: usize) -> Result<Option<T>, DeserializationError> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        let mut rows = self.root.read(self.batch.slice(index, 1))?;
+        Ok(rows.pop())
+    }
+
+    /// Returns `length` rows starting from `offset`, without checking the schema again
+    ///
+    /// # Panics
+    ///
+    /// If `offset + length` is greater than [`len`](Self::len).
+    pub fn slice(&self, offset: usize, length: usize) -> Self {
+        TypedRecordBatch {
+            batch: self.batch.slice(offset, length),
+            root: self.root,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ArRowDeserialize> Clone for TypedRecordBatch<T> {
+    fn clone(&self) -> Self {
+        TypedRecordBatch {
+            batch: self.batch.clone(),
+            root: self.root,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ArRowDeserialize> std::fmt::Debug for TypedRecordBatch<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedRecordBatch")
+            .field("type", &std::any::type_name::<T>())
+            .field("batch", &self.batch)
+            .finish()
+    }
+}
@@ -0,0 +1,574 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Dynamically typed values
+//!
+//! [`ArRowValue`] can represent a value of any array supported by `ar_row`, so it
+//! can be used by tools which only know the schema of their input at runtime,
+//! instead of a structure implementing
+//! [`ArRowDeserialize`].
+//!
+//! Both [`ArRowValue`] and `HashMap<String, ArRowValue>` implement [`ArRowDeserialize`],
+//! so they can be used as fields of structures deriving it, to mix typed and untyped
+//! columns; or to read whole rows with [`CheckedReader`](crate::reader::CheckedReader).
+
+use std::collections::HashMap;
+
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+
+use crate::deserialize::{
+    ArRowDeserialize, ArRowDeserializeOption, ArRowStruct, CheckableDataType, ColumnTree,
+    DeserializationError, DeserializationTarget,
+};
+use crate::structured_reader::ArrayTree;
+use crate::{Date, NaiveDecimal128, Timestamp};
+
+/// Number of milliseconds in a day, to convert `Date64` values to [`Date`]
+const MILLISECONDS_PER_DAY: i64 = 86_400_000;
+
+/// Value of a single row of an array of any type supported by `ar_row`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ArRowValue {
+    #[default]
+    Null,
+    Boolean(bool),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    /// Value of a `Float16` or `Float32` array
+    Float32(f32),
+    Float64(f64),
+    /// Value of a `Utf8` or `LargeUtf8` array
+    String(String),
+    /// Value of a `Binary`, `LargeBinary`, or `FixedSizeBinary` array
+    Binary(Vec<u8>),
+    /// Value of a `Decimal128` array, which should be divided by 10^`scale` to get
+    /// a meaningful value
+    Decimal128 {
+        value: NaiveDecimal128,
+        scale: i8,
+    },
+    /// Value of a `Date32` or `Date64` array
+    Date(Date),
+    /// Value of a `Timestamp` array; the timezone, if any, is dropped.
+    Timestamp(Timestamp),
+    /// Value of a `List`, `LargeList`, or `FixedSizeList` array
+    List(Vec<ArRowValue>),
+    /// Entries of a `Map` value, in order
+    Map(Vec<(ArRowValue, ArRowValue)>),
+    /// Fields of a `Struct` value, in order
+    Struct(Vec<(String, ArRowValue)>),
+    /// Value of a `Union` array, with the name of its variant
+    Union {
+        variant: String,
+        value: Box<ArRowValue>,
+    },
+}
+
+impl ArRowValue {
+    /// Returns the value of every row of `array`
+    pub fn from_array(array: &dyn Array) -> Result<Vec<ArRowValue>, DeserializationError> {
+        let tree = ArrayTree::new(array);
+        (0..tree.len())
+            .map(|index| ArRowValue::from_tree(&tree, index))
+            .collect()
+    }
+
+    /// Returns an [`ArRowValue::Struct`] for every row of `batch`
+    pub fn from_record_batch(batch: RecordBatch) -> Result<Vec<ArRowValue>, DeserializationError> {
+        ArRowValue::from_array(&StructArray::from(batch))
+    }
+
+    /// Returns the value of the `index`-th row of `tree`
+    pub fn from_tree(
+        tree: &ArrayTree<'_>,
+        index: usize,
+    ) -> Result<ArRowValue, DeserializationError> {
+        if tree.is_null(index) {
+            return Ok(ArRowValue::Null);
+        }
+        Ok(match tree {
+            ArrayTree::Leaf(array) => leaf_value(*array, index)?,
+            ArrayTree::Struct { fields, .. } => ArRowValue::Struct(
+                fields
+                    .iter()
+                    .map(|(name, field)| {
+                        Ok((name.to_string(), ArRowValue::from_tree(field, index)?))
+                    })
+                    .collect::<Result<_, DeserializationError>>()?,
+            ),
+            ArrayTree::List {
+                offsets, elements, ..
+            } => ArRowValue::List(
+                offsets
+                    .range(index)
+                    .map(|i| ArRowValue::from_tree(elements, i))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ArrayTree::Map {
+                offsets,
+                keys,
+                values,
+                ..
+            } => ArRowValue::Map(
+                offsets
+                    .range(index)
+                    .map(|i| {
+                        Ok((
+                            ArRowValue::from_tree(keys, i)?,
+                            ArRowValue::from_tree(values, i)?,
+                        ))
+                    })
+                    .collect::<Result<_, DeserializationError>>()?,
+            ),
+            ArrayTree::Dictionary {
+                array,
+                keys,
+                values,
+            } => {
+                let key = keys[index];
+                if key >= values.len() {
+                    return Err(DeserializationError::DictionaryOverflow {
+                        key,
+                        len: values.len(),
+                        data_type: array.data_type().clone(),
+                    });
+                }
+                ArRowValue::from_tree(values, key)?
+            }
+            ArrayTree::Union { array, variants } => {
+                let type_id = array.type_id(index);
+                match variants
+                    .iter()
+                    .find(|(variant_type_id, _, _)| *variant_type_id == type_id)
+                {
+                    Some((_, name, variant)) => ArRowValue::Union {
+                        variant: name.to_string(),
+                        value: Box::new(ArRowValue::from_tree(variant, array.value_offset(index))?),
+                    },
+                    None => ArRowValue::Null,
+                }
+            }
+        })
+    }
+
+    /// Returns whether this is [`ArRowValue::Null`]
+    pub fn is_null(&self) -> bool {
+        matches!(self, ArRowValue::Null)
+    }
+
+    /// Returns the fields of an [`ArRowValue::Struct`] as a map from their names,
+    /// or `None` for other values
+    pub fn into_map(self) -> Option<HashMap<String, ArRowValue>> {
+        match self {
+            ArRowValue::Struct(fields) => Some(fields.into_iter().collect()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns an error if arrays of type `datatype`, or one of its children, cannot be
+/// represented by [`ArRowValue`]
+fn check_value_datatype(datatype: &DataType) -> Result<(), String> {
+    match datatype {
+        DataType::Null
+        | DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Float16
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Utf8
+        | DataType::LargeUtf8
+        | DataType::Binary
+        | DataType::LargeBinary
+        | DataType::FixedSizeBinary(_)
+        | DataType::Decimal128(_, _)
+        | DataType::Date32
+        | DataType::Date64
+        | DataType::Timestamp(_, _) => Ok(()),
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _)
+        | DataType::Map(field, _) => check_value_datatype(field.data_type()),
+        DataType::Struct(fields) => fields
+            .iter()
+            .try_for_each(|field| check_value_datatype(field.data_type()))
+            .map_err(|e| format!("Struct cannot be decoded: {}", e)),
+        DataType::Dictionary(_, values) => check_value_datatype(values),
+        DataType::Union(fields, _) => fields
+            .iter()
+            .try_for_each(|(_, field)| check_value_datatype(field.data_type())),
+        datatype => Err(format!(
+            "ArRowValue cannot be decoded from Arrow {:?}",
+            datatype
+        )),
+    }
+}
+
+/// Checks `src` is no longer than `dst`, and writes the values of `src` to `dst`
+fn write_values<'a, 'b, T, V>(
+    src: impl Array,
+    dst: &'b mut T,
+    mut convert: impl FnMut(ArRowValue) -> Result<V, DeserializationError>,
+) -> Result<usize, DeserializationError>
+where
+    V: 'a,
+    &'b mut T: DeserializationTarget<'a, Item = V> + 'b,
+{
+    let mut dst = dst;
+    if src.len() > dst.len() {
+        return Err(DeserializationError::MismatchedLength {
+            src: src.len(),
+            dst: dst.len(),
+        });
+    }
+    let values = ArRowValue::from_array(&src)?;
+    let len = values.len();
+    for (s, d) in values.into_iter().zip(dst.iter_mut()) {
+        *d = convert(s)?;
+    }
+    Ok(len)
+}
+
+impl ArRowStruct for ArRowValue {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+/// Nulls are represented by [`ArRowValue::Null`], so this can be deserialized from
+/// arrays of any supported type, with or without nulls.
+impl CheckableDataType for ArRowValue {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        check_value_datatype(datatype)
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::Null
+    }
+
+    fn nullable() -> bool {
+        true
+    }
+}
+
+impl ArRowDeserialize for ArRowValue {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        write_values(src, dst, Ok)
+    }
+}
+
+/// Each row of a struct array is deserialized to a map from field names to values.
+///
+/// Nested structs are represented by [`ArRowValue::Struct`], which can be converted
+/// to maps with [`ArRowValue::into_map`].
+impl ArRowStruct for HashMap<String, ArRowValue> {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        vec![prefix.to_string()]
+    }
+
+    fn column_tree() -> ColumnTree {
+        ColumnTree::Leaf
+    }
+}
+
+impl CheckableDataType for HashMap<String, ArRowValue> {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        match datatype {
+            DataType::Struct(_) => check_value_datatype(datatype),
+            _ => Err(format!(
+                "HashMap<String, ArRowValue> must be decoded from Arrow Struct, not Arrow {:?}",
+                datatype
+            )),
+        }
+    }
+
+    fn expected_datatype() -> DataType {
+        DataType::Struct(Fields::empty())
+    }
+}
+
+impl ArRowDeserialize for HashMap<String, ArRowValue> {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        check_struct(&src)?;
+        write_values(src, dst, |value| match value {
+            ArRowValue::Null => Err(DeserializationError::UnexpectedNull(
+                "HashMap<String, ArRowValue> column contains nulls".to_owned(),
+            )),
+            value => Ok(value.into_map().unwrap_or_default()),
+        })
+    }
+}
+
+impl ArRowDeserializeOption for HashMap<String, ArRowValue> {
+    fn read_options_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Option<Self>> + 'b,
+    {
+        check_struct(&src)?;
+        write_values(src, dst, |value| Ok(value.into_map()))
+    }
+}
+
+fn check_struct(src: &dyn Array) -> Result<(), DeserializationError> {
+    match src.data_type() {
+        DataType::Struct(_) => Ok(()),
+        datatype => Err(DeserializationError::MismatchedColumnDataType(format!(
+            "Could not cast {:?} array with as_struct_opt",
+            datatype
+        ))),
+    }
+}
+
+fn timestamp(value: i64, ratio: i64) -> Timestamp {
+    Timestamp::normalized(value / ratio, (value % ratio) * (1_000_000_000 / ratio))
+}
+
+fn leaf_value(array: &dyn Array, index: usize) -> Result<ArRowValue, DeserializationError> {
+    macro_rules! primitive {
+        ($variant:ident, $ty:ty) => {
+            ArRowValue::$variant(array.as_primitive::<$ty>().value(index))
+        };
+    }
+
+    Ok(match array.data_type() {
+        DataType::Null => ArRowValue::Null,
+        DataType::Boolean => ArRowValue::Boolean(array.as_boolean().value(index)),
+        DataType::Int8 => primitive!(Int8, Int8Type),
+        DataType::Int16 => primitive!(Int16, Int16Type),
+        DataType::Int32 => primitive!(Int32, Int32Type),
+        DataType::Int64 => primitive!(Int64, Int64Type),
+        DataType::UInt8 => primitive!(UInt8, UInt8Type),
+        DataType::UInt16 => primitive!(UInt16, UInt16Type),
+        DataType::UInt32 => primitive!(UInt32, UInt32Type),
+        DataType::UInt64 => primitive!(UInt64, UInt64Type),
+        DataType::Float16 => {
+            ArRowValue::Float32(array.as_primitive::<Float16Type>().value(index).to_f32())
+        }
+        DataType::Float32 => primitive!(Float32, Float32Type),
+        DataType::Float64 => primitive!(Float64, Float64Type),
+        DataType::Utf8 => ArRowValue::String(array.as_string::<i32>().value(index).to_owned()),
+        DataType::LargeUtf8 => ArRowValue::String(array.as_string::<i64>().value(index).to_owned()),
+        DataType::Binary => ArRowValue::Binary(array.as_binary::<i32>().value(index).to_vec()),
+        DataType::LargeBinary => ArRowValue::Binary(array.as_binary::<i64>().value(index).to_vec()),
+        DataType::FixedSizeBinary(_) => {
+            ArRowValue::Binary(array.as_fixed_size_binary().value(index).to_vec())
+        }
+        DataType::Decimal128(_, scale) => ArRowValue::Decimal128 {
+            value: NaiveDecimal128(array.as_primitive::<Decimal128Type>().value(index)),
+            scale: *scale,
+        },
+        DataType::Date32 => {
+            ArRowValue::Date(Date(array.as_primitive::<Date32Type>().value(index).into()))
+        }
+        // Round towards negative infinity, so times before 1970 are on the previous day
+        DataType::Date64 => ArRowValue::Date(Date(
+            array
+                .as_primitive::<Date64Type>()
+                .value(index)
+                .div_euclid(MILLISECONDS_PER_DAY),
+        )),
+        DataType::Timestamp(TimeUnit::Second, _) => ArRowValue::Timestamp(timestamp(
+            array.as_primitive::<TimestampSecondType>().value(index),
+            1,
+        )),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => ArRowValue::Timestamp(timestamp(
+            array
+                .as_primitive::<TimestampMillisecondType>()
+                .value(index),
+            1_000,
+        )),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => ArRowValue::Timestamp(timestamp(
+            array
+                .as_primitive::<TimestampMicrosecondType>()
+                .value(index),
+            1_000_000,
+        )),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => ArRowValue::Timestamp(timestamp(
+            array.as_primitive::<TimestampNanosecondType>().value(index),
+            1_000_000_000,
+        )),
+        datatype => {
+            return Err(DeserializationError::MismatchedColumnDataType(format!(
+                "ArRowValue cannot be decoded from Arrow {:?}",
+                datatype
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_from_record_batch() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), None]),
+            None,
+        ]);
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+            ),
+            ("b", Arc::new(list) as ArrayRef),
+            (
+                "c",
+                Arc::new(TimestampMillisecondArray::from(vec![1_500, -1_500])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            ArRowValue::from_record_batch(batch).unwrap(),
+            vec![
+                ArRowValue::Struct(vec![
+                    ("a".to_owned(), ArRowValue::String("foo".to_owned())),
+                    (
+                        "b".to_owned(),
+                        ArRowValue::List(vec![ArRowValue::Int32(1), ArRowValue::Null])
+                    ),
+                    (
+                        "c".to_owned(),
+                        ArRowValue::Timestamp(Timestamp {
+                            seconds: 1,
+                            nanoseconds: 500_000_000
+                        })
+                    ),
+                ]),
+                ArRowValue::Struct(vec![
+                    ("a".to_owned(), ArRowValue::Null),
+                    ("b".to_owned(), ArRowValue::Null),
+                    (
+                        "c".to_owned(),
+                        ArRowValue::Timestamp(Timestamp {
+                            seconds: -2,
+                            nanoseconds: 500_000_000
+                        })
+                    ),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_array_dictionary() {
+        let array: DictionaryArray<Int8Type> = vec![Some("a"), None, Some("b"), Some("a")]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            ArRowValue::from_array(&array).unwrap(),
+            vec![
+                ArRowValue::String("a".to_owned()),
+                ArRowValue::Null,
+                ArRowValue::String("b".to_owned()),
+                ArRowValue::String("a".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_array_date64() {
+        let array = Date64Array::from(vec![
+            0,
+            MILLISECONDS_PER_DAY - 1,
+            MILLISECONDS_PER_DAY,
+            -1,
+            -MILLISECONDS_PER_DAY,
+            -MILLISECONDS_PER_DAY - 1,
+        ]);
+
+        assert_eq!(
+            ArRowValue::from_array(&array).unwrap(),
+            vec![
+                ArRowValue::Date(Date(0)),
+                ArRowValue::Date(Date(0)),
+                ArRowValue::Date(Date(1)),
+                ArRowValue::Date(Date(-1)),
+                ArRowValue::Date(Date(-1)),
+                ArRowValue::Date(Date(-2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_map() {
+        let batch = RecordBatch::try_from_iter(vec![
+            ("a", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "b",
+                Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        HashMap::<String, ArRowValue>::check_schema(&batch.schema()).unwrap();
+
+        let rows = HashMap::<String, ArRowValue>::from_record_batch(batch).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                HashMap::from([
+                    ("a".to_owned(), ArRowValue::Int64(1)),
+                    ("b".to_owned(), ArRowValue::String("foo".to_owned())),
+                ]),
+                HashMap::from([
+                    ("a".to_owned(), ArRowValue::Int64(2)),
+                    ("b".to_owned(), ArRowValue::Null),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_map_not_struct() {
+        let array = Int64Array::from(vec![1]);
+        assert!(HashMap::<String, ArRowValue>::check_datatype(array.data_type()).is_err());
+        assert!(HashMap::<String, ArRowValue>::from_array(array).is_err());
+    }
+
+    #[test]
+    fn test_from_array_unsupported() {
+        let array = Time32SecondArray::from(vec![1]);
+        assert!(ArRowValue::check_datatype(array.data_type()).is_err());
+        assert!(ArRowValue::from_array(&array).is_err());
+    }
+}
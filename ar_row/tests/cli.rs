@@ -0,0 +1,471 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests of the `ar-row` command-line tool. Requires the `cli` feature.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    ArrayRef, Float64Array, Int64Array, MapArray, StringArray, StructArray,
+};
+use ar_row::arrow::datatypes::{DataType, Field, Fields, Schema};
+use ar_row::arrow::ipc::writer::FileWriter;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::schema::schema_to_json;
+use assert_cmd::Command;
+use parquet::arrow::ArrowWriter;
+use serde_json::{json, Value};
+
+/// Structure with fields `a` and `b`, with `b = 10 * a`
+fn ab_struct(a: Vec<i64>) -> StructArray {
+    let b = a.iter().map(|a| a * 10).collect::<Vec<_>>();
+    StructArray::from(vec![
+        (
+            Arc::new(Field::new("a", DataType::Int64, false)),
+            Arc::new(Int64Array::from(a)) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("b", DataType::Int64, false)),
+            Arc::new(Int64Array::from(b)) as ArrayRef,
+        ),
+    ])
+}
+
+fn batch() -> RecordBatch {
+    let point = StructArray::from(vec![
+        (
+            Arc::new(Field::new("x", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("y", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![4, 5, 6])) as ArrayRef,
+        ),
+    ]);
+    let attrs = MapArray::new_from_strings(
+        ["x", "y", "z"].into_iter(),
+        &ab_struct(vec![1, 2, 3]),
+        &[0, 2, 3, 3],
+    )
+    .unwrap();
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+        ("point", Arc::new(point) as ArrayRef),
+        ("attrs", Arc::new(attrs) as ArrayRef),
+        (
+            "name",
+            Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+fn write_ipc(path: &Path, batches: &[RecordBatch]) {
+    let mut writer =
+        FileWriter::try_new(File::create(path).unwrap(), &batches[0].schema()).unwrap();
+    for batch in batches {
+        writer.write(batch).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn write_parquet(path: &Path, batches: &[RecordBatch]) {
+    let mut writer =
+        ArrowWriter::try_new(File::create(path).unwrap(), batches[0].schema(), None).unwrap();
+    for batch in batches {
+        writer.write(batch).unwrap();
+    }
+    writer.close().unwrap();
+}
+
+/// Writes [`batch`] as an Arrow IPC file in `dir`
+fn ipc_file(dir: &tempfile::TempDir) -> PathBuf {
+    let path = dir.path().join("rows.arrow");
+    write_ipc(&path, &[batch()]);
+    path
+}
+
+/// Writes a schema with nested structures as JSON in `dir`
+fn schema_file(dir: &tempfile::TempDir) -> PathBuf {
+    let point = Fields::from(vec![
+        Field::new("x", DataType::Int64, false),
+        Field::new("y", DataType::Int64, false),
+    ]);
+    let tag = Fields::from(vec![Field::new("name", DataType::Utf8, true)]);
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("point", DataType::Struct(point), true),
+        Field::new(
+            "user-tags",
+            DataType::new_list(DataType::Struct(tag), false),
+            true,
+        ),
+        Field::new("name", DataType::Utf8, true),
+    ]);
+    let path = dir.path().join("schema.json");
+    std::fs::write(&path, schema_to_json(&schema)).unwrap();
+    path
+}
+
+fn ar_row() -> Command {
+    Command::cargo_bin("ar-row").unwrap()
+}
+
+/// Runs `ar-row` with `args`, checks it succeeded, and returns its standard output
+fn run(args: &[&str]) -> String {
+    let assert = ar_row().args(args).assert().success();
+    String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+}
+
+fn json_lines(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_convert_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = ipc_file(&dir);
+    assert_eq!(
+        json_lines(&run(&["convert", path.to_str().unwrap()])),
+        vec![
+            json!({
+                "id": 1,
+                "point": {"x": 1, "y": 4},
+                "attrs": {"x": {"a": 1, "b": 10}, "y": {"a": 2, "b": 20}},
+                "name": "a",
+            }),
+            json!({
+                "id": 2,
+                "point": {"x": 2, "y": 5},
+                "attrs": {"z": {"a": 3, "b": 30}},
+                "name": "b",
+            }),
+            json!({"id": 3, "point": {"x": 3, "y": 6}, "attrs": {}, "name": "c"}),
+        ]
+    );
+}
+
+#[test]
+fn test_convert_nested_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = ipc_file(&dir);
+    // Columns are written in the order they are given, and nested columns of
+    // structures and map values are pruned
+    assert_eq!(
+        json_lines(&run(&[
+            "convert",
+            path.to_str().unwrap(),
+            "--columns",
+            "name,point.y,attrs.b",
+            "--offset",
+            "1",
+        ])),
+        vec![
+            json!({"name": "b", "point": {"y": 5}, "attrs": {"z": {"b": 30}}}),
+            json!({"name": "c", "point": {"y": 6}, "attrs": {}}),
+        ]
+    );
+
+    ar_row()
+        .args(["convert", path.to_str().unwrap(), "--columns", "name.x"])
+        .assert()
+        .failure();
+    ar_row()
+        .args(["convert", path.to_str().unwrap(), "--columns", "missing"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_convert_dotted_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.arrow");
+    let a = StructArray::from(vec![(
+        Arc::new(Field::new("b", DataType::Int64, false)),
+        Arc::new(Int64Array::from(vec![1])) as ArrayRef,
+    )]);
+    let batch = RecordBatch::try_from_iter(vec![
+        ("a", Arc::new(a) as ArrayRef),
+        ("a.b", Arc::new(Int64Array::from(vec![2])) as ArrayRef),
+    ])
+    .unwrap();
+    write_ipc(&path, &[batch]);
+
+    // Escaped dots are part of the field name
+    assert_eq!(
+        json_lines(&run(&[
+            "convert",
+            path.to_str().unwrap(),
+            "--columns",
+            r"a\.b"
+        ])),
+        vec![json!({"a.b": 2})]
+    );
+    assert_eq!(
+        json_lines(&run(&[
+            "convert",
+            path.to_str().unwrap(),
+            "--columns",
+            "a.b"
+        ])),
+        vec![json!({"a": {"b": 1}})]
+    );
+}
+
+#[test]
+fn test_convert_parquet() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.parquet");
+    write_parquet(&path, &[batch()]);
+    assert_eq!(
+        json_lines(&run(&[
+            "convert",
+            path.to_str().unwrap(),
+            "--columns",
+            "point.x,id",
+        ])),
+        vec![
+            json!({"point": {"x": 1}, "id": 1}),
+            json!({"point": {"x": 2}, "id": 2}),
+            json!({"point": {"x": 3}, "id": 3}),
+        ]
+    );
+}
+
+#[test]
+fn test_convert_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = ipc_file(&dir);
+    let output = dir.path().join("rows.csv");
+    run(&[
+        "convert",
+        path.to_str().unwrap(),
+        "--columns",
+        "id,point",
+        "--output-format",
+        "csv",
+        "--output",
+        output.to_str().unwrap(),
+    ]);
+    assert_eq!(
+        std::fs::read_to_string(output).unwrap(),
+        "id,point.x,point.y\n1,1,4\n2,2,5\n3,3,6\n"
+    );
+}
+
+#[test]
+fn test_convert_batch_size() {
+    // Batches of IPC files are split to --batch-size, so --offset and --limit
+    // span several batches
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.arrow");
+    write_ipc(&path, &[batch(), batch()]);
+    for batch_size in ["1", "2", "1024"] {
+        assert_eq!(
+            json_lines(&run(&[
+                "convert",
+                path.to_str().unwrap(),
+                "--columns",
+                "id",
+                "--batch-size",
+                batch_size,
+                "--offset",
+                "2",
+                "--limit",
+                "3",
+            ])),
+            vec![json!({"id": 3}), json!({"id": 1}), json!({"id": 2})]
+        );
+    }
+}
+
+#[test]
+fn test_cat() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = ipc_file(&dir);
+    assert_eq!(
+        run(&[
+            "cat",
+            path.to_str().unwrap(),
+            "--columns",
+            "name,point.x",
+            "--offset",
+            "1",
+            "--limit",
+            "1",
+        ]),
+        "\
++------+---------+
+| name | point.x |
++------+---------+
+| b    | 2       |
++------+---------+
+"
+    );
+}
+
+#[test]
+fn test_unknown_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.txt");
+    write_ipc(&path, &[batch()]);
+    ar_row()
+        .args(["convert", path.to_str().unwrap()])
+        .assert()
+        .failure();
+    assert_eq!(
+        json_lines(&run(&[
+            "convert",
+            path.to_str().unwrap(),
+            "--format",
+            "ipc",
+            "--columns",
+            "id",
+        ])),
+        vec![json!({"id": 1}), json!({"id": 2}), json!({"id": 3})]
+    );
+}
+
+#[test]
+fn test_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = schema_file(&dir);
+    // The structure of `user-tags` is not printed, as the field is commented out
+    assert_eq!(
+        run(&["schema", path.to_str().unwrap()]),
+        "\
+Arrow schema:
+
+    id: Int64
+    point: Struct, nullable
+        x: Int64
+        y: Int64
+    user-tags: List, nullable
+        item: Struct
+            name: Utf8, nullable
+    name: Utf8, nullable
+
+Rust structures:
+
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    point: Option<RowPoint>,
+    // user_tags: Option<Vec<RowUserTags>>, // column \"user-tags\" is not a valid Rust identifier
+    name: Option<String>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct RowPoint {
+    x: i64,
+    y: i64,
+}
+"
+    );
+
+    // Schemas of data files are read too
+    let path = ipc_file(&dir);
+    let output = run(&["schema", path.to_str().unwrap(), "--name", "Data"]);
+    assert!(output.contains("struct Data {"), "{}", output);
+    assert!(output.contains("struct DataPoint {"), "{}", output);
+}
+
+#[test]
+fn test_codegen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = schema_file(&dir);
+    let output = dir.path().join("row.rs");
+    run(&[
+        "codegen",
+        path.to_str().unwrap(),
+        "--name",
+        "Record",
+        "--output",
+        output.to_str().unwrap(),
+    ]);
+    assert_eq!(
+        std::fs::read_to_string(output).unwrap(),
+        "\
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Record {
+    id: i64,
+    point: Option<RecordPoint>,
+    // user_tags: Option<Vec<RecordUserTags>>, // column \"user-tags\" is not a valid Rust identifier
+    name: Option<String>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct RecordPoint {
+    x: i64,
+    y: i64,
+}
+"
+    );
+}
+
+/// Batch with an `id` column and a `value` column
+fn values_batch(values: Vec<f64>) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "id",
+            Arc::new(Int64Array::from_iter_values(0..values.len() as i64)) as ArrayRef,
+        ),
+        ("value", Arc::new(Float64Array::from(values)) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let left = dir.path().join("left.arrow");
+    let right = dir.path().join("right.parquet");
+    write_ipc(&left, &[values_batch(vec![1.0, f64::NAN, 3.0])]);
+    write_parquet(&right, &[values_batch(vec![1.0, f64::NAN, 3.0])]);
+    let (left, right) = (left.to_str().unwrap(), right.to_str().unwrap());
+
+    // NaN values are equal to each other
+    assert_eq!(
+        run(&["diff", left, right]),
+        format!(
+            "3 rows compared, 0 differ; 0 rows only in {}, 0 rows only in {}\n",
+            left, right
+        )
+    );
+
+    let other = dir.path().join("other.arrow");
+    write_ipc(&other, &[values_batch(vec![1.0, 2.0, 3.0, 4.0])]);
+    let other = other.to_str().unwrap();
+    let assert = ar_row().args(["diff", left, other]).assert().code(1);
+    assert_eq!(
+        String::from_utf8(assert.get_output().stdout.clone()).unwrap(),
+        format!(
+            "Row 1:\n    value: null | 2.0\n\
+             3 rows compared, 1 differ; 0 rows only in {}, 1 rows only in {}\n",
+            left, other
+        )
+    );
+
+    // Only the selected columns are compared
+    ar_row()
+        .args(["diff", left, other, "--columns", "id"])
+        .assert()
+        .code(1);
+
+    // Errors are told apart from differences
+    ar_row()
+        .args(["diff", left, "missing.arrow"])
+        .assert()
+        .code(2);
+}
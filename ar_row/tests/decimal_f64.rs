@@ -0,0 +1,46 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Decimal128Array, Int64Array};
+
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::DecimalF64;
+
+#[test]
+fn test_decimal_f64() {
+    let array: ArrayRef = Arc::new(
+        Decimal128Array::from(vec![12345, -5, 0])
+            .with_precision_and_scale(10, 2)
+            .unwrap(),
+    );
+    assert!(DecimalF64::check_datatype(array.data_type()).is_ok());
+    assert_eq!(
+        DecimalF64::from_array(array),
+        Ok(vec![DecimalF64(123.45), DecimalF64(-0.05), DecimalF64(0.)])
+    );
+}
+
+#[test]
+fn test_decimal_f64_negative_scale() {
+    let array: ArrayRef = Arc::new(
+        Decimal128Array::from(vec![Some(12), None])
+            .with_precision_and_scale(10, -3)
+            .unwrap(),
+    );
+    assert!(DecimalF64::from_array(array.clone()).is_err());
+    assert_eq!(
+        <Option<DecimalF64>>::from_array(array),
+        Ok(vec![Some(DecimalF64(12000.)), None])
+    );
+}
+
+#[test]
+fn test_decimal_f64_mismatched_type() {
+    let array: ArrayRef = Arc::new(Int64Array::from(vec![1]));
+    assert!(DecimalF64::check_datatype(array.data_type()).is_err());
+    assert!(DecimalF64::from_array(array).is_err());
+}
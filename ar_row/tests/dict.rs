@@ -5,10 +5,14 @@
 
 use std::sync::Arc;
 
-use ar_row::arrow::array::{Array, DictionaryArray, Int8Array};
-use ar_row::arrow::datatypes::Int8Type;
+use ar_row::arrow::array::{
+    Array, BinaryViewArray, DictionaryArray, Int8Array, ListArray, StringArray, StringViewArray,
+};
+use ar_row::arrow::datatypes::{DataType, Int32Type, Int8Type};
 
-use ar_row::deserialize::ArRowDeserialize;
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::dictionaries::DictionaryCache;
+use ar_row::SharedString;
 
 #[test]
 fn test_utf8_dict() {
@@ -61,3 +65,225 @@ fn test_utf8_dict_opt() {
 
     assert!(<String>::from_array(array).is_err());
 }
+
+#[test]
+fn test_utf8_dict_shared_values() {
+    let array: DictionaryArray<Int8Type> = vec!["a", "b", "c"].into_iter().collect();
+    let other_keys = Int8Array::from(vec![2, 2, 0]);
+    let other_array = array.with_values(Arc::new(StringArray::from(vec!["d", "e", "f"])));
+    let same_values_array = DictionaryArray::new(other_keys, array.values().clone());
+
+    // Reading a dictionary with the same values must not return the values of the
+    // previous dictionary (which may be cached)
+    let mut cache = DictionaryCache::new();
+    cache.scope(|| {
+        assert_eq!(
+            <String>::from_array(Arc::new(array) as Arc<dyn Array>).unwrap(),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+        assert_eq!(
+            <String>::from_array(Arc::new(other_array) as Arc<dyn Array>).unwrap(),
+            vec!["d".to_string(), "e".to_string(), "f".to_string()]
+        );
+        assert_eq!(
+            <String>::from_array(Arc::new(same_values_array) as Arc<dyn Array>).unwrap(),
+            vec!["c".to_string(), "c".to_string(), "a".to_string()]
+        );
+    });
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_dictionary_cache_reuse() {
+    let array: DictionaryArray<Int8Type> = vec!["a", "b"].into_iter().collect();
+    let same_values_array =
+        DictionaryArray::new(Int8Array::from(vec![1, 0]), array.values().clone());
+    let array: Arc<dyn Array> = Arc::new(array);
+    let same_values_array: Arc<dyn Array> = Arc::new(same_values_array);
+
+    // Without a cache, values are deserialized again for every array
+    let rows = SharedString::from_array(array.clone()).unwrap();
+    let other_rows = SharedString::from_array(same_values_array.clone()).unwrap();
+    assert_eq!(other_rows, vec!["b".into(), "a".into()]);
+    assert!(!Arc::ptr_eq(&rows[0].0, &other_rows[1].0));
+
+    let mut cache = DictionaryCache::new();
+    let rows = cache.scope(|| SharedString::from_array(array.clone()).unwrap());
+    let other_rows = cache.scope(|| SharedString::from_array(same_values_array).unwrap());
+    assert_eq!(other_rows, vec!["b".into(), "a".into()]);
+    assert!(Arc::ptr_eq(&rows[0].0, &other_rows[1].0));
+    assert!(Arc::ptr_eq(&rows[1].0, &other_rows[0].0));
+    assert_eq!(cache.len(), 1);
+
+    // The cache is only used in its scope
+    let rows_outside_scope = SharedString::from_array(array).unwrap();
+    assert!(!Arc::ptr_eq(&rows[0].0, &rows_outside_scope[0].0));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_utf8_dict_shared_string() {
+    let array: DictionaryArray<Int8Type> = vec![Some("a"), None, Some("b"), Some("a")]
+        .into_iter()
+        .collect();
+    let array: Arc<dyn Array> = Arc::new(array);
+
+    let rows: Vec<Option<SharedString>> = <Option<SharedString>>::from_array(array).unwrap();
+
+    assert_eq!(
+        rows,
+        vec![Some("a".into()), None, Some("b".into()), Some("a".into())]
+    );
+    // Rows with the same key share the same allocation
+    assert!(Arc::ptr_eq(
+        &rows[0].as_ref().unwrap().0,
+        &rows[3].as_ref().unwrap().0
+    ));
+}
+
+#[test]
+fn test_list_dict() {
+    let values = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), Some(2)]),
+        Some(vec![]),
+        Some(vec![Some(3)]),
+    ]);
+    let keys = Int8Array::from(vec![Some(2), Some(0), None, Some(1), Some(0)]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    let rows: Vec<Option<Vec<i32>>> = <Option<Vec<i32>>>::from_array(array.clone()).unwrap();
+
+    assert_eq!(
+        rows,
+        vec![
+            Some(vec![3]),
+            Some(vec![1, 2]),
+            None,
+            Some(vec![]),
+            Some(vec![1, 2])
+        ]
+    );
+
+    assert!(<Vec<i32>>::from_array(array).is_err());
+}
+
+#[test]
+fn test_utf8_dict_sparse() {
+    // Only a few entries of a large dictionary are referenced, so the null entry
+    // (which cannot be deserialized to String) is not decoded
+    let values: StringArray = (0..100)
+        .map(|i| if i == 50 { None } else { Some(i.to_string()) })
+        .collect();
+    let keys = Int8Array::from(vec![Some(3), None, Some(99), Some(3)]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    let rows: Vec<Option<String>> = <Option<String>>::from_array(array).unwrap();
+
+    assert_eq!(
+        rows,
+        vec![
+            Some("3".to_string()),
+            None,
+            Some("99".to_string()),
+            Some("3".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_dict_unreferenced_null() {
+    // The null entry is never referenced, so it does not prevent reading a String,
+    // whether the whole dictionary is decoded (dense) or only referenced values
+    // (sparse)
+    let values: Arc<StringArray> = Arc::new(
+        (0..100)
+            .map(|i| if i == 50 { None } else { Some(i.to_string()) })
+            .collect(),
+    );
+    let dense_keys = Int8Array::from((0..100).map(|i| (i * 7) % 50).collect::<Vec<_>>());
+    let sparse_keys = Int8Array::from(vec![3, 99, 3]);
+    for keys in [dense_keys, sparse_keys] {
+        let expected: Vec<String> = keys.values().iter().map(|key| key.to_string()).collect();
+        let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, values.clone()));
+        assert_eq!(<String>::from_array(array.clone()), Ok(expected.clone()));
+        let mut cache = DictionaryCache::new();
+        assert_eq!(cache.scope(|| <String>::from_array(array)), Ok(expected));
+    }
+
+    // A referenced null is an error in both cases
+    for keys in [vec![50; 100], vec![50]] {
+        let array: Arc<dyn Array> =
+            Arc::new(DictionaryArray::new(Int8Array::from(keys), values.clone()));
+        assert!(<String>::from_array(array).is_err());
+    }
+}
+
+#[test]
+fn test_check_dict() {
+    let datatype = DataType::Dictionary(DataType::Int8.into(), DataType::Utf8.into());
+    assert_eq!(String::check_datatype(&datatype), Ok(()));
+    assert_eq!(<Option<SharedString>>::check_datatype(&datatype), Ok(()));
+    assert_eq!(
+        i64::check_datatype(&datatype),
+        Err("i64 must be decoded from Arrow Int64, not Arrow Utf8".to_string())
+    );
+
+    let datatype = DataType::Dictionary(DataType::Int32.into(), DataType::Utf8View.into());
+    assert_eq!(String::check_datatype(&datatype), Ok(()));
+    assert!(Box::<[u8]>::check_datatype(&datatype).is_err());
+
+    let datatype = DataType::Dictionary(DataType::Int32.into(), DataType::BinaryView.into());
+    assert_eq!(Box::<[u8]>::check_datatype(&datatype), Ok(()));
+    assert!(String::check_datatype(&datatype).is_err());
+}
+
+#[test]
+fn test_utf8_view_dict() {
+    let values = StringViewArray::from(vec!["a", "a string longer than twelve bytes"]);
+    let keys = Int8Array::from(vec![Some(1), None, Some(0), Some(1)]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    let rows: Vec<Option<String>> = <Option<String>>::from_array(array.clone()).unwrap();
+
+    assert_eq!(
+        rows,
+        vec![
+            Some("a string longer than twelve bytes".to_string()),
+            None,
+            Some("a".to_string()),
+            Some("a string longer than twelve bytes".to_string())
+        ]
+    );
+
+    assert!(<String>::from_array(array).is_err());
+}
+
+#[test]
+fn test_utf8_view_dict_sparse() {
+    let values: StringViewArray = (0..100).map(|i| Some(i.to_string())).collect();
+    let keys = Int8Array::from(vec![3, 99, 3]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    assert_eq!(
+        <String>::from_array(array).unwrap(),
+        vec!["3".to_string(), "99".to_string(), "3".to_string()]
+    );
+}
+
+#[test]
+fn test_binary_view_dict() {
+    let values = BinaryViewArray::from(vec![&b"foo"[..], &b"bar"[..]]);
+    let keys = Int8Array::from(vec![1, 0, 1]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    assert_eq!(
+        <Box<[u8]>>::from_array(array).unwrap(),
+        vec![
+            b"bar".to_vec().into_boxed_slice(),
+            b"foo".to_vec().into_boxed_slice(),
+            b"bar".to_vec().into_boxed_slice()
+        ]
+    );
+}
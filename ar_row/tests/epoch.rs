@@ -0,0 +1,70 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    ArrayRef, TimestampMicrosecondArray, TimestampNanosecondArray, TimestampSecondArray,
+};
+use ar_row::arrow::datatypes::{DataType, TimeUnit};
+
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType, DeserializationError};
+use ar_row::{EpochMillis, EpochNanos};
+
+#[test]
+fn test_epoch_millis() {
+    let array: ArrayRef = Arc::new(TimestampMicrosecondArray::from(vec![
+        1_500_999, 0, -1, -1_000,
+    ]));
+    assert_eq!(
+        EpochMillis::from_array(array),
+        Ok(vec![
+            EpochMillis(1_500),
+            EpochMillis(0),
+            EpochMillis(-1),
+            EpochMillis(-1)
+        ])
+    );
+
+    let array: ArrayRef = Arc::new(TimestampSecondArray::from(vec![Some(2), None]));
+    assert!(EpochMillis::from_array(array.clone()).is_err());
+    assert_eq!(
+        <Option<EpochMillis>>::from_array(array),
+        Ok(vec![Some(EpochMillis(2_000)), None])
+    );
+}
+
+#[test]
+fn test_epoch_nanos() {
+    let array: ArrayRef = Arc::new(TimestampNanosecondArray::from(vec![Some(123), None]));
+    assert_eq!(
+        <Option<EpochNanos>>::from_array(array),
+        Ok(vec![Some(EpochNanos(123)), None])
+    );
+
+    let array: ArrayRef = Arc::new(TimestampSecondArray::from(vec![i64::MAX / 1000]));
+    assert_eq!(
+        EpochNanos::from_array(array),
+        Err(DeserializationError::EpochOverflow {
+            value: i64::MAX / 1000,
+            from: TimeUnit::Second,
+            to: TimeUnit::Nanosecond
+        })
+    );
+}
+
+#[test]
+fn test_epoch_datatype() {
+    assert_eq!(
+        EpochMillis::expected_datatype(),
+        DataType::Timestamp(TimeUnit::Millisecond, None)
+    );
+    assert!(EpochNanos::check_datatype(&DataType::Timestamp(TimeUnit::Second, None)).is_ok());
+    assert!(EpochNanos::check_datatype(&DataType::Int64).is_err());
+    assert!(
+        EpochNanos::check_datatype(&DataType::Timestamp(TimeUnit::Second, Some("UTC".into())))
+            .is_err()
+    );
+}
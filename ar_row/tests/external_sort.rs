@@ -0,0 +1,109 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::io::{self, BufRead, Read, Write};
+
+use ar_row::arrow::error::ArrowError;
+use ar_row::external_sort::{external_sort_by_key, RunCodec, SortError, SortOptions};
+use ar_row::reader::ReadError;
+
+/// Writes `(key, index)` pairs as 16 little-endian bytes
+struct PairCodec;
+
+impl RunCodec<(i64, u64)> for PairCodec {
+    fn write_row(&self, writer: &mut dyn Write, row: &(i64, u64)) -> io::Result<()> {
+        writer.write_all(&row.0.to_le_bytes())?;
+        writer.write_all(&row.1.to_le_bytes())
+    }
+
+    fn read_row(&self, reader: &mut dyn BufRead) -> io::Result<Option<(i64, u64)>> {
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut bytes = [0; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(Some((
+            i64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+        )))
+    }
+}
+
+/// Returns rows with keys in pseudo-random order, with many duplicates, and their
+/// index as second item
+fn rows(num_rows: u64) -> Vec<(i64, u64)> {
+    (0..num_rows)
+        .map(|i| (((i * 7919) % 13) as i64 - 6, i))
+        .collect()
+}
+
+fn options(run_size: usize) -> (tempfile::TempDir, SortOptions) {
+    let dir = tempfile::tempdir().unwrap();
+    let options = SortOptions {
+        run_size,
+        temp_dir: dir.path().to_owned(),
+    };
+    (dir, options)
+}
+
+#[test]
+fn test_sort() {
+    for run_size in [1, 3, 7, 100, 1000] {
+        let (dir, options) = options(run_size);
+        let sorted_rows = external_sort_by_key(
+            rows(100).into_iter().map(Ok),
+            |row: &(i64, u64)| row.0,
+            PairCodec,
+            &options,
+        )
+        .unwrap();
+        if run_size < 100 {
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+        let sorted_rows: Vec<_> = sorted_rows.map(Result::unwrap).collect();
+
+        // Stable sort by key, so equivalent to sorting by (key, index)
+        let mut expected = rows(100);
+        expected.sort_unstable();
+        assert_eq!(sorted_rows, expected, "run_size = {}", run_size);
+
+        // Temporary files were removed
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
+
+#[test]
+fn test_empty() {
+    let (dir, options) = options(3);
+    let mut sorted_rows = external_sort_by_key(
+        Vec::<Result<_, ReadError>>::new(),
+        |row: &(i64, u64)| row.0,
+        PairCodec,
+        &options,
+    )
+    .unwrap();
+    assert!(sorted_rows.next().is_none());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_read_error() {
+    let (dir, options) = options(3);
+    let rows = rows(10)
+        .into_iter()
+        .map(Ok)
+        .chain(std::iter::once(Err(ReadError::Arrow(
+            ArrowError::ComputeError("oops".to_owned()),
+        ))));
+    match external_sort_by_key(rows, |row: &(i64, u64)| row.0, PairCodec, &options) {
+        Err(SortError::Read(ReadError::Arrow(ArrowError::ComputeError(message)))) => {
+            assert_eq!(message, "oops")
+        }
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Expected an error"),
+    }
+    // Runs written before the error were removed
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
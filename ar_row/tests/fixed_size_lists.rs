@@ -0,0 +1,68 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{Array, FixedSizeListArray};
+use ar_row::arrow::datatypes::{DataType, Field, Int32Type};
+
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::FixedSizeList;
+
+fn array() -> Arc<dyn Array> {
+    Arc::new(FixedSizeListArray::from_iter_primitive::<Int32Type, _, _>(
+        vec![
+            Some(vec![Some(1), None]),
+            None,
+            Some(vec![Some(3), Some(4)]),
+        ],
+        2,
+    ))
+}
+
+#[test]
+fn test_nullable_elements() {
+    assert_eq!(
+        <FixedSizeList<Option<i32>, 2>>::check_datatype(array().data_type()),
+        Ok(())
+    );
+    assert_eq!(
+        <Option<FixedSizeList<Option<i32>, 2>>>::from_array(array()),
+        Ok(vec![
+            Some(FixedSizeList([Some(1), None])),
+            None,
+            Some(FixedSizeList([Some(3), Some(4)])),
+        ])
+    );
+    assert_eq!(
+        <Option<FixedSizeList<Option<i32>, 2>>>::from_array(array().slice(1, 2)),
+        Ok(vec![None, Some(FixedSizeList([Some(3), Some(4)]))])
+    );
+}
+
+#[test]
+fn test_mismatched_datatype() {
+    assert!(<FixedSizeList<i32, 2>>::check_datatype(array().data_type()).is_err());
+    assert!(<FixedSizeList<Option<i32>, 3>>::check_datatype(array().data_type()).is_err());
+
+    let non_nullable =
+        DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Int32, false)), 2);
+    assert_eq!(
+        <FixedSizeList<i32, 2>>::check_datatype(&non_nullable),
+        Ok(())
+    );
+    assert_eq!(
+        <FixedSizeList<Option<i32>, 2>>::check_datatype(&non_nullable),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_default() {
+    assert_eq!(
+        FixedSizeList::<String, 40>::default(),
+        FixedSizeList(std::array::from_fn(|_| String::new()))
+    );
+}
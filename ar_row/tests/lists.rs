@@ -0,0 +1,231 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    Array, FixedSizeListArray, FixedSizeListBuilder, GenericListBuilder, LargeListArray,
+    LargeStringBuilder, ListArray, StringBuilder,
+};
+use ar_row::arrow::datatypes::{Int32Type, UInt8Type};
+
+use ar_row::deserialize::ArRowDeserialize;
+
+fn byte_lists() -> ListArray {
+    ListArray::from_iter_primitive::<UInt8Type, _, _>(vec![
+        Some(vec![Some(1), Some(2)]),
+        None,
+        Some(vec![]),
+        Some(vec![Some(3), Some(4), Some(5)]),
+    ])
+}
+
+#[test]
+fn test_byte_lists() {
+    let array: Arc<dyn Array> = Arc::new(byte_lists().slice(2, 2));
+    assert_eq!(
+        <Vec<u8>>::from_array(array),
+        Ok(vec![vec![], vec![3, 4, 5]])
+    );
+
+    let array: Arc<dyn Array> = Arc::new(byte_lists());
+    assert!(<Vec<u8>>::from_array(array).is_err());
+}
+
+#[test]
+fn test_byte_lists_options() {
+    let array: Arc<dyn Array> = Arc::new(byte_lists());
+
+    // Allocations of the buffer are reused
+    let mut rows = vec![Some(vec![9; 10]); 4];
+    assert_eq!(
+        <Option<Vec<u8>>>::read_from_array_into_vec(array.clone(), &mut rows),
+        Ok(4)
+    );
+    assert_eq!(
+        rows,
+        vec![Some(vec![1, 2]), None, Some(vec![]), Some(vec![3, 4, 5])]
+    );
+
+    let array: Arc<dyn Array> = Arc::new(LargeListArray::from_iter_primitive::<UInt8Type, _, _>(
+        vec![None, Some(vec![Some(6)])],
+    ));
+    assert_eq!(
+        <Option<Vec<u8>>>::from_array(array),
+        Ok(vec![None, Some(vec![6])])
+    );
+}
+
+#[test]
+fn test_byte_lists_null_values() {
+    let array: Arc<dyn Array> = Arc::new(ListArray::from_iter_primitive::<UInt8Type, _, _>(vec![
+        Some(vec![Some(1), None]),
+    ]));
+
+    assert!(<Vec<u8>>::from_array(array.clone()).is_err());
+    assert_eq!(
+        <Vec<Option<u8>>>::from_array(array),
+        Ok(vec![vec![Some(1), None]])
+    );
+}
+
+fn string_lists() -> ListArray {
+    let mut builder = GenericListBuilder::<i32, _>::new(StringBuilder::new());
+    builder.values().append_value("a");
+    builder.values().append_value("bc");
+    builder.append(true);
+    builder.append(false);
+    builder.append(true);
+    builder.values().append_value("");
+    builder.values().append_value("def");
+    builder.values().append_value("g");
+    builder.append(true);
+    builder.finish()
+}
+
+#[test]
+fn test_string_lists() {
+    let array: Arc<dyn Array> = Arc::new(string_lists().slice(2, 2));
+    assert_eq!(
+        <Vec<String>>::from_array(array),
+        Ok(vec![
+            vec![],
+            vec!["".to_owned(), "def".to_owned(), "g".to_owned()]
+        ])
+    );
+
+    let array: Arc<dyn Array> = Arc::new(string_lists());
+    assert!(<Vec<String>>::from_array(array).is_err());
+}
+
+#[test]
+fn test_string_lists_options() {
+    let array: Arc<dyn Array> = Arc::new(string_lists());
+
+    // Vectors and strings are reused, and must be overwritten entirely
+    let mut rows = vec![
+        Some(vec!["xxxxxx".to_owned()]),
+        Some(vec!["y".to_owned()]),
+        Some(vec!["z".to_owned(); 3]),
+        None,
+    ];
+    assert_eq!(
+        <Option<Vec<String>>>::read_from_array_into_vec(array, &mut rows),
+        Ok(4)
+    );
+    assert_eq!(
+        rows,
+        vec![
+            Some(vec!["a".to_owned(), "bc".to_owned()]),
+            None,
+            Some(vec![]),
+            Some(vec!["".to_owned(), "def".to_owned(), "g".to_owned()]),
+        ]
+    );
+}
+
+#[test]
+fn test_large_string_lists() {
+    let mut builder = GenericListBuilder::<i64, _>::new(LargeStringBuilder::new());
+    builder.values().append_value("a");
+    builder.append(true);
+    builder.values().append_value("b");
+    builder.values().append_value("c");
+    builder.append(true);
+    let array: Arc<dyn Array> = Arc::new(builder.finish());
+
+    assert_eq!(
+        <Vec<String>>::from_array(array),
+        Ok(vec![
+            vec!["a".to_owned()],
+            vec!["b".to_owned(), "c".to_owned()]
+        ])
+    );
+}
+
+#[test]
+fn test_string_lists_null_values() {
+    let mut builder = GenericListBuilder::<i32, _>::new(StringBuilder::new());
+    builder.values().append_value("a");
+    builder.values().append_null();
+    builder.append(true);
+    let array: Arc<dyn Array> = Arc::new(builder.finish());
+
+    assert!(<Vec<String>>::from_array(array.clone()).is_err());
+    assert_eq!(
+        <Vec<Option<String>>>::from_array(array),
+        Ok(vec![vec![Some("a".to_owned()), None]])
+    );
+}
+
+fn fixed_size_int_lists() -> FixedSizeListArray {
+    FixedSizeListArray::from_iter_primitive::<Int32Type, _, _>(
+        vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![Some(3), Some(4)]),
+            Some(vec![Some(5), Some(6)]),
+        ],
+        2,
+    )
+}
+
+#[test]
+fn test_fixed_size_int_lists() {
+    let array: Arc<dyn Array> = Arc::new(fixed_size_int_lists().slice(2, 2));
+    assert_eq!(
+        <Vec<i32>>::from_array(array),
+        Ok(vec![vec![3, 4], vec![5, 6]])
+    );
+
+    let array: Arc<dyn Array> = Arc::new(fixed_size_int_lists());
+    assert!(<Vec<i32>>::from_array(array).is_err());
+}
+
+#[test]
+fn test_fixed_size_int_lists_options() {
+    let array: Arc<dyn Array> = Arc::new(fixed_size_int_lists());
+
+    // Allocations of the buffer are reused
+    let mut rows = vec![Some(vec![9; 10]); 4];
+    assert_eq!(
+        <Option<Vec<i32>>>::read_from_array_into_vec(array, &mut rows),
+        Ok(4)
+    );
+    assert_eq!(
+        rows,
+        vec![Some(vec![1, 2]), None, Some(vec![3, 4]), Some(vec![5, 6])]
+    );
+}
+
+#[test]
+fn test_fixed_size_string_lists() {
+    let mut builder = FixedSizeListBuilder::new(StringBuilder::new(), 2);
+    builder.values().append_value("a");
+    builder.values().append_value("bc");
+    builder.append(true);
+    builder.values().append_null();
+    builder.values().append_null();
+    builder.append(false);
+    builder.values().append_value("");
+    builder.values().append_value("def");
+    builder.append(true);
+    let array: Arc<dyn Array> = Arc::new(builder.finish());
+
+    assert!(<Vec<String>>::from_array(array.clone()).is_err());
+    assert_eq!(
+        <Option<Vec<String>>>::from_array(array.slice(2, 1)),
+        Ok(vec![Some(vec!["".to_owned(), "def".to_owned()])])
+    );
+    // Null values under null lists are not deserialized
+    assert_eq!(
+        <Option<Vec<String>>>::from_array(array),
+        Ok(vec![
+            Some(vec!["a".to_owned(), "bc".to_owned()]),
+            None,
+            Some(vec!["".to_owned(), "def".to_owned()]),
+        ])
+    );
+}
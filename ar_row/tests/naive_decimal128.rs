@@ -0,0 +1,43 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::NaiveDecimal128;
+
+#[test]
+fn test_format() {
+    let cases = [
+        (0, 0, "0"),
+        (0, 2, "0.00"),
+        (0, -2, "0"),
+        (7, 0, "7"),
+        (-7, 1, "-0.7"),
+        (-5, 3, "-0.005"),
+        (123_456, 2, "1234.56"),
+        (-10_005_000, 4, "-1000.5000"),
+        (-12, -3, "-12000"),
+        (i128::MIN, 38, "-1.70141183460469231731687303715884105728"),
+        (i128::MAX, -1, "1701411834604692317316873037158841057270"),
+    ];
+    for (value, scale, expected) in cases {
+        assert_eq!(NaiveDecimal128(value).format(scale), expected);
+    }
+}
+
+#[test]
+fn test_display_padding() {
+    assert_eq!(
+        format!("[{:>8}]", NaiveDecimal128(-15).display(1)),
+        "[    -1.5]"
+    );
+    assert_eq!(
+        format!("[{:<8}]", NaiveDecimal128(15).display(1)),
+        "[1.5     ]"
+    );
+    assert_eq!(
+        format!("[{:08}]", NaiveDecimal128(-15).display(1)),
+        "[-00001.5]"
+    );
+    assert_eq!(format!("{:+}", NaiveDecimal128(15).display(1)), "+1.5");
+}
@@ -0,0 +1,39 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{Array, BooleanArray, Int64Array};
+
+use ar_row::deserialize::ArRowDeserialize;
+
+#[test]
+fn test_options_without_nulls() {
+    // More rows than fit in a single word of the validity bitmap
+    let array = Int64Array::from((0..150).collect::<Vec<i64>>());
+    assert!(array.nulls().is_none());
+    assert_eq!(
+        <Option<i64>>::from_array(Arc::new(array) as Arc<dyn Array>).unwrap(),
+        (0..150).map(Some).collect::<Vec<_>>()
+    );
+
+    let array = BooleanArray::from((0..150).map(|i| i % 3 == 0).collect::<Vec<_>>());
+    assert_eq!(
+        <Option<bool>>::from_array(Arc::new(array) as Arc<dyn Array>).unwrap(),
+        (0..150).map(|i| Some(i % 3 == 0)).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_options_with_nulls() {
+    let values: Vec<Option<i64>> = (0..150)
+        .map(|i| if i % 70 == 0 { None } else { Some(i) })
+        .collect();
+    let array = Int64Array::from(values.clone());
+    assert_eq!(
+        <Option<i64>>::from_array(Arc::new(array) as Arc<dyn Array>).unwrap(),
+        values
+    );
+}
@@ -0,0 +1,119 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, DictionaryArray, Int32Array, Int64Array, Int8Array};
+use ar_row::arrow::datatypes::Int8Type;
+use ar_row::arrow::record_batch::RecordBatch;
+
+use ar_row::deserialize::{ArRowDeserialize, DeserializationError};
+use ar_row::options::{DeserializeOptions, NullPolicy};
+use ar_row::row_iterator::RowIterator;
+use ar_row::SharedString;
+
+#[test]
+fn test_default_options() {
+    let array: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+    let options = DeserializeOptions::default();
+    assert_eq!(
+        i64::from_array_with_options(array, &options),
+        Ok(vec![1, 2])
+    );
+
+    let array: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+    assert!(matches!(
+        i64::from_array_with_options(array, &options),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+
+    let array: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None]));
+    assert!(matches!(
+        i64::from_array_with_options(array, &options),
+        Err(DeserializationError::UnexpectedNull(_))
+    ));
+}
+
+#[test]
+fn test_coerce_types() {
+    let options = DeserializeOptions {
+        coerce_types: true,
+        ..Default::default()
+    };
+    let array: ArrayRef = Arc::new(Int32Array::from(vec![Some(1), None]));
+    assert_eq!(
+        <Option<i64>>::from_array_with_options(array, &options),
+        Ok(vec![Some(1), None])
+    );
+}
+
+#[test]
+fn test_skip_null_rows() {
+    let options = DeserializeOptions {
+        nulls: NullPolicy::SkipRows,
+        ..Default::default()
+    };
+    let array: ArrayRef = Arc::new(Int64Array::from(vec![None, Some(1), None, Some(2)]));
+    assert_eq!(
+        i64::from_array_with_options(array.clone(), &options),
+        Ok(vec![1, 2])
+    );
+    assert_eq!(
+        <Option<i64>>::from_array_with_options(array, &options),
+        Ok(vec![None, Some(1), None, Some(2)])
+    );
+}
+
+#[test]
+fn test_dictionary_cache() {
+    let dictionary: DictionaryArray<Int8Type> = vec!["a", "b", "a"].into_iter().collect();
+    let other_dictionary =
+        DictionaryArray::new(Int8Array::from(vec![1, 0]), dictionary.values().clone());
+    let batches = vec![
+        RecordBatch::try_from_iter(vec![("s", Arc::new(dictionary) as ArrayRef)]).unwrap(),
+        RecordBatch::try_from_iter(vec![("s", Arc::new(other_dictionary) as ArrayRef)]).unwrap(),
+    ];
+    for dictionary_cache in [true, false] {
+        let options = DeserializeOptions {
+            dictionary_cache,
+            ..Default::default()
+        };
+        let rows: Vec<SharedString> =
+            RowIterator::with_options(batches.clone().into_iter(), options)
+                .unwrap()
+                .collect();
+        assert_eq!(
+            rows,
+            vec!["a".into(), "b".into(), "a".into(), "b".into(), "a".into()]
+        );
+        // Values of the second batch are only deserialized again without the cache
+        assert_eq!(Arc::ptr_eq(&rows[0].0, &rows[4].0), dictionary_cache);
+    }
+}
+
+#[test]
+fn test_row_iterator_with_options() {
+    let batches = vec![
+        RecordBatch::try_from_iter(vec![(
+            "id",
+            Arc::new(Int32Array::from(vec![Some(1), None])) as ArrayRef,
+        )])
+        .unwrap(),
+        RecordBatch::try_from_iter(vec![(
+            "id",
+            Arc::new(Int32Array::from(vec![None, Some(3)])) as ArrayRef,
+        )])
+        .unwrap(),
+    ];
+    assert!(RowIterator::<_, i64>::new(batches.clone().into_iter()).is_err());
+
+    let options = DeserializeOptions {
+        coerce_types: true,
+        nulls: NullPolicy::SkipRows,
+        ..Default::default()
+    };
+    let rows = RowIterator::<_, i64>::with_options(batches.into_iter(), options).unwrap();
+    assert_eq!(rows.collect::<Vec<_>>(), vec![1, 3]);
+}
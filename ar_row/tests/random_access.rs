@@ -0,0 +1,244 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::datatypes::SchemaRef;
+use ar_row::arrow::error::ArrowError;
+use ar_row::arrow::ipc::reader::FileReader;
+use ar_row::arrow::ipc::writer::FileWriter;
+use ar_row::arrow::record_batch::RecordBatch;
+
+use ar_row::deserialize::DeserializationError;
+use ar_row::random_access::{CachedBatches, RandomAccessBatchReader, RandomAccessBatches};
+use ar_row::reader::ReadError;
+use ar_row::row_iterator::{Decision, RowIterator, RowIteratorError};
+
+fn batches() -> Vec<RecordBatch> {
+    vec![vec![0i64, 1, 2], vec![], vec![3, 4], vec![5]]
+        .into_iter()
+        .map(|values| {
+            RecordBatch::try_from_iter(vec![("", Arc::new(Int64Array::from(values)) as ArrayRef)])
+                .unwrap()
+        })
+        .collect()
+}
+
+fn ipc_file() -> Mutex<FileReader<Cursor<Vec<u8>>>> {
+    let batches = batches();
+    let mut writer = FileWriter::try_new(Vec::new(), &batches[0].schema()).unwrap();
+    for batch in &batches {
+        writer.write(batch).unwrap();
+    }
+    let file = writer.into_inner().unwrap();
+    Mutex::new(FileReader::try_new(Cursor::new(file), None).unwrap())
+}
+
+fn rows<R: RandomAccessBatchReader>(reader: R) -> RowIterator<RandomAccessBatches<R>, i64> {
+    RowIterator::new(RandomAccessBatches::new(reader)).unwrap()
+}
+
+#[test]
+fn test_forward() {
+    let rows: Vec<i64> = rows(batches()).collect();
+    assert_eq!(rows, vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_batches() {
+    let mut batches = RandomAccessBatches::new(ipc_file());
+    assert_eq!(batches.len(), 4);
+    assert_eq!(batches.batch_starts().unwrap(), &[0, 3, 3, 5, 6]);
+    assert_eq!(batches.next_back().unwrap().num_rows(), 1);
+    assert_eq!(batches.next().unwrap().num_rows(), 3);
+    assert_eq!(batches.remaining(), 1..3);
+
+    batches.seek_to_batch(2);
+    assert_eq!(
+        batches.map(|batch| batch.num_rows()).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_double_ended() {
+    let rows_rev: Vec<i64> = rows(ipc_file()).rev().collect();
+    assert_eq!(rows_rev, vec![5, 4, 3, 2, 1, 0]);
+
+    // Both ends meet in the middle of a batch
+    let mut rows = rows(batches());
+    assert_eq!(rows.next_back(), Some(5));
+    assert_eq!(rows.next(), Some(0));
+    assert_eq!(rows.next_back(), Some(4));
+    assert_eq!(rows.next_back(), Some(3));
+    assert_eq!(rows.next_back(), Some(2));
+    assert_eq!(rows.next(), Some(1));
+    assert_eq!(rows.next(), None);
+    assert_eq!(rows.next_back(), None);
+}
+
+#[test]
+fn test_double_ended_meet_in_back_batch() {
+    let mut rows = rows(batches());
+    assert_eq!(rows.next_back(), Some(5));
+    assert_eq!(rows.next_back(), Some(4));
+    assert_eq!(rows.collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_seek() {
+    let mut rows = rows(ipc_file());
+    rows.seek_to_row(4).unwrap();
+    assert_eq!(rows.by_ref().collect::<Vec<_>>(), vec![4, 5]);
+
+    rows.seek_to_row(3).unwrap();
+    assert_eq!(rows.next_back(), Some(5));
+    assert_eq!(rows.next(), Some(3));
+    assert_eq!(rows.next(), Some(4));
+    assert_eq!(rows.next(), None);
+
+    // Seeking in the middle of a batch, then reading from the back only
+    rows.seek_to_row(1).unwrap();
+    assert_eq!(rows.by_ref().rev().collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+
+    rows.seek_to_row(6).unwrap();
+    assert_eq!(rows.next(), None);
+    assert_eq!(rows.next_back(), None);
+}
+
+/// Batches of rows 0 to 4, where row 3 is null
+fn batches_with_null() -> Vec<RecordBatch> {
+    vec![
+        RecordBatch::try_from_iter(vec![(
+            "",
+            Arc::new(Int64Array::from(vec![Some(0), Some(1)])) as ArrayRef,
+        )])
+        .unwrap(),
+        RecordBatch::try_from_iter(vec![(
+            "",
+            Arc::new(Int64Array::from(vec![Some(2), None, Some(4)])) as ArrayRef,
+        )])
+        .unwrap(),
+    ]
+}
+
+#[test]
+fn test_next_back_on_error() {
+    let batches = batches_with_null();
+    let failed_rows = Arc::new(Mutex::new(Vec::new()));
+    let rows = {
+        let failed_rows = failed_rows.clone();
+        rows(batches).on_error(move |row, _error| {
+            failed_rows.lock().unwrap().push(row);
+            Decision::Skip
+        })
+    };
+    assert_eq!(rows.rev().collect::<Vec<_>>(), vec![4, 2, 1, 0]);
+    assert_eq!(*failed_rows.lock().unwrap(), vec![3]);
+}
+
+/// Fails to count rows of its batches
+struct UncountableReader(Vec<RecordBatch>);
+
+impl RandomAccessBatchReader for UncountableReader {
+    fn schema(&self) -> SchemaRef {
+        self.0.schema()
+    }
+
+    fn num_batches(&self) -> usize {
+        self.0.num_batches()
+    }
+
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        self.0.read_batch(batch_index)
+    }
+
+    fn batch_num_rows(&self, _batch_index: usize) -> Result<usize, ReadError> {
+        Err(ArrowError::ComputeError("Cannot count rows".to_owned()).into())
+    }
+}
+
+/// Errors of batches read from the end are returned with an unknown first row when
+/// rows cannot be counted, instead of panicking
+#[test]
+fn test_next_back_on_error_uncountable() {
+    let mut rows = rows(UncountableReader(batches_with_null())).on_error(|_, _| Decision::Skip);
+    let error = rows.try_next_back().unwrap_err();
+    assert!(matches!(
+        error,
+        RowIteratorError {
+            batch_index: 1,
+            first_row: None,
+            num_rows: 3,
+            source: DeserializationError::UnexpectedNull(_),
+        }
+    ));
+    assert!(error
+        .to_string()
+        .starts_with("Could not deserialize batch 1 (3 rows): "));
+    assert_eq!(rows.try_next_back(), Ok(Some(1)));
+    assert_eq!(rows.try_next_back(), Ok(Some(0)));
+    assert_eq!(rows.try_next_back(), Ok(None));
+}
+
+#[test]
+fn test_mismatched_schema() {
+    let batch = RecordBatch::try_from_iter(vec![(
+        "",
+        Arc::new(StringArray::from(vec!["foo"])) as ArrayRef,
+    )])
+    .unwrap();
+    assert!(RowIterator::<_, i64>::new(RandomAccessBatches::new(vec![batch.clone()])).is_err());
+    assert!(CachedBatches::<_, i64>::new(vec![batch], NonZeroUsize::new(1).unwrap()).is_err());
+}
+
+/// Counts the batches read from the wrapped reader
+struct CountingReader {
+    batches: Vec<RecordBatch>,
+    reads: AtomicUsize,
+}
+
+impl RandomAccessBatchReader for CountingReader {
+    fn schema(&self) -> SchemaRef {
+        self.batches.schema()
+    }
+
+    fn num_batches(&self) -> usize {
+        self.batches.num_batches()
+    }
+
+    fn read_batch(&self, batch_index: usize) -> Result<RecordBatch, ReadError> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.batches.read_batch(batch_index)
+    }
+
+    fn batch_num_rows(&self, batch_index: usize) -> Result<usize, ReadError> {
+        self.batches.batch_num_rows(batch_index)
+    }
+}
+
+#[test]
+fn test_cached_batches() {
+    let reader = CountingReader {
+        batches: batches(),
+        reads: AtomicUsize::new(0),
+    };
+    let rows = CachedBatches::<_, i64>::new(reader, NonZeroUsize::new(2).unwrap()).unwrap();
+    assert_eq!(rows.num_rows(), 6);
+    assert_eq!(rows.get(1).unwrap(), Some(1));
+    assert_eq!(rows.get(0).unwrap(), Some(0));
+    assert_eq!(rows.get(3).unwrap(), Some(3));
+    assert_eq!(rows.get(4).unwrap(), Some(4));
+    assert_eq!(rows.get(6).unwrap(), None);
+    assert_eq!(rows.with_row(5, |row| row * 10).unwrap(), Some(50));
+    // Batch 0 is the least recently used, so reading batch 3 evicted it
+    assert_eq!(rows.get(2).unwrap(), Some(2));
+    assert_eq!(rows.get(5).unwrap(), Some(5));
+    assert_eq!(rows.into_inner().reads.load(Ordering::Relaxed), 4);
+}
@@ -0,0 +1,28 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{Array, Int64Array};
+
+use ar_row::deserialize::ArRowDeserialize;
+
+#[test]
+fn test_read_from_array_into_vec_grows() {
+    let array: Arc<dyn Array> = Arc::new(Int64Array::from(vec![1, 2, 3]));
+
+    let mut rows = vec![0i64; 2];
+    assert_eq!(<i64>::read_from_array_into_vec(array, &mut rows), Ok(3));
+    assert_eq!(rows, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_read_from_array_into_vec_shrinks() {
+    let array: Arc<dyn Array> = Arc::new(Int64Array::from(vec![1, 2, 3]));
+
+    let mut rows = vec![0i64; 5];
+    assert_eq!(<i64>::read_from_array_into_vec(array, &mut rows), Ok(3));
+    assert_eq!(rows, vec![1, 2, 3]);
+}
@@ -0,0 +1,126 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    ArrayRef, Int32Array, ListArray, StringArray, TimestampNanosecondArray,
+};
+use ar_row::arrow::datatypes::{Field, Int32Type, Schema};
+use ar_row::arrow::record_batch::RecordBatch;
+
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::dynamic_row::DynamicRow;
+use ar_row::row_iterator::RowIterator;
+use ar_row::schema::SchemaOptions;
+use ar_row::value::ArRowValue;
+use ar_row::Timestamp;
+
+fn batch(array: ArrayRef) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![("", array)]).unwrap()
+}
+
+#[test]
+fn test_scalar_root() {
+    let batch = batch(Arc::new(TimestampNanosecondArray::from(vec![
+        1_500_000_000,
+        -1,
+    ])));
+    Timestamp::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        Timestamp::from_record_batch(batch),
+        Ok(vec![
+            Timestamp {
+                seconds: 1,
+                nanoseconds: 500_000_000
+            },
+            Timestamp {
+                seconds: -1,
+                nanoseconds: 999_999_999
+            }
+        ])
+    );
+}
+
+#[test]
+fn test_option_root() {
+    let batch = batch(Arc::new(StringArray::from(vec![Some("foo"), None])));
+    <Option<String>>::check_schema(&batch.schema()).unwrap();
+    String::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        <Option<String>>::from_record_batch(batch.clone()),
+        Ok(vec![Some("foo".to_owned()), None])
+    );
+    assert!(String::from_record_batch(batch).is_err());
+}
+
+#[test]
+fn test_list_root() {
+    let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), Some(2)]),
+        Some(vec![]),
+    ]);
+    let batch = batch(Arc::new(list));
+    <Vec<i32>>::check_schema(&batch.schema()).unwrap();
+
+    let mut rows = vec![vec![0; 10]; 3];
+    assert_eq!(
+        <Vec<i32>>::read_from_record_batch_into_vec(batch.clone(), &mut rows),
+        Ok(2)
+    );
+    assert_eq!(rows, vec![vec![1, 2], vec![]]);
+
+    assert_eq!(
+        RowIterator::<_, Vec<i32>>::new(vec![batch.clone(), batch].into_iter())
+            .unwrap()
+            .collect::<Vec<_>>(),
+        vec![vec![1, 2], vec![], vec![1, 2], vec![]]
+    );
+}
+
+#[test]
+fn test_reader_schema_roundtrip() {
+    let schema = Timestamp::reader_schema(&SchemaOptions::default());
+    Timestamp::check_schema(&schema).unwrap();
+    assert_eq!(
+        schema,
+        Schema::new(vec![Field::new("", Timestamp::expected_datatype(), false)])
+    );
+}
+
+#[test]
+fn test_mismatched_root() {
+    let batch = batch(Arc::new(Int32Array::from(vec![1, 2])));
+    assert!(Timestamp::check_schema(&batch.schema()).is_err());
+    assert!(Timestamp::from_record_batch(batch).is_err());
+
+    // Two columns are never read as a scalar root
+    let batch = RecordBatch::try_from_iter(vec![
+        ("a", Arc::new(Int32Array::from(vec![1])) as ArrayRef),
+        ("b", Arc::new(Int32Array::from(vec![2])) as ArrayRef),
+    ])
+    .unwrap();
+    assert!(i32::check_schema(&batch.schema()).is_err());
+    assert!(i32::from_record_batch(batch).is_err());
+}
+
+#[test]
+fn test_struct_roots_unchanged() {
+    // Types which can be read from a structure still read the whole batch
+    let batch =
+        RecordBatch::try_from_iter(vec![("a", Arc::new(Int32Array::from(vec![1])) as ArrayRef)])
+            .unwrap();
+    assert_eq!(
+        ArRowValue::from_record_batch(batch.clone()),
+        Ok(vec![ArRowValue::Struct(vec![(
+            "a".to_owned(),
+            ArRowValue::Int32(1)
+        )])])
+    );
+    assert_eq!(
+        DynamicRow::from_record_batch(batch).unwrap()[0].get::<i32>("a"),
+        Ok(1)
+    );
+}
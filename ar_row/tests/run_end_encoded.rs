@@ -0,0 +1,191 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    Array, DictionaryArray, Int16Array, Int32Array, Int64Array, Int8Array, ListArray, RunArray,
+    StringArray,
+};
+use ar_row::arrow::datatypes::{Int16Type, Int32Type, Int64Type, Int8Type};
+
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::DisplayString;
+
+/// `["a", "a", "b", "c", "c", "c"]`
+fn string_runs() -> RunArray<Int32Type> {
+    RunArray::try_new(
+        &Int32Array::from(vec![2, 3, 6]),
+        &StringArray::from(vec!["a", "b", "c"]),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_strings() {
+    let array: Arc<dyn Array> = Arc::new(string_runs());
+    assert_eq!(
+        <String>::from_array(array),
+        Ok(vec!["a", "a", "b", "c", "c", "c"]
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect())
+    );
+}
+
+#[test]
+fn test_sliced() {
+    let array: Arc<dyn Array> = Arc::new(string_runs());
+    assert_eq!(
+        <String>::from_array(array.slice(1, 3)),
+        Ok(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()])
+    );
+    assert_eq!(<String>::from_array(array.slice(6, 0)), Ok(vec![]));
+}
+
+#[test]
+fn test_run_end_types() {
+    let values = Int64Array::from(vec![Some(1), None, Some(3)]);
+    let expected = Ok(vec![Some(1), None, None, Some(3)]);
+
+    let array: Arc<dyn Array> = Arc::new(
+        RunArray::<Int16Type>::try_new(&Int16Array::from(vec![1, 3, 4]), &values).unwrap(),
+    );
+    assert_eq!(<Option<i64>>::from_array(array), expected);
+
+    let array: Arc<dyn Array> = Arc::new(
+        RunArray::<Int64Type>::try_new(&Int64Array::from(vec![1, 3, 4]), &values).unwrap(),
+    );
+    assert_eq!(<Option<i64>>::from_array(array.clone()), expected);
+    assert!(matches!(
+        <i64>::from_array(array),
+        Err(ar_row::deserialize::DeserializationError::UnexpectedNull(_))
+    ));
+}
+
+#[test]
+fn test_lists() {
+    let values =
+        ListArray::from_iter_primitive::<Int32Type, _, _>(vec![Some(vec![Some(1), Some(2)]), None]);
+    let array: Arc<dyn Array> =
+        Arc::new(RunArray::<Int32Type>::try_new(&Int32Array::from(vec![2, 3]), &values).unwrap());
+    assert_eq!(
+        <Option<Vec<i32>>>::from_array(array),
+        Ok(vec![Some(vec![1, 2]), Some(vec![1, 2]), None])
+    );
+}
+
+#[test]
+fn test_run_end_encoded_dictionary() {
+    let values: DictionaryArray<Int8Type> = vec!["a", "b", "a"].into_iter().collect();
+    let array: Arc<dyn Array> = Arc::new(
+        RunArray::<Int32Type>::try_new(&Int32Array::from(vec![2, 3, 5]), &values).unwrap(),
+    );
+    assert_eq!(
+        <String>::from_array(array),
+        Ok(vec!["a", "a", "b", "a", "a"]
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect())
+    );
+}
+
+#[test]
+fn test_dictionary_of_run_end_encoded() {
+    let array: Arc<dyn Array> = Arc::new(
+        DictionaryArray::<Int8Type>::try_new(
+            Int8Array::from(vec![Some(5), None, Some(0), Some(2)]),
+            Arc::new(string_runs()),
+        )
+        .unwrap(),
+    );
+    assert_eq!(
+        <Option<String>>::from_array(array),
+        Ok(vec![
+            Some("c".to_owned()),
+            None,
+            Some("a".to_owned()),
+            Some("b".to_owned())
+        ])
+    );
+
+    // Sparse dictionary, whose referenced values only are decoded
+    let values = RunArray::<Int32Type>::try_new(
+        &Int32Array::from(vec![10, 20]),
+        &StringArray::from(vec!["a", "b"]),
+    )
+    .unwrap();
+    let array: Arc<dyn Array> = Arc::new(
+        DictionaryArray::<Int8Type>::try_new(Int8Array::from(vec![15, 3]), Arc::new(values))
+            .unwrap(),
+    );
+    assert_eq!(
+        <String>::from_array(array),
+        Ok(vec!["b".to_owned(), "a".to_owned()])
+    );
+}
+
+#[test]
+fn test_check_datatype() {
+    let array = string_runs();
+    assert_eq!(String::check_datatype(array.data_type()), Ok(()));
+    assert_eq!(<Option<String>>::check_datatype(array.data_type()), Ok(()));
+    assert!(i64::check_datatype(array.data_type()).is_err());
+
+    let list_runs = RunArray::<Int32Type>::try_new(
+        &Int32Array::from(vec![2]),
+        &ListArray::from_iter_primitive::<Int64Type, _, _>(vec![Some(vec![Some(1)])]),
+    )
+    .unwrap();
+    assert_eq!(<Vec<i64>>::check_datatype(list_runs.data_type()), Ok(()));
+    assert_eq!(
+        <Vec<i64>>::from_array(Arc::new(list_runs) as Arc<dyn Array>),
+        Ok(vec![vec![1], vec![1]])
+    );
+}
+
+#[test]
+fn test_display_strings() {
+    let array = string_runs();
+    assert_eq!(DisplayString::check_datatype(array.data_type()), Ok(()));
+    assert_eq!(
+        DisplayString::from_array(Arc::new(array) as Arc<dyn Array>),
+        Ok(vec!["a", "a", "b", "c", "c", "c"]
+            .into_iter()
+            .map(|s| DisplayString(s.to_owned()))
+            .collect())
+    );
+
+    let array = RunArray::<Int16Type>::try_new(
+        &Int16Array::from(vec![1, 3, 4]),
+        &Int64Array::from(vec![Some(1), None, Some(3)]),
+    )
+    .unwrap();
+    assert_eq!(
+        <Option<DisplayString>>::check_datatype(array.data_type()),
+        Ok(())
+    );
+    let array: Arc<dyn Array> = Arc::new(array);
+    assert_eq!(
+        <Option<DisplayString>>::from_array(array.clone()),
+        Ok(vec![
+            Some(DisplayString("1".to_owned())),
+            None,
+            None,
+            Some(DisplayString("3".to_owned()))
+        ])
+    );
+    assert!(matches!(
+        DisplayString::from_array(array),
+        Err(ar_row::deserialize::DeserializationError::UnexpectedNull(_))
+    ));
+
+    let list_runs = RunArray::<Int32Type>::try_new(
+        &Int32Array::from(vec![2]),
+        &ListArray::from_iter_primitive::<Int64Type, _, _>(vec![Some(vec![Some(1)])]),
+    )
+    .unwrap();
+    assert!(DisplayString::check_datatype(list_runs.data_type()).is_err());
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::time::Duration;
+
+use ar_row::{Date, ParseTimestampError, Timestamp};
+
+#[test]
+fn test_to_rfc3339() {
+    let cases = [
+        ((0, 0), "1970-01-01T00:00:00Z"),
+        ((951_782_400, 1_000), "2000-02-29T00:00:00.000001Z"),
+        (
+            (1_700_000_000, 123_456_789),
+            "2023-11-14T22:13:20.123456789Z",
+        ),
+        ((-1, 999_000_000), "1969-12-31T23:59:59.999Z"),
+        ((-62_135_596_800, 0), "0001-01-01T00:00:00Z"),
+        ((-62_167_219_200, 0), "0000-01-01T00:00:00Z"),
+        ((-62_167_219_201, 0), "-0001-12-31T23:59:59Z"),
+        ((253_402_300_800, 0), "+10000-01-01T00:00:00Z"),
+    ];
+    for ((seconds, nanoseconds), expected) in cases {
+        let timestamp = Timestamp::normalized(seconds, nanoseconds);
+        assert_eq!(timestamp.to_rfc3339(), expected);
+        if !expected.starts_with(['+', '-']) {
+            assert_eq!(Timestamp::parse_rfc3339(expected), Ok(timestamp));
+        }
+    }
+
+    // Not normalized
+    assert_eq!(
+        Timestamp {
+            seconds: 0,
+            nanoseconds: -1
+        }
+        .to_rfc3339(),
+        "1969-12-31T23:59:59.999999999Z"
+    );
+}
+
+#[test]
+fn test_parse_rfc3339() {
+    let cases = [
+        ("1970-01-01t00:00:00z", (0, 0)),
+        ("1970-01-01 01:00:00+01:00", (0, 0)),
+        ("1969-12-31T19:30:00-04:30", (0, 0)),
+        ("1970-01-01T00:00:00.1Z", (0, 100_000_000)),
+        ("1970-01-01T00:00:00.1234567891Z", (0, 123_456_789)),
+        ("2016-12-31T23:59:60Z", (1_483_228_800, 0)),
+    ];
+    for (s, (seconds, nanoseconds)) in cases {
+        assert_eq!(
+            Timestamp::parse_rfc3339(s),
+            Ok(Timestamp::normalized(seconds, nanoseconds)),
+            "{}",
+            s
+        );
+    }
+}
+
+#[test]
+fn test_parse_rfc3339_invalid() {
+    for s in [
+        "",
+        "1970-01-01",
+        "1970-01-01T00:00:00",
+        "1970-01-01T00:00Z",
+        "1970-1-01T00:00:00Z",
+        "1970-01-01T00:00:00.Z",
+        "1970-01-01T00:00:00+0100",
+        "1970-01-01T00:00:00+01:00:00",
+        "1970-01-01T00:00:00Zjunk",
+        "1970-13-01T00:00:00Z",
+        "1970-04-31T00:00:00Z",
+        "1900-02-29T00:00:00Z",
+        "1970-01-01T24:00:00Z",
+        "1970-01-01T00:60:00Z",
+        "1970-01-01T00:00:61Z",
+        "+1970-01-01T00:00:00Z",
+    ] {
+        assert_eq!(
+            Timestamp::parse_rfc3339(s),
+            Err(ParseTimestampError(s.to_owned()))
+        );
+    }
+}
+
+#[test]
+fn test_duration_arithmetic() {
+    let timestamp = Timestamp::normalized(10, 700_000_000);
+    assert_eq!(
+        timestamp + Duration::from_millis(1_500),
+        Timestamp::normalized(12, 200_000_000)
+    );
+    assert_eq!(
+        timestamp - Duration::from_millis(10_800),
+        Timestamp::normalized(-1, 900_000_000)
+    );
+
+    let mut timestamp2 = timestamp;
+    timestamp2 += Duration::from_nanos(300_000_000);
+    assert_eq!(timestamp2, Timestamp::normalized(11, 0));
+    assert_eq!(timestamp2 - timestamp, Duration::from_millis(300));
+    timestamp2 -= Duration::from_secs(1);
+    assert_eq!(timestamp2, Timestamp::normalized(10, 0));
+    assert_eq!(timestamp2.checked_duration_since(timestamp), None);
+
+    // Not normalized
+    let timestamp3 = Timestamp {
+        seconds: 0,
+        nanoseconds: 2_500_000_000,
+    };
+    assert_eq!(
+        timestamp3 + Duration::from_millis(500),
+        Timestamp::normalized(3, 0)
+    );
+    assert_eq!(
+        timestamp3 - Timestamp::normalized(1, 0),
+        Duration::from_millis(1_500)
+    );
+}
+
+#[test]
+fn test_duration_overflow() {
+    let max = Timestamp::normalized(i64::MAX, 999_999_999);
+    assert_eq!(max.checked_add(Duration::from_nanos(1)), None);
+    assert_eq!(max.checked_add(Duration::from_secs(u64::MAX)), None);
+    assert_eq!(
+        max.checked_sub(Duration::from_nanos(999_999_999)),
+        Some(Timestamp::normalized(i64::MAX, 0))
+    );
+    let min = Timestamp::normalized(i64::MIN, 0);
+    assert_eq!(min.checked_sub(Duration::from_nanos(1)), None);
+    assert_eq!(max.checked_duration_since(min), None);
+
+    // Not normalized, and overflowing when normalized
+    let unnormalized_max = Timestamp {
+        seconds: i64::MAX,
+        nanoseconds: 1_000_000_000,
+    };
+    assert_eq!(
+        Timestamp::checked_normalized(unnormalized_max.seconds, unnormalized_max.nanoseconds),
+        None
+    );
+    assert_eq!(unnormalized_max.checked_add(Duration::ZERO), None);
+    assert_eq!(unnormalized_max.checked_sub(Duration::ZERO), None);
+    assert_eq!(unnormalized_max.checked_duration_since(min), None);
+    assert_eq!(min.checked_duration_since(unnormalized_max), None);
+
+    // Borrowing a second from i64::MIN
+    let start = Timestamp::normalized(0, 1);
+    let end = Timestamp::normalized(i64::MIN, 0);
+    assert_eq!(end.checked_duration_since(start), None);
+}
+
+#[test]
+#[should_panic(expected = "overflow when normalizing timestamp")]
+fn test_normalized_overflow() {
+    let _ = Timestamp::normalized(i64::MIN, -1);
+}
+
+#[test]
+#[should_panic]
+fn test_sub_later_timestamp() {
+    let _ = Timestamp::normalized(0, 0) - Timestamp::normalized(0, 1);
+}
+
+#[test]
+fn test_date() {
+    assert_eq!(Timestamp::normalized(86_399, 999_999_999).date(), Date(0));
+    assert_eq!(Timestamp::normalized(86_400, 0).date(), Date(1));
+    assert_eq!(Timestamp::normalized(-1, 0).date(), Date(-1));
+    assert_eq!(
+        Date(-1).checked_to_timestamp(),
+        Some(Timestamp::normalized(-86_400, 0))
+    );
+
+    let mut date = Date(10);
+    date += 5;
+    assert_eq!(date, Date(15));
+    date -= 20;
+    assert_eq!(date, Date(-5));
+    assert_eq!(date - Date(10), -15);
+    assert_eq!(Date(i64::MAX).checked_add_days(1), None);
+    assert_eq!(Date(0).checked_add_days(-1), Some(Date(-1)));
+    assert_eq!(Date(i64::MIN).checked_sub_days(1), None);
+    assert_eq!(Date(0).checked_sub_days(-1), Some(Date(1)));
+}
+
+#[test]
+#[should_panic(expected = "overflow when adding days to date")]
+fn test_date_add_overflow() {
+    let _ = Date(i64::MAX) + 1;
+}
+
+#[test]
+#[should_panic(expected = "overflow when subtracting days from date")]
+fn test_date_sub_overflow() {
+    let _ = Date(i64::MIN) - 1;
+}
+
+#[test]
+#[should_panic(expected = "overflow when subtracting dates")]
+fn test_date_sub_date_overflow() {
+    let _ = Date(i64::MIN) - Date(1);
+}
+
+#[test]
+fn test_date_to_timestamp_overflow() {
+    assert_eq!(
+        Date(i64::MAX / 86_400).checked_to_timestamp(),
+        Some(Timestamp::normalized(i64::MAX / 86_400 * 86_400, 0))
+    );
+    assert_eq!(Date(i64::MAX / 86_400 + 1).checked_to_timestamp(), None);
+    assert_eq!(Date(i64::MIN / 86_400 - 1).checked_to_timestamp(), None);
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+
+use ar_row::deserialize::DeserializationError;
+use ar_row::typed_batch::TypedRecordBatch;
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "id",
+            Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec!["a", "b", "c"])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_rows() {
+    let batch = TypedRecordBatch::<(Option<i64>, String)>::try_new(batch()).unwrap();
+    assert_eq!(batch.len(), 3);
+    assert!(!batch.is_empty());
+    assert_eq!(
+        batch.rows(),
+        Ok(vec![
+            (Some(1), "a".to_owned()),
+            (None, "b".to_owned()),
+            (Some(3), "c".to_owned())
+        ])
+    );
+    assert_eq!(batch.row(1), Ok(Some((None, "b".to_owned()))));
+    assert_eq!(batch.row(3), Ok(None));
+
+    let slice = batch.slice(2, 1);
+    assert_eq!(slice.len(), 1);
+    assert_eq!(slice.rows(), Ok(vec![(Some(3), "c".to_owned())]));
+    assert_eq!(batch.into_inner(), self::batch());
+}
+
+#[test]
+fn test_mismatched_schema() {
+    assert!(matches!(
+        TypedRecordBatch::<(String, String)>::try_new(batch()),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+    assert!(matches!(
+        TypedRecordBatch::<i64>::try_new(batch()),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+}
+
+#[test]
+fn test_unexpected_null() {
+    // Nullability is only checked when deserializing
+    let batch = TypedRecordBatch::<(i64, String)>::try_new(batch()).unwrap();
+    assert!(matches!(
+        batch.rows(),
+        Err(DeserializationError::UnexpectedNull(_))
+    ));
+    assert!(matches!(
+        batch.row(1),
+        Err(DeserializationError::UnexpectedNull(_))
+    ));
+}
@@ -12,9 +12,22 @@
 //!
 //! * [`bool`], [`i8`], [`i16`], [`i32`], [`i64`], [`u8`], [`u16`], [`u32`], [`u64`], [`f32`], [`f64`], [`String`], `Box<[u8]>` (binary strings),
 //!   mapping to their respective Arrow type
-//! * `Vec<T>` when `T` is a supported type, mapping to an Arrow list
-//! * `HashMap<K, V>` and `Vec<(K, V)>` are not supported yet to deserialize ORC maps
-//!   (see <https://gitlab.softwareheritage.org/swh/devel/ar_row-rs/-/issues/1>)
+//! * [`ar_row::SharedString`], mapping to an Arrow string like [`String`], but sharing
+//!   allocations between rows of a dictionary-encoded array with the same value
+//! * `[u8; N]` (and [`ar_row::FixedSizeBinary<N>`], which implements [`Default`]
+//!   for any `N`), mapping to an Arrow `FixedSizeBinary(N)`
+//! * `[T; N]` for other supported types `T`, including `[Option<T>; N]` (and
+//!   [`ar_row::FixedSizeList<T, N>`], which implements [`Default`] for any `N`),
+//!   mapping to an Arrow `FixedSizeList(N)`; elements must be wrapped in `Option`
+//!   if the list's field is nullable
+//! * `Vec<T>` when `T` is a supported type, mapping to an Arrow list (or read from
+//!   a fixed-size list)
+//! * `HashSet<T>` and `BTreeSet<T>`, read from the same columns as `Vec<T>` but
+//!   without duplicate values
+//! * `ar_row::lazy::Lazy<T>` when `T` is a supported type, mapping to the same Arrow
+//!   type as `T` but only decoded when accessed
+//! * `Vec<ar_row::MapEntry<K, V>>`, mapping to an Arrow map; `HashMap<K, V>` is not
+//!   supported yet (see <https://gitlab.softwareheritage.org/swh/devel/ar_row-rs/-/issues/1>)
 //!
 //! # About null values
 //!
@@ -26,6 +39,65 @@
 //! If you omit `Option`, then `ar_row_derive` will return an error early for files
 //! containing null values, and avoid this overhead for files which don't.
 //!
+//! # Columns
+//!
+//! Fields are read from the columns with the same names, in the same order.
+//! Trailing columns without a matching field are ignored, so a structure without
+//! fields (eg. `struct Row;`) can be deserialized from any record batch, to count
+//! its rows.
+//!
+//! # Cursors
+//!
+//! With `#[ar_row(cursor)]`, the derive also defines a `{Name}Ref<'a>` type for the
+//! structure, with the same visibility, and one method per field decoding that field
+//! for a single row. It is yielded by
+//! [`ArRowCursor<Name>`](../ar_row/cursor/struct.ArRowCursor.html), to avoid decoding
+//! fields which are not used.
+//!
+//! # Partial updates
+//!
+//! The derive also implements
+//! [`ArRowUpdate`](../ar_row/deserialize/trait.ArRowUpdate.html), which overwrites
+//! fields of existing rows from a batch containing only some of their columns
+//! (matched by name, in any order), and leaves the other fields untouched.
+//!
+//! # Hashing
+//!
+//! `#[derive(ArRowHash)]` implements
+//! [`StableHash`](../ar_row/hash/trait.StableHash.html), whose hashes do not depend
+//! on the platform or Rust version, for structures whose fields all implement it
+//! (which includes all the types above, except `Lazy<T>`).
+//!
+//! # Attributes
+//!
+//! The derived implementation can be configured with `#[ar_row(...)]` attributes
+//! on the structure:
+//!
+//! * `#[ar_row(direct_field_writes)]` decodes each column into a temporary vector,
+//!   then moves values to the structure's fields in a plain loop. By default, columns
+//!   are decoded directly into the fields through a chain of projections (one per
+//!   level of nesting), which avoids the temporary allocation but may be slower on
+//!   structures with many fields. Run `cargo bench --bench wide_structs` in `ar_row`
+//!   to compare both on your machine before enabling it.
+//! * `#[ar_row(cursor)]` defines `{Name}Ref<'a>` and implements
+//!   [`CursorRow`](../ar_row/cursor/trait.CursorRow.html) (see [Cursors](#cursors)).
+//!
+//! and on fields:
+//!
+//! * `#[ar_row(binary)]` on a `Vec<u8>` or `Option<Vec<u8>>` field reads it only from
+//!   Arrow `Binary`/`LargeBinary` arrays, and makes it a `Binary` column in the
+//!   [`reader_schema`](../ar_row/deserialize/trait.CheckableDataType.html#method.reader_schema).
+//!   Without it, `Vec<u8>` fields can be read from both binary arrays and lists of
+//!   `UInt8`, but are lists of `UInt8` in the schema.
+//! * `#[ar_row(stringify)]` on a `String` or `Option<String>` field reads it from
+//!   any primitive, boolean, or string array, using the display form of its values
+//!   (see [`ar_row::DisplayString`]).
+//! * `#[ar_row(null_as_default)]` on a non-`Option` field reads nulls as
+//!   `Default::default()` instead of failing with
+//!   [`UnexpectedNull`](../ar_row/deserialize/enum.DeserializationError.html#variant.UnexpectedNull).
+//!   The field is then nullable in the
+//!   [`reader_schema`](../ar_row/deserialize/trait.CheckableDataType.html#method.reader_schema).
+//!
 //! # Examples
 //!
 //! <!-- Keep this in sync with README.md -->
@@ -75,8 +147,9 @@
 //! );
 //! ```
 //!
-//! Or equivalently, using `RowIterator` to reuse the buffer between record batches,
-//! but needs `RecordBatch` instead of `Result<RecordBatch, _>` as input:
+//! Or equivalently, using `RowIterator` to reuse the buffer between record batches;
+//! `into_rows()` builds it from any iterator of `RecordBatch` or
+//! `Result<RecordBatch, _>`, and panics on errors of the latter:
 //!
 //! <!-- Keep this in sync with README.md -->
 //!
@@ -88,7 +161,7 @@
 //! use orc_rust::{ArrowReader, ArrowReaderBuilder};
 //!
 //! use ar_row::deserialize::{ArRowDeserialize, ArRowStruct};
-//! use ar_row::row_iterator::RowIterator;
+//! use ar_row::row_iterator::IntoRows;
 //! use ar_row_derive::ArRowDeserialize;
 //!
 //! // Define structure
@@ -106,7 +179,8 @@
 //!     &["long1"],
 //! );
 //! let reader = builder.with_projection(projection).build();
-//! let mut rows: Vec<Option<Test1>> = RowIterator::new(reader.map(|batch| batch.unwrap()))
+//! let mut rows: Vec<Option<Test1>> = reader
+//!     .into_rows()
 //!     .expect("Could not create iterator")
 //!     .collect();
 //!
@@ -152,37 +226,140 @@
 //! ```
 
 use proc_macro::TokenStream;
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use syn::*;
 
+/// Options set with `#[ar_row(...)]` on the structure
+#[derive(Default)]
+struct StructOptions {
+    /// Decode each column into a temporary buffer, then move values to fields
+    /// in a loop, instead of projecting the destination with `MultiMap`.
+    direct_field_writes: bool,
+    /// Define `{ident}Ref` and implement `ar_row::cursor::CursorRow`
+    cursor: bool,
+}
+
+/// Options set with `#[ar_row(...)]` on a field
+#[derive(Default)]
+struct FieldOptions {
+    /// Check the field's datatype and build its schema as `Box<[u8]>`
+    binary: bool,
+    /// Read the field through `ar_row::DisplayString`
+    stringify: bool,
+    /// Read the field as an `Option`, and replace `None` with `Default::default()`
+    null_as_default: bool,
+}
+
+impl FieldOptions {
+    fn from_attributes(field: &Field) -> Result<FieldOptions> {
+        let attrs = &field.attrs;
+        let mut options = FieldOptions::default();
+        for attr in attrs {
+            if !attr.path().is_ident("ar_row") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("binary") {
+                    options.binary = true;
+                    Ok(())
+                } else if meta.path.is_ident("stringify") {
+                    options.stringify = true;
+                    Ok(())
+                } else if meta.path.is_ident("null_as_default") {
+                    if option_argument(&field.ty).is_some() {
+                        return Err(meta.error("null_as_default cannot be used on Option fields"));
+                    }
+                    options.null_as_default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported #[ar_row(...)] field attribute"))
+                }
+            })?;
+            if options.binary && options.stringify {
+                return Err(Error::new_spanned(
+                    attr,
+                    "binary and stringify cannot be combined",
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl StructOptions {
+    fn from_attributes(attrs: &[Attribute]) -> Result<StructOptions> {
+        let mut options = StructOptions::default();
+        for attr in attrs {
+            if !attr.path().is_ident("ar_row") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("direct_field_writes") {
+                    options.direct_field_writes = true;
+                    Ok(())
+                } else if meta.path.is_ident("cursor") {
+                    options.cursor = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported #[ar_row(...)] structure attribute"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
 /// `#[derive(ArRowDeserialize)] struct T { ... }` implements
 /// [`ArRowDeserialize`](../ar_row/deserialize/struct.ArRowDeserialize.html),
 /// [`CheckableDataType`](../ar_row/deserialize/struct.CheckableDataType.html), and
 /// [`ArRowStruct`](../ar_row/deserialize/struct.ArRowStruct.html) for `T`
 ///
 /// This automatically gives implementations for `Option<T>` and `Vec<T>` as well.
-#[proc_macro_derive(ArRowDeserialize)]
+#[proc_macro_derive(ArRowDeserialize, attributes(ar_row))]
 pub fn ar_row_deserialize(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
 
+    let options = match StructOptions::from_attributes(&ast.attrs) {
+        Ok(options) => options,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
     let tokens = match ast.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(FieldsNamed { named, .. }),
             ..
-        }) => impl_struct(
-            &ast.ident,
-            named
+        }) => {
+            let field_options = match named
                 .iter()
-                .map(|field| {
-                    field
-                        .ident
-                        .as_ref()
-                        .expect("#ident must not have anonymous fields")
-                })
-                .collect(),
-            named.iter().map(|field| &field.ty).collect(),
-        ),
+                .map(FieldOptions::from_attributes)
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(field_options) => field_options,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            impl_struct(
+                &ast.ident,
+                &ast.vis,
+                named
+                    .iter()
+                    .map(|field| {
+                        field
+                            .ident
+                            .as_ref()
+                            .expect("#ident must not have anonymous fields")
+                    })
+                    .collect(),
+                named.iter().map(|field| &field.ty).collect(),
+                &field_options,
+                &options,
+            )
+        }
+        // Unit structures have no columns, but can still be used to count rows
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => impl_struct(&ast.ident, &ast.vis, Vec::new(), Vec::new(), &[], &options),
         Data::Struct(DataStruct { .. }) => panic!("#ident must have named fields"),
         _ => panic!("#ident must be a structure"),
     };
@@ -192,21 +369,318 @@ pub fn ar_row_deserialize(input: TokenStream) -> TokenStream {
     tokens
 }
 
-fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>) -> TokenStream {
+/// `#[derive(ArRowHash)] struct T { ... }` implements
+/// [`StableHash`](../ar_row/hash/trait.StableHash.html) for `T`, by writing the name
+/// of each field followed by its value.
+#[proc_macro_derive(ArRowHash)]
+pub fn ar_row_hash(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let field_names: Vec<&Ident> = match &ast.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) => named
+            .iter()
+            .map(|field| {
+                field
+                    .ident
+                    .as_ref()
+                    .expect("#ident must not have anonymous fields")
+            })
+            .collect(),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => Vec::new(),
+        Data::Struct(DataStruct { .. }) => panic!("#ident must have named fields"),
+        _ => panic!("#ident must be a structure"),
+    };
+    let unescaped_field_names: Vec<_> = field_names
+        .iter()
+        .map(|field_name| format_ident!("{}", field_name))
+        .collect();
+    let ident = &ast.ident;
+
+    quote!(
+        impl ::ar_row::hash::StableHash for #ident {
+            fn stable_hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                #(
+                    ::ar_row::hash::StableHash::stable_hash(stringify!(#unescaped_field_names), state);
+                    ::ar_row::hash::StableHash::stable_hash(&self.#field_names, state);
+                )*
+            }
+        }
+    )
+    .into()
+}
+
+/// Returns the expression of `value` converting each field from its read type, which
+/// is `value` itself for fields without a conversion (see [`read_type`])
+fn converted_values(conversions: &[Option<TokenStream2>]) -> Vec<TokenStream2> {
+    conversions
+        .iter()
+        .map(|conversion| conversion.clone().unwrap_or_else(|| quote!(value)))
+        .collect()
+}
+
+/// Returns code reading each column of `columns` into the matching field of
+/// the structures in `dst`.
+///
+/// `struct_access` turns `struct_` (a `&mut` reference to an item of `dst`) into
+/// a reference to the structure.
+///
+/// Each column is deserialized to its `read_types` item, then converted to the type
+/// of the field with its `converted_values` item (an expression of `value`), when
+/// its `conversions` item is not `None` or when `options.direct_field_writes` is set.
+///
+/// Reading each column is wrapped in `ar_row::metrics::time_column`.
+fn read_fields(
+    ident: &Ident,
+    field_names: &[&Ident],
+    read_types: &[TokenStream2],
+    conversions: &[Option<TokenStream2>],
+    converted_values: &[TokenStream2],
+    struct_access: TokenStream2,
+    options: &StructOptions,
+) -> TokenStream2 {
+    let unescaped_field_names: Vec<_> = field_names
+        .iter()
+        .map(|field_name| format_ident!("{}", field_name))
+        .collect();
+    let read_columns = field_names
+        .iter()
+        .zip(read_types)
+        .zip(conversions)
+        .zip(converted_values)
+        .map(|(((field_name, read_type), conversion), converted_value)| {
+            if options.direct_field_writes || conversion.is_some() {
+                // Values need to go through a temporary vector
+                quote!(
+                    let mut values: ::std::vec::Vec<#read_type> = ::std::vec::Vec::new();
+                    <#read_type as ArRowDeserialize>::read_from_array_into_vec(column.clone(), &mut values)?;
+                    for (struct_, value) in dst.iter_mut().zip(values) {
+                        #struct_access.#field_name = #converted_value;
+                    }
+                    ::std::result::Result::Ok(())
+                )
+            } else {
+                quote!(
+                    ArRowDeserialize::read_from_array::<::ar_row::deserialize::MultiMap<&mut T, _>>(
+                        column.clone(),
+                        &mut dst.map(|struct_| &mut #struct_access.#field_name),
+                    )?;
+                    ::std::result::Result::Ok(())
+                )
+            }
+        });
+    quote!(
+        #(
+            let column: &Arc<_> = columns.next().expect(
+                &format!("Failed to get '{}' column", stringify!(#field_names)));
+            ::ar_row::metrics::time_column(stringify!(#ident), stringify!(#unescaped_field_names), src.len(), || -> ::std::result::Result<(), DeserializationError> {
+                #read_columns
+            })?;
+        )*
+    )
+}
+
+/// Returns the length of `ty` if it is `[u8; N]`
+fn byte_array_len(ty: &Type) -> Option<&Expr> {
+    match ty {
+        Type::Array(TypeArray { elem, len, .. }) => match &**elem {
+            Type::Path(TypePath { qself: None, path }) if path.is_ident("u8") => Some(len),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the element type and length of `ty` if it is an array
+fn array_type(ty: &Type) -> Option<(&Type, &Expr)> {
+    match ty {
+        Type::Array(TypeArray { elem, len, .. }) => Some((elem, len)),
+        _ => None,
+    }
+}
+
+/// Returns `T` if `ty` is `Option<T>`
+fn option_argument(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(TypePath { qself: None, path }) => path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match &arguments.args[0] {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the type a field of type `ty` is deserialized to, and the expression
+/// converting it (as `value`) to `ty`, if it is not `ty` itself.
+///
+/// `[u8; N]` and `Option<[u8; N]>` do not implement `ArRowDeserialize` (as `[u8; N]`
+/// only implements `Default` for small values of `N`), so they are deserialized
+/// through `ar_row::FixedSizeBinary`, and other arrays through
+/// `ar_row::FixedSizeList`.
+///
+/// `#[ar_row(stringify)]` fields are deserialized through `ar_row::DisplayString`.
+///
+/// `#[ar_row(null_as_default)]` fields are deserialized to an `Option` of the type
+/// they would otherwise be deserialized to, and `None` is replaced by its default.
+fn read_type(ty: &Type, field_options: &FieldOptions) -> (TokenStream2, Option<TokenStream2>) {
+    if field_options.null_as_default {
+        let field_options = FieldOptions {
+            null_as_default: false,
+            ..*field_options
+        };
+        let (read_type, conversion) = read_type(ty, &field_options);
+        let conversion = conversion.unwrap_or_else(|| quote!(value));
+        (
+            quote!(::std::option::Option<#read_type>),
+            Some(quote!({
+                let value = value.unwrap_or_default();
+                #conversion
+            })),
+        )
+    } else if field_options.stringify {
+        if option_argument(ty).is_some() {
+            (
+                quote!(::std::option::Option<::ar_row::DisplayString>),
+                Some(quote!(value.map(|value| value.0))),
+            )
+        } else {
+            (quote!(::ar_row::DisplayString), Some(quote!(value.0)))
+        }
+    } else if let Some(len) = byte_array_len(ty) {
+        (
+            quote!(::ar_row::FixedSizeBinary<{ #len }>),
+            Some(quote!(value.0)),
+        )
+    } else if let Some(len) = option_argument(ty).and_then(byte_array_len) {
+        (
+            quote!(::std::option::Option<::ar_row::FixedSizeBinary<{ #len }>>),
+            Some(quote!(value.map(|value| value.0))),
+        )
+    } else if let Some((elem, len)) = array_type(ty) {
+        (
+            quote!(::ar_row::FixedSizeList<#elem, { #len }>),
+            Some(quote!(value.0)),
+        )
+    } else if let Some((elem, len)) = option_argument(ty).and_then(array_type) {
+        (
+            quote!(::std::option::Option<::ar_row::FixedSizeList<#elem, { #len }>>),
+            Some(quote!(value.map(|value| value.0))),
+        )
+    } else {
+        (quote!(#ty), None)
+    }
+}
+
+/// Returns the definition of `{ident}Ref`, with a method decoding each field of a
+/// row of an `ar_row::cursor::ArRowCursor`, and the implementation of
+/// `ar_row::cursor::CursorRow` for `ident`.
+fn impl_cursor_row(
+    ident: &Ident,
+    vis: &Visibility,
+    field_names: &[&Ident],
+    field_types: &[&Type],
+    read_types: &[TokenStream2],
+    converted_values: &[TokenStream2],
+) -> TokenStream2 {
+    let ref_ident = format_ident!("{}Ref", ident);
+    let indices = 0..field_names.len();
+    let unescaped_field_names: Vec<_> = field_names
+        .iter()
+        .map(|field_name| format_ident!("{}", field_name))
+        .collect();
+    quote!(
+        #[doc = concat!("Reference to a row of [`", stringify!(#ident), "`] in an `ar_row::cursor::ArRowCursor`")]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #ref_ident<'a>(::ar_row::cursor::RowRef<'a>);
+
+        impl<'a> ::std::convert::From<::ar_row::cursor::RowRef<'a>> for #ref_ident<'a> {
+            fn from(row: ::ar_row::cursor::RowRef<'a>) -> Self {
+                #ref_ident(row)
+            }
+        }
+
+        impl<'a> ::std::ops::Deref for #ref_ident<'a> {
+            type Target = ::ar_row::cursor::RowRef<'a>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #ref_ident<'_> {
+            #(
+                #[doc = concat!("Decodes the `", stringify!(#unescaped_field_names), "` field of this row")]
+                #vis fn #field_names(&self) -> ::std::result::Result<#field_types, ::ar_row::deserialize::DeserializationError> {
+                    let value: #read_types = self.0.read(#indices)?;
+                    ::std::result::Result::Ok(#converted_values)
+                }
+            )*
+        }
+
+        impl ::ar_row::cursor::CursorRow for #ident {
+            type Ref<'a> = #ref_ident<'a>;
+        }
+    )
+}
+
+fn impl_struct(
+    ident: &Ident,
+    vis: &Visibility,
+    field_names: Vec<&Ident>,
+    field_types: Vec<&Type>,
+    field_options: &[FieldOptions],
+    options: &StructOptions,
+) -> TokenStream {
     let num_fields = field_names.len();
     let unescaped_field_names: Vec<_> = field_names
         .iter()
         .map(|field_name| format_ident!("{}", field_name))
         .collect();
+    let (read_types, conversions): (Vec<_>, Vec<_>) = field_types
+        .iter()
+        .zip(field_options)
+        .map(|(field_type, field_options)| read_type(field_type, field_options))
+        .unzip();
+    let converted_values = converted_values(&conversions);
+    // Types whose CheckableDataType implementation is used for each field
+    let checked_types: Vec<_> = read_types
+        .iter()
+        .zip(field_options)
+        .map(|(read_type, field_options)| {
+            if field_options.binary {
+                quote!(::std::boxed::Box<[u8]>)
+            } else {
+                read_type.clone()
+            }
+        })
+        .collect();
 
     let check_datatype_impl = quote!(
         impl ::ar_row::deserialize::CheckableDataType for #ident {
             fn check_datatype(datatype: &::ar_row::arrow::datatypes::DataType) -> ::std::result::Result<(), ::std::string::String> {
                 use ::ar_row::arrow::datatypes::DataType;
+                let datatype = ::ar_row::dictionaries::decoded_datatype(datatype);
                 match datatype {
                     DataType::Struct(fields) => {
+                        #[allow(unused_mut, unused_variables)]
                         let mut fields = fields.iter().enumerate();
-                        let mut errors = ::std::vec::Vec::new();
+                        #[allow(unused_mut)]
+                        let mut errors: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
                         #(
                             match fields.next() {
                                 ::std::option::Option::Some((i, field)) => {
@@ -215,7 +689,7 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
                                                 "Field #{} must be called {}, not {}",
                                                 i, stringify!(#unescaped_field_names), field.name()))
                                     }
-                                    else if let ::std::result::Result::Err(s) = <#field_types as ::ar_row::deserialize::CheckableDataType>::check_datatype(field.data_type()) {
+                                    else if let ::std::result::Result::Err(s) = <#checked_types as ::ar_row::deserialize::CheckableDataType>::check_datatype(field.data_type()) {
                                         errors.push(format!(
                                             "Field {} cannot be decoded: {}",
                                             stringify!(#unescaped_field_names), s));
@@ -243,35 +717,89 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
                         datatype))
                 }
             }
+
+            fn expected_datatype() -> ::ar_row::arrow::datatypes::DataType {
+                let fields: ::std::vec::Vec<::ar_row::arrow::datatypes::Field> = ::std::vec![
+                    #(
+                        ::ar_row::arrow::datatypes::Field::new(
+                            stringify!(#unescaped_field_names),
+                            <#checked_types as ::ar_row::deserialize::CheckableDataType>::expected_datatype(),
+                            <#read_types as ::ar_row::deserialize::CheckableDataType>::nullable(),
+                        ),
+                    )*
+                ];
+                ::ar_row::arrow::datatypes::DataType::Struct(fields.into())
+            }
         }
     );
 
+    // Field names escaped like ColumnPath's Display implementation, so the output
+    // of ArRowStruct::columns() can be parsed back with ColumnPath::parse
+    let column_path_segments: Vec<_> = unescaped_field_names
+        .iter()
+        .map(|field_name| {
+            field_name
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('.', "\\.")
+        })
+        .collect();
+
     let orc_struct_impl = quote!(
         impl ::ar_row::deserialize::ArRowStruct for #ident {
+            #[allow(unused_mut, unused_variables)]
             fn columns_with_prefix(prefix: &str) -> ::std::vec::Vec<::std::string::String> {
                 let mut columns = ::std::vec::Vec::with_capacity(#num_fields);
 
-                // Hack to get types. Hopefully the compiler notices we don't
-                // actually use it at runtime.
-                let instance: #ident = ::std::default::Default::default();
-
                 #({
-                    #[inline(always)]
-                    fn add_columns<FieldType: ::ar_row::deserialize::ArRowStruct>(columns: &mut ::std::vec::Vec<::std::string::String>, prefix: &str, _: FieldType) {
-                        let mut field_name_prefix = prefix.to_string();
-                        if prefix.len() != 0 {
-                            field_name_prefix.push_str(".");
-                        }
-                        field_name_prefix.push_str(stringify!(#unescaped_field_names));
-                        columns.extend(FieldType::columns_with_prefix(&field_name_prefix));
+                    let mut field_name_prefix = ::std::string::ToString::to_string(prefix);
+                    if !prefix.is_empty() {
+                        field_name_prefix.push_str(".");
                     }
-                    add_columns(&mut columns, prefix, instance.#field_names);
+                    field_name_prefix.push_str(#column_path_segments);
+                    columns.extend(<#read_types as ::ar_row::deserialize::ArRowStruct>::columns_with_prefix(&field_name_prefix));
                 })*
                 columns
             }
+
+            fn column_tree() -> ::ar_row::deserialize::ColumnTree {
+                ::ar_row::deserialize::ColumnTree::Struct(::std::vec![
+                    #((
+                        ::std::string::ToString::to_string(stringify!(#unescaped_field_names)),
+                        <#read_types as ::ar_row::deserialize::ArRowStruct>::column_tree(),
+                    ),)*
+                ])
+            }
+
+            #[allow(unused_variables)]
+            fn for_each_column_path(
+                path: &mut ::std::vec::Vec<&str>,
+                f: &mut dyn ::std::ops::FnMut(&[&str]),
+            ) {
+                #(
+                    path.push(stringify!(#unescaped_field_names));
+                    <#read_types as ::ar_row::deserialize::ArRowStruct>::for_each_column_path(path, f);
+                    path.pop();
+                )*
+            }
         }
     );
 
+    // Trailing columns are ignored, like in check_datatype. Structures without fields
+    // can be read from any number of columns, so the comparison is skipped to avoid
+    // an unused_comparisons warning.
+    let check_num_columns = if num_fields == 0 {
+        quote!()
+    } else {
+        quote!(
+            if columns.len() < #num_fields {
+                return ::std::result::Result::Err(DeserializationError::MismatchedColumnDataType(format!(
+                    "{} has {} fields, but got {} columns.",
+                    stringify!(#ident), #num_fields, columns.len())));
+            }
+        )
+    };
+
     let prelude = quote!(
         use ::std::sync::Arc;
         use ::std::convert::TryInto;
@@ -289,19 +817,24 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
             ))
         })?;
         let columns = src.columns();
-        assert_eq!(
-            columns.len(),
-            #num_fields,
-            "{} has {} fields, but got {} columns.",
-            stringify!(#ident), #num_fields, columns.len());
-        let mut columns = columns.into_iter();
+        #check_num_columns
+        #[allow(unused_mut, unused_variables)]
+        let mut columns = columns.iter();
 
         if src.len() > dst.len() {
-            println!("{} src = {} dst = {}", stringify!(#ident), src.len(), dst.len());
             return ::std::result::Result::Err(::ar_row::deserialize::DeserializationError::MismatchedLength { src: src.len(), dst: dst.len() });
         }
     );
 
+    let read_fields = read_fields(
+        ident,
+        &field_names,
+        &read_types,
+        &conversions,
+        &converted_values,
+        quote!(struct_),
+        options,
+    );
     let read_from_array_impl = quote!(
         impl ::ar_row::deserialize::ArRowDeserialize for #ident {
             fn read_from_array<'a, 'b, T> (
@@ -309,6 +842,14 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
             ) -> ::std::result::Result<usize, ::ar_row::deserialize::DeserializationError>
             where
                 &'b mut T: ::ar_row::deserialize::DeserializationTarget<'a, Item=#ident> + 'b {
+                if let ::std::option::Option::Some(src) = src.as_any_dictionary_opt() {
+                    let src = ::ar_row::dictionaries::unpack_dictionary(src)?;
+                    return <#ident as ::ar_row::deserialize::ArRowDeserialize>::read_from_array(src, dst);
+                }
+                if let ::std::option::Option::Some(src) = ::ar_row::dictionaries::unpack_run_end_encoded(&src)? {
+                    return <#ident as ::ar_row::deserialize::ArRowDeserialize>::read_from_array(src, dst);
+                }
+
                 #prelude
 
                 match src.nulls() {
@@ -326,20 +867,22 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
                     }
                 }
 
-                #(
-                    let column: &Arc<_> = columns.next().expect(
-                        &format!("Failed to get '{}' column", stringify!(#field_names)));
-                    ArRowDeserialize::read_from_array::<ar_row::deserialize::MultiMap<&mut T, _>>(
-                        column.clone(),
-                        &mut dst.map(|struct_| &mut struct_.#field_names),
-                    )?;
-                )*
+                #read_fields
 
                 ::std::result::Result::Ok(src.len())
             }
         }
     );
 
+    let read_option_fields = read_fields(
+        ident,
+        &field_names,
+        &read_types,
+        &conversions,
+        &converted_values,
+        quote!(struct_.get_or_insert_with(::std::default::Default::default)),
+        options,
+    );
     let read_options_from_array_impl = quote!(
         impl ::ar_row::deserialize::ArRowDeserializeOption for #ident {
             fn read_options_from_array<'a, 'b, T> (
@@ -347,43 +890,104 @@ fn impl_struct(ident: &Ident, field_names: Vec<&Ident>, field_types: Vec<&Type>)
             ) -> ::std::result::Result<usize, ::ar_row::deserialize::DeserializationError>
             where
                 &'b mut T: ::ar_row::deserialize::DeserializationTarget<'a, Item=::std::option::Option<#ident>> + 'b {
+                if let ::std::option::Option::Some(src) = src.as_any_dictionary_opt() {
+                    let src = ::ar_row::dictionaries::unpack_dictionary(src)?;
+                    return <#ident as ::ar_row::deserialize::ArRowDeserializeOption>::read_options_from_array(src, dst);
+                }
+                if let ::std::option::Option::Some(src) = ::ar_row::dictionaries::unpack_run_end_encoded(&src)? {
+                    return <#ident as ::ar_row::deserialize::ArRowDeserializeOption>::read_options_from_array(src, dst);
+                }
+
                 #prelude
 
-                match src.nulls() {
-                    ::std::option::Option::None => {
-                        for struct_ in dst.iter_mut() {
-                            *struct_ = ::std::option::Option::Some(::std::default::Default::default())
-                        }
-                    },
-                    ::std::option::Option::Some(nulls) => {
-                        for (struct_, b) in dst.iter_mut().zip(nulls) {
-                            if !b {
-                                *struct_ = ::std::option::Option::Some(::std::default::Default::default())
-                            }
+                // Child arrays have values even for null structs, so write every row
+                // before setting null ones to None.
+                for struct_ in dst.iter_mut() {
+                    *struct_ = ::std::option::Option::Some(::std::default::Default::default())
+                }
+
+                #read_option_fields
+
+                if let ::std::option::Option::Some(nulls) = src.nulls() {
+                    for (struct_, is_valid) in dst.iter_mut().zip(nulls) {
+                        if !is_valid {
+                            *struct_ = ::std::option::Option::None
                         }
                     }
                 }
 
-                #(
-                    let column: &Arc<_> = columns.next().expect(
-                        &format!("Failed to get '{}' column", stringify!(#field_names)));
-                    ArRowDeserialize::read_from_array::<::ar_row::deserialize::MultiMap<&mut T, _>>(
-                        column.clone(),
-                        &mut dst.map(|struct_| &mut unsafe { struct_.as_mut().unwrap_unchecked() }.#field_names),
-                    )?;
-                )*
+                ::std::result::Result::Ok(src.len())
+            }
+        }
+    );
+
+    let update_impl = quote!(
+        impl ::ar_row::deserialize::ArRowUpdate for #ident {
+            fn update_from_array(
+                src: impl ::ar_row::arrow::array::Array + ::ar_row::arrow::array::AsArray,
+                dst: &mut [#ident],
+            ) -> ::std::result::Result<usize, ::ar_row::deserialize::DeserializationError> {
+                use ::ar_row::arrow::array::Array;
+                use ::ar_row::deserialize::DeserializationError;
+                use ::ar_row::deserialize::ArRowDeserialize;
+
+                let src = src.as_struct_opt().ok_or_else(|| {
+                    DeserializationError::MismatchedColumnDataType(format!(
+                        "Could not cast {:?} array to struct array",
+                        src.data_type(),
+                    ))
+                })?;
+                if src.len() > dst.len() {
+                    return ::std::result::Result::Err(DeserializationError::MismatchedLength { src: src.len(), dst: dst.len() });
+                }
+
+                for (field, column) in src.fields().iter().zip(src.columns()) {
+                    match field.name().as_str() {
+                        #(
+                            stringify!(#unescaped_field_names) => {
+                                <#checked_types as ::ar_row::deserialize::CheckableDataType>::check_datatype(column.data_type())
+                                    .map_err(|s| DeserializationError::MismatchedColumnDataType(format!(
+                                        "Field {} cannot be decoded: {}",
+                                        stringify!(#unescaped_field_names), s)))?;
+                                let values: ::std::vec::Vec<#read_types> = ArRowDeserialize::from_array(column.clone())?;
+                                for (struct_, value) in dst.iter_mut().zip(values) {
+                                    struct_.#field_names = #converted_values;
+                                }
+                            }
+                        )*
+                        name => return ::std::result::Result::Err(DeserializationError::MismatchedColumnDataType(format!(
+                            "{} has no field named {}",
+                            stringify!(#ident), name))),
+                    }
+                }
 
                 ::std::result::Result::Ok(src.len())
             }
         }
     );
 
+    let cursor_impl = if options.cursor {
+        impl_cursor_row(
+            ident,
+            vis,
+            &field_names,
+            &field_types,
+            &read_types,
+            &converted_values,
+        )
+    } else {
+        quote!()
+    };
+
     quote!(
         #check_datatype_impl
         #orc_struct_impl
 
         #read_from_array_impl
         #read_options_from_array_impl
+        #update_impl
+
+        #cursor_impl
     )
     .into()
 }
@@ -0,0 +1,47 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::deserialize::CheckableDataType;
+use ar_row::schema::{schema_from_json, schema_to_json};
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct RenamedRow {
+    id: i64,
+    full_name: Option<String>,
+}
+
+ar_row::assert_schema!(row_matches_schema: Row, "schemas/row.arrow.json");
+
+#[test]
+fn test_assert_schema() {
+    ar_row::assert_schema!(Row, "schemas/row.arrow.json");
+}
+
+#[test]
+#[should_panic(expected = "Field #1 must be called full_name, not name")]
+fn test_assert_schema_mismatch() {
+    ar_row::assert_schema!(RenamedRow, "schemas/row.arrow.json");
+}
+
+#[test]
+fn test_schema_to_json() {
+    let json = schema_to_json(&Row::arrow_schema());
+    assert_eq!(schema_from_json(&json).unwrap(), Row::arrow_schema());
+    assert_eq!(
+        schema_from_json(include_str!("schemas/row.arrow.json")).unwrap(),
+        Row::arrow_schema()
+    );
+    assert_eq!(
+        format!("{}\n", json),
+        include_str!("schemas/row.arrow.json")
+    );
+}
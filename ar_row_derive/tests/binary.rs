@@ -0,0 +1,114 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{Array, ArrayRef, BinaryArray, LargeBinaryArray, ListArray};
+use ar_row::arrow::datatypes::{DataType, Field, Schema, UInt8Type};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType, DeserializationError};
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    data: Vec<u8>,
+}
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct BinaryRow {
+    #[ar_row(binary)]
+    data: Option<Vec<u8>>,
+}
+
+fn binary_batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![(
+        "data",
+        Arc::new(BinaryArray::from(vec![&b"foo"[..], b"", b"bar"])) as ArrayRef,
+    )])
+    .unwrap()
+}
+
+fn list_batch() -> RecordBatch {
+    let list = ListArray::from_iter_primitive::<UInt8Type, _, _>(vec![
+        Some(vec![Some(1), Some(2)]),
+        Some(vec![]),
+    ]);
+    RecordBatch::try_from_iter(vec![("data", Arc::new(list) as ArrayRef)]).unwrap()
+}
+
+#[test]
+fn test_vec_from_binary() {
+    let batch = binary_batch();
+    Row::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        Row::from_record_batch(batch),
+        Ok(vec![
+            Row {
+                data: b"foo".to_vec()
+            },
+            Row { data: Vec::new() },
+            Row {
+                data: b"bar".to_vec()
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_vec_from_list() {
+    let batch = list_batch();
+    Row::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        Row::from_record_batch(batch),
+        Ok(vec![Row { data: vec![1, 2] }, Row { data: Vec::new() }])
+    );
+    assert_eq!(
+        Row::arrow_schema(),
+        Schema::new(vec![Field::new_list(
+            "data",
+            Field::new("item", DataType::UInt8, false),
+            false
+        )])
+    );
+}
+
+#[test]
+fn test_binary_attribute() {
+    assert_eq!(
+        BinaryRow::arrow_schema(),
+        Schema::new(vec![Field::new("data", DataType::Binary, true)])
+    );
+    assert!(BinaryRow::check_schema(&list_batch().schema()).is_err());
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "data",
+        Arc::new(LargeBinaryArray::from(vec![Some(&b"foo"[..]), None])) as ArrayRef,
+    )])
+    .unwrap();
+    BinaryRow::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        BinaryRow::from_record_batch(batch),
+        Ok(vec![
+            BinaryRow {
+                data: Some(b"foo".to_vec())
+            },
+            BinaryRow { data: None },
+        ])
+    );
+}
+
+#[test]
+fn test_vec_from_binary_errors() {
+    let array: ArrayRef = Arc::new(BinaryArray::from(vec![Some(&b"foo"[..]), None]));
+    assert!(matches!(
+        <Vec<u8>>::from_array(array.clone()),
+        Err(DeserializationError::UnexpectedNull(_))
+    ));
+    assert!(<Vec<Option<u8>>>::check_datatype(array.data_type()).is_err());
+    assert!(matches!(
+        <Vec<i8>>::from_array(array),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+}
@@ -0,0 +1,109 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, FixedSizeBinaryArray};
+use ar_row::arrow::datatypes::{DataType, Field, Schema};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType};
+use ar_row_derive::ArRowDeserialize;
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "sha1",
+            Arc::new(
+                FixedSizeBinaryArray::try_from_iter(vec![[1u8; 20], [2u8; 20]].into_iter())
+                    .unwrap(),
+            ) as ArrayRef,
+        ),
+        (
+            "tag",
+            Arc::new(
+                FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    vec![None, Some(*b"abcd")].into_iter(),
+                    4,
+                )
+                .unwrap(),
+            ) as ArrayRef,
+        ),
+        (
+            "large",
+            Arc::new(
+                FixedSizeBinaryArray::try_from_iter(vec![[3u8; 64], [4u8; 64]].into_iter())
+                    .unwrap(),
+            ) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+macro_rules! test_byte_arrays {
+    ($test_name:ident, $(#[$attr:meta])*) => {
+        #[test]
+        fn $test_name() {
+            #[derive(ArRowDeserialize, Clone, Debug, PartialEq)]
+            $(#[$attr])*
+            struct Row {
+                sha1: [u8; 20],
+                tag: Option<[u8; 4]>,
+                large: [u8; 64],
+            }
+
+            // [u8; 64] does not implement Default
+            impl Default for Row {
+                fn default() -> Self {
+                    Row {
+                        sha1: Default::default(),
+                        tag: Default::default(),
+                        large: [0; 64],
+                    }
+                }
+            }
+
+            assert_eq!(
+                Row::arrow_schema(),
+                Schema::new(vec![
+                    Field::new("sha1", DataType::FixedSizeBinary(20), false),
+                    Field::new("tag", DataType::FixedSizeBinary(4), true),
+                    Field::new("large", DataType::FixedSizeBinary(64), false),
+                ])
+            );
+            assert_eq!(Row::columns(), vec!["sha1", "tag", "large"]);
+
+            let batch = batch();
+            Row::check_schema(&batch.schema()).unwrap();
+            assert_eq!(
+                Row::from_record_batch(batch),
+                Ok(vec![
+                    Row {
+                        sha1: [1; 20],
+                        tag: None,
+                        large: [3; 64],
+                    },
+                    Row {
+                        sha1: [2; 20],
+                        tag: Some(*b"abcd"),
+                        large: [4; 64],
+                    },
+                ])
+            );
+        }
+    };
+}
+
+test_byte_arrays!(test_byte_arrays,);
+test_byte_arrays!(test_byte_arrays_direct_field_writes, #[ar_row(direct_field_writes)]);
+
+#[test]
+fn test_mismatched_size() {
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Row {
+        sha1: [u8; 32],
+    }
+
+    assert!(Row::check_schema(&batch().schema()).is_err());
+}
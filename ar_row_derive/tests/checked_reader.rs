@@ -0,0 +1,103 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fs::File;
+
+use orc_rust::projection::ProjectionMask;
+use orc_rust::{ArrowReader, ArrowReaderBuilder};
+
+use ar_row::deserialize::DeserializationError;
+use ar_row::reader::CheckedReader;
+use ar_row_derive::ArRowDeserialize;
+
+fn get_reader(batch_size: usize) -> ArrowReader<File> {
+    let orc_path = "../test_data/TestOrcFile.test1.orc";
+    let file = File::open(orc_path).expect("could not open .orc");
+    let builder = ArrowReaderBuilder::try_new(file).expect("Could not make builder");
+    let projection =
+        ProjectionMask::named_roots(builder.file_metadata().root_data_type(), &["long1"]);
+    builder
+        .with_projection(projection)
+        .with_batch_size(batch_size)
+        .build()
+}
+
+/// Does not implement `Clone`, to check `CheckedRows` does not need it.
+#[derive(ArRowDeserialize, Default, Debug, PartialEq, Eq)]
+struct Test1 {
+    long1: Option<i64>,
+}
+
+#[test]
+fn test_batches() {
+    let reader = CheckedReader::<_, Test1>::new(get_reader(10)).unwrap();
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    assert_eq!(
+        batches,
+        vec![vec![
+            Test1 {
+                long1: Some(9223372036854775807)
+            },
+            Test1 {
+                long1: Some(9223372036854775807)
+            }
+        ]]
+    );
+}
+
+#[test]
+fn test_rows() {
+    for batch_size in [1, 2, 10] {
+        let reader = CheckedReader::<_, Test1>::new(get_reader(batch_size)).unwrap();
+        let rows: Vec<_> = reader.rows().map(|row| row.unwrap()).collect();
+        assert_eq!(
+            rows,
+            vec![
+                Test1 {
+                    long1: Some(9223372036854775807)
+                },
+                Test1 {
+                    long1: Some(9223372036854775807)
+                }
+            ],
+            "batch_size = {batch_size}"
+        );
+    }
+}
+
+#[test]
+fn test_mismatched_schema() {
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq, Eq)]
+    struct Test1 {
+        long1: Option<String>,
+    }
+
+    assert_eq!(
+        CheckedReader::<_, Test1>::new(get_reader(10)).err(),
+        Some(DeserializationError::MismatchedColumnDataType(
+            "Test1 cannot be decoded:\n\tField long1 cannot be decoded: String must be decoded from Arrow Utf8/LargeUtf8, not Arrow Int64
+
+  expected    | actual
+! long1: Utf8 | long1: Int64".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn test_skip_rows() {
+    for batch_size in [1, 2, 10] {
+        for count in 0..4 {
+            let mut rows = CheckedReader::<_, Test1>::new(get_reader(batch_size))
+                .unwrap()
+                .rows();
+            rows.skip_rows(count).unwrap();
+            assert_eq!(
+                rows.map(|row| row.unwrap()).count(),
+                2usize.saturating_sub(count),
+                "batch_size = {batch_size}, count = {count}"
+            );
+        }
+    }
+}
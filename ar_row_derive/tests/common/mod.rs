@@ -0,0 +1,49 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Fixtures shared by the tests of readers for the various input formats
+
+// Each test crate only uses some of the fixtures
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+pub struct Row {
+    pub id: i64,
+    pub name: Option<String>,
+}
+
+/// Row whose `id` column has a different type than in [`batch`]
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+pub struct OtherRow {
+    pub id: String,
+}
+
+/// Batch with the rows returned by [`expected_rows`]
+pub fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![Some("foo"), None])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+pub fn expected_rows() -> Vec<Row> {
+    vec![
+        Row {
+            id: 1,
+            name: Some("foo".to_owned()),
+        },
+        Row { id: 2, name: None },
+    ]
+}
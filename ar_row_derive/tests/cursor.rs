@@ -0,0 +1,108 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    ArrayRef, AsArray, FixedSizeBinaryArray, Int64Array, StringArray, StructArray,
+};
+use ar_row::arrow::datatypes::{DataType, Field, Int64Type};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::cursor::ArRowCursor;
+use ar_row::deserialize::DeserializationError;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Inner {
+    value: Option<i64>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+#[ar_row(cursor)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+    hash: [u8; 2],
+    inner: Option<Inner>,
+}
+
+fn batch() -> RecordBatch {
+    let inner = StructArray::new(
+        vec![Field::new("value", DataType::Int64, true)].into(),
+        vec![Arc::new(Int64Array::from(vec![Some(10), None, Some(30)])) as ArrayRef],
+        Some(vec![true, true, false].into()),
+    );
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![Some("foo"), None, Some("baz")])) as ArrayRef,
+        ),
+        (
+            "hash",
+            Arc::new(
+                FixedSizeBinaryArray::try_from_iter(vec![[1u8, 2], [3, 4], [5, 6]].into_iter())
+                    .unwrap(),
+            ) as ArrayRef,
+        ),
+        ("inner", Arc::new(inner) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_cursor() {
+    let cursor = ArRowCursor::<Row>::from_record_batch(batch()).unwrap();
+    assert_eq!(cursor.len(), 3);
+
+    let ids: Result<Vec<i64>, _> = cursor.iter().map(|row| row.id()).collect();
+    assert_eq!(ids, Ok(vec![1, 2, 3]));
+
+    let row = cursor.get(1).unwrap();
+    assert_eq!(row.index(), 1);
+    assert!(!row.is_null());
+    assert_eq!(row.name(), Ok(None));
+    assert_eq!(row.hash(), Ok([3, 4]));
+    assert_eq!(row.inner(), Ok(Some(Inner { value: None })));
+
+    let row = cursor.get(2).unwrap();
+    assert_eq!(row.name(), Ok(Some("baz".to_owned())));
+    assert_eq!(row.inner(), Ok(None));
+
+    assert!(cursor.get(3).is_none());
+}
+
+#[test]
+fn test_cursor_columns() {
+    let cursor = ArRowCursor::<Row>::from_record_batch(batch()).unwrap();
+    let row = cursor.get(2).unwrap();
+    assert_eq!(
+        row.column(0).as_primitive::<Int64Type>().value(row.index()),
+        3
+    );
+}
+
+#[test]
+fn test_cursor_null_rows() {
+    let (fields, columns, _) = StructArray::from(batch()).into_parts();
+    let array = StructArray::new(fields, columns, Some(vec![false, true, true].into()));
+    let cursor = ArRowCursor::<Row>::new(Arc::new(array.slice(0, 2)) as ArrayRef).unwrap();
+    assert_eq!(cursor.len(), 2);
+    assert!(cursor.get(0).unwrap().is_null());
+    assert!(!cursor.get(1).unwrap().is_null());
+}
+
+#[test]
+fn test_cursor_mismatched_type() {
+    let batch = RecordBatch::try_from_iter(vec![(
+        "id",
+        Arc::new(StringArray::from(vec!["foo"])) as ArrayRef,
+    )])
+    .unwrap();
+    assert!(matches!(
+        ArRowCursor::<Row>::from_record_batch(batch),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::path::Path;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::dataset::{list_files, par_read_dir, par_read_files, par_send_rows, FileError};
+use ar_row_derive::ArRowDeserialize;
+use parquet::arrow::ArrowWriter;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+}
+
+fn write_parquet(path: &Path, array: ArrayRef) {
+    let batch = RecordBatch::try_from_iter(vec![("id", array)]).unwrap();
+    let mut writer =
+        ArrowWriter::try_new(std::fs::File::create(path).unwrap(), batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+}
+
+/// Writes 5 Parquet files with 10 rows each, and files to ignore
+fn dataset() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..5 {
+        write_parquet(
+            &dir.path().join(format!("part-{}.parquet", i)),
+            Arc::new(Int64Array::from((i * 10..i * 10 + 10).collect::<Vec<_>>())),
+        );
+    }
+    std::fs::write(dir.path().join("README"), "not a Parquet file").unwrap();
+    std::fs::create_dir(dir.path().join("subdir.parquet")).unwrap();
+    dir
+}
+
+#[test]
+fn test_list_files() {
+    let dir = dataset();
+    let files = list_files(dir.path(), ".parquet").unwrap();
+    assert_eq!(
+        files,
+        (0..5)
+            .map(|i| dir.path().join(format!("part-{}.parquet", i)))
+            .collect::<Vec<_>>()
+    );
+    assert!(list_files(dir.path().join("missing"), "").is_err());
+}
+
+#[test]
+fn test_par_read_dir() {
+    let dir = dataset();
+    let rows: Vec<Row> = par_read_dir(dir.path(), ".parquet").unwrap();
+    assert_eq!(rows, (0..50).map(|id| Row { id }).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_par_send_rows() {
+    let dir = dataset();
+    let files = list_files(dir.path(), ".parquet").unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    par_send_rows::<Row>(&files, tx).unwrap();
+    let mut ids: Vec<i64> = rx.into_iter().map(|row| row.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, (0..50).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_mismatched_file() {
+    let dir = dataset();
+    let bad_path = dir.path().join("part-5.parquet");
+    write_parquet(&bad_path, Arc::new(StringArray::from(vec!["foo"])));
+    let files = list_files(dir.path(), ".parquet").unwrap();
+
+    match par_read_files::<Row>(&files) {
+        Err(FileError { path, .. }) => assert_eq!(path, bad_path),
+        Ok(_) => panic!("Expected an error"),
+    }
+    let (tx, _rx) = std::sync::mpsc::channel();
+    match par_send_rows::<Row>(&files, tx) {
+        Err(e) => assert!(e.to_string().contains("part-5.parquet"), "{}", e),
+        Ok(()) => panic!("Expected an error"),
+    }
+}
@@ -37,7 +37,12 @@ fn incorrect_order() {
     let reader = builder.with_projection(projection).build();
     assert_eq!(
         Test1IncorrectOrder::check_schema(&reader.schema()),
-        Err("Test1IncorrectOrder cannot be decoded:\n\tField #1 must be called string1, not bytes1\n\tField #2 must be called bytes1, not string1".to_string()));
+        Err("Test1IncorrectOrder cannot be decoded:\n\tField #1 must be called string1, not bytes1\n\tField #2 must be called bytes1, not string1
+
+  expected       | actual
+  long1: Int64   | long1: Int64
+! string1: Utf8  | bytes1: Binary
+! bytes1: Binary | string1: Utf8".to_string()));
 }
 
 #[derive(ArRowDeserialize, Default, Debug, PartialEq, Eq)]
@@ -58,5 +63,9 @@ fn incorrect_type() {
     let reader = builder.with_projection(projection).build();
     assert_eq!(
         Test1IncorrectType::check_schema(&reader.schema()),
-        Err("Test1IncorrectType cannot be decoded:\n\tField bytes1 cannot be decoded: String must be decoded from Arrow Utf8/LargeUtf8, not Arrow Binary".to_string()));
+        Err("Test1IncorrectType cannot be decoded:\n\tField bytes1 cannot be decoded: String must be decoded from Arrow Utf8/LargeUtf8, not Arrow Binary
+
+  expected     | actual
+  long1: Int64 | long1: Int64
+! bytes1: Utf8 | bytes1: Binary".to_string()));
 }
@@ -0,0 +1,77 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fs::File;
+use std::path::Path;
+
+use ar_row::delta::open_delta_rows;
+use futures::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+
+mod common;
+use common::{batch, expected_rows, Row};
+
+const SCHEMA_STRING: &str = r#"{\"type\":\"struct\",\"fields\":[{\"name\":\"id\",\"type\":\"long\",\"nullable\":false,\"metadata\":{}},{\"name\":\"name\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}}]}"#;
+
+/// Writes a Delta table with a single data file, whose `add` action has the given
+/// extra fields
+fn write_table(dir: &Path, protocol: &str, add_fields: &str) {
+    let batch = batch();
+    let data_path = dir.join("part-00000.parquet");
+    let mut writer =
+        ArrowWriter::try_new(File::create(&data_path).unwrap(), batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    let size = std::fs::metadata(&data_path).unwrap().len();
+
+    std::fs::create_dir(dir.join("_delta_log")).unwrap();
+    let log = [
+        format!(r#"{{"protocol":{protocol}}}"#),
+        format!(
+            r#"{{"metaData":{{"id":"00000000-0000-0000-0000-000000000000","format":{{"provider":"parquet","options":{{}}}},"schemaString":"{SCHEMA_STRING}","partitionColumns":[],"configuration":{{}},"createdTime":0}}}}"#
+        ),
+        format!(
+            r#"{{"add":{{"path":"part-00000.parquet","partitionValues":{{}},"size":{size},"modificationTime":0,"dataChange":true{add_fields}}}}}"#
+        ),
+    ];
+    std::fs::write(
+        dir.join("_delta_log").join("00000000000000000000.json"),
+        log.join("\n"),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_open_delta_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    write_table(
+        dir.path(),
+        r#"{"minReaderVersion":1,"minWriterVersion":2}"#,
+        "",
+    );
+
+    let rows: Vec<Row> = open_delta_rows(dir.path().to_str().unwrap())
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(rows, expected_rows());
+}
+
+#[tokio::test]
+async fn test_deletion_vectors() {
+    let dir = tempfile::tempdir().unwrap();
+    write_table(
+        dir.path(),
+        r#"{"minReaderVersion":3,"minWriterVersion":7,"readerFeatures":["deletionVectors"],"writerFeatures":["deletionVectors"]}"#,
+        r#","deletionVector":{"storageType":"u","pathOrInlineDv":"ab^-aqEH.-t@S}K{vb[*k^","offset":4,"sizeInBytes":40,"cardinality":1}"#,
+    );
+
+    // Reading the data file would return the rows marked as deleted
+    assert!(open_delta_rows::<Row>(dir.path().to_str().unwrap())
+        .await
+        .is_err());
+}
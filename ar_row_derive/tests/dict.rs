@@ -5,7 +5,9 @@
 
 use std::sync::Arc;
 
-use ar_row::arrow::array::{ArrayRef, DictionaryArray, Int8Array, StructArray};
+use ar_row::arrow::array::{
+    Array, ArrayRef, DictionaryArray, Int64Array, Int8Array, StringArray, StructArray,
+};
 use ar_row::arrow::datatypes::{DataType, Field, Int8Type};
 use ar_row::arrow::record_batch::RecordBatch;
 
@@ -57,3 +59,37 @@ fn test_utf8_dict() {
         ]
     );
 }
+
+#[test]
+fn test_struct_dict() {
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+    struct Value {
+        name: String,
+        count: i64,
+    }
+
+    let values = StructArray::from(vec![
+        (
+            Arc::new(Field::new("name", DataType::Utf8, false)),
+            Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("count", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+        ),
+    ]);
+    let keys = Int8Array::from(vec![Some(1), None, Some(0), Some(1)]);
+    let array: Arc<dyn Array> = Arc::new(DictionaryArray::new(keys, Arc::new(values)));
+
+    let rows: Vec<_> = <Option<Value>>::from_array(array).unwrap();
+
+    let a = Value {
+        name: "a".to_string(),
+        count: 1,
+    };
+    let b = Value {
+        name: "b".to_string(),
+        count: 2,
+    };
+    assert_eq!(rows, vec![Some(b.clone()), None, Some(a), Some(b)]);
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::datatypes::Schema;
+use ar_row::arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use ar_row::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType, DeserializationError};
+use ar_row::options::DeserializeOptions;
+use ar_row::row_iterator::RowIterator;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Unit;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct NoFields {}
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    a: i64,
+    b: String,
+}
+
+fn batch(a: Vec<i64>, b: Vec<&str>) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        ("a", Arc::new(Int64Array::from(a)) as ArrayRef),
+        ("b", Arc::new(StringArray::from(b)) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+/// Options reading rows in blocks of 2 rows
+fn blocks() -> DeserializeOptions {
+    DeserializeOptions {
+        block_size: NonZeroUsize::new(2),
+        ..Default::default()
+    }
+}
+
+fn zero_column_batch(num_rows: usize) -> RecordBatch {
+    RecordBatch::try_new_with_options(
+        Arc::new(Schema::empty()),
+        Vec::new(),
+        &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(Unit::arrow_schema(), Schema::empty());
+    assert_eq!(NoFields::arrow_schema(), Schema::empty());
+    assert!(Unit::columns().is_empty());
+    assert!(NoFields::columns().is_empty());
+}
+
+#[test]
+fn test_count_zero_column_batch() {
+    let batch = zero_column_batch(3);
+    Unit::check_schema(&batch.schema()).unwrap();
+    assert_eq!(Unit::from_record_batch(batch.clone()), Ok(vec![Unit; 3]));
+    assert_eq!(
+        NoFields::from_record_batch(batch.clone()),
+        Ok(vec![NoFields {}; 3])
+    );
+    assert_eq!(
+        NoFields::from_record_batch_with_options(batch.clone(), &blocks()),
+        Ok(vec![NoFields {}; 3])
+    );
+    assert_eq!(
+        <Option<Unit>>::from_record_batch(batch),
+        Ok(vec![Some(Unit); 3])
+    );
+}
+
+#[test]
+fn test_count_batch_with_columns() {
+    let batch = batch(vec![1, 2, 3, 4, 5], vec!["a", "b", "c", "d", "e"]);
+    Unit::check_schema(&batch.schema()).unwrap();
+    NoFields::check_schema(&batch.schema()).unwrap();
+
+    let mut rows = vec![Unit; 2];
+    assert_eq!(
+        Unit::read_from_record_batch_into_vec(batch.clone(), &mut rows),
+        Ok(5)
+    );
+    assert_eq!(rows.len(), 5);
+
+    assert_eq!(
+        NoFields::from_record_batch_with_options(batch.clone(), &blocks()).map(|rows| rows.len()),
+        Ok(5)
+    );
+    assert_eq!(
+        RowIterator::<_, NoFields>::new(vec![batch.clone(), batch].into_iter())
+            .unwrap()
+            .count(),
+        10
+    );
+}
+
+#[test]
+fn test_zero_row_batches() {
+    let empty = batch(Vec::new(), Vec::new());
+    Row::check_schema(&empty.schema()).unwrap();
+    assert_eq!(Row::from_record_batch(empty.clone()), Ok(Vec::new()));
+
+    let mut rows = vec![Row::default(); 2];
+    assert_eq!(
+        Row::read_from_record_batch_into_vec(empty.clone(), &mut rows),
+        Ok(0)
+    );
+    assert!(rows.is_empty());
+
+    let batches = vec![
+        empty.clone(),
+        batch(vec![1], vec!["foo"]),
+        empty.clone(),
+        empty.clone(),
+        batch(vec![2], vec!["bar"]),
+        empty,
+    ];
+    assert_eq!(
+        RowIterator::<_, Row>::new(batches.into_iter())
+            .unwrap()
+            .collect::<Vec<_>>(),
+        vec![
+            Row {
+                a: 1,
+                b: "foo".to_owned()
+            },
+            Row {
+                a: 2,
+                b: "bar".to_owned()
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_missing_columns() {
+    // Returns an error instead of panicking
+    match Row::from_record_batch(zero_column_batch(3)) {
+        Err(DeserializationError::MismatchedColumnDataType(_)) => (),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
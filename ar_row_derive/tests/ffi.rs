@@ -0,0 +1,53 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::arrow::array::{Array, StructArray};
+use ar_row::arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use ar_row::arrow::ffi_stream::FFI_ArrowArrayStream;
+use ar_row::arrow::record_batch::RecordBatchIterator;
+use ar_row::ffi::{ffi_stream_reader_for, from_ffi};
+
+mod common;
+use common::{batch, expected_rows, OtherRow, Row};
+
+#[test]
+fn test_from_ffi() {
+    let data = StructArray::from(batch()).into_data();
+    let array = FFI_ArrowArray::new(&data);
+    let schema = FFI_ArrowSchema::try_from(data.data_type()).unwrap();
+
+    let rows: Vec<Row> = unsafe { from_ffi(array, &schema) }.unwrap();
+
+    assert_eq!(rows, expected_rows());
+}
+
+#[test]
+fn test_from_ffi_mismatched_datatype() {
+    let data = StructArray::from(batch()).into_data();
+    let array = FFI_ArrowArray::new(&data);
+    let schema = FFI_ArrowSchema::try_from(data.data_type()).unwrap();
+
+    assert!(unsafe { from_ffi::<OtherRow>(array, &schema) }.is_err());
+}
+
+#[test]
+fn test_ffi_stream() {
+    let batch = batch();
+    let schema = batch.schema();
+    let stream = FFI_ArrowArrayStream::new(Box::new(RecordBatchIterator::new(
+        vec![Ok(batch.clone()), Ok(batch)],
+        schema,
+    )));
+
+    let rows: Vec<Row> = ffi_stream_reader_for(stream)
+        .unwrap()
+        .rows()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut expected = expected_rows();
+    expected.extend(expected_rows());
+    assert_eq!(rows, expected);
+}
@@ -0,0 +1,92 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, FixedSizeListArray};
+use ar_row::arrow::datatypes::{DataType, Field, Int32Type, Schema};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType};
+use ar_row_derive::ArRowDeserialize;
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "point",
+            Arc::new(FixedSizeListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![Some(vec![Some(1), None]), Some(vec![Some(3), Some(4)])],
+                2,
+            )) as ArrayRef,
+        ),
+        (
+            "range",
+            Arc::new(FixedSizeListArray::from_iter_primitive::<Int32Type, _, _>(
+                vec![None, Some(vec![Some(5), Some(6)])],
+                2,
+            )) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+macro_rules! test_fixed_size_lists {
+    ($test_name:ident, $(#[$attr:meta])*) => {
+        #[test]
+        fn $test_name() {
+            #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+            $(#[$attr])*
+            struct Row {
+                point: [Option<i32>; 2],
+                range: Option<[Option<i32>; 2]>,
+            }
+
+            assert_eq!(
+                Row::arrow_schema(),
+                Schema::new(vec![
+                    Field::new(
+                        "point",
+                        DataType::new_fixed_size_list(DataType::Int32, 2, true),
+                        false
+                    ),
+                    Field::new(
+                        "range",
+                        DataType::new_fixed_size_list(DataType::Int32, 2, true),
+                        true
+                    ),
+                ])
+            );
+            assert_eq!(Row::columns(), vec!["point", "range"]);
+
+            let batch = batch();
+            Row::check_schema(&batch.schema()).unwrap();
+            assert_eq!(
+                Row::from_record_batch(batch),
+                Ok(vec![
+                    Row {
+                        point: [Some(1), None],
+                        range: None,
+                    },
+                    Row {
+                        point: [Some(3), Some(4)],
+                        range: Some([Some(5), Some(6)]),
+                    },
+                ])
+            );
+        }
+    };
+}
+
+test_fixed_size_lists!(test_fixed_size_lists,);
+test_fixed_size_lists!(test_fixed_size_lists_direct_field_writes, #[ar_row(direct_field_writes)]);
+
+#[test]
+fn test_non_nullable_elements() {
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Row {
+        point: [i32; 2],
+    }
+
+    assert!(Row::check_schema(&batch().schema()).is_err());
+}
@@ -0,0 +1,71 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::from_csv::{csv_reader_for, from_csv};
+use ar_row::reader::ReadError;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+    score: Option<f64>,
+}
+
+const ROWS: &str = "id,name,score
+1,foo,1.5
+2,,
+3,\"bar, baz\",-2
+";
+
+#[test]
+fn test_from_csv() {
+    let rows: Vec<Row> = from_csv(ROWS.as_bytes()).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                id: 1,
+                name: Some("foo".to_owned()),
+                score: Some(1.5),
+            },
+            Row {
+                id: 2,
+                name: None,
+                score: None,
+            },
+            Row {
+                id: 3,
+                name: Some("bar, baz".to_owned()),
+                score: Some(-2.),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_batch_size() {
+    let batches: Vec<Vec<Row>> = csv_reader_for(ROWS.as_bytes(), 2)
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 1]);
+}
+
+#[test]
+fn test_mismatched_header() {
+    let result = from_csv::<Row, _>("id,score,name\n1,1.5,foo\n".as_bytes());
+    assert!(
+        matches!(result, Err(ReadError::Deserialization(_))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_invalid_value() {
+    let result = from_csv::<Row, _>("id,name,score\nfoo,bar,1.5\n".as_bytes());
+    assert!(matches!(result, Err(ReadError::Arrow(_))), "{:?}", result);
+}
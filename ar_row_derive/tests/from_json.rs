@@ -0,0 +1,87 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::from_json::{from_json, json_reader_for, json_rows};
+use ar_row::reader::ReadError;
+use ar_row::Timestamp;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+    tags: Vec<String>,
+    inner: Option<Inner>,
+    time: Option<Timestamp>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Inner {
+    value: Option<f64>,
+}
+
+const ROWS: &str = r#"
+{"id": 1, "name": "foo", "tags": ["a", "b"], "inner": {"value": 1.5}, "time": "1970-01-01T00:00:01.5Z"}
+{"id": 2, "tags": [], "inner": null, "time": null}
+{"id": 3, "name": null, "tags": ["c"], "inner": {}}
+"#;
+
+#[test]
+fn test_from_json() {
+    let rows: Vec<Row> = from_json(ROWS.as_bytes()).unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                id: 1,
+                name: Some("foo".to_owned()),
+                tags: vec!["a".to_owned(), "b".to_owned()],
+                inner: Some(Inner { value: Some(1.5) }),
+                time: Some(Timestamp {
+                    seconds: 1,
+                    nanoseconds: 500_000_000
+                }),
+            },
+            Row {
+                id: 2,
+                name: None,
+                tags: vec![],
+                inner: None,
+                time: None,
+            },
+            Row {
+                id: 3,
+                name: None,
+                tags: vec!["c".to_owned()],
+                inner: Some(Inner { value: None }),
+                time: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_batch_size() {
+    let batches: Vec<Vec<Row>> = json_reader_for(ROWS.as_bytes(), 2)
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 1]);
+}
+
+#[test]
+fn test_json_rows() {
+    let ids: Vec<i64> = json_rows::<Row, _>(ROWS.as_bytes())
+        .unwrap()
+        .map(|row| row.unwrap().id)
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_unknown_column() {
+    let result = from_json::<Row, _>(r#"{"id": 1, "tags": [], "extra": 2}"#.as_bytes());
+    assert!(matches!(result, Err(ReadError::Arrow(_))), "{:?}", result);
+}
@@ -0,0 +1,113 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, ListArray, StringArray};
+use ar_row::arrow::datatypes::Int64Type;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::ArRowDeserialize;
+use ar_row::hash::{hash_rows, stable_hash, Hashed};
+use ar_row_derive::{ArRowDeserialize, ArRowHash};
+
+#[derive(ArRowDeserialize, ArRowHash, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+    values: Option<Vec<Option<i64>>>,
+}
+
+#[derive(ArRowHash)]
+struct Renamed {
+    id: i64,
+    label: Option<String>,
+    values: Option<Vec<Option<i64>>>,
+}
+
+#[derive(ArRowHash)]
+struct Empty;
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "id",
+            Arc::new(Int64Array::from(vec![1, 2, 1, 1])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![
+                Some("foo"),
+                Some("foo"),
+                Some("foo"),
+                None,
+            ])) as ArrayRef,
+        ),
+        (
+            "values",
+            Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+                Some(vec![Some(1), None]),
+                Some(vec![Some(1), None]),
+                Some(vec![Some(1), None]),
+                Some(vec![Some(1), None]),
+            ])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_hash_rows() {
+    let rows = Row::from_record_batch(batch()).unwrap();
+    let hashes = hash_rows(&rows);
+    assert_eq!(hashes[0], hashes[2]);
+    assert_eq!(hashes.iter().collect::<HashSet<_>>().len(), 3);
+
+    // Hashes depend on the values only, not on how they were built
+    assert_eq!(
+        hashes[0],
+        stable_hash(&Row {
+            id: 1,
+            name: Some("foo".to_owned()),
+            values: Some(vec![Some(1), None]),
+        })
+    );
+}
+
+#[test]
+fn test_hashed() {
+    let rows = <Hashed<Row>>::from_record_batch(batch()).unwrap();
+    assert_eq!(
+        rows.iter().map(|row| row.hash).collect::<Vec<_>>(),
+        hash_rows(&Row::from_record_batch(batch()).unwrap())
+    );
+    assert_eq!(rows[3].value.name, None);
+
+    // Rows are hashed by blocks, across which none must be skipped
+    let array: ArrayRef = Arc::new(Int64Array::from((0..3000).collect::<Vec<_>>()));
+    let rows = <Hashed<i64>>::from_array(array).unwrap();
+    assert_eq!(rows.len(), 3000);
+    assert!(rows
+        .iter()
+        .enumerate()
+        .all(|(i, row)| row.value == i as i64 && row.hash == stable_hash(&(i as i64))));
+}
+
+#[test]
+fn test_field_names() {
+    assert_ne!(
+        stable_hash(&Row {
+            id: 1,
+            name: None,
+            values: None,
+        }),
+        stable_hash(&Renamed {
+            id: 1,
+            label: None,
+            values: None,
+        })
+    );
+    assert_eq!(stable_hash(&Empty), stable_hash(&()));
+}
@@ -0,0 +1,162 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use ar_row::arrow::ipc::writer::{FileWriter, StreamWriter};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::ipc::{
+    ipc_rows_from_bytes, ipc_stream_reader_for, open_ipc_rows, open_ipc_rows_mmap, MmapIpcReader,
+};
+use ar_row::random_access::RandomAccessBatchReader;
+use ar_row_derive::ArRowDeserialize;
+
+mod common;
+use common::{batch, expected_rows, OtherRow, Row};
+
+fn ipc_stream() -> Vec<u8> {
+    let mut stream = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut stream, &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    stream
+}
+
+#[test]
+fn test_ipc_stream() {
+    let stream = ipc_stream();
+
+    let rows: Vec<Row> = ipc_stream_reader_for(stream.as_slice())
+        .unwrap()
+        .rows()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut expected = expected_rows();
+    expected.extend(expected_rows());
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn test_ipc_stream_mismatched_schema() {
+    let stream = ipc_stream();
+
+    assert!(ipc_stream_reader_for::<OtherRow, _>(stream.as_slice()).is_err());
+}
+
+#[test]
+fn test_open_ipc_rows() {
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct NameRow {
+        name: Option<String>,
+    }
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let mut writer = FileWriter::try_new(file.reopen().unwrap(), &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.finish().unwrap();
+
+    let rows: Vec<Row> = open_ipc_rows(file.path())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows, expected_rows());
+
+    // The "id" column is not projected
+    let rows: Vec<NameRow> = open_ipc_rows(file.path())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            NameRow {
+                name: Some("foo".to_owned())
+            },
+            NameRow { name: None },
+        ]
+    );
+
+    assert!(open_ipc_rows::<OtherRow>(file.path()).is_err());
+}
+
+#[test]
+fn test_ipc_rows_from_bytes() {
+    let mut bytes = Vec::new();
+    let mut writer = FileWriter::try_new(&mut bytes, &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+
+    let rows: Vec<Row> = ipc_rows_from_bytes(&bytes)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows, expected_rows());
+
+    assert!(ipc_rows_from_bytes::<OtherRow>(&bytes).is_err());
+    assert!(ipc_rows_from_bytes::<Row>(&bytes[..10]).is_err());
+}
+
+#[test]
+fn test_open_ipc_rows_mmap() {
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct NameRow {
+        name: Option<String>,
+    }
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let mut writer = FileWriter::try_new(file.reopen().unwrap(), &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.finish().unwrap();
+
+    let rows: Vec<Row> = unsafe { open_ipc_rows_mmap(file.path()) }
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let mut expected = expected_rows();
+    expected.extend(expected_rows());
+    assert_eq!(rows, expected);
+
+    // The "id" column is not projected
+    let rows: Vec<NameRow> = unsafe { open_ipc_rows_mmap(file.path()) }
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[3], NameRow { name: None });
+
+    assert!(unsafe { open_ipc_rows_mmap::<OtherRow>(file.path()) }.is_err());
+}
+
+#[test]
+fn test_mmap_ipc_reader() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let mut writer = FileWriter::try_new(file.reopen().unwrap(), &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.write(&batch().slice(1, 1)).unwrap();
+    writer.finish().unwrap();
+
+    let mut reader = unsafe { MmapIpcReader::open(file.path()) }.unwrap();
+    assert_eq!(reader.schema(), batch().schema());
+    assert_eq!(reader.num_batches(), 2);
+    assert_eq!(reader.read_batch(1).unwrap().unwrap(), batch().slice(1, 1));
+    assert!(reader.read_batch(2).is_none());
+    assert!(RandomAccessBatchReader::read_batch(&reader, 2).is_err());
+
+    reader.set_index(1);
+    let batches: Vec<RecordBatch> = reader.collect::<Result<_, _>>().unwrap();
+    assert_eq!(batches, vec![batch().slice(1, 1)]);
+}
+
+#[test]
+fn test_mmap_ipc_reader_invalid_file() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    assert!(unsafe { MmapIpcReader::open(file.path()) }.is_err());
+
+    std::fs::write(file.path(), b"ARROW1\0\0not an arrow file").unwrap();
+    assert!(unsafe { MmapIpcReader::open(file.path()) }.is_err());
+}
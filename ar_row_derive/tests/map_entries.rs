@@ -0,0 +1,62 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, Int64Builder, MapBuilder, StringBuilder};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType};
+use ar_row::MapEntry;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    attributes: Option<Vec<MapEntry<String, Option<i64>>>>,
+}
+
+fn batch() -> RecordBatch {
+    let mut attributes = MapBuilder::new(None, StringBuilder::new(), Int64Builder::new());
+    attributes.keys().append_value("size");
+    attributes.values().append_value(10);
+    attributes.keys().append_value("color");
+    attributes.values().append_null();
+    attributes.append(true).unwrap();
+    attributes.append(false).unwrap();
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+        ("attributes", Arc::new(attributes.finish()) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_map_entries() {
+    let batch = batch();
+    assert_eq!(Row::check_schema(&batch.schema()), Ok(()));
+    assert_eq!(Row::columns(), vec!["id", "attributes"]);
+    assert_eq!(
+        Row::from_record_batch(batch),
+        Ok(vec![
+            Row {
+                id: 1,
+                attributes: Some(vec![
+                    MapEntry {
+                        key: "size".to_owned(),
+                        value: Some(10)
+                    },
+                    MapEntry {
+                        key: "color".to_owned(),
+                        value: None
+                    },
+                ]),
+            },
+            Row {
+                id: 2,
+                attributes: None,
+            },
+        ])
+    );
+}
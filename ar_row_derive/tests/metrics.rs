@@ -0,0 +1,68 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::ArRowDeserialize;
+use ar_row::metrics::{set_metrics, DeserializationMetrics};
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    a: i64,
+    b: Option<String>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    batches: Mutex<Vec<usize>>,
+    columns: Mutex<Vec<(&'static str, &'static str, usize)>>,
+}
+
+impl DeserializationMetrics for Recorder {
+    fn batch(&self, rows: usize, bytes: usize, _duration: Duration) {
+        assert!(bytes > 0);
+        self.batches.lock().unwrap().push(rows);
+    }
+
+    fn column(
+        &self,
+        structure: &'static str,
+        field: &'static str,
+        rows: usize,
+        _duration: Duration,
+    ) {
+        self.columns.lock().unwrap().push((structure, field, rows));
+    }
+}
+
+/// This is the only test in this file, as metrics are global to the process
+#[test]
+fn test_metrics() {
+    let batch = RecordBatch::try_from_iter(vec![
+        ("a", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+        (
+            "b",
+            Arc::new(StringArray::from(vec![Some("x"), None, Some("z")])) as ArrayRef,
+        ),
+    ])
+    .unwrap();
+
+    let recorder = Arc::new(Recorder::default());
+    set_metrics(Some(recorder.clone()));
+    let rows = Row::from_record_batch(batch.clone()).unwrap();
+    set_metrics(None);
+    Row::from_record_batch(batch).unwrap();
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(*recorder.batches.lock().unwrap(), vec![3]);
+    assert_eq!(
+        *recorder.columns.lock().unwrap(),
+        vec![("Row", "a", 3), ("Row", "b", 3)]
+    );
+}
@@ -0,0 +1,75 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Float64Array, Int64Array};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, DeserializationError};
+use ar_row::ndarray::{read_record_batch_into_array2, record_batch_to_array2};
+use ndarray::{array, Array1, Array2, ShapeBuilder};
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "width",
+            Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])) as ArrayRef,
+        ),
+        (
+            "height",
+            Arc::new(Float64Array::from(vec![4.0, 5.0, 6.0])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_read_into_array1() {
+    let array: ArrayRef = Arc::new(Int64Array::from(vec![1, 2, 3]));
+    let mut dst = Array1::<i64>::zeros(4);
+    assert_eq!(i64::read_from_array(array.clone(), &mut dst), Ok(3));
+    assert_eq!(dst, array![1, 2, 3, 0]);
+
+    // Views of a column of a matrix
+    let mut matrix = Array2::<i64>::zeros((3, 2));
+    i64::read_from_array(array, &mut matrix.column_mut(1)).unwrap();
+    assert_eq!(matrix, array![[0, 1], [0, 2], [0, 3]]);
+}
+
+#[test]
+fn test_record_batch_to_array2() {
+    assert_eq!(
+        record_batch_to_array2::<f64>(&batch()),
+        Ok(array![[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]])
+    );
+    assert!(matches!(
+        record_batch_to_array2::<i64>(&batch()),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+}
+
+#[test]
+fn test_read_record_batch_into_array2() {
+    let mut dst = Array2::<f64>::from_elem((4, 2), -1.0);
+    assert_eq!(read_record_batch_into_array2(&batch(), &mut dst), Ok(3));
+    assert_eq!(
+        dst,
+        array![[1.0, 4.0], [2.0, 5.0], [3.0, 6.0], [-1.0, -1.0]]
+    );
+
+    // Column-major matrices are filled the same way
+    let mut dst = Array2::<f64>::zeros((3, 2).f());
+    read_record_batch_into_array2(&batch(), &mut dst).unwrap();
+    assert_eq!(dst, array![[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]]);
+
+    assert_eq!(
+        read_record_batch_into_array2(&batch(), &mut Array2::<f64>::zeros((2, 2))),
+        Err(DeserializationError::MismatchedLength { src: 3, dst: 2 })
+    );
+    assert!(matches!(
+        read_record_batch_into_array2(&batch(), &mut Array2::<f64>::zeros((3, 3))),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+}
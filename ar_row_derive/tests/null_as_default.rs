@@ -0,0 +1,111 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, FixedSizeBinaryArray, Int64Array, StringArray};
+use ar_row::arrow::datatypes::{DataType, Field, Schema};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType, DeserializationError};
+use ar_row_derive::ArRowDeserialize;
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "count",
+            Arc::new(Int64Array::from(vec![Some(1), None])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![None, Some("foo")])) as ArrayRef,
+        ),
+        (
+            "tag",
+            Arc::new(
+                FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    vec![None, Some(*b"abcd")].into_iter(),
+                    4,
+                )
+                .unwrap(),
+            ) as ArrayRef,
+        ),
+        (
+            "label",
+            Arc::new(Int64Array::from(vec![None, Some(42)])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+macro_rules! test_null_as_default {
+    ($test_name:ident, $(#[$attr:meta])*) => {
+        #[test]
+        fn $test_name() {
+            #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+            $(#[$attr])*
+            struct Row {
+                #[ar_row(null_as_default)]
+                count: i64,
+                #[ar_row(null_as_default)]
+                name: String,
+                #[ar_row(null_as_default)]
+                tag: [u8; 4],
+                #[ar_row(null_as_default, stringify)]
+                label: String,
+            }
+
+            assert_eq!(
+                Row::arrow_schema(),
+                Schema::new(vec![
+                    Field::new("count", DataType::Int64, true),
+                    Field::new("name", DataType::Utf8, true),
+                    Field::new("tag", DataType::FixedSizeBinary(4), true),
+                    Field::new("label", DataType::Utf8, true),
+                ])
+            );
+
+            let batch = batch();
+            Row::check_schema(&batch.schema()).unwrap();
+            assert_eq!(
+                Row::from_record_batch(batch),
+                Ok(vec![
+                    Row {
+                        count: 1,
+                        name: String::new(),
+                        tag: [0; 4],
+                        label: String::new(),
+                    },
+                    Row {
+                        count: 0,
+                        name: "foo".to_owned(),
+                        tag: *b"abcd",
+                        label: "42".to_owned(),
+                    },
+                ])
+            );
+        }
+    };
+}
+
+test_null_as_default!(test_null_as_default,);
+test_null_as_default!(test_null_as_default_direct_field_writes, #[ar_row(direct_field_writes)]);
+
+#[test]
+fn test_without_null_as_default() {
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Row {
+        count: i64,
+    }
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "count",
+        Arc::new(Int64Array::from(vec![Some(1), None])) as ArrayRef,
+    )])
+    .unwrap();
+    assert!(matches!(
+        Row::from_record_batch(batch),
+        Err(DeserializationError::UnexpectedNull(_))
+    ));
+}
@@ -87,3 +87,125 @@ fn test_string_no_option() {
         ))
     );
 }
+
+#[test]
+fn test_null_structs() {
+    use std::sync::Arc;
+
+    use ar_row::arrow::array::{Array, ArrayRef, Int64Array, StructArray};
+    use ar_row::arrow::buffer::NullBuffer;
+    use ar_row::arrow::datatypes::{DataType, Field};
+
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct Row {
+        a: i64,
+    }
+
+    let array: Arc<dyn Array> = Arc::new(StructArray::new(
+        vec![Field::new("a", DataType::Int64, false)].into(),
+        vec![Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef],
+        Some(NullBuffer::from(vec![true, false, true])),
+    ));
+
+    assert_eq!(
+        <Option<Row>>::from_array(array),
+        Ok(vec![Some(Row { a: 1 }), None, Some(Row { a: 3 })])
+    );
+}
+
+#[test]
+fn test_null_structs_many_rows() {
+    use std::sync::Arc;
+
+    use ar_row::arrow::array::{Array, ArrayRef, Int64Array, StructArray};
+    use ar_row::arrow::buffer::NullBuffer;
+    use ar_row::arrow::datatypes::{DataType, Field};
+
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct Row {
+        a: Option<i64>,
+    }
+
+    // Spans several 64-bits words of the validity bitmaps, with both runs of nulls
+    // within a word and words without any null
+    let is_valid = |i: i64| !(i % 7 == 0 || (130..140).contains(&i));
+    let values: Vec<i64> = (0..200).collect();
+    let array: Arc<dyn Array> = Arc::new(StructArray::new(
+        vec![Field::new("a", DataType::Int64, true)].into(),
+        vec![Arc::new(Int64Array::from(
+            values
+                .iter()
+                .map(|&i| Some(i).filter(|i| i % 3 != 0))
+                .collect::<Vec<_>>(),
+        )) as ArrayRef],
+        Some(NullBuffer::from(
+            values.iter().map(|&i| is_valid(i)).collect::<Vec<_>>(),
+        )),
+    ));
+
+    assert_eq!(
+        <Option<Row>>::from_array(array.slice(1, 150)),
+        Ok(values[1..151]
+            .iter()
+            .map(|&i| is_valid(i).then(|| Row {
+                a: Some(i).filter(|i| i % 3 != 0)
+            }))
+            .collect())
+    );
+}
+
+#[test]
+fn test_null_structs_blocks() {
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+
+    use ar_row::arrow::array::{Array, ArrayRef, Int64Array, StringArray, StructArray};
+    use ar_row::arrow::buffer::NullBuffer;
+    use ar_row::arrow::datatypes::{DataType, Field};
+    use ar_row::options::DeserializeOptions;
+
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct Row {
+        a: Option<i64>,
+        b: String,
+    }
+
+    // Not a multiple of the block size
+    let values: Vec<i64> = (0..100).collect();
+    let array: Arc<dyn Array> = Arc::new(StructArray::new(
+        vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, false),
+        ]
+        .into(),
+        vec![
+            Arc::new(Int64Array::from(
+                values
+                    .iter()
+                    .map(|&i| Some(i).filter(|i| i % 3 != 0))
+                    .collect::<Vec<_>>(),
+            )) as ArrayRef,
+            Arc::new(StringArray::from(
+                values.iter().map(|i| i.to_string()).collect::<Vec<_>>(),
+            )) as ArrayRef,
+        ],
+        Some(NullBuffer::from(
+            values.iter().map(|&i| i % 7 != 0).collect::<Vec<_>>(),
+        )),
+    ));
+
+    let options = DeserializeOptions {
+        block_size: NonZeroUsize::new(16),
+        ..Default::default()
+    };
+    assert_eq!(
+        <Option<Row>>::from_array_with_options(array.slice(1, 90), &options),
+        Ok(values[1..91]
+            .iter()
+            .map(|&i| (i % 7 != 0).then(|| Row {
+                a: Some(i).filter(|i| i % 3 != 0),
+                b: i.to_string(),
+            }))
+            .collect())
+    );
+}
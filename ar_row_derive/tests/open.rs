@@ -0,0 +1,81 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::io::Write;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array};
+use ar_row::arrow::ipc::writer::FileWriter;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::open::{detect_format, open_rows, FileFormat};
+use ar_row_derive::ArRowDeserialize;
+use parquet::arrow::ArrowWriter;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    long1: Option<i64>,
+}
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![(
+        "long1",
+        Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+    )])
+    .unwrap()
+}
+
+fn read_rows(path: &std::path::Path) -> Vec<Option<i64>> {
+    open_rows::<Row>(path)
+        .unwrap()
+        .map(|row| row.unwrap().long1)
+        .collect()
+}
+
+#[test]
+fn test_open_orc() {
+    let path = std::path::Path::new("../test_data/TestOrcFile.test1.orc");
+    assert_eq!(detect_format(path).unwrap(), Some(FileFormat::Orc));
+    assert_eq!(
+        read_rows(path),
+        vec![Some(9223372036854775807), Some(9223372036854775807)]
+    );
+}
+
+#[test]
+fn test_open_parquet() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let mut writer = ArrowWriter::try_new(file.reopen().unwrap(), batch().schema(), None).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.close().unwrap();
+
+    assert_eq!(
+        detect_format(file.path()).unwrap(),
+        Some(FileFormat::Parquet)
+    );
+    assert_eq!(read_rows(file.path()), vec![Some(1), Some(2)]);
+}
+
+#[test]
+fn test_open_ipc() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let mut writer = FileWriter::try_new(file.reopen().unwrap(), &batch().schema()).unwrap();
+    writer.write(&batch()).unwrap();
+    writer.finish().unwrap();
+
+    assert_eq!(
+        detect_format(file.path()).unwrap(),
+        Some(FileFormat::ArrowIpc)
+    );
+    assert_eq!(read_rows(file.path()), vec![Some(1), Some(2)]);
+}
+
+#[test]
+fn test_open_unknown() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"long1\n1\n2\n").unwrap();
+
+    assert_eq!(detect_format(file.path()).unwrap(), None);
+    assert!(open_rows::<Row>(file.path()).is_err());
+}
@@ -0,0 +1,140 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, DeserializationError};
+use ar_row::options::{DeserializeOptions, NullPolicy, UnknownColumns};
+use ar_row::row_iterator::RowIterator;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+}
+
+/// `id` is an `Int32` column, and `extra` is not a field of `Row`
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        (
+            "extra",
+            Arc::new(Int64Array::from(vec![0, 0, 0, 0, 0])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![
+                Some("a"),
+                Some("b"),
+                None,
+                Some("d"),
+                Some("e"),
+            ])) as ArrayRef,
+        ),
+        (
+            "id",
+            Arc::new(Int32Array::from(vec![
+                Some(1),
+                None,
+                Some(3),
+                Some(4),
+                Some(5),
+            ])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+fn expected_rows() -> Vec<Row> {
+    vec![
+        Row {
+            id: 1,
+            name: Some("a".to_owned()),
+        },
+        Row { id: 3, name: None },
+        Row {
+            id: 4,
+            name: Some("d".to_owned()),
+        },
+        Row {
+            id: 5,
+            name: Some("e".to_owned()),
+        },
+    ]
+}
+
+fn options() -> DeserializeOptions {
+    DeserializeOptions {
+        coerce_types: true,
+        unknown_columns: UnknownColumns::Ignore,
+        nulls: NullPolicy::SkipRows,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_from_record_batch_with_options() {
+    assert!(matches!(
+        Row::from_record_batch_with_options(batch(), &DeserializeOptions::default()),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+    assert_eq!(
+        Row::from_record_batch_with_options(batch(), &options()),
+        Ok(expected_rows())
+    );
+}
+
+#[test]
+fn test_parallel_chunks() {
+    // usize::MAX checks the number of chunks is computed without overflowing
+    for chunk_size in [1, 2, 3, 10, usize::MAX] {
+        let options = DeserializeOptions {
+            parallel_chunk_size: NonZeroUsize::new(chunk_size),
+            ..options()
+        };
+        assert_eq!(
+            Row::from_record_batch_with_options(batch(), &options),
+            Ok(expected_rows())
+        );
+    }
+}
+
+#[test]
+fn test_blocks() {
+    for block_size in [1, 2, 3, 10] {
+        let options = DeserializeOptions {
+            block_size: NonZeroUsize::new(block_size),
+            ..options()
+        };
+        assert_eq!(
+            Row::from_record_batch_with_options(batch(), &options),
+            Ok(expected_rows())
+        );
+        assert_eq!(
+            <Option<Row>>::from_record_batch_with_options(batch(), &options),
+            Ok(expected_rows().into_iter().map(Some).collect())
+        );
+
+        // Blocks of parallel chunks
+        let options = DeserializeOptions {
+            parallel_chunk_size: NonZeroUsize::new(3),
+            ..options
+        };
+        assert_eq!(
+            Row::from_record_batch_with_options(batch(), &options),
+            Ok(expected_rows())
+        );
+    }
+}
+
+#[test]
+fn test_row_iterator_with_options() {
+    let batches = vec![batch().slice(0, 2), batch().slice(2, 3)];
+    let rows = RowIterator::<_, Row>::with_options(batches.into_iter(), options()).unwrap();
+    assert_eq!(rows.collect::<Vec<_>>(), expected_rows());
+}
@@ -0,0 +1,196 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fs::File;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::orc::orc_projection_for;
+use ar_row::parallel_row_iterator::{IntoParRows, ParallelRowIterator};
+use ar_row::random_access::ChunkedBatchReader;
+use ar_row::rayon::prelude::*;
+use ar_row::reader::ReadError;
+use ar_row_derive::ArRowDeserialize;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    a: i64,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1 {
+    long1: Option<i64>,
+}
+
+fn batch(values: Vec<Option<i64>>) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![("a", Arc::new(Int64Array::from(values)) as ArrayRef)]).unwrap()
+}
+
+fn batches() -> Vec<RecordBatch> {
+    (0..10)
+        .map(|i| batch((i * 10..i * 10 + 10).map(Some).collect()))
+        .collect()
+}
+
+#[test]
+fn test_collect_into_vec() {
+    let batches = batches();
+    let mut rows = Vec::new();
+    ParallelRowIterator::<_, Row>::new(&batches)
+        .unwrap()
+        .with_max_len(3)
+        .collect_into_vec(&mut rows);
+    let rows: Vec<i64> = rows.into_iter().map(|row| row.unwrap().a).collect();
+    assert_eq!(rows, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_zip() {
+    let batches = batches();
+    let iter = ParallelRowIterator::<_, Row>::new(&batches).unwrap();
+    assert_eq!(iter.len(), 100);
+    assert!(iter
+        .zip(0..100usize)
+        .all(|(row, i)| row.map(|row| row.a == i as i64).unwrap_or(false)));
+}
+
+#[test]
+fn test_for_each_with() {
+    let batches = batches();
+    let (tx, rx) = std::sync::mpsc::channel();
+    ParallelRowIterator::<_, Row>::new(&batches)
+        .unwrap()
+        .enumerate()
+        .for_each_with(tx, |tx, (i, row)| {
+            tx.send((i, row.unwrap().a)).unwrap();
+        });
+    let mut rows: Vec<(usize, i64)> = rx.into_iter().collect();
+    rows.sort_unstable();
+    assert_eq!(rows, (0..100).map(|i| (i, i as i64)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_errors() {
+    let mut batches = batches();
+    batches[1] = batch(vec![Some(10), None, Some(12)]);
+    let mut rows = Vec::new();
+    ParallelRowIterator::<_, Row>::new(&batches)
+        .unwrap()
+        .collect_into_vec(&mut rows);
+    assert_eq!(rows.len(), 93);
+    assert!(rows[..10].iter().all(Result::is_ok));
+    assert!(rows[13..].iter().all(Result::is_ok));
+    // Depending on how the batch was split, its other rows may be errors too, and
+    // they all share the original error
+    assert!(matches!(&rows[11], Err(e) if matches!(**e, ReadError::Deserialization(_))));
+    for row in &rows[10..13] {
+        if let Err(e) = row {
+            assert!(matches!(**e, ReadError::Deserialization(_)));
+        }
+    }
+    assert!(rows[10..13].windows(2).all(|pair| match pair {
+        [Err(e1), Err(e2)] => Arc::ptr_eq(e1, e2),
+        _ => true,
+    }));
+}
+
+#[test]
+fn test_into_buffered_par_rows() {
+    let rows: Vec<Row> = batches()
+        .into_iter()
+        .map(Ok::<_, ReadError>)
+        .into_buffered_par_rows()
+        .unwrap()
+        .with_max_len(3)
+        .collect();
+    assert_eq!(rows, (0..100).map(|a| Row { a }).collect::<Vec<_>>());
+
+    let rows: Vec<Row> = Vec::<RecordBatch>::new()
+        .into_iter()
+        .into_buffered_par_rows()
+        .unwrap()
+        .collect();
+    assert_eq!(rows, vec![]);
+
+    let batch =
+        RecordBatch::try_from_iter(vec![("b", Arc::new(Int64Array::from(vec![1])) as ArrayRef)])
+            .unwrap();
+    assert!(vec![batch.clone()]
+        .into_iter()
+        .into_buffered_par_rows::<Row>()
+        .is_err());
+
+    // Every batch's schema is checked, not only the first one
+    assert!(vec![batches()[0].clone(), batch]
+        .into_iter()
+        .into_buffered_par_rows::<Row>()
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "ParallelBatchRows failed")]
+fn test_into_buffered_par_rows_errors() {
+    let mut batches = batches();
+    batches[1] = batch(vec![Some(10), None, Some(12)]);
+    batches
+        .into_iter()
+        .into_buffered_par_rows::<Row>()
+        .unwrap()
+        .for_each(drop);
+}
+
+#[test]
+fn test_parquet() {
+    let batch = RecordBatch::try_from_iter(vec![(
+        "a",
+        Arc::new(Int64Array::from((0..10).collect::<Vec<_>>())) as ArrayRef,
+    )])
+    .unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(4)
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(file.reopen().unwrap(), batch.schema(), Some(properties)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let reader = ChunkedBatchReader::new(|| {
+        Ok(
+            ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap())
+                .unwrap()
+                .with_batch_size(3),
+        )
+    })
+    .unwrap();
+    let mut rows = Vec::new();
+    ParallelRowIterator::<_, Row>::new(&reader)
+        .unwrap()
+        .with_max_len(1)
+        .collect_into_vec(&mut rows);
+    let rows: Vec<i64> = rows.into_iter().map(|row| row.unwrap().a).collect();
+    assert_eq!(rows, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_orc() {
+    let reader = ChunkedBatchReader::new(|| {
+        let file = File::open("../test_data/TestOrcFile.test1.orc").unwrap();
+        let builder = orc_rust::ArrowReaderBuilder::try_new(file).unwrap();
+        let projection = orc_projection_for::<Test1>(builder.file_metadata().root_data_type());
+        Ok(builder.with_projection(projection))
+    })
+    .unwrap();
+    let mut rows = Vec::new();
+    ParallelRowIterator::<_, Test1>::new(&reader)
+        .unwrap()
+        .collect_into_vec(&mut rows);
+    assert_eq!(rows.len(), 2);
+    assert!(rows.into_iter().all(|row| row.is_ok()));
+}
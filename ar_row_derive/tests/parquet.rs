@@ -0,0 +1,110 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::parquet::{open_parquet_row_groups, open_parquet_rows};
+use ar_row_derive::ArRowDeserialize;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+
+mod common;
+use common::{OtherRow, Row};
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct NameRow {
+    name: Option<String>,
+}
+
+/// Writes a Parquet file with three row groups of two rows each
+fn parquet_file() -> tempfile::NamedTempFile {
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "id",
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4, 5, 6])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![
+                Some("a"),
+                None,
+                Some("c"),
+                Some("d"),
+                None,
+                Some("f"),
+            ])) as ArrayRef,
+        ),
+    ])
+    .unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(2)
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(file.reopen().unwrap(), batch.schema(), Some(properties)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    file
+}
+
+#[test]
+fn test_open_parquet_rows() {
+    let file = parquet_file();
+
+    let rows: Vec<Row> = open_parquet_rows(file.path())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows.iter().map(|row| row.id).collect::<Vec<_>>(),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+
+    // The "id" column is not projected
+    let rows: Vec<NameRow> = open_parquet_rows(file.path())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows.into_iter().map(|row| row.name).collect::<Vec<_>>(),
+        vec![
+            Some("a".to_owned()),
+            None,
+            Some("c".to_owned()),
+            Some("d".to_owned()),
+            None,
+            Some("f".to_owned()),
+        ]
+    );
+
+    assert!(open_parquet_rows::<OtherRow>(file.path()).is_err());
+}
+
+#[test]
+fn test_open_parquet_row_groups() {
+    let file = parquet_file();
+
+    let rows: Vec<Row> = open_parquet_row_groups(file.path(), vec![2, 0])
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row { id: 5, name: None },
+            Row {
+                id: 6,
+                name: Some("f".to_owned())
+            },
+            Row {
+                id: 1,
+                name: Some("a".to_owned())
+            },
+            Row { id: 2, name: None },
+        ]
+    );
+}
@@ -0,0 +1,42 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::prelude::*;
+
+/// The derive macro and the traits it implements are both available from the prelude
+#[test]
+fn test_prelude() {
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct Row {
+        a: i64,
+        b: Option<String>,
+    }
+
+    let batch = RecordBatch::try_from_iter(vec![
+        ("a", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+        (
+            "b",
+            Arc::new(StringArray::from(vec![Some("x"), None])) as ArrayRef,
+        ),
+    ])
+    .unwrap();
+
+    Row::check_schema(&batch.schema()).unwrap();
+    assert_eq!(Row::columns(), vec!["a", "b"]);
+    assert_eq!(
+        Row::from_record_batch(batch),
+        Ok(vec![
+            Row {
+                a: 1,
+                b: Some("x".to_owned())
+            },
+            Row { a: 2, b: None }
+        ])
+    );
+}
@@ -0,0 +1,457 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fs::File;
+use std::sync::Arc;
+
+use orc_rust::ArrowReaderBuilder;
+
+use ar_row::arrow::array::{
+    Array, ArrayRef, AsArray, FixedSizeListArray, Int64Array, ListArray, MapArray,
+    RecordBatchIterator, RecordBatchReader, StringArray, StructArray,
+};
+use ar_row::arrow::buffer::OffsetBuffer;
+use ar_row::arrow::datatypes::{DataType, Field};
+use ar_row::arrow::error::ArrowError;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{
+    ArRowDeserialize, ArRowStruct, CheckableDataType, ColumnPath, ColumnTree, DeserializationError,
+    DeserializationTarget,
+};
+use ar_row::orc::{open_orc_rows, orc_projection_for};
+use ar_row::projection::{project_batch, project_batch_for, ProjectedReader};
+use ar_row_derive::ArRowDeserialize;
+
+fn get_reader_builder() -> ArrowReaderBuilder<File> {
+    let orc_path = "../test_data/TestOrcFile.test1.orc";
+    let file = File::open(orc_path).expect("could not open .orc");
+    ArrowReaderBuilder::try_new(file).expect("Could not make builder")
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1 {
+    int1: Option<i32>,
+    list: Option<Vec<Option<Test1Item>>>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1Item {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+#[test]
+fn test_orc_projection_for() {
+    let builder = get_reader_builder();
+    let projection = orc_projection_for::<Test1>(builder.file_metadata().root_data_type());
+    let reader = builder.with_projection(projection).build();
+    Test1::check_schema(&reader.schema()).unwrap();
+
+    let rows: Vec<Test1> = reader
+        .flat_map(|batch| Test1::from_record_batch(batch.unwrap()).unwrap())
+        .collect();
+
+    assert_eq!(
+        rows.iter().map(|row| row.int1).collect::<Vec<_>>(),
+        vec![Some(65536), Some(65536)]
+    );
+    assert_eq!(
+        rows.iter()
+            .map(|row| row.list.as_ref().unwrap().len())
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+}
+
+#[test]
+fn test_open_orc_rows() {
+    let rows: Vec<Test1> = open_orc_rows("../test_data/TestOrcFile.test1.orc")
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect();
+
+    assert_eq!(
+        rows.iter().map(|row| row.int1).collect::<Vec<_>>(),
+        vec![Some(65536), Some(65536)]
+    );
+    assert_eq!(
+        rows.iter()
+            .map(|row| row.list.as_ref().unwrap().len())
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1Nested {
+    list: Option<Vec<Option<Test1NestedItem>>>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1NestedItem {
+    string1: Option<String>,
+}
+
+/// Tests `open_orc_rows` prunes columns nested in the top-level columns it reads
+#[test]
+fn test_open_orc_rows_nested() {
+    let rows: Vec<Test1Nested> = open_orc_rows("../test_data/TestOrcFile.test1.orc")
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect();
+
+    assert_eq!(
+        rows.iter()
+            .map(|row| row.list.as_ref().unwrap().len())
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert!(rows
+        .iter()
+        .flat_map(|row| row.list.as_ref().unwrap())
+        .all(|item| item.as_ref().unwrap().string1.is_some()));
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Projected {
+    id: i64,
+    inner: ProjectedInner,
+    items: Vec<ProjectedItem>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct ProjectedInner {
+    id: i64,
+    name: String,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct ProjectedItem {
+    a: i64,
+}
+
+/// Batch with more columns than [`Projected`] reads, in a different order
+fn unprojected_batch() -> RecordBatch {
+    let inner = StructArray::from(vec![
+        (
+            Arc::new(Field::new("unused", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![0, 0])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("name", DataType::Utf8, false)),
+            Arc::new(StringArray::from(vec!["foo", "bar"])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("id", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![10, 20])) as ArrayRef,
+        ),
+    ]);
+    let item = StructArray::from(vec![
+        (
+            Arc::new(Field::new("b", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![0, 0, 0])) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("a", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef,
+        ),
+    ]);
+    let items = ListArray::try_new(
+        Arc::new(Field::new("item", item.data_type().clone(), false)),
+        OffsetBuffer::from_lengths([2, 1]),
+        Arc::new(item),
+        None,
+    )
+    .unwrap();
+    RecordBatch::try_from_iter(vec![
+        ("extra", Arc::new(Int64Array::from(vec![0, 0])) as ArrayRef),
+        ("items", Arc::new(items) as ArrayRef),
+        ("inner", Arc::new(inner) as ArrayRef),
+        ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_project_batch_for() {
+    let batch = unprojected_batch();
+    assert!(Projected::check_schema(&batch.schema()).is_err());
+
+    let batch = project_batch_for::<Projected>(&batch).unwrap();
+    Projected::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        Projected::from_record_batch(batch),
+        Ok(vec![
+            Projected {
+                id: 1,
+                inner: ProjectedInner {
+                    id: 10,
+                    name: "foo".to_owned()
+                },
+                items: vec![ProjectedItem { a: 1 }, ProjectedItem { a: 2 }],
+            },
+            Projected {
+                id: 2,
+                inner: ProjectedInner {
+                    id: 20,
+                    name: "bar".to_owned()
+                },
+                items: vec![ProjectedItem { a: 3 }],
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_projected_reader() {
+    let batch = unprojected_batch();
+    let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+    let reader = ProjectedReader::new_for::<Projected>(reader).unwrap();
+    Projected::check_schema(&reader.schema()).unwrap();
+
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    assert_eq!(
+        batches,
+        vec![project_batch_for::<Projected>(&batch).unwrap()]
+    );
+}
+
+#[test]
+fn test_project_batch_for_missing_column() {
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct Missing {
+        id: i64,
+        inner: MissingInner,
+    }
+
+    #[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+    struct MissingInner {
+        other: i64,
+    }
+
+    assert!(matches!(
+        project_batch_for::<Missing>(&unprojected_batch()),
+        Err(ArrowError::SchemaError(message)) if message == "No column named inner.other"
+    ));
+}
+
+#[test]
+fn test_column_tree_from_paths() {
+    let paths: Vec<_> = ["b.c", "a", "b.d.e", "a.f"]
+        .iter()
+        .map(|path| ColumnPath(path.split('.').map(ToOwned::to_owned).collect()))
+        .collect();
+    assert_eq!(
+        ColumnTree::from_paths(&paths),
+        ColumnTree::Struct(vec![
+            (
+                "b".to_owned(),
+                ColumnTree::Struct(vec![
+                    ("c".to_owned(), ColumnTree::Leaf),
+                    (
+                        "d".to_owned(),
+                        ColumnTree::Struct(vec![("e".to_owned(), ColumnTree::Leaf)])
+                    ),
+                ])
+            ),
+            ("a".to_owned(), ColumnTree::Leaf),
+        ])
+    );
+}
+
+/// Structure with a single field named `a.b`, which cannot be derived as Rust
+/// identifiers cannot contain dots. It relies on the default implementation of
+/// `ArRowStruct::column_tree`, which parses `columns()`.
+#[derive(Default, Debug, PartialEq)]
+struct Dotted {
+    a_b: i64,
+}
+
+impl ArRowStruct for Dotted {
+    fn columns_with_prefix(prefix: &str) -> Vec<String> {
+        let mut column = prefix.to_owned();
+        if !prefix.is_empty() {
+            column.push('.');
+        }
+        column.push_str(&ColumnPath(vec!["a.b".to_owned()]).to_string());
+        vec![column]
+    }
+}
+
+impl CheckableDataType for Dotted {
+    fn check_datatype(datatype: &DataType) -> Result<(), String> {
+        match datatype {
+            DataType::Struct(fields) if fields.len() == 1 && fields[0].name() == "a.b" => {
+                i64::check_datatype(fields[0].data_type())
+            }
+            _ => Err(format!(
+                "Dotted must be decoded from a struct with a single a.b field, not {:?}",
+                datatype
+            )),
+        }
+    }
+}
+
+impl ArRowDeserialize for Dotted {
+    fn read_from_array<'a, 'b, T>(
+        src: impl Array + AsArray,
+        mut dst: &'b mut T,
+    ) -> Result<usize, DeserializationError>
+    where
+        &'b mut T: DeserializationTarget<'a, Item = Self> + 'b,
+    {
+        let src = src.as_struct_opt().ok_or_else(|| {
+            DeserializationError::MismatchedColumnDataType(
+                "Dotted must be decoded from a struct".to_owned(),
+            )
+        })?;
+        let values = i64::from_array(src.column(0).clone())?;
+        for (row, value) in dst.iter_mut().zip(values) {
+            row.a_b = value;
+        }
+        Ok(src.len())
+    }
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct WithDotted {
+    id: i64,
+    inner: Dotted,
+}
+
+/// Tests dots in field names are escaped by `columns()`, so they round-trip through
+/// `ColumnPath::parse` and projection
+#[test]
+fn test_dotted_field_names() {
+    let paths = vec![
+        ColumnPath(vec!["id".to_owned()]),
+        ColumnPath(vec!["inner".to_owned(), "a.b".to_owned()]),
+    ];
+    assert_eq!(WithDotted::columns(), vec!["id", r"inner.a\.b"]);
+    assert_eq!(
+        WithDotted::columns()
+            .iter()
+            .map(|column| ColumnPath::parse(column))
+            .collect::<Vec<_>>(),
+        paths
+    );
+    assert_eq!(
+        Dotted::column_tree(),
+        ColumnTree::Struct(vec![("a.b".to_owned(), ColumnTree::Leaf)])
+    );
+    assert_eq!(WithDotted::column_paths(), paths);
+
+    // `inner` also has a field `a` with a field `b`, which must not be selected
+    let a = StructArray::from(vec![(
+        Arc::new(Field::new("b", DataType::Int64, false)),
+        Arc::new(Int64Array::from(vec![0, 0])) as ArrayRef,
+    )]);
+    let inner = StructArray::from(vec![
+        (
+            Arc::new(Field::new("a", a.data_type().clone(), false)),
+            Arc::new(a) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("a.b", DataType::Int64, false)),
+            Arc::new(Int64Array::from(vec![10, 20])) as ArrayRef,
+        ),
+    ]);
+    let batch = RecordBatch::try_from_iter(vec![
+        ("inner", Arc::new(inner) as ArrayRef),
+        ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+    ])
+    .unwrap();
+    assert!(WithDotted::check_schema(&batch.schema()).is_err());
+
+    let projected = project_batch(&batch, &ColumnTree::from_paths(&paths)).unwrap();
+    assert_eq!(projected, project_batch_for::<WithDotted>(&batch).unwrap());
+    WithDotted::check_schema(&projected.schema()).unwrap();
+    assert_eq!(
+        WithDotted::from_record_batch(projected),
+        Ok(vec![
+            WithDotted {
+                id: 1,
+                inner: Dotted { a_b: 10 }
+            },
+            WithDotted {
+                id: 2,
+                inner: Dotted { a_b: 20 }
+            },
+        ])
+    );
+}
+
+/// Structure with fields `a` and `b`, with `b = 10 * a`
+fn ab_struct(a: Vec<i64>) -> StructArray {
+    let b = a.iter().map(|a| a * 10).collect::<Vec<_>>();
+    StructArray::from(vec![
+        (
+            Arc::new(Field::new("a", DataType::Int64, false)),
+            Arc::new(Int64Array::from(a)) as ArrayRef,
+        ),
+        (
+            Arc::new(Field::new("b", DataType::Int64, false)),
+            Arc::new(Int64Array::from(b)) as ArrayRef,
+        ),
+    ])
+}
+
+#[test]
+fn test_project_batch_maps_and_fixed_size_lists() {
+    let attrs = MapArray::new_from_strings(
+        ["x", "y", "z"].into_iter(),
+        &ab_struct(vec![1, 2, 3]),
+        &[0, 2, 3],
+    )
+    .unwrap();
+    let values = ab_struct(vec![4, 5, 6, 7]);
+    let pairs = FixedSizeListArray::try_new(
+        Arc::new(Field::new("item", values.data_type().clone(), false)),
+        2,
+        Arc::new(values),
+        None,
+    )
+    .unwrap();
+    let batch = RecordBatch::try_from_iter(vec![
+        ("attrs", Arc::new(attrs) as ArrayRef),
+        ("pairs", Arc::new(pairs) as ArrayRef),
+    ])
+    .unwrap();
+
+    let tree = ColumnTree::Struct(vec![
+        (
+            "pairs".to_owned(),
+            ColumnTree::Struct(vec![("b".to_owned(), ColumnTree::Leaf)]),
+        ),
+        (
+            "attrs".to_owned(),
+            ColumnTree::Struct(vec![("a".to_owned(), ColumnTree::Leaf)]),
+        ),
+    ]);
+    let batch = project_batch(&batch, &tree).unwrap();
+
+    let pairs = batch.column(0).as_fixed_size_list();
+    assert_eq!(pairs.value_length(), 2);
+    let pair_values = pairs.values().as_struct();
+    assert_eq!(pair_values.num_columns(), 1);
+    assert_eq!(
+        pair_values.column_by_name("b").unwrap().as_ref(),
+        &Int64Array::from(vec![40, 50, 60, 70]) as &dyn Array
+    );
+
+    // Keys are kept, values are pruned
+    let attrs = batch.column(1).as_map();
+    assert_eq!(attrs.value_offsets(), &[0, 2, 3]);
+    assert_eq!(
+        attrs.keys().as_ref(),
+        &StringArray::from(vec!["x", "y", "z"]) as &dyn Array
+    );
+    let attr_values = attrs.values().as_struct();
+    assert_eq!(attr_values.num_columns(), 1);
+    assert_eq!(
+        attr_values.column_by_name("a").unwrap().as_ref(),
+        &Int64Array::from(vec![1, 2, 3]) as &dyn Array
+    );
+}
@@ -0,0 +1,223 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::{Arc, Mutex};
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::error::ArrowError;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::DeserializationError;
+use ar_row::row_iterator::{Decision, IntoRows, RowIterator, RowIteratorError};
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    a: i64,
+}
+
+fn batch(values: Vec<Option<i64>>) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![("a", Arc::new(Int64Array::from(values)) as ArrayRef)]).unwrap()
+}
+
+fn batches() -> Vec<RecordBatch> {
+    vec![
+        batch(vec![Some(1), Some(2), Some(3)]),
+        batch(vec![Some(4), Some(5)]),
+        batch(vec![Some(6), None]),
+        batch(vec![Some(7)]),
+    ]
+}
+
+#[test]
+fn test_try_next_error_position() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter()).unwrap();
+    for i in 1..=5 {
+        assert_eq!(iter.try_next(), Ok(Some(Row { a: i })));
+    }
+    match iter.try_next() {
+        Err(RowIteratorError {
+            batch_index: 2,
+            first_row: Some(5),
+            num_rows: 2,
+            source: DeserializationError::UnexpectedNull(_),
+        }) => (),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn test_try_next_end() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter().take(2)).unwrap();
+    for i in 1..=5 {
+        assert_eq!(iter.try_next(), Ok(Some(Row { a: i })));
+    }
+    assert_eq!(iter.try_next(), Ok(None));
+    assert_eq!(iter.try_next(), Ok(None));
+}
+
+#[test]
+#[should_panic(expected = "Could not deserialize batch 2 (rows 5..7)")]
+fn test_next_error_position() {
+    RowIterator::<_, Row>::new(batches().into_iter())
+        .unwrap()
+        .for_each(drop);
+}
+
+#[test]
+fn test_into_rows() {
+    let rows: Vec<Row> = batches().into_iter().take(2).into_rows().unwrap().collect();
+    assert_eq!(rows, (1..=5).map(|a| Row { a }).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_into_rows_results() {
+    let batches: Vec<Result<RecordBatch, ArrowError>> = batches().into_iter().map(Ok).collect();
+    let mut iter = batches.into_iter().take(2).into_rows::<Row>().unwrap();
+    for i in 1..=5 {
+        assert_eq!(iter.try_next(), Ok(Some(Row { a: i })));
+    }
+    assert_eq!(iter.try_next(), Ok(None));
+}
+
+#[test]
+#[should_panic(expected = "Could not read batch")]
+fn test_into_rows_error() {
+    let batches = vec![
+        Ok(batch(vec![Some(1)])),
+        Err(ArrowError::ComputeError("oops".to_owned())),
+    ];
+    batches
+        .into_iter()
+        .into_rows::<Row>()
+        .unwrap()
+        .for_each(drop);
+}
+
+#[test]
+fn test_on_error_skip() {
+    let skipped = Arc::new(Mutex::new(Vec::new()));
+    let skipped2 = skipped.clone();
+    let rows: Vec<Row> = RowIterator::<_, Row>::new(batches().into_iter())
+        .unwrap()
+        .on_error(move |row, error| {
+            assert_eq!(error.first_row, Some(row));
+            assert_eq!(error.batch_index, 2);
+            assert_eq!(error.num_rows, 1);
+            skipped2.lock().unwrap().push(row);
+            Decision::Skip
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        [1, 2, 3, 4, 5, 6, 7]
+            .into_iter()
+            .map(|a| Row { a })
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(*skipped.lock().unwrap(), vec![6]);
+}
+
+#[test]
+fn test_on_error_abort() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter())
+        .unwrap()
+        .on_error(|_, _| Decision::Abort);
+    for i in 1..=5 {
+        assert_eq!(iter.try_next(), Ok(Some(Row { a: i })));
+    }
+    match iter.try_next() {
+        Err(RowIteratorError {
+            batch_index: 2,
+            first_row: Some(6),
+            num_rows: 1,
+            source: DeserializationError::UnexpectedNull(_),
+        }) => (),
+        res => panic!("Unexpected result: {:?}", res),
+    }
+    assert_eq!(iter.try_next(), Ok(Some(Row { a: 7 })));
+    assert_eq!(iter.try_next(), Ok(None));
+}
+
+#[test]
+fn test_on_error_first_batch() {
+    let batches = vec![batch(vec![None, Some(1)]), batch(vec![Some(2)])];
+    let mut iter = RowIterator::<_, Row>::new(batches.clone().into_iter()).unwrap();
+    assert!(matches!(
+        iter.try_next(),
+        Err(RowIteratorError {
+            batch_index: 0,
+            first_row: Some(0),
+            num_rows: 2,
+            source: DeserializationError::UnexpectedNull(_),
+        })
+    ));
+    assert_eq!(iter.try_next(), Ok(Some(Row { a: 2 })));
+
+    let rows: Vec<Row> = RowIterator::<_, Row>::new(batches.into_iter())
+        .unwrap()
+        .on_error(|_, _| Decision::Skip)
+        .collect();
+    assert_eq!(rows, vec![Row { a: 1 }, Row { a: 2 }]);
+}
+
+#[test]
+fn test_on_error_mismatched_batch() {
+    let mismatched = RecordBatch::try_from_iter(vec![(
+        "a",
+        Arc::new(StringArray::from(vec!["foo", "bar"])) as ArrayRef,
+    )])
+    .unwrap();
+    let batches = vec![batch(vec![Some(1)]), mismatched, batch(vec![Some(2)])];
+    let skipped = Arc::new(Mutex::new(Vec::new()));
+    let skipped2 = skipped.clone();
+    let rows: Vec<Row> = RowIterator::<_, Row>::new(batches.into_iter())
+        .unwrap()
+        .on_error(move |row, error| {
+            assert_eq!(error.num_rows, 2);
+            skipped2.lock().unwrap().push(row);
+            Decision::Skip
+        })
+        .collect();
+    assert_eq!(rows, vec![Row { a: 1 }, Row { a: 2 }]);
+    assert_eq!(*skipped.lock().unwrap(), vec![1]);
+}
+
+#[test]
+fn test_current_batch() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter()).unwrap();
+    for (i, expected_batch) in [0, 0, 0, 1, 1].into_iter().enumerate() {
+        assert_eq!(iter.try_next(), Ok(Some(Row { a: i as i64 + 1 })));
+        assert_eq!(iter.current_batch(), Some(&batches()[expected_batch]));
+    }
+}
+
+#[test]
+fn test_try_next_batch() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter().take(2)).unwrap();
+    let (batch, rows) = iter.try_next_batch().unwrap().unwrap();
+    assert_eq!(batch, &batches()[0]);
+    assert_eq!(rows, &[Row { a: 1 }, Row { a: 2 }, Row { a: 3 }]);
+    assert_eq!(iter.current_batch(), Some(&batches()[0]));
+
+    assert_eq!(iter.try_next(), Ok(Some(Row { a: 4 })));
+    // The rest of the second batch is skipped
+    assert_eq!(iter.try_next_batch(), Ok(None));
+    assert_eq!(iter.current_batch(), None);
+    assert_eq!(iter.try_next(), Ok(None));
+}
+
+#[test]
+fn test_try_next_batch_skipped_rows() {
+    let mut iter = RowIterator::<_, Row>::new(batches().into_iter().skip(2))
+        .unwrap()
+        .on_error(|_, _| Decision::Skip);
+    let (batch, rows) = iter.try_next_batch().unwrap().unwrap();
+    assert_eq!(batch, &batches()[2]);
+    assert_eq!(rows, &[Row { a: 6 }]);
+    let (batch, rows) = iter.try_next_batch().unwrap().unwrap();
+    assert_eq!(batch, &batches()[3]);
+    assert_eq!(rows, &[Row { a: 7 }]);
+    assert_eq!(iter.try_next_batch(), Ok(None));
+}
@@ -0,0 +1,174 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray};
+use ar_row::arrow::error::ArrowError;
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::object_store::open_parquet_rows;
+use ar_row::row_stream::{decode_in_background, RowStream};
+use ar_row_derive::ArRowDeserialize;
+use futures::executor::block_on;
+use futures::{StreamExt, TryStreamExt};
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use parquet::arrow::ArrowWriter;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    name: Option<String>,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct OtherRow {
+    name: i64,
+}
+
+fn batch(ids: Vec<i64>, names: Vec<Option<&str>>) -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(ids)) as ArrayRef),
+        ("name", Arc::new(StringArray::from(names)) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+fn rows(names: &[Option<&str>]) -> Vec<Row> {
+    names
+        .iter()
+        .map(|name| Row {
+            name: name.map(ToOwned::to_owned),
+        })
+        .collect()
+}
+
+#[test]
+fn test_row_stream() {
+    let batches = vec![
+        batch(vec![1, 2], vec![Some("a"), None]),
+        batch(vec![], vec![]),
+        batch(vec![3], vec![Some("c")]),
+    ];
+    let schema = batches[0].schema();
+    let batches: Vec<Result<_, ArrowError>> = batches
+        .into_iter()
+        .map(|batch| Ok(batch.project(&[1]).unwrap()))
+        .collect();
+
+    let stream = RowStream::<_, Row>::new(
+        futures::stream::iter(batches),
+        &schema.project(&[1]).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        block_on(stream.try_collect::<Vec<_>>()).unwrap(),
+        rows(&[Some("a"), None, Some("c")])
+    );
+}
+
+#[test]
+fn test_row_stream_mismatched_schema() {
+    let schema = batch(vec![], vec![]).schema();
+    let batches: Vec<Result<RecordBatch, ArrowError>> = Vec::new();
+
+    assert!(RowStream::<_, Row>::new(futures::stream::iter(batches), &schema).is_err());
+}
+
+/// Batch with a single "name" column, and one row
+fn name_batch(name: &str) -> RecordBatch {
+    batch(vec![0], vec![Some(name)]).project(&[1]).unwrap()
+}
+
+#[test]
+fn test_decode_in_background() {
+    let schema = name_batch("a").schema();
+    let batches: Vec<Result<_, ArrowError>> = (0..10).map(|_| Ok(name_batch("a"))).collect();
+    let read_batches = AtomicUsize::new(0);
+    let stream = futures::stream::iter(batches).inspect(|_| {
+        read_batches.fetch_add(1, Ordering::Relaxed);
+    });
+    let (decoder, mut decoded) =
+        decode_in_background::<_, _, Row>(stream, &schema, NonZeroUsize::new(2).unwrap()).unwrap();
+    let mut decoder = Box::pin(decoder);
+    let mut poll_decoder = || {
+        block_on(futures::future::poll_fn(|cx| {
+            Poll::Ready(decoder.as_mut().poll(cx).is_pending())
+        }))
+    };
+
+    // The decoder waits for the consumer when the queue is full
+    assert!(poll_decoder());
+    let read = read_batches.load(Ordering::Relaxed);
+    assert!((2..=3).contains(&read), "{}", read);
+    assert!(poll_decoder());
+    assert_eq!(read_batches.load(Ordering::Relaxed), read);
+
+    assert_eq!(
+        block_on(decoded.next()).unwrap().unwrap(),
+        rows(&[Some("a")])
+    );
+    assert!(poll_decoder());
+    assert_eq!(read_batches.load(Ordering::Relaxed), read + 1);
+
+    let (_, results) = block_on(futures::future::join(
+        decoder,
+        decoded.rows().try_collect::<Vec<_>>(),
+    ));
+    assert_eq!(results.unwrap().len(), 9);
+    assert_eq!(read_batches.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn test_decode_in_background_error() {
+    let schema = name_batch("a").schema();
+    let batches: Vec<Result<_, ArrowError>> = vec![
+        Ok(name_batch("a")),
+        Err(ArrowError::ComputeError("oops".to_owned())),
+        Ok(name_batch("b")),
+    ];
+    let (decoder, decoded) = decode_in_background::<_, _, Row>(
+        futures::stream::iter(batches),
+        &schema,
+        NonZeroUsize::new(1).unwrap(),
+    )
+    .unwrap();
+    let (_, results) = block_on(futures::future::join(
+        decoder,
+        decoded.rows().collect::<Vec<_>>(),
+    ));
+    // Decoding stops at the first error
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap(), &rows(&[Some("a")])[0]);
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_open_parquet_rows() {
+    let mut file = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut file, batch(vec![], vec![]).schema(), None).unwrap();
+    writer
+        .write(&batch(vec![1, 2, 3], vec![Some("a"), None, Some("c")]))
+        .unwrap();
+    writer.close().unwrap();
+
+    let store = Arc::new(InMemory::new());
+    let path = Path::from("dir/file.parquet");
+    block_on(store.put(&path, file.into())).unwrap();
+
+    // The "id" column is not projected
+    let stream = block_on(open_parquet_rows::<Row>(store.clone(), &path)).unwrap();
+    assert_eq!(
+        block_on(stream.try_collect::<Vec<_>>()).unwrap(),
+        rows(&[Some("a"), None, Some("c")])
+    );
+
+    assert!(block_on(open_parquet_rows::<OtherRow>(store, &path)).is_err());
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{
+    ArrayRef, DictionaryArray, Int32Array, Int64Array, RunArray, StringArray,
+};
+use ar_row::arrow::datatypes::{Int32Type, Int8Type};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::row_iterator::RowIterator;
+use ar_row::typed_batch::TypedRecordBatch;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: String,
+    category: Option<String>,
+}
+
+/// `name` is run-end encoded, and `category` is a dictionary with run-end encoded
+/// values
+fn batch() -> RecordBatch {
+    let names = RunArray::<Int32Type>::try_new(
+        &Int32Array::from(vec![2, 3]),
+        &StringArray::from(vec!["a", "b"]),
+    )
+    .unwrap();
+    let category_values = RunArray::<Int32Type>::try_new(
+        &Int32Array::from(vec![1, 2]),
+        &StringArray::from(vec!["x", "y"]),
+    )
+    .unwrap();
+    let categories = DictionaryArray::<Int8Type>::try_new(
+        vec![Some(1), None, Some(0)].into(),
+        Arc::new(category_values),
+    )
+    .unwrap();
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+        ("name", Arc::new(names) as ArrayRef),
+        ("category", Arc::new(categories) as ArrayRef),
+    ])
+    .unwrap()
+}
+
+fn expected_rows() -> Vec<Row> {
+    vec![
+        Row {
+            id: 1,
+            name: "a".to_owned(),
+            category: Some("y".to_owned()),
+        },
+        Row {
+            id: 2,
+            name: "a".to_owned(),
+            category: None,
+        },
+        Row {
+            id: 3,
+            name: "b".to_owned(),
+            category: Some("x".to_owned()),
+        },
+    ]
+}
+
+#[test]
+fn test_check_schema() {
+    assert_eq!(Row::check_schema(&batch().schema()), Ok(()));
+    assert!(TypedRecordBatch::<Row>::try_new(batch()).is_ok());
+
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+    struct OtherRow {
+        id: i64,
+        name: i64,
+        category: Option<String>,
+    }
+    assert!(OtherRow::check_schema(&batch().schema()).is_err());
+}
+
+#[test]
+fn test_from_record_batch() {
+    assert_eq!(Row::from_record_batch(batch()), Ok(expected_rows()));
+}
+
+#[test]
+fn test_row_iterator() {
+    let rows: Vec<Row> = RowIterator::new(vec![batch(), batch()].into_iter())
+        .unwrap()
+        .collect();
+    let mut expected = expected_rows();
+    expected.extend(expected_rows());
+    assert_eq!(rows, expected);
+}
@@ -0,0 +1,75 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fs::File;
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::orc::orc_projection_for;
+use ar_row::seek::seek_to_row;
+use ar_row_derive::ArRowDeserialize;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Test1 {
+    long1: Option<i64>,
+}
+
+/// Writes a Parquet file with rows 0 to 9, in row groups of 4 rows
+fn parquet_file() -> tempfile::NamedTempFile {
+    let batch = RecordBatch::try_from_iter(vec![(
+        "id",
+        Arc::new(Int64Array::from((0..10).collect::<Vec<_>>())) as ArrayRef,
+    )])
+    .unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(4)
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(file.reopen().unwrap(), batch.schema(), Some(properties)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    file
+}
+
+#[test]
+fn test_seek_parquet() {
+    let file = parquet_file();
+    for row in 0..12 {
+        // Batches do not align with row groups, so some are partially skipped
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file.reopen().unwrap())
+            .unwrap()
+            .with_batch_size(3);
+        let rows: Vec<i64> = seek_to_row::<_, Row>(builder, row)
+            .unwrap()
+            .map(|row| row.unwrap().id)
+            .collect();
+        assert_eq!(rows, (row as i64..10).collect::<Vec<_>>(), "row {}", row);
+    }
+}
+
+#[test]
+fn test_seek_orc() {
+    for row in 0..3 {
+        let file = File::open("../test_data/TestOrcFile.test1.orc").unwrap();
+        let builder = orc_rust::ArrowReaderBuilder::try_new(file).unwrap();
+        let projection = orc_projection_for::<Test1>(builder.file_metadata().root_data_type());
+        let builder = builder.with_projection(projection).with_batch_size(1);
+        let rows: Vec<Test1> = seek_to_row(builder, row)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2 - row, "row {}", row);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, BooleanArray, Date32Array, Float64Array, Int64Array};
+use ar_row::arrow::datatypes::{DataType, Field};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::DisplayString;
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+struct Row {
+    #[ar_row(stringify)]
+    id: String,
+    #[ar_row(stringify)]
+    score: Option<String>,
+    #[ar_row(stringify)]
+    date: String,
+    #[ar_row(stringify)]
+    flag: Option<String>,
+}
+
+fn batch() -> RecordBatch {
+    RecordBatch::try_from_iter(vec![
+        ("id", Arc::new(Int64Array::from(vec![1, -2])) as ArrayRef),
+        (
+            "score",
+            Arc::new(Float64Array::from(vec![Some(1.5), None])) as ArrayRef,
+        ),
+        (
+            "date",
+            Arc::new(Date32Array::from(vec![0, 19753])) as ArrayRef,
+        ),
+        (
+            "flag",
+            Arc::new(BooleanArray::from(vec![None, Some(true)])) as ArrayRef,
+        ),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_stringify() {
+    let batch = batch();
+    Row::check_schema(&batch.schema()).unwrap();
+    assert_eq!(
+        Row::from_record_batch(batch),
+        Ok(vec![
+            Row {
+                id: "1".to_owned(),
+                score: Some("1.5".to_owned()),
+                date: "1970-01-01".to_owned(),
+                flag: None,
+            },
+            Row {
+                id: "-2".to_owned(),
+                score: None,
+                date: "2024-01-31".to_owned(),
+                flag: Some("true".to_owned()),
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_stringify_reused_rows() {
+    let mut rows = vec![
+        Row {
+            id: "previous value".to_owned(),
+            ..Default::default()
+        };
+        2
+    ];
+    Row::read_from_record_batch(batch(), &mut rows).unwrap();
+    assert_eq!(rows[0].id, "1");
+}
+
+#[test]
+fn test_stringify_datatype() {
+    assert_eq!(
+        Row::expected_datatype(),
+        DataType::Struct(
+            vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("score", DataType::Utf8, true),
+                Field::new("date", DataType::Utf8, false),
+                Field::new("flag", DataType::Utf8, true),
+            ]
+            .into()
+        )
+    );
+
+    assert!(DisplayString::check_datatype(&DataType::Int64).is_ok());
+    assert!(DisplayString::check_datatype(&DataType::new_list(DataType::Int64, true)).is_err());
+}
@@ -4,9 +4,11 @@
 // See top-level LICENSE file for more information
 
 use std::fs::File;
+use std::num::NonZeroUsize;
 
 use ar_row::arrow::array::RecordBatchReader;
 use ar_row::deserialize::{ArRowDeserialize, ArRowStruct, CheckableDataType};
+use ar_row::options::DeserializeOptions;
 use ar_row::row_iterator::RowIterator;
 use ar_row_derive::ArRowDeserialize;
 use orc_rust::projection::ProjectionMask;
@@ -313,3 +315,82 @@ fn test1_inner_nooption_outer_option() {
 fn test1_inner_nooption_outer_nooption() {
     test::<Test1NoOption>(expected_rows_nooptions());
 }
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+#[ar_row(direct_field_writes)]
+struct Test1DirectFieldWrites {
+    boolean1: Option<bool>,
+    byte1: Option<i8>,
+    short1: Option<i16>,
+    int1: Option<i32>,
+    long1: Option<i64>,
+    float1: Option<f32>,
+    double1: Option<f64>,
+    bytes1: Option<Box<[u8]>>,
+    string1: Option<String>,
+    list: Option<Vec<Option<Test1ItemDirectFieldWrites>>>,
+}
+
+#[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+#[ar_row(direct_field_writes)]
+struct Test1ItemDirectFieldWrites {
+    int1: Option<i32>,
+    string1: Option<String>,
+}
+
+/// Tests `#[ar_row(direct_field_writes)]`, with and without an outer `Option`
+#[test]
+fn test1_direct_field_writes() {
+    let expected_rows: Vec<_> = expected_rows_options()
+        .into_iter()
+        .map(|row| Test1DirectFieldWrites {
+            boolean1: row.boolean1,
+            byte1: row.byte1,
+            short1: row.short1,
+            int1: row.int1,
+            long1: row.long1,
+            float1: row.float1,
+            double1: row.double1,
+            bytes1: row.bytes1,
+            string1: row.string1,
+            list: row.list.map(|list| {
+                list.into_iter()
+                    .map(|item| {
+                        item.map(|item| Test1ItemDirectFieldWrites {
+                            int1: item.int1,
+                            string1: item.string1,
+                        })
+                    })
+                    .collect()
+            }),
+        })
+        .collect();
+    test::<Option<Test1DirectFieldWrites>>(expected_rows.iter().cloned().map(Some).collect());
+    test::<Test1DirectFieldWrites>(expected_rows);
+}
+
+fn test_blocks<T: ArRowDeserialize + ArRowStruct + Send + PartialEq + std::fmt::Debug>(
+    expected_rows: Vec<T>,
+) {
+    for block_size in [1, 2, 3] {
+        let options = DeserializeOptions {
+            block_size: NonZeroUsize::new(block_size),
+            ..Default::default()
+        };
+        let rows: Vec<T> = get_reader(10)
+            .flat_map(|batch| T::from_record_batch_with_options(batch.unwrap(), &options).unwrap())
+            .collect();
+        assert_eq!(
+            rows, expected_rows,
+            "Unexpected rows with block_size = {}",
+            block_size
+        );
+    }
+}
+
+/// Tests `DeserializeOptions::block_size`, with and without an outer `Option`
+#[test]
+fn test1_blocks() {
+    test_blocks::<Test1Option>(expected_rows_options());
+    test_blocks::<Option<Test1Option>>(expected_rows_options().into_iter().map(Some).collect());
+}
@@ -4,7 +4,9 @@
 // See top-level LICENSE file for more information
 
 use ar_row::arrow::datatypes::{DataType, Field};
-use ar_row::deserialize::{ArRowStruct, CheckableDataType};
+use ar_row::deserialize::{
+    ArRowStruct, CheckableDataType, ColumnNameOptions, ColumnPath, ColumnTree, Flattening,
+};
 use ar_row_derive::ArRowDeserialize;
 
 #[test]
@@ -27,6 +29,28 @@ fn test_basic() {
     assert_eq!(Test::columns(), vec!["abc", "def"]);
 }
 
+#[test]
+fn test_expected_datatype_options() {
+    #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
+    struct Test {
+        abc: Option<String>,
+        def: Option<Vec<Option<i64>>>,
+    }
+
+    assert_eq!(
+        Test::expected_datatype(),
+        DataType::Struct(
+            vec![
+                Field::new("abc", DataType::Utf8, true),
+                Field::new("def", DataType::new_list(DataType::Int64, true), true),
+            ]
+            .into(),
+        )
+    );
+    assert!(!Test::nullable());
+    assert!(<Option<Test>>::nullable());
+}
+
 #[test]
 fn test_raw_literal() {
     #[derive(ArRowDeserialize, Clone, Default, Debug, PartialEq)]
@@ -40,6 +64,10 @@ fn test_raw_literal() {
     .unwrap();
 
     assert_eq!(Test::columns(), vec!["type"]);
+    assert_eq!(
+        Test::column_paths(),
+        vec![ColumnPath(vec!["type".to_owned()])]
+    );
 }
 
 #[test]
@@ -64,18 +92,108 @@ fn test_nested() {
         ]
         .into(),
     );
-    Test::check_datatype(&DataType::Struct(
+    let datatype = DataType::Struct(
         vec![
             Field::new("abc", DataType::Utf8, false),
             Field::new("def", inner_datatype.clone(), false),
-            Field::new("def2", DataType::new_list(inner_datatype, false), false),
+            Field::new(
+                "def2",
+                DataType::new_list(inner_datatype.clone(), false),
+                false,
+            ),
         ]
         .into(),
-    ))
-    .unwrap();
+    );
+    Test::check_datatype(&datatype).unwrap();
+    assert_eq!(Test::expected_datatype(), datatype);
+    assert_eq!(
+        Test::fields(),
+        vec![
+            (ColumnPath(vec!["abc".to_owned()]), DataType::Utf8, false),
+            (
+                ColumnPath(vec!["def".to_owned(), "ghi".to_owned()]),
+                DataType::Int64,
+                false
+            ),
+            (
+                ColumnPath(vec!["def".to_owned(), "jkl".to_owned()]),
+                DataType::new_list(DataType::Int32, false),
+                false
+            ),
+            (
+                ColumnPath(vec!["def2".to_owned()]),
+                DataType::new_list(inner_datatype, false),
+                false
+            ),
+        ]
+    );
 
     assert_eq!(
         Test::columns(),
         vec!["abc", "def.ghi", "def.jkl", "def2.ghi", "def2.jkl"]
     );
+
+    let inner_tree = ColumnTree::Struct(vec![
+        ("ghi".to_owned(), ColumnTree::Leaf),
+        ("jkl".to_owned(), ColumnTree::Leaf),
+    ]);
+    assert_eq!(
+        Test::column_tree(),
+        ColumnTree::Struct(vec![
+            ("abc".to_owned(), ColumnTree::Leaf),
+            ("def".to_owned(), inner_tree.clone()),
+            ("def2".to_owned(), inner_tree),
+        ])
+    );
+    assert_eq!(
+        Test::column_paths()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        Test::columns()
+    );
+
+    let mut columns = Vec::new();
+    let mut path = vec!["root"];
+    Test::for_each_column_path(&mut path, &mut |column| columns.push(column.join("/")));
+    assert_eq!(path, vec!["root"]);
+    assert_eq!(
+        columns,
+        vec![
+            "root/abc",
+            "root/def/ghi",
+            "root/def/jkl",
+            "root/def2/ghi",
+            "root/def2/jkl"
+        ]
+    );
+
+    assert_eq!(
+        Test::columns_with_options(&ColumnNameOptions {
+            separator: ":".to_owned(),
+            ..Default::default()
+        }),
+        vec!["abc", "def:ghi", "def:jkl", "def2:ghi", "def2:jkl"]
+    );
+    assert_eq!(
+        Test::columns_with_options(&ColumnNameOptions {
+            separator: "_".to_owned(),
+            flattening: Flattening::FullPath,
+        }),
+        vec!["abc", "def_ghi", "def_jkl", "def2_ghi", "def2_jkl"]
+    );
+    assert_eq!(
+        Test::columns_with_options(&ColumnNameOptions {
+            flattening: Flattening::TopLevel,
+            ..Default::default()
+        }),
+        vec!["abc", "def", "def2"]
+    );
+    assert_eq!(
+        Test::columns_with_options(&ColumnNameOptions {
+            flattening: Flattening::LeafName,
+            ..Default::default()
+        }),
+        vec!["abc", "ghi", "jkl"]
+    );
 }
@@ -10,6 +10,7 @@ use ar_row::arrow::datatypes::{DataType, Field, Schema};
 use orc_rust::ArrowReaderBuilder;
 
 use ar_row::deserialize::{ArRowDeserialize, CheckableDataType};
+use ar_row::schema::SchemaOptions;
 use ar_row::{Date, Timestamp};
 use ar_row_derive::ArRowDeserialize;
 
@@ -109,22 +110,22 @@ fn test_timestamp_1900() {
         vec![
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -900000000
+                    seconds: -2198229904,
+                    nanoseconds: 100000000
                 },
                 date: Date(-25209),
             },
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -899900000
+                    seconds: -2198229904,
+                    nanoseconds: 100100000
                 },
                 date: Date(-25209),
             },
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -899800000
+                    seconds: -2198229904,
+                    nanoseconds: 100200000
                 },
                 date: Date(-25209),
             },
@@ -134,10 +135,17 @@ fn test_timestamp_1900() {
 
 #[test]
 fn test_timestamp_1900_decimal() {
-    let schema = Schema::new(vec![
-        Field::new("time", DataType::Decimal128(38, 9), false),
-        Field::new("date", DataType::Date32, false),
-    ]);
+    let schema = TimeAndDate::reader_schema(&SchemaOptions {
+        timestamps_as_decimal128: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        schema,
+        Schema::new(vec![
+            Field::new("time", DataType::Decimal128(38, 9), false),
+            Field::new("date", DataType::Date32, false),
+        ])
+    );
     let reader = reader_builder("../test_data/TestOrcFile.testDate1900.orc")
         .with_schema(schema.clone().into())
         .build();
@@ -155,22 +163,22 @@ fn test_timestamp_1900_decimal() {
         vec![
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -900000000
+                    seconds: -2198229904,
+                    nanoseconds: 100000000
                 },
                 date: Date(-25209),
             },
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -899900000
+                    seconds: -2198229904,
+                    nanoseconds: 100100000
                 },
                 date: Date(-25209),
             },
             TimeAndDate {
                 time: Timestamp {
-                    seconds: -2198229903,
-                    nanoseconds: -899800000
+                    seconds: -2198229904,
+                    nanoseconds: 100200000
                 },
                 date: Date(-25209),
             },
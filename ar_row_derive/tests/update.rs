@@ -0,0 +1,140 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::sync::Arc;
+
+use ar_row::arrow::array::{ArrayRef, Int64Array, StringArray, StructArray};
+use ar_row::arrow::datatypes::{DataType, Field};
+use ar_row::arrow::record_batch::RecordBatch;
+use ar_row::deserialize::{ArRowUpdate, DeserializationError};
+use ar_row_derive::ArRowDeserialize;
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Inner {
+    value: i64,
+}
+
+#[derive(ArRowDeserialize, Default, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: Option<String>,
+    inner: Inner,
+}
+
+fn rows() -> Vec<Row> {
+    (1..=3)
+        .map(|id| Row {
+            id,
+            name: Some(format!("row{}", id)),
+            inner: Inner { value: id * 10 },
+        })
+        .collect()
+}
+
+#[test]
+fn test_update_one_column() {
+    let batch = RecordBatch::try_from_iter(vec![(
+        "name",
+        Arc::new(StringArray::from(vec![Some("foo"), None, Some("baz")])) as ArrayRef,
+    )])
+    .unwrap();
+    let mut rows = rows();
+    assert_eq!(Row::update_from_record_batch(batch, &mut rows), Ok(3));
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                id: 1,
+                name: Some("foo".to_owned()),
+                inner: Inner { value: 10 },
+            },
+            Row {
+                id: 2,
+                name: None,
+                inner: Inner { value: 20 },
+            },
+            Row {
+                id: 3,
+                name: Some("baz".to_owned()),
+                inner: Inner { value: 30 },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_update_prefix_out_of_order() {
+    let inner = StructArray::new(
+        vec![Field::new("value", DataType::Int64, false)].into(),
+        vec![Arc::new(Int64Array::from(vec![100, 200])) as ArrayRef],
+        None,
+    );
+    let batch = RecordBatch::try_from_iter(vec![
+        ("inner", Arc::new(inner) as ArrayRef),
+        ("id", Arc::new(Int64Array::from(vec![4, 5])) as ArrayRef),
+    ])
+    .unwrap();
+    let mut rows = rows();
+    assert_eq!(Row::update_from_record_batch(batch, &mut rows), Ok(2));
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                id: 4,
+                name: Some("row1".to_owned()),
+                inner: Inner { value: 100 },
+            },
+            Row {
+                id: 5,
+                name: Some("row2".to_owned()),
+                inner: Inner { value: 200 },
+            },
+            Row {
+                id: 3,
+                name: Some("row3".to_owned()),
+                inner: Inner { value: 30 },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_update_errors() {
+    let mut rows = rows();
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "unknown",
+        Arc::new(Int64Array::from(vec![1])) as ArrayRef,
+    )])
+    .unwrap();
+    assert_eq!(
+        Row::update_from_record_batch(batch, &mut rows),
+        Err(DeserializationError::MismatchedColumnDataType(
+            "Row has no field named unknown".to_owned()
+        ))
+    );
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "id",
+        Arc::new(StringArray::from(vec!["foo"])) as ArrayRef,
+    )])
+    .unwrap();
+    assert!(matches!(
+        Row::update_from_record_batch(batch, &mut rows),
+        Err(DeserializationError::MismatchedColumnDataType(_))
+    ));
+
+    let batch = RecordBatch::try_from_iter(vec![(
+        "id",
+        Arc::new(Int64Array::from(vec![1, 2, 3, 4])) as ArrayRef,
+    )])
+    .unwrap();
+    assert_eq!(
+        Row::update_from_record_batch(batch, &mut rows),
+        Err(DeserializationError::MismatchedLength { src: 4, dst: 3 })
+    );
+
+    assert_eq!(rows, self::rows());
+}